@@ -0,0 +1,46 @@
+/// Fraction of the remaining distance covered each frame
+const EASING: f64 = 0.25;
+const SNAP: f64 = 0.002;
+
+/// A value that eases toward its target when animation is enabled and
+/// jumps straight there otherwise
+pub struct Animated {
+    current: f64,
+    target: f64,
+    enabled: bool,
+}
+
+impl Animated {
+    pub fn new(enabled: bool) -> Animated {
+        Animated {
+            current: 0.0,
+            target: 0.0,
+            enabled,
+        }
+    }
+
+    pub fn set(&mut self, target: f64) {
+        self.target = target;
+        if !self.enabled {
+            self.current = target;
+        }
+    }
+
+    /// Advance one frame
+    pub fn step(&mut self) {
+        let delta = self.target - self.current;
+        if delta.abs() < SNAP {
+            self.current = self.target;
+        } else {
+            self.current += delta * EASING;
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.current
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.current != self.target
+    }
+}
@@ -0,0 +1,3117 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use crossterm::event::KeyCode;
+use ratatui::widgets::ListState;
+use serde::Deserialize;
+
+use crate::anim::Animated;
+use crate::audit;
+use crate::backend;
+use crate::backlight::{self, Backlight};
+use crate::battery::{self, read_battery_info, BatteryInfo, FORCE_DISCHARGE_FLOOR};
+use crate::budget::{self, Budget};
+use crate::calls;
+use crate::capabilities::Capabilities;
+use crate::charger::{self, Charger};
+use crate::charging::{self, Anomaly};
+use crate::clipboard;
+use crate::command::{self, Command, CommandLine, LineAction};
+use crate::config::{
+    BagAction, BagConfig, BatteryConfig, CallsConfig, Config, GaugesConfig, PeripheralsConfig,
+    QuietHours, RefreshConfig, ResumeConfig, UpsConfig, Widget, WidgetSlot,
+};
+use crate::cores::{self, CoreFreq, CoreHistory};
+use crate::cpufreq::{self, Policy};
+use crate::daemon;
+use crate::desktop::{self, Desktop, DesktopSettings, SUSPEND_PRESETS};
+use crate::doctor::{Check, Severity};
+use crate::dpms::Dpms;
+use crate::draw::DrawHistory;
+use crate::editor::{EditorAction, FanEditor, ProfileEditor, Ranges, ThresholdEditor};
+use crate::events::{EventLog, Level};
+use crate::fans;
+use crate::gpu::{self, Gpu};
+use crate::health::{self, Finding};
+use crate::hibernate;
+use crate::history;
+use crate::hotplug::SupplyMonitor;
+use crate::hwp::{self, HwpStatus};
+use crate::i915::{self, GraphicsStatus};
+use crate::inhibit::{self, IdleBlock, Inhibitor};
+use crate::journal::JournalTail;
+use crate::keymap::{Action, Keymap};
+use crate::lid::{self, Display};
+use crate::locale;
+use crate::msr;
+use crate::netpower::{self, Nic};
+use crate::notifications::Notifier;
+use crate::peripherals::{self, Peripheral};
+use crate::platform;
+use crate::plug::Poller;
+use crate::preview::Preview;
+use crate::privileged;
+use crate::profile::{self, available_governors, resolve_governor, save_custom_profiles, Profile};
+use crate::quiet::{self, NoiseModel, Quiet, Suggestion};
+use crate::rapl::{self, Domain, EnergyMeter, Limit, Package};
+use crate::resume::{self, SleepMonitor};
+use crate::rfkill::{self, Radio};
+use crate::rules::{self, Conditions, Rule};
+use crate::ryzen::{self, RyzenStatus};
+use crate::sessions;
+use crate::simulate::{self, Change};
+use crate::sleep::{self, Cycle};
+use crate::sources::{self, Source};
+use crate::steering::{self, Process};
+use crate::storage::{self, Storage, StorageConfig};
+use crate::stream::{Message, Subscription};
+use crate::swap::{self, SwapStatus};
+use crate::sway::{Output, Sway};
+use crate::sysfs::{self, Choice};
+use crate::thermal;
+use crate::toast::Toasts;
+use crate::tour::{self, Tour};
+use crate::typec::{self, Port};
+use crate::ups::{self, UpsStatus};
+use crate::usage::{self, Tracker};
+use crate::usb;
+use crate::wake::{self, Wakeup};
+use crate::webhook::{self, WebhookConfig};
+
+#[derive(Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Page {
+    #[default]
+    Overview,
+    Cpu,
+    Cores,
+    Sleep,
+    Ports,
+    History,
+    /// Only with `[ui] msr = true`
+    Msr,
+}
+
+impl Page {
+    pub const ALL: [Page; 7] = [
+        Page::Overview,
+        Page::Cpu,
+        Page::Cores,
+        Page::Sleep,
+        Page::Ports,
+        Page::History,
+        Page::Msr,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Page::Overview => "Overview",
+            Page::Cpu => "CPU",
+            Page::Cores => "Cores",
+            Page::Sleep => "Sleep",
+            Page::Ports => "USB-C",
+            Page::History => "History",
+            Page::Msr => "MSR",
+        }
+    }
+}
+
+/// Other names for pages, after what they show: the battery gauges are
+/// on the Overview, the thermal zones on the CPU page
+const PAGE_ALIASES: [(&str, Page); 2] = [("battery", Page::Overview), ("thermal", Page::Cpu)];
+
+/// By title or alias, any case: `[ui] page = "cpu"`
+impl TryFrom<String> for Page {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Page::ALL
+            .into_iter()
+            .find(|p| p.title().eq_ignore_ascii_case(&s))
+            .or_else(|| {
+                PAGE_ALIASES
+                    .into_iter()
+                    .find(|(alias, _)| alias.eq_ignore_ascii_case(&s))
+                    .map(|(_, page)| page)
+            })
+            .ok_or_else(|| {
+                let names: Vec<&str> = Page::ALL
+                    .iter()
+                    .map(|p| p.title())
+                    .chain(PAGE_ALIASES.iter().map(|(alias, _)| *alias))
+                    .collect();
+                format!("unknown page {}, expected one of {}", s, names.join(", "))
+            })
+    }
+}
+
+/// How long a boost lasts unless `:boost` says otherwise
+const DEFAULT_BOOST: Duration = Duration::from_secs(15 * 60);
+
+/// A temporary switch to Performance that reverts on its own
+pub struct Boost {
+    pub until: Instant,
+    /// Profile to return to, and whether Automatic was on
+    previous: Option<usize>,
+    was_auto: bool,
+}
+
+impl Boost {
+    pub fn remaining(&self) -> Duration {
+        self.until.saturating_duration_since(Instant::now())
+    }
+}
+
+/// Keeps the machine awake and steady while presenting
+pub struct Presentation {
+    _idle: IdleBlock,
+    /// Brightness to hold, if the desktop or backlight reports one
+    brightness: Option<u8>,
+    was_auto: bool,
+}
+
+/// A call in progress: something is recording from the microphone or
+/// camera
+pub struct Call {
+    /// Who is recording, e.g. `Firefox (camera, microphone)`
+    pub apps: String,
+    /// Held when `no_suspend` is set
+    _idle: Option<IdleBlock>,
+}
+
+/// What travel mode changed, so leaving it puts everything back
+pub struct Travel {
+    previous: Option<usize>,
+    was_auto: bool,
+    /// Radios travel mode turned off; ones already off stay off
+    radios: Vec<Radio>,
+    brightness: Option<u8>,
+    /// Suspend timeouts on battery and AC that were shortened
+    suspend: Vec<(bool, Option<u32>)>,
+}
+
+/// Travel mode dims to at most this brightness
+const TRAVEL_BRIGHTNESS: u8 = 30;
+
+/// Name of the virtual profile entry, also accepted by `:profile`
+pub const AUTO_NAME: &str = "Automatic";
+
+/// Power samples kept for the Overview graph, per source
+const POWER_HISTORY: usize = 120;
+
+pub struct App {
+    pub page: Page,
+    /// Overview widgets, top to bottom
+    pub overview: Vec<WidgetSlot>,
+    /// What the hardware supports, deciding which controls are offered
+    pub caps: Capabilities,
+    pub gauges: GaugesConfig,
+    /// Recent system draw in watts, oldest first
+    pub power_history: VecDeque<f64>,
+    /// The `[plug]`, read on its own thread
+    plug: Option<Poller>,
+    /// Desktop notifications for battery events
+    notifier: Option<Notifier>,
+    /// Where alerts are posted besides the screen
+    webhook: Option<WebhookConfig>,
+    /// CPU went past `[gauges] temp_hot`, until it cools below `temp_warm`
+    running_hot: bool,
+    quiet_hours: Option<QuietHours>,
+    /// Wall power from the smart plug, oldest first
+    pub wall_history: VecDeque<f64>,
+    plug_offline: bool,
+    pub ups_config: Option<UpsConfig>,
+    /// The `[ups]`, queried on its own thread
+    ups_poller: Option<ups::Poller>,
+    pub ups: Option<UpsStatus>,
+    ups_offline: bool,
+    /// Low runtime was already reported for this outage
+    ups_low: bool,
+    bag_config: Option<BagConfig>,
+    /// CPU temperature when the lid closed on battery
+    bag_start: Option<f64>,
+    /// The bag action already ran for this lid close
+    bag_fired: bool,
+    /// The `mem_sleep` mode a bag suspend switched from, put back on
+    /// resume
+    bag_mem_sleep: Option<String>,
+    /// A `powertui daemon` running alongside
+    pub daemon: Option<daemon::State>,
+    /// Its socket, which passes on every change any powertui process
+    /// makes
+    stream: Option<Subscription>,
+    /// How often each kind of reading is taken
+    rates: RefreshConfig,
+    pub temps: Vec<(String, f64)>,
+    temps_read: Option<Instant>,
+    pub core_freqs: Vec<CoreFreq>,
+    core_freqs_read: Option<Instant>,
+    /// Thermal zones and hwmon inputs with their trip points, for the
+    /// CPU page
+    pub sensors: Vec<thermal::Sensor>,
+    /// Heat throttling events since boot, as `(core, package)`
+    pub throttle_counts: Option<(u64, u64)>,
+    sensors_read: Option<Instant>,
+    /// hwmon fans, read with the sensors
+    pub fans: Vec<fans::Fan>,
+    /// Fans made manual from here, as they were before
+    fans_held: Vec<fans::Pwm>,
+    /// Per-core samples for the Cores page
+    pub core_history: CoreHistory,
+    /// Draw over the last few minutes, for the `chart` widget
+    pub draw_history: DrawHistory,
+    pub audit: Vec<audit::Entry>,
+    /// Time in each profile over the last week, for the History page
+    pub usage: Vec<usage::Day>,
+    pub profile_time: Tracker,
+    /// The last week of the battery log, for the History page
+    pub charge_history: Vec<history::Sample>,
+    battery_log: Option<history::Logger>,
+    /// Discharges recorded with their kernel and profile
+    pub session_log: Option<sessions::Recorder>,
+    /// Recorded discharges by kernel and profile, for the History page
+    pub battery_sessions: Vec<sessions::Group>,
+    pub sleep_cycles: Vec<Cycle>,
+    /// RTC alarms and timers armed to wake the machine
+    pub wakeups: Vec<Wakeup>,
+    pub mem_sleep: Option<Choice>,
+    pub hibernate: Vec<Check>,
+    pub typec: Vec<Port>,
+    pub usb: Vec<usb::Device>,
+    /// Whether the MSR page is enabled
+    msr_page: bool,
+    pub msr: Result<Vec<msr::Register>, String>,
+    /// Problems found at launch, until dismissed
+    pub health: Vec<Finding>,
+    /// Rows scrolled past on table pages
+    pub page_scroll: usize,
+    pub battery: Option<BatteryInfo>,
+    /// Every system battery; more than one on dual-battery ThinkPads
+    pub batteries: Vec<BatteryInfo>,
+    /// All batteries as one, for charge, time left and draw; the same as
+    /// `battery` on machines with one
+    pub combined: Option<BatteryInfo>,
+    /// Battery to fill before the others, and to drain before them
+    pub charge_first: Option<String>,
+    pub discharge_first: Option<String>,
+    /// Batteries whose charge behaviour the priorities changed, to be put
+    /// back to auto
+    held_batteries: Vec<String>,
+    pub charger: Option<Charger>,
+    supplies: Option<SupplyMonitor>,
+    resume: ResumeConfig,
+    /// Watches for wake-ups while there's something to do on resume
+    sleep_monitor: Option<SleepMonitor>,
+    pub battery_alerts: BatteryConfig,
+    /// Low battery was already reported for this discharge
+    battery_low: bool,
+    pub charge_anomalies: Vec<Anomaly>,
+    /// None on machines without a lid
+    pub lid_closed: Option<bool>,
+    pub displays: Vec<Display>,
+    pub peripherals: Vec<Peripheral>,
+    pub cpufreq: Vec<Policy>,
+    pub hwp: Option<HwpStatus>,
+    /// Whether the CPU may turbo, where there's a switch for it
+    pub turbo: Option<bool>,
+    /// PSR, FBC and GuC/HuC on Intel graphics
+    pub graphics: Option<GraphicsStatus>,
+    /// PSR turned off from here; debugfs needs root to read back
+    pub psr_off: bool,
+    /// The panel backlight, for brightness without a desktop to ask
+    pub backlight: Option<Backlight>,
+    /// amdgpu and i915 clocks and forced levels
+    pub gpus: Vec<Gpu>,
+    /// zswap and zram settings and how well they compress
+    pub swap: Option<SwapStatus>,
+    pub rapl: Option<Package>,
+    rapl_meter: EnergyMeter,
+    rapl_domains: Vec<(Domain, EnergyMeter)>,
+    /// Draw of the core, uncore, DRAM and platform domains, in watts
+    pub domain_power: Vec<(&'static str, f64)>,
+    /// CPU package draw in watts, from RAPL energy counters
+    pub package_power: Option<f64>,
+    /// Package power against fan speed, for the quiet suggestion
+    noise: NoiseModel,
+    pub ryzen: Option<RyzenStatus>,
+    /// Efficiency cores on hybrid CPUs, e.g. `12-19`
+    pub ecores: Option<String>,
+    pub steer_patterns: Vec<String>,
+    /// Running processes matching `steer_patterns`
+    pub steer_matches: Vec<Process>,
+    /// Processes currently held on the E-cores
+    pub steered: Vec<Process>,
+    /// Wired interfaces the profile took down on battery, or tried to
+    nics_down: Vec<Nic>,
+    /// When Bluetooth was last seen with nothing connected
+    bluetooth_idle_since: Option<Instant>,
+    peripheral_alerts: PeripheralsConfig,
+    /// Devices already warned about, until they charge back up
+    low_peripherals: Vec<String>,
+    /// Battery gauge fill, eased when animations are on
+    pub gauge: Animated,
+    pub profiles: Vec<Profile>,
+    /// What the cpufreq driver offers, to map profiles onto
+    pub governors: Vec<String>,
+    /// Index into `profiles` of the active profile
+    pub current_profile: Option<usize>,
+    /// The Automatic profile: the rules engine picks the profile
+    pub auto: bool,
+    pub rules: Vec<Rule>,
+    /// Rule that last matched, so each decision is applied once
+    auto_target: Option<String>,
+    /// Rule that last changed the effective profile, and when
+    pub auto_reason: Option<(String, String)>,
+    pub boost: Option<Boost>,
+    /// Runtime target from `:budget`
+    pub budget: Option<Budget>,
+    /// Quiet settings from the quiet key, until pressed again
+    pub quiet: Option<Quiet>,
+    pub presentation: Option<Presentation>,
+    pub travel: Option<Travel>,
+    /// Batteries held at the storage level, across restarts
+    pub storage: Option<Storage>,
+    storage_config: StorageConfig,
+    /// Save profiles picked by hand for the daemon to apply after a
+    /// reboot
+    persist_profile: bool,
+    /// Said this session that the machine has been stored a while
+    storage_reminded: bool,
+    pub calls: CallsConfig,
+    pub call: Option<Call>,
+    pub editor: Option<ProfileEditor>,
+    pub threshold_editor: Option<ThresholdEditor>,
+    pub fan_editor: Option<FanEditor>,
+    /// The guided tour, shown over the page on first launch
+    pub tour: Option<Tour>,
+    pub preview: Option<Preview>,
+    pub selected: usize,
+    pub list_state: ListState,
+    pub toasts: Toasts,
+    pub events: EventLog,
+    journal: JournalTail,
+    pub show_log: bool,
+    pub show_help: bool,
+    pub keymap: Keymap,
+    pub command_line: Option<CommandLine>,
+    pub refresh_interval: Duration,
+    pub last_refresh: Instant,
+    pub quit: bool,
+    pub desktop: Option<Desktop>,
+    pub desktop_settings: Option<DesktopSettings>,
+    pub inhibitors: Vec<Inhibitor>,
+    pub sway: Option<Sway>,
+    pub outputs: Vec<Output>,
+    /// How outputs get forced off, if the session allows it
+    pub dpms: Option<Dpms>,
+    pub outputs_off: bool,
+    /// Running against a simulated machine (`--simulate`)
+    pub simulating: bool,
+    /// The compact view instead of the dashboard (`--minimal`)
+    pub minimal: bool,
+    /// Outputs were blanked by a rule, to be woken when it stops matching
+    rule_blanked: bool,
+}
+
+impl App {
+    pub fn new(config: Config) -> Self {
+        let (keymap, keys_error) = match Keymap::with_overrides(&config.keys) {
+            Ok(keymap) => (keymap, None),
+            Err(e) => (Keymap::default(), Some(e)),
+        };
+        let start_page = config.ui.page;
+        let mut app = Self {
+            page: Page::Overview,
+            overview: config.overview.widgets,
+            caps: Capabilities::probe(),
+            gauges: config.gauges,
+            power_history: VecDeque::new(),
+            plug: config
+                .plug
+                .map(|p| Poller::start(p, config.refresh.battery.0)),
+            notifier: config
+                .notifications
+                .map(|n| Notifier::new(n, config.quiet_hours)),
+            webhook: config.webhook,
+            running_hot: false,
+            quiet_hours: config.quiet_hours,
+            wall_history: VecDeque::new(),
+            plug_offline: false,
+            ups_poller: config
+                .ups
+                .as_ref()
+                .map(|u| ups::Poller::start(u.name.clone(), config.refresh.battery.0)),
+            ups_config: config.ups,
+            ups: None,
+            ups_offline: false,
+            ups_low: false,
+            bag_config: config.bag,
+            bag_start: None,
+            bag_fired: false,
+            bag_mem_sleep: None,
+            daemon: None,
+            stream: None,
+            rates: config.refresh,
+            temps: Vec::new(),
+            temps_read: None,
+            core_freqs: Vec::new(),
+            core_freqs_read: None,
+            sensors: Vec::new(),
+            throttle_counts: None,
+            sensors_read: None,
+            fans: Vec::new(),
+            fans_held: Vec::new(),
+            core_history: CoreHistory::new(config.refresh.cores.0),
+            draw_history: DrawHistory::new(config.overview.chart_window.0, config.refresh.power.0),
+            audit: Vec::new(),
+            usage: Vec::new(),
+            profile_time: Tracker::default(),
+            charge_history: Vec::new(),
+            // Simulated batteries stay out of the log
+            battery_log: (config.history.log && !sysfs::replaying())
+                .then(|| history::Logger::new(config.history.interval.0)),
+            session_log: (config.history.log && !sysfs::replaying())
+                .then(sessions::Recorder::default),
+            battery_sessions: Vec::new(),
+            sleep_cycles: Vec::new(),
+            wakeups: Vec::new(),
+            mem_sleep: None,
+            hibernate: Vec::new(),
+            typec: Vec::new(),
+            usb: Vec::new(),
+            msr_page: config.ui.msr,
+            msr: Ok(Vec::new()),
+            health: Vec::new(),
+            page_scroll: 0,
+            gauge: Animated::new(config.ui.animate()),
+            battery: None,
+            batteries: Vec::new(),
+            combined: None,
+            charge_first: config
+                .battery
+                .packs
+                .iter()
+                .find(|p| p.charge_first)
+                .map(|p| p.name.clone()),
+            discharge_first: config
+                .battery
+                .packs
+                .iter()
+                .find(|p| p.discharge_first)
+                .map(|p| p.name.clone()),
+            held_batteries: Vec::new(),
+            charger: None,
+            supplies: SupplyMonitor::start(),
+            sleep_monitor: (config.resume.reapply || !config.resume.hooks.is_empty())
+                .then(SleepMonitor::start)
+                .flatten(),
+            resume: config.resume,
+            battery_alerts: config.battery,
+            battery_low: false,
+            charge_anomalies: Vec::new(),
+            lid_closed: None,
+            displays: Vec::new(),
+            peripherals: Vec::new(),
+            cpufreq: Vec::new(),
+            hwp: None,
+            turbo: None,
+            graphics: None,
+            psr_off: false,
+            backlight: None,
+            gpus: Vec::new(),
+            swap: None,
+            rapl: None,
+            rapl_meter: EnergyMeter::default(),
+            rapl_domains: rapl::domains()
+                .into_iter()
+                .map(|d| (d, EnergyMeter::default()))
+                .collect(),
+            domain_power: Vec::new(),
+            package_power: None,
+            noise: NoiseModel::default(),
+            ryzen: None,
+            ecores: steering::ecore_cpus(),
+            steer_patterns: config.steering.patterns,
+            steer_matches: Vec::new(),
+            steered: Vec::new(),
+            nics_down: Vec::new(),
+            bluetooth_idle_since: None,
+            peripheral_alerts: config.peripherals,
+            low_peripherals: Vec::new(),
+            profiles: Vec::new(),
+            governors: Vec::new(),
+            current_profile: None,
+            auto: false,
+            rules: if config.rules.is_empty() {
+                rules::default_rules()
+            } else {
+                config.rules
+            },
+            auto_target: None,
+            auto_reason: None,
+            boost: None,
+            budget: None,
+            quiet: None,
+            presentation: None,
+            travel: None,
+            storage: storage::read(),
+            storage_config: config.storage,
+            persist_profile: config.daemon.persist,
+            storage_reminded: false,
+            calls: config.calls,
+            call: None,
+            editor: None,
+            threshold_editor: None,
+            fan_editor: None,
+            tour: (!tour::seen()).then_some(Tour { step: 0 }),
+            preview: None,
+            selected: 0,
+            list_state: ListState::default(),
+            toasts: Toasts::default(),
+            events: EventLog::default(),
+            journal: JournalTail::default(),
+            show_log: false,
+            show_help: false,
+            keymap,
+            command_line: None,
+            refresh_interval: config.refresh.battery.0,
+            last_refresh: Instant::now(),
+            quit: false,
+            desktop: Desktop::detect(),
+            desktop_settings: None,
+            inhibitors: Vec::new(),
+            sway: Sway::detect(),
+            outputs: Vec::new(),
+            dpms: None,
+            outputs_off: false,
+            simulating: sysfs::replaying(),
+            minimal: config.ui.minimal,
+            rule_blanked: false,
+        };
+        match profile::load_profiles() {
+            Ok(profiles) => app.profiles = profiles,
+            Err(e) => {
+                app.profiles = Profile::builtin();
+                app.error(format!("Ignoring custom profiles: {}", e));
+            }
+        }
+        if let Some(e) = keys_error {
+            app.error(format!("Ignoring [keys]: {}", e));
+        }
+        if let Some(preferred) = backend::preferred() {
+            let active = backend::active().name();
+            if active != preferred {
+                app.notify(
+                    Level::Warning,
+                    format!("Backend {} isn't usable here, using {}", preferred, active),
+                );
+            }
+        }
+        app.list_state.select(Some(0));
+        app.dpms = Dpms::detect(app.sway.as_ref());
+        app.refresh();
+        if start_page != Page::Msr || app.msr_page {
+            app.show_page(start_page);
+        }
+        if app.battery_alerts.sync_alarm {
+            app.sync_alarm();
+        }
+        app.apply_pack_thresholds();
+        app.health = health::check(app.battery.as_ref())
+            .into_iter()
+            .filter(|f| f.check.severity != Severity::Ok)
+            .collect();
+        app
+    }
+
+    /// Show a toast and record it in the event log
+    pub fn notify(&mut self, level: Level, text: String) {
+        self.events.push(level, text.clone());
+        // Nothing pops up over the slides; the event log still has it
+        if self.presentation.is_none() {
+            self.toasts.push(level, text);
+        }
+    }
+
+    /// Within the configured quiet hours
+    pub fn in_quiet_hours(&self) -> bool {
+        self.quiet_hours.is_some_and(|q| q.now())
+    }
+
+    pub fn info(&mut self, text: String) {
+        self.notify(Level::Info, text);
+    }
+
+    pub fn error(&mut self, text: String) {
+        self.notify(Level::Error, text);
+    }
+
+    /// An error worth hearing about away from the machine: also posted
+    /// to the webhook, unless a running daemon is posting its own or
+    /// it's quiet hours or a presentation and the alert can wait
+    fn alert(&mut self, event: &str, text: String) {
+        let held = (self.in_quiet_hours() || self.presentation.is_some())
+            && !webhook::URGENT.contains(&event);
+        if let (Some(webhook), None, false) = (self.webhook.as_ref(), self.daemon.as_ref(), held) {
+            webhook.send(event, &text);
+        }
+        self.error(text);
+    }
+
+    /// Whether an action does anything on this system, so help only
+    /// lists what works
+    pub fn action_available(&self, action: Action) -> bool {
+        match action {
+            Action::BrightnessUp | Action::BrightnessDown => {
+                (self.desktop.is_some() || self.backlight.is_some()) && self.presentation.is_none()
+            }
+            Action::CycleSuspend => self.desktop.is_some(),
+            Action::BlankOutputs => self.dpms.is_some(),
+            Action::TogglePsr => self.graphics.as_ref().is_some_and(|g| g.psr_toggleable()),
+            Action::ToggleTurbo => self.turbo.is_some(),
+            Action::CycleGpuLevel => !self.gpus.is_empty(),
+            Action::SimBatteryUp
+            | Action::SimBatteryDown
+            | Action::SimTempUp
+            | Action::SimTempDown
+            | Action::SimCycleStatus => self.simulating,
+            Action::CycleChargeBehaviour => self.caps.charge_behaviour,
+            Action::EditThresholds => self.caps.charge_thresholds,
+            Action::CycleSleepMode => self.caps.mem_sleep,
+            Action::Quiet => self.rapl.is_some(),
+            Action::Fans => self
+                .fans
+                .iter()
+                .any(|f| f.pwm.as_ref().is_some_and(|p| p.writable)),
+            _ => true,
+        }
+    }
+
+    pub fn perform(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.quit = true,
+            Action::CommandMode => self.command_line = Some(CommandLine::default()),
+            Action::Help => self.show_help = true,
+            Action::Tour => self.start_tour(),
+            Action::Refresh => self.refresh(),
+            Action::ShowLog => self.show_log = true,
+            Action::DismissErrors => {
+                self.toasts.dismiss();
+                self.health.clear();
+            }
+            Action::NextPage => self.switch_page(1),
+            Action::PrevPage => self.switch_page(-1),
+            Action::Down if self.page != Page::Overview => self.page_scroll += 1,
+            Action::Up if self.page != Page::Overview => {
+                self.page_scroll = self.page_scroll.saturating_sub(1)
+            }
+            Action::Down => self.move_down(),
+            Action::Up => self.move_up(),
+            Action::Select => self.preview_profile(),
+            Action::NewProfile => self.open_editor(false),
+            Action::EditProfile => self.open_editor(true),
+            Action::DeleteProfile => self.delete_profile(),
+            Action::Boost if self.boost.is_some() => self.end_boost(),
+            Action::Boost => self.start_boost(DEFAULT_BOOST),
+            Action::BrightnessUp => self.adjust_brightness(10),
+            Action::BrightnessDown => self.adjust_brightness(-10),
+            Action::CycleSuspend => self.cycle_suspend_timeout(),
+            Action::BlankOutputs => self.set_outputs_power(false),
+            Action::TogglePsr => self.toggle_psr(),
+            Action::ToggleTurbo => self.toggle_turbo(),
+            Action::CycleGpuLevel => self.cycle_gpu_level(),
+            Action::SimBatteryUp
+            | Action::SimBatteryDown
+            | Action::SimTempUp
+            | Action::SimTempDown
+            | Action::SimCycleStatus => self.nudge_simulation(action),
+            Action::CycleChargeBehaviour => self.cycle_charge_behaviour(),
+            Action::EditThresholds => self.open_threshold_editor(),
+            Action::Fans => self.open_fan_editor(),
+            Action::Quiet => self.toggle_quiet(),
+            Action::Presentation => self.toggle_presentation(),
+            Action::Travel => self.toggle_travel(),
+            Action::CycleSleepMode => self.cycle_sleep_mode(),
+            Action::CopyStatus => self.copy(self.status_summary(), "status"),
+            Action::CopyRow => {
+                let Some(profile) = self.profiles.get(self.selected) else {
+                    return;
+                };
+                let row = format!("{}\t{}", profile.name, profile.governor);
+                self.copy(row, "profile row");
+            }
+        }
+    }
+
+    /// One-line summary of battery and profile, for pasting elsewhere
+    pub fn status_summary(&self) -> String {
+        let mut parts = Vec::new();
+        match self.combined {
+            Some(ref bat) => {
+                let mut battery = format!("Battery {}% {}", bat.capacity, bat.status);
+                if let Some(ref t) = bat.time_remaining {
+                    battery.push_str(&format!(" ({})", t));
+                }
+                parts.push(battery);
+                if let Some(power) = bat.power {
+                    parts.push(format!("{:.1} W", power));
+                }
+                if let Some(health) = bat.health {
+                    parts.push(format!("Health {}%", health));
+                }
+            }
+            None => parts.push("No battery".to_string()),
+        }
+        if let Some(profile) = self.current_profile.and_then(|i| self.profiles.get(i)) {
+            let auto = if self.auto { ", automatic" } else { "" };
+            parts.push(format!(
+                "Profile {} ({}{})",
+                profile.name, profile.governor, auto
+            ));
+        }
+        parts.join(" · ")
+    }
+
+    fn copy(&mut self, text: String, what: &str) {
+        match clipboard::copy(&text) {
+            Ok(()) => self.info(format!("Copied {} to clipboard", what)),
+            Err(e) => self.error(format!("Copy failed: {}", e)),
+        }
+    }
+
+    /// Pages in tab order, leaving out the ones that are opt-in
+    pub fn pages(&self) -> Vec<Page> {
+        Page::ALL
+            .into_iter()
+            .filter(|p| *p != Page::Msr || self.msr_page)
+            .collect()
+    }
+
+    fn switch_page(&mut self, delta: isize) {
+        let pages = self.pages();
+        let len = pages.len() as isize;
+        let i = pages.iter().position(|p| *p == self.page).unwrap_or(0) as isize;
+        self.show_page(pages[(i + delta).rem_euclid(len) as usize]);
+    }
+
+    pub fn show_page(&mut self, page: Page) {
+        self.page = page;
+        self.page_scroll = 0;
+        match page {
+            Page::Overview | Page::Cpu => {}
+            // Start the graphs over rather than join across the gap
+            Page::Cores => self.core_history = CoreHistory::new(self.rates.cores.0),
+            Page::Sleep => {
+                self.sleep_cycles = sleep::read_cycles();
+                self.wakeups = wake::read_wakeups();
+                self.hibernate = hibernate::check();
+            }
+            Page::Ports => {
+                self.typec = typec::read_ports();
+                self.usb = usb::read_devices();
+            }
+            Page::History => {
+                self.audit = audit::read_entries();
+                self.profile_time.flush();
+                self.usage = usage::read_week();
+                self.charge_history = history::read_samples();
+                self.battery_sessions = sessions::compare(&sessions::read_sessions());
+            }
+            Page::Msr => self.msr = msr::read_registers(),
+        }
+    }
+
+    pub fn handle_command_key(&mut self, code: KeyCode) {
+        let mut names: Vec<String> = self.profiles.iter().map(|p| p.name.clone()).collect();
+        names.push(AUTO_NAME.to_string());
+        let Some(ref mut line) = self.command_line else {
+            return;
+        };
+        match line.handle_key(code, &names) {
+            LineAction::None => {}
+            LineAction::Cancel => self.command_line = None,
+            LineAction::Submit(input) => {
+                self.command_line = None;
+                if !input.trim().is_empty() {
+                    self.run_command(&input);
+                }
+            }
+        }
+    }
+
+    fn run_command(&mut self, input: &str) {
+        let command = match command::parse(input) {
+            Ok(command) => command,
+            Err(e) => return self.error(e),
+        };
+        match command {
+            Command::Profile(name) => {
+                let wanted = name.to_lowercase();
+                if wanted == "auto" || wanted == "automatic" {
+                    return self.enable_auto();
+                }
+                let found = self.profiles.iter().position(|p| {
+                    p.name.to_lowercase() == wanted
+                        || p.name.to_lowercase().replace(' ', "-") == wanted
+                        || p.governor == wanted
+                });
+                match found {
+                    Some(i) => {
+                        self.selected = i;
+                        self.list_state.select(Some(i));
+                        self.select_profile();
+                    }
+                    None => self.error(format!("No profile named {}", name)),
+                }
+            }
+            Command::Boost(duration) => self.start_boost(duration),
+            Command::Budget { runtime, cap } => self.start_budget(runtime, cap),
+            Command::EndBudget => self.end_budget(),
+            Command::Storage => self.start_storage(),
+            Command::EndStorage => self.end_storage(),
+            Command::SetRefresh(interval) => {
+                self.refresh_interval = interval;
+                self.info(format!("Refreshing every {:?}", interval));
+            }
+            Command::SetAlarm(percent) => {
+                self.battery_alerts.low = percent;
+                self.info(format!("Low battery warning at {}%", percent));
+                self.sync_alarm();
+            }
+            Command::SetThresholds {
+                battery,
+                start,
+                end,
+            } => {
+                self.set_thresholds(&battery, start, end);
+                self.refresh();
+            }
+            Command::SetChargeFirst(battery) => {
+                if let Some(ref name) = battery {
+                    if !self.batteries.iter().any(|b| &b.name == name) {
+                        return self.error(format!("No battery named {}", name));
+                    }
+                }
+                self.charge_first = battery;
+                self.refresh();
+            }
+            Command::SetDischargeFirst(battery) => {
+                if let Some(ref name) = battery {
+                    let Some(found) = self.batteries.iter().find(|b| &b.name == name) else {
+                        return self.error(format!("No battery named {}", name));
+                    };
+                    if !found.supports("force-discharge") {
+                        return self.error(format!("{} can't be forced to discharge", name));
+                    }
+                }
+                self.discharge_first = battery;
+                self.refresh();
+            }
+            // Each policy clamps to its own range, so the hardware's
+            // bound is just the widest value there is
+            Command::SetMinFreq(mhz) | Command::SetMaxFreq(mhz) => {
+                let min = matches!(command, Command::SetMinFreq(_));
+                if let (Some(mhz), Some((low, high))) = (mhz, cpufreq::hardware_range()) {
+                    let (low, high) = (low / 1000, high / 1000);
+                    if !(low..=high).contains(&mhz) {
+                        return self.error(format!("Frequency must be {}–{} MHz", low, high));
+                    }
+                }
+                if min {
+                    self.set_freq_limits(Some(mhz.map_or(0, |m| m * 1000)), None)
+                } else {
+                    self.set_freq_limits(None, Some(mhz.map_or(u64::MAX, |m| m * 1000)))
+                }
+            }
+            Command::SetPowerLimit(limit, watts) => self.set_power_limit(limit, watts),
+            Command::SetGpuLevel(level) => self.set_gpu_level(level),
+            Command::SetBrightness(percent) => {
+                if self.brightness().is_none() {
+                    return self.error("No desktop or backlight to set brightness".to_string());
+                }
+                if self.presentation.is_some() {
+                    return self.error("Brightness is pinned while presenting".to_string());
+                }
+                self.set_brightness(percent);
+            }
+            Command::Export { format, path } => self.export(&format, path),
+            Command::Simulate(change) => self.simulate(&change),
+            Command::Refresh => self.refresh(),
+            Command::Log => self.show_log = true,
+            Command::Help => self.show_help = true,
+            Command::Quit => self.quit = true,
+        }
+    }
+
+    /// Draw by RAPL, for machines without a battery to measure: the whole
+    /// platform where the firmware reports it, else the CPU package
+    pub fn rapl_power(&self) -> Option<f64> {
+        self.domain_power
+            .iter()
+            .find(|(name, _)| *name == "psys")
+            .map(|(_, watts)| *watts)
+            .or(self.package_power)
+    }
+
+    /// Change a RAPL limit for the session, outside any profile
+    fn set_power_limit(&mut self, limit: Limit, watts: f64) {
+        let Some(ref package) = self.rapl else {
+            return self.error("No RAPL package zone for power limits".to_string());
+        };
+        if let Some(max) = package.max(limit).filter(|max| watts > *max) {
+            return self.error(format!("{} must be at most {:.0} W", limit.label(), max));
+        }
+        match package.set_limit(limit, watts) {
+            Ok(()) => self.info(format!("{} set to {:.1} W", limit.label(), watts)),
+            Err(e) => self.error(e),
+        }
+    }
+
+    /// Force every GPU to a level for the session
+    fn set_gpu_level(&mut self, level: gpu::Level) {
+        if self.gpus.is_empty() {
+            return self.error("No amdgpu or i915 GPU".to_string());
+        }
+        let errors: Vec<String> = self
+            .gpus
+            .iter()
+            .filter_map(|gpu| {
+                gpu::set_level(gpu, level)
+                    .err()
+                    .map(|e| format!("{}: {}", gpu.card, e))
+            })
+            .collect();
+        self.gpus = gpu::read_gpus();
+        if !errors.is_empty() {
+            return self.error(errors.join("; "));
+        }
+        if !privileged::dry_run() {
+            self.info(format!("GPU level {}", level.name()));
+        }
+    }
+
+    /// Step every GPU through low, auto and high, going by the first
+    fn cycle_gpu_level(&mut self) {
+        let next = match self.gpus.first().and_then(|g| g.level()) {
+            Some(level) => level.next(),
+            None => gpu::Level::Auto,
+        };
+        self.set_gpu_level(next);
+    }
+
+    /// Change the frequency limits for the session, outside any profile
+    fn set_freq_limits(&mut self, min: Option<u64>, max: Option<u64>) {
+        if let Err(e) = cpufreq::set_limits(min, max) {
+            return self.error(e);
+        }
+        self.cpufreq = cpufreq::read_policies();
+        if privileged::dry_run() {
+            return;
+        }
+        if let Some((min, max)) = cpufreq::read_limits() {
+            self.info(format!(
+                "Frequency limits {}–{} MHz",
+                min / 1000,
+                max / 1000
+            ));
+        }
+    }
+
+    /// Write the current status as a CSV header and row
+    fn export(&mut self, format: &str, path: Option<String>) {
+        if format == "sources" {
+            return self.export_sources(path);
+        }
+        let path = path.unwrap_or_else(|| format!("powertui-export.{}", format));
+        let profile = self.current_profile.and_then(|i| self.profiles.get(i));
+        let bat = self.combined.as_ref();
+        let field = |v: Option<String>| v.unwrap_or_default();
+        let row = [
+            Local::now().to_rfc3339(),
+            field(bat.map(|b| b.capacity.to_string())),
+            field(bat.map(|b| b.status.clone())),
+            field(bat.and_then(|b| b.power).map(|p| format!("{:.2}", p))),
+            field(bat.and_then(|b| b.health).map(|h| h.to_string())),
+            field(profile.map(|p| p.name.clone())),
+            field(profile.map(|p| p.governor.clone())),
+        ];
+        let csv = format!(
+            "time,capacity,status,power_w,health,profile,governor\n{}\n",
+            row.iter()
+                .map(|v| csv_field(v))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        match fs::write(&path, csv) {
+            Ok(()) => self.info(format!("Exported to {}", path)),
+            Err(e) => self.error(format!("Export failed: {}", e)),
+        }
+    }
+
+    /// One CSV row per battery and UPS
+    fn export_sources(&mut self, path: Option<String>) {
+        let path = path.unwrap_or_else(|| "powertui-sources.csv".to_string());
+        let sources = self.sources();
+        let mut csv =
+            "source,kind,charge,health,cycles,energy_wh,runtime_s,runtime_share\n".to_string();
+        for (source, share) in sources.iter().zip(sources::shares(&sources)) {
+            let field = |v: Option<String>| v.unwrap_or_default();
+            let row = [
+                source.name.clone(),
+                source.kind.to_string(),
+                field(source.charge.map(|c| format!("{:.0}", c))),
+                field(source.health.map(|h| h.to_string())),
+                field(source.cycles.map(|c| c.to_string())),
+                field(source.energy.map(|e| format!("{:.1}", e))),
+                field(source.runtime.map(|r| r.to_string())),
+                field(share.map(|s| format!("{:.0}", s))),
+            ];
+            csv.push_str(
+                &row.iter()
+                    .map(|v| csv_field(v))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+            csv.push('\n');
+        }
+        match fs::write(&path, csv) {
+            Ok(()) => self.info(format!("Exported {} sources to {}", sources.len(), path)),
+            Err(e) => self.error(format!("Export failed: {}", e)),
+        }
+    }
+
+    /// Refresh if the interval has passed and advance animations,
+    /// called every loop tick
+    pub fn tick(&mut self) {
+        for op in privileged::drain_skipped() {
+            self.info(format!("[dry-run] {}", op));
+        }
+        for failure in webhook::drain_failed() {
+            self.notify(Level::Warning, failure);
+        }
+        if self.boost.as_ref().is_some_and(|b| b.remaining().is_zero()) {
+            self.end_boost();
+        }
+        let events = self
+            .supplies
+            .as_ref()
+            .map(|m| m.drain())
+            .unwrap_or_default();
+        for event in &events {
+            match event.action.as_str() {
+                "add" => self.info(format!("Power supply {} connected", event.name)),
+                "remove" => self.info(format!("Power supply {} removed", event.name)),
+                _ => {}
+            }
+        }
+        // A swapped battery may support different controls
+        if events.iter().any(|e| e.action != "change") {
+            self.caps = Capabilities::probe();
+        }
+        // A running daemon takes care of resume itself
+        if self.sleep_monitor.as_ref().is_some_and(|m| m.resumed()) && self.daemon.is_none() {
+            self.on_resume();
+        }
+        // Settings changed from the command line, another instance or the
+        // daemon's rules show up on the next frame rather than the next
+        // refresh. This process's own changes are already on screen, so
+        // nothing a refresh writes can set off another.
+        let mut changed = false;
+        if let Some(ref stream) = self.stream {
+            match stream.drain() {
+                Some(messages) => {
+                    for message in messages.into_iter().filter(|m| !m.own()) {
+                        match message {
+                            Message::Changed { .. } => changed = true,
+                            Message::State(state) => self.daemon = Some(state),
+                        }
+                    }
+                }
+                // The daemon stopped
+                None => {
+                    self.stream = None;
+                    self.daemon = None;
+                }
+            }
+        }
+        if changed && self.page == Page::History {
+            self.audit = audit::read_entries();
+        }
+        if changed || !events.is_empty() || self.last_refresh.elapsed() >= self.refresh_interval {
+            self.refresh();
+        }
+        // Fast-moving readings keep their own pace, and only while
+        // something on screen shows them
+        match self.page {
+            Page::Overview => {
+                if self.shows_widget(Widget::Temps)
+                    && due(&mut self.temps_read, self.rates.thermal.0)
+                {
+                    self.temps = thermal::read_zones();
+                }
+                if self.shows_widget(Widget::Cores)
+                    && due(&mut self.core_freqs_read, self.rates.cores.0)
+                {
+                    self.core_freqs = cores::read_frequencies();
+                }
+            }
+            Page::Cpu if due(&mut self.sensors_read, self.rates.thermal.0) => {
+                self.sensors = thermal::read_sensors();
+                self.throttle_counts = thermal::read_throttle_counts();
+                self.fans = fans::read_fans();
+            }
+            Page::Cores => self.core_history.sample(),
+            _ => {}
+        }
+        // Kept up off the Overview too, so the chart has no gap on return
+        if self.shows_widget(Widget::Chart) && self.draw_history.due() {
+            let power = battery::combined(&battery::read_batteries())
+                .and_then(|b| b.power)
+                .or(self.rapl_power());
+            self.draw_history.push(power);
+        }
+        self.gauge.step();
+    }
+
+    /// How long the main loop may wait for a key before the next tick
+    pub fn poll_timeout(&self) -> Duration {
+        // Redraw at ~30fps only while something is animating
+        if self.animating() {
+            return Duration::from_millis(33);
+        }
+        let mut timeout = self.rates.redraw.0;
+        let shown = |widget| self.page == Page::Overview && self.shows_widget(widget);
+        if shown(Widget::Temps) {
+            timeout = timeout.min(self.rates.thermal.0);
+        }
+        if shown(Widget::Cores) || self.page == Page::Cores {
+            timeout = timeout.min(self.rates.cores.0);
+        }
+        timeout
+    }
+
+    pub fn shows_widget(&self, widget: Widget) -> bool {
+        self.overview.iter().any(|s| s.widget == widget)
+    }
+
+    /// Put back what firmware tends to reset across suspend, then run
+    /// the configured hooks
+    fn on_resume(&mut self) {
+        // Taken before refreshing, which would pick the profile matching
+        // whatever governor firmware left behind
+        let current = self
+            .current_profile
+            .and_then(|i| Some((i, self.profiles.get(i)?.clone())));
+        self.restore_mem_sleep();
+        self.refresh();
+        if self.resume.reapply {
+            if let Some((index, profile)) = current {
+                self.info(format!("Resumed: applying {} again", profile.name));
+                self.switch_to(index, &profile);
+            }
+            self.apply_pack_thresholds();
+        }
+        for e in resume::run_hooks(&self.resume.hooks) {
+            self.error(format!("Resume hook failed: {}", e));
+        }
+        self.refresh();
+    }
+
+    /// Charge thresholds from `[[battery.pack]]`
+    fn apply_pack_thresholds(&mut self) {
+        let thresholds: Vec<(String, Option<u8>, u8)> = self
+            .battery_alerts
+            .packs
+            .iter()
+            .filter_map(|p| Some((p.name.clone(), p.start_threshold, p.end_threshold?)))
+            .collect();
+        for (name, start, end) in thresholds {
+            self.set_thresholds(&name, start, end);
+        }
+    }
+
+    /// Warn once per outage when the UPS runtime drops to the threshold,
+    /// and power off then if the config asks for it
+    fn check_ups(&mut self) {
+        let Some(ref config) = self.ups_config else {
+            return;
+        };
+        let (threshold, shutdown) = (config.low_runtime, config.shutdown);
+        let Some(runtime) = self
+            .ups
+            .as_ref()
+            .filter(|u| u.on_battery())
+            .and_then(|u| u.runtime)
+            .filter(|&r| r <= threshold)
+        else {
+            self.ups_low = false;
+            return;
+        };
+        if self.ups_low {
+            return;
+        }
+        self.ups_low = true;
+        self.alert(
+            "ups_low",
+            format!("UPS runtime low: {} left", locale::duration(runtime as u64)),
+        );
+        if shutdown {
+            self.power_action("poweroff", "low UPS runtime");
+        }
+    }
+
+    /// A laptop that heats up with its lid closed on battery is likely
+    /// running in a bag. Act once per lid close when the CPU passes the
+    /// threshold and has climbed by `rise` since the lid closed.
+    fn check_bag(&mut self) {
+        let Some(ref config) = self.bag_config else {
+            return;
+        };
+        let (limit, rise, action) = (config.temp, config.rise, config.action);
+        let discharging = self
+            .combined
+            .as_ref()
+            .is_some_and(|b| b.status == "Discharging");
+        let temp = thermal::read_cpu_temp();
+        let (Some(true), true, Some(temp)) = (self.lid_closed, discharging, temp) else {
+            self.bag_start = None;
+            self.bag_fired = false;
+            return;
+        };
+        let start = *self.bag_start.get_or_insert(temp);
+        if self.bag_fired || temp < limit || temp - start < rise {
+            return;
+        }
+        self.bag_fired = true;
+        self.alert(
+            "bag",
+            format!(
+                "Lid closed on battery and CPU at {:.0}°C (up {:.0}°C)",
+                temp,
+                temp - start
+            ),
+        );
+        match action {
+            BagAction::Hibernate => self.power_action("hibernate", "a hot closed lid"),
+            BagAction::Suspend => {
+                let mem_sleep = sleep::read_mem_sleep();
+                let deep = mem_sleep.as_ref().is_some_and(|m| {
+                    m.options.iter().any(|o| o == "deep") && m.current.as_deref() != Some("deep")
+                });
+                if deep {
+                    match sleep::set_mem_sleep("deep") {
+                        Ok(()) => self.bag_mem_sleep = mem_sleep.and_then(|m| m.current),
+                        Err(e) => self.error(e),
+                    }
+                }
+                self.power_action("suspend", "a hot closed lid");
+            }
+        }
+    }
+
+    /// `systemctl poweroff`, `suspend` or `hibernate`, audited like
+    /// privileged changes. logind lets the active session do any of them
+    /// without root.
+    fn power_action(&mut self, verb: &str, reason: &str) {
+        let command = format!("systemctl {}", verb);
+        if privileged::dry_run() {
+            audit::record("power", &command, None, verb, "dry-run");
+            return self.info(format!("Dry run: would {} for {}", verb, reason));
+        }
+        let outcome = match std::process::Command::new("systemctl").arg(verb).status() {
+            Ok(status) if status.success() => "ok".to_string(),
+            Ok(_) => "failed: systemctl refused".to_string(),
+            Err(e) => format!("failed: {}", e),
+        };
+        audit::record("power", &command, None, verb, &outcome);
+        if outcome == "ok" {
+            self.info(format!("Running {} for {}", command, reason));
+        } else {
+            self.error(format!("Could not {}: {}", verb, outcome));
+        }
+    }
+
+    /// Warn once per discharge when the battery reaches the low threshold
+    fn check_battery_low(&mut self) {
+        let low = self.battery_alerts.low;
+        let capacity = match self.combined {
+            Some(ref bat) if bat.status == "Discharging" && bat.capacity <= low => bat.capacity,
+            _ => {
+                self.battery_low = false;
+                return;
+            }
+        };
+        if !self.battery_low {
+            self.battery_low = true;
+            self.alert("battery_low", format!("Battery low: {}%", capacity));
+        }
+    }
+
+    /// Point the firmware alarm at powertui's low-battery threshold, so
+    /// the desktop's own warning fires at the same level
+    fn sync_alarm(&mut self) {
+        let low = self.battery_alerts.low;
+        let Some(battery) = self.battery.as_ref().filter(|b| b.alarm.is_some()) else {
+            return;
+        };
+        if battery.alarm == Some(low) {
+            return;
+        }
+        match battery::set_alarm(battery, low) {
+            Ok(()) => {
+                self.info(format!("Battery alarm set to {}%", low));
+                self.battery = read_battery_info();
+            }
+            Err(e) => self.error(format!("Battery alarm: {}", e)),
+        }
+    }
+
+    /// Write charge thresholds for one battery
+    fn set_thresholds(&mut self, name: &str, start: Option<u8>, end: u8) {
+        let Some(battery) = self.batteries.iter().find(|b| b.name == name) else {
+            return self.error(format!("No battery named {}", name));
+        };
+        match battery::set_thresholds(battery, start, end) {
+            Ok(()) => self.info(format!(
+                "{} charges {}",
+                name,
+                match start {
+                    Some(start) => format!("from {}% to {}%", start, end),
+                    None => format!("up to {}%", end),
+                }
+            )),
+            Err(e) => self.error(e),
+        }
+    }
+
+    /// Charge thresholds of the first battery that has them
+    pub fn open_threshold_editor(&mut self) {
+        match self.batteries.iter().find(|b| b.end_threshold.is_some()) {
+            Some(battery) => self.threshold_editor = Some(ThresholdEditor::new(battery)),
+            None => self.error("No battery supports charge thresholds".to_string()),
+        }
+    }
+
+    pub fn handle_threshold_key(&mut self, code: KeyCode) {
+        let Some(ref mut editor) = self.threshold_editor else {
+            return;
+        };
+        match editor.handle_key(code) {
+            EditorAction::None => {}
+            EditorAction::Cancel => self.threshold_editor = None,
+            EditorAction::Save => match editor.values() {
+                Ok((start, end)) => {
+                    let battery = editor.battery.clone();
+                    self.threshold_editor = None;
+                    self.set_thresholds(&battery, start, end);
+                    self.refresh();
+                }
+                Err(e) => self.error(e),
+            },
+        }
+    }
+
+    /// Start the guided tour from the beginning
+    pub fn start_tour(&mut self) {
+        self.show_help = false;
+        self.tour = Some(Tour { step: 0 });
+        self.show_page(tour::STEPS[0].page);
+    }
+
+    /// Step through the tour; leaving it, or finishing, means it isn't
+    /// shown on its own again
+    pub fn handle_tour_key(&mut self, code: KeyCode) {
+        let Some(ref mut tour) = self.tour else {
+            return;
+        };
+        match code {
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ' | 'l') if tour.is_last() => {
+                self.end_tour()
+            }
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ' | 'l') => tour.step += 1,
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => {
+                tour.step = tour.step.saturating_sub(1)
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.end_tour(),
+            _ => return,
+        }
+        if let Some(page) = self.tour.as_ref().map(|t| t.current().page) {
+            if self.pages().contains(&page) {
+                self.show_page(page);
+            }
+        }
+    }
+
+    fn end_tour(&mut self) {
+        self.tour = None;
+        tour::mark_seen();
+    }
+
+    /// Put back the suspend mode a bag suspend replaced with `deep`
+    pub fn restore_mem_sleep(&mut self) {
+        if let Some(mode) = self.bag_mem_sleep.take() {
+            if let Err(e) = sleep::set_mem_sleep(&mode) {
+                self.error(e);
+            }
+        }
+    }
+
+    /// Hand back fans taken off their curve, so none is left at a
+    /// fixed speed with nothing watching the heat
+    pub fn release_fans(&mut self) {
+        for pwm in self.fans_held.drain(..) {
+            let _ = fans::restore(&pwm);
+        }
+    }
+
+    pub fn open_fan_editor(&mut self) {
+        self.fans = fans::read_fans();
+        let editor = FanEditor::new(&self.fans);
+        if editor.fans.is_empty() {
+            self.error("No fan with writable PWM control".to_string());
+        } else {
+            self.fan_editor = Some(editor);
+        }
+    }
+
+    pub fn handle_fan_key(&mut self, code: KeyCode) {
+        let Some(ref mut editor) = self.fan_editor else {
+            return;
+        };
+        match editor.handle_key(code) {
+            EditorAction::None => {}
+            EditorAction::Cancel => self.fan_editor = None,
+            EditorAction::Save => {
+                let Some(editor) = self.fan_editor.take() else {
+                    return;
+                };
+                for fan in editor.fans.iter().filter(|f| f.changed()) {
+                    let held = self.fans_held.iter().position(|p| p.is(&fan.pwm));
+                    let result = match (fan.manual, held) {
+                        (true, _) => fans::set_manual(&fan.pwm, fan.percent),
+                        (false, Some(i)) => fans::restore(&self.fans_held.remove(i)),
+                        (false, None) => fans::set_automatic(&fan.pwm),
+                    };
+                    // Remembered as it was, to hand back on quit
+                    if result.is_ok() && fan.manual && held.is_none() && !fan.pwm.manual() {
+                        self.fans_held.push(fan.pwm.clone());
+                    }
+                    match result {
+                        Ok(()) if fan.manual => {
+                            self.info(format!("{} held at {}%", fan.name, fan.percent))
+                        }
+                        Ok(()) => self.info(format!("{} back on automatic", fan.name)),
+                        Err(e) => self.error(format!("{}: {}", fan.name, e)),
+                    }
+                }
+                self.fans = fans::read_fans();
+            }
+        }
+    }
+
+    /// Charge and discharge order on dual-battery machines, done the way
+    /// thinkpad_acpi allows: the other batteries are held in
+    /// `inhibit-charge` while the preferred one fills, and the preferred
+    /// one is forced to discharge while running on battery
+    fn prioritize_batteries(&mut self) {
+        if self.batteries.len() < 2 && self.held_batteries.is_empty() {
+            return;
+        }
+        let on_ac = self.charger.is_some();
+        let mut wanted: Vec<(&str, &'static str)> = Vec::new();
+        if let Some(first) = self.charge_first.as_deref().filter(|_| on_ac) {
+            let filling = self.batteries.iter().any(|b| {
+                b.name == first && b.status != "Full" && b.reported < b.end_threshold.unwrap_or(100)
+            });
+            if filling {
+                for other in &self.batteries {
+                    if other.name != first && other.supports("inhibit-charge") {
+                        wanted.push((&other.name, "inhibit-charge"));
+                    }
+                }
+            }
+        }
+        if let Some(first) = self.discharge_first.as_deref().filter(|_| !on_ac) {
+            let draining = self.batteries.iter().any(|b| {
+                b.name == first
+                    && b.supports("force-discharge")
+                    && b.capacity > FORCE_DISCHARGE_FLOOR
+            });
+            if draining {
+                wanted.push((first, "force-discharge"));
+            }
+        }
+
+        let mut held = Vec::new();
+        let mut error = None;
+        for battery in &self.batteries {
+            let was_held = self.held_batteries.contains(&battery.name);
+            let mode = wanted
+                .iter()
+                .find(|(name, _)| *name == battery.name)
+                .map(|(_, mode)| *mode);
+            let result = match mode {
+                Some(mode) if battery.behaviour() == Some(mode) => {
+                    if was_held {
+                        held.push(battery.name.clone());
+                    }
+                    continue;
+                }
+                // Leave alone anything set by hand
+                Some(_) if !was_held && battery.behaviour() != Some("auto") => continue,
+                Some(mode) => {
+                    held.push(battery.name.clone());
+                    battery::set_charge_behaviour(battery, mode)
+                }
+                None if was_held => battery::set_charge_behaviour(battery, "auto"),
+                None => continue,
+            };
+            if let Err(e) = result {
+                error = Some(e);
+            }
+        }
+        self.held_batteries = held;
+        // Stop rather than retry a failing write every refresh
+        if let Some(e) = error {
+            self.charge_first = None;
+            self.discharge_first = None;
+            self.error(format!("Battery priority turned off: {}", e));
+        }
+    }
+
+    /// Put batteries held by the priorities back to auto
+    pub fn release_batteries(&mut self) {
+        self.charge_first = None;
+        self.discharge_first = None;
+        self.prioritize_batteries();
+    }
+
+    /// Notify when a charging anomaly first shows up; it stays listed in
+    /// the charger widget for as long as it lasts
+    fn check_charging(&mut self) {
+        let Some(ref battery) = self.battery else {
+            self.charge_anomalies.clear();
+            return;
+        };
+        let found = charging::anomalies(
+            &charging::read_history(),
+            battery,
+            self.charger.as_ref(),
+            Local::now().timestamp(),
+        );
+        let new: Vec<String> = found
+            .iter()
+            .filter(|a| !self.charge_anomalies.iter().any(|o| o.kind == a.kind))
+            .map(|a| a.text.clone())
+            .collect();
+        self.charge_anomalies = found;
+        for text in new {
+            self.alert("charging", text);
+        }
+    }
+
+    /// Warn once per device when it drops to the low-battery threshold
+    fn check_peripherals(&mut self) {
+        if !self.peripheral_alerts.alerts {
+            return;
+        }
+        let threshold = self.peripheral_alerts.low_battery as f64;
+        let mut low = Vec::new();
+        for device in &self.peripherals {
+            if device.percentage > threshold {
+                continue;
+            }
+            if !self.low_peripherals.contains(&device.path) {
+                low.push(format!(
+                    "{} battery low: {:.0}%",
+                    device.name, device.percentage
+                ));
+            }
+        }
+        self.low_peripherals = self
+            .peripherals
+            .iter()
+            .filter(|d| d.percentage <= threshold)
+            .map(|d| d.path.clone())
+            .collect();
+        for text in low {
+            self.alert("peripheral_low", text);
+        }
+    }
+
+    /// Say once when the CPU runs past `[gauges] temp_hot`, and again
+    /// only after it has cooled below `temp_warm`
+    fn check_thermal(&mut self) {
+        let Some(temp) = thermal::read_cpu_temp() else {
+            return;
+        };
+        if temp < self.gauges.temp_warm {
+            self.running_hot = false;
+        } else if temp >= self.gauges.temp_hot && !self.running_hot {
+            self.running_hot = true;
+            self.alert("thermal", format!("CPU running hot: {:.0}°C", temp));
+        }
+    }
+
+    /// Keep matching processes on the E-cores while on battery, and let
+    /// them go again on AC
+    fn steer(&mut self) {
+        let Some(ecores) = self.ecores.clone() else {
+            return;
+        };
+        if self.steer_patterns.is_empty() {
+            return;
+        }
+        self.steer_matches = steering::matching(&self.steer_patterns);
+        let on_battery = self
+            .battery
+            .as_ref()
+            .is_some_and(|b| b.status == "Discharging");
+        if !on_battery {
+            return self.release_steering();
+        }
+
+        let mut errors = Vec::new();
+        for process in &self.steer_matches {
+            if self.steered.iter().any(|s| s.pid == process.pid) {
+                continue;
+            }
+            // Processes sharing an app unit are covered by its cpuset
+            let unit_done =
+                process.unit.is_some() && self.steered.iter().any(|s| s.unit == process.unit);
+            if !unit_done {
+                if let Err(e) = steering::restrict(process, Some(&ecores)) {
+                    errors.push(e);
+                }
+            }
+            // Recorded even on failure so it isn't retried every refresh
+            self.steered.push(process.clone());
+        }
+        for e in errors {
+            self.error(e);
+        }
+    }
+
+    /// Lift E-core restrictions from every steered process that's
+    /// still running
+    pub fn release_steering(&mut self) {
+        let mut released_units = Vec::new();
+        let mut errors = Vec::new();
+        for process in self.steered.drain(..) {
+            if !Path::new(&format!("/proc/{}", process.pid)).exists() {
+                continue;
+            }
+            if let Some(ref unit) = process.unit {
+                if released_units.contains(unit) {
+                    continue;
+                }
+                released_units.push(unit.clone());
+            }
+            if let Err(e) = steering::restrict(&process, None) {
+                errors.push(e);
+            }
+        }
+        for e in errors {
+            self.error(e);
+        }
+    }
+
+    /// The active profile's link and Bluetooth tweaks, which depend on
+    /// the power source and idle time rather than being set once
+    fn network_power(&mut self) {
+        let profile = self.current_profile.and_then(|i| self.profiles.get(i));
+        let nics_down = profile.and_then(|p| p.nics_down).unwrap_or(false);
+        let bluetooth_idle = profile.and_then(|p| p.bluetooth_idle);
+        let on_battery = self
+            .battery
+            .as_ref()
+            .is_some_and(|b| b.status == "Discharging");
+
+        if nics_down && on_battery {
+            let mut errors = Vec::new();
+            for nic in netpower::wired_nics() {
+                if !nic.up || nic.carrier || self.nics_down.iter().any(|n| n.name == nic.name) {
+                    continue;
+                }
+                if let Err(e) = netpower::set_link(&nic, false) {
+                    errors.push(e);
+                }
+                // Recorded even on failure so it isn't retried every refresh
+                self.nics_down.push(nic);
+            }
+            for e in errors {
+                self.error(e);
+            }
+        } else {
+            self.restore_nics();
+        }
+
+        let Some(minutes) = bluetooth_idle else {
+            self.bluetooth_idle_since = None;
+            return;
+        };
+        if netpower::bluetooth_connections() != Some(0) {
+            self.bluetooth_idle_since = None;
+            return;
+        }
+        let since = *self.bluetooth_idle_since.get_or_insert_with(Instant::now);
+        if since.elapsed() < Duration::from_secs(minutes * 60) {
+            return;
+        }
+        self.bluetooth_idle_since = None;
+        let radios = rfkill::read_radios();
+        let Some(radio) = radios
+            .iter()
+            .find(|r| r.kind == "bluetooth" && !r.soft_blocked)
+        else {
+            return;
+        };
+        match rfkill::set_blocked(radio, true) {
+            Ok(()) => self.info(format!(
+                "Bluetooth off after {} min with nothing connected",
+                minutes
+            )),
+            Err(e) => self.error(e),
+        }
+    }
+
+    /// Bring back up the interfaces taken down on battery
+    pub fn restore_nics(&mut self) {
+        let mut errors = Vec::new();
+        for nic in self.nics_down.drain(..) {
+            let nic = Nic { up: false, ..nic };
+            if let Err(e) = netpower::set_link(&nic, true) {
+                errors.push(e);
+            }
+        }
+        for e in errors {
+            self.error(e);
+        }
+    }
+
+    /// Object paths of peripherals at or below the alert threshold
+    pub fn low_peripherals(&self) -> impl Iterator<Item = &str> {
+        self.low_peripherals.iter().map(String::as_str)
+    }
+
+    /// Batteries and the UPS, for comparing side by side
+    pub fn sources(&self) -> Vec<Source> {
+        let ups = self
+            .ups_config
+            .as_ref()
+            .zip(self.ups.as_ref())
+            .map(|(config, status)| (config.name.as_str(), status));
+        sources::collect(&self.batteries, ups)
+    }
+
+    /// Set when the machine discharges while plugged in
+    pub fn charger_warning(&self) -> Option<String> {
+        charger::shortfall_warning(
+            self.charger.as_ref()?,
+            self.combined.as_ref()?,
+            &self.batteries,
+        )
+    }
+
+    pub fn animating(&self) -> bool {
+        self.gauge.is_moving()
+    }
+
+    pub fn refresh(&mut self) {
+        self.last_refresh = Instant::now();
+        // Credit the time since the last refresh before looking again.
+        // Simulated machines don't run real profiles, and a running
+        // daemon counts its own.
+        if !self.simulating {
+            let current = self
+                .current_profile
+                .and_then(|i| self.profiles.get(i))
+                .filter(|_| self.daemon.is_none());
+            self.profile_time.tick(current.map(|p| p.name.as_str()));
+        }
+        self.batteries = battery::read_batteries();
+        self.battery = self.batteries.first().cloned();
+        self.combined = battery::combined(&self.batteries);
+        self.charger = charger::read_charger();
+        self.guard_force_discharge();
+        self.prioritize_batteries();
+        self.check_charging();
+        self.check_battery_low();
+        // A running daemon sends its own, and nothing pops up over slides
+        if let (Some(notifier), None, None) = (
+            self.notifier.as_mut(),
+            self.daemon.as_ref(),
+            self.presentation.as_ref(),
+        ) {
+            notifier.update(
+                self.combined.as_ref(),
+                self.charger.is_some(),
+                self.battery_alerts.low,
+            );
+        }
+        self.check_storage();
+        self.lid_closed = lid::read_lid_closed();
+        self.displays = lid::connected_displays();
+        self.fans = fans::read_fans();
+        if let Some(ref bat) = self.combined {
+            self.gauge.set(bat.capacity as f64 / 100.0);
+        }
+        let governor = backend::active().current();
+        self.governors = backend::active().governors();
+        // A profile whose governor was substituted runs its stand-in
+        let runs = |p: &Profile| {
+            governor.is_some() && resolve_governor(&p.governor, &self.governors) == governor
+        };
+        // Several profiles may share a governor; keep the one we applied
+        let still_current = self
+            .current_profile
+            .and_then(|i| self.profiles.get(i))
+            .is_some_and(runs);
+        if !still_current {
+            self.current_profile = self.profiles.iter().position(runs);
+            // Follow outside changes, but leave the cursor alone otherwise
+            if let Some(current) = self.current_profile.filter(|_| !self.auto) {
+                self.selected = current;
+                self.list_state.select(Some(current));
+            }
+        }
+        self.check_call();
+        // Rules wait out a call rather than switch profiles mid-sentence
+        if self.auto && !(self.call.is_some() && self.calls.hold_profile) {
+            self.apply_rules();
+        }
+        self.desktop_settings = self.desktop.map(|d| d.read_settings());
+        self.backlight = backlight::read();
+        self.hold_brightness();
+        self.mem_sleep = sleep::read_mem_sleep();
+        self.cpufreq = cpufreq::read_policies();
+        self.hwp = hwp::read_status();
+        self.turbo = cpufreq::read_turbo();
+        self.graphics = i915::read_status();
+        self.gpus = gpu::read_gpus();
+        self.swap = swap::read_status();
+        if self.caps.rapl {
+            self.rapl = rapl::package();
+        }
+        self.package_power = self
+            .rapl
+            .as_ref()
+            .and_then(|p| self.rapl_meter.sample(p.zone()));
+        self.domain_power = self
+            .rapl_domains
+            .iter_mut()
+            .filter_map(|(domain, meter)| Some((domain.name, meter.sample(domain.zone())?)))
+            .collect();
+        if let Some(watts) = self.package_power {
+            self.noise.push(watts, self.loudest_fan());
+        }
+        if self.caps.ryzenadj {
+            self.ryzen = ryzen::read_status();
+        }
+        self.check_budget();
+        // Battery draw where there is one, else RAPL
+        let power = self
+            .combined
+            .as_ref()
+            .and_then(|b| b.power)
+            .or(self.rapl_power());
+        if let Some(power) = power {
+            push_sample(&mut self.power_history, power);
+        }
+        let readings = self.plug.as_ref().map(|p| p.drain()).unwrap_or_default();
+        for reading in readings {
+            match reading {
+                Ok(watts) => {
+                    push_sample(&mut self.wall_history, watts);
+                    if self.plug_offline {
+                        self.plug_offline = false;
+                        self.info("Smart plug is reachable again".to_string());
+                    }
+                }
+                // Say so once, not on every reading while it's unplugged
+                Err(e) if !self.plug_offline => {
+                    self.plug_offline = true;
+                    self.error(format!("Smart plug: {}", e));
+                }
+                Err(_) => {}
+            }
+        }
+        // Cables come and go; keep the page live while it's open
+        if self.page == Page::Ports {
+            self.typec = typec::read_ports();
+            self.usb = usb::read_devices();
+        }
+        if self.page == Page::Msr {
+            self.msr = msr::read_registers();
+        }
+        self.peripherals = peripherals::read_peripherals();
+        self.check_peripherals();
+        let statuses = self
+            .ups_poller
+            .as_ref()
+            .map(|p| p.drain())
+            .unwrap_or_default();
+        for status in statuses {
+            match status {
+                Ok(status) => {
+                    self.ups = Some(status);
+                    self.ups_offline = false;
+                }
+                Err(e) => {
+                    self.ups = None;
+                    if !self.ups_offline {
+                        self.ups_offline = true;
+                        self.error(format!("UPS: {}", e));
+                    }
+                }
+            }
+        }
+        self.check_ups();
+        self.check_bag();
+        self.check_thermal();
+        // While subscribed the daemon says what it does as it does it
+        if self.stream.is_none() {
+            self.daemon = daemon::read_state();
+            if self.daemon.is_some() {
+                self.stream = Subscription::connect();
+            }
+        }
+        // A running daemon keeps the battery log itself
+        if let (Some(log), None) = (self.battery_log.as_mut(), self.daemon.as_ref()) {
+            log.record(self.combined.as_ref());
+        }
+        if let Some(recorder) = self.session_log.as_mut() {
+            match self.daemon {
+                Some(_) => recorder.abandon(),
+                None => recorder.update(
+                    self.combined.as_ref(),
+                    self.current_profile
+                        .and_then(|i| self.profiles.get(i))
+                        .map(|p| p.name.as_str()),
+                ),
+            }
+        }
+        if self.page == Page::History {
+            self.charge_history = history::read_samples();
+        }
+        self.steer();
+        self.network_power();
+        self.inhibitors = inhibit::read_inhibitors(self.sway.as_ref());
+        // Kernel messages only go to the event log; a toast per line
+        // during a throttling episode would bury everything else
+        for event in self.journal.poll() {
+            self.events.push_at(event.time, event.level, event.text);
+        }
+        self.outputs = self.sway.as_ref().map(|s| s.outputs()).unwrap_or_default();
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    /// The Automatic entry sits below the real profiles
+    pub fn move_down(&mut self) {
+        if self.selected < self.profiles.len() {
+            self.selected += 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    /// Show what the selected profile would change before applying it
+    pub fn preview_profile(&mut self) {
+        if self.selected == self.profiles.len() {
+            return self.enable_auto();
+        }
+        if let Some(profile) = self.profiles.get(self.selected) {
+            self.preview = Some(Preview::new(self.selected, profile, self.brightness()));
+        }
+    }
+
+    pub fn handle_preview_key(&mut self, code: KeyCode) {
+        let Some(preview) = self.preview.take() else {
+            return;
+        };
+        match code {
+            KeyCode::Enter | KeyCode::Char('y') => {
+                self.auto = false;
+                self.selected = preview.profile;
+                self.list_state.select(Some(self.selected));
+                self.select_profile();
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => {}
+            // Ignore anything else and keep the preview open
+            _ => self.preview = Some(preview),
+        }
+    }
+
+    pub fn select_profile(&mut self) {
+        if self.selected == self.profiles.len() {
+            return self.enable_auto();
+        }
+        let Some(profile) = self.profiles.get(self.selected).cloned() else {
+            return;
+        };
+        if self.presentation.is_some() {
+            return self.error("Profile is pinned while presenting".to_string());
+        }
+        // Picking a profile by hand overrides automation, boost and quiet
+        self.auto = false;
+        self.boost = None;
+        self.end_quiet();
+        self.switch_to(self.selected, &profile);
+        if self.persist_profile && self.current_profile == Some(self.selected) {
+            if let Err(e) = daemon::save_choice(&profile.name) {
+                self.notify(Level::Warning, format!("Couldn't save the profile: {}", e));
+            }
+        }
+    }
+
+    fn switch_to(&mut self, index: usize, profile: &Profile) {
+        let substitute = match profile::apply(profile) {
+            Ok(substitute) => substitute,
+            Err(e) => return self.error(e),
+        };
+        if let Some(governor) = substitute {
+            self.notify(
+                Level::Warning,
+                format!(
+                    "{}: governor {} isn't available, using {}",
+                    profile.name, profile.governor, governor
+                ),
+            );
+        }
+        if privileged::dry_run() {
+            return self.info(format!("Dry run: would switch to {}", profile.name));
+        }
+        self.current_profile = Some(index);
+        self.info(format!("Switched to {}", profile.name));
+        if let Some(percent) = profile.brightness {
+            self.set_brightness(percent);
+        }
+        if let Some(ref sway) = self.sway {
+            sway.send_tick(&format!("powertui profile {}", profile.governor));
+        }
+    }
+
+    /// Hand profile selection to the rules engine
+    pub fn enable_auto(&mut self) {
+        if self.presentation.is_some() {
+            return self.error("Profile is pinned while presenting".to_string());
+        }
+        self.auto = true;
+        self.boost = None;
+        self.auto_target = None;
+        if self.persist_profile {
+            daemon::clear_choice();
+        }
+        self.selected = self.profiles.len();
+        self.list_state.select(Some(self.selected));
+        self.info("Automatic profile: rules are in control".to_string());
+        if self.daemon.is_some() {
+            self.notify(
+                Level::Warning,
+                "powertui daemon is also switching profiles on AC and battery".to_string(),
+            );
+        }
+        self.apply_rules();
+    }
+
+    /// Switch to whatever the first matching rule asks for. A decision
+    /// is acted on once, so a failing switch doesn't retry every refresh.
+    fn apply_rules(&mut self) {
+        let conditions = Conditions::read(self.combined.as_ref());
+        let Some(rule) = rules::evaluate(&self.rules, &conditions).cloned() else {
+            return;
+        };
+        if self.auto_target.as_ref() == Some(&rule.name) {
+            return;
+        }
+        self.auto_target = Some(rule.name.clone());
+        if self.rule_blanked && !rule.blank {
+            self.set_outputs_power(true);
+        }
+        if rule.blank && !self.outputs_off {
+            self.set_outputs_power(false);
+            self.rule_blanked = self.outputs_off;
+            if let Some(dpms) = self.dpms.filter(|_| self.rule_blanked) {
+                self.info(format!(
+                    "Rule {} turned the displays off ({})",
+                    rule.name,
+                    dpms.name()
+                ));
+            }
+        }
+        let Some(index) = self.profiles.iter().position(|p| p.name == rule.profile) else {
+            return self.error(format!(
+                "Rule {} wants unknown profile {}",
+                rule.name, rule.profile
+            ));
+        };
+        if self.current_profile != Some(index) {
+            let profile = self.profiles[index].clone();
+            self.switch_to(index, &profile);
+        }
+        if rule.suspend && !(self.call.is_some() && self.calls.no_suspend) {
+            self.power_action("suspend", &format!("rule {}", rule.name));
+        }
+        if self.current_profile == Some(index) {
+            self.auto_reason = Some((rule.name, locale::clock(&Local::now())));
+        }
+    }
+
+    /// Switch to Performance for a while, remembering what to go back to
+    pub fn start_boost(&mut self, duration: Duration) {
+        if self.presentation.is_some() {
+            return self.error("Profile is pinned while presenting".to_string());
+        }
+        let Some(index) = self
+            .profiles
+            .iter()
+            .position(|p| p.governor == "performance")
+        else {
+            return self.error("No profile uses the performance governor".to_string());
+        };
+        let (previous, was_auto) = match self.boost.take() {
+            // Extending a running boost keeps the original profile
+            Some(boost) => (boost.previous, boost.was_auto),
+            None => (self.current_profile, self.auto),
+        };
+        self.auto = false;
+        if self.current_profile != Some(index) {
+            let profile = self.profiles[index].clone();
+            self.switch_to(index, &profile);
+        }
+        self.boost = Some(Boost {
+            until: Instant::now() + duration,
+            previous,
+            was_auto,
+        });
+        self.info(format!("Boosting for {}", format_countdown(duration)));
+    }
+
+    /// Return to the profile that was active before the boost
+    pub fn end_boost(&mut self) {
+        let Some(boost) = self.boost.take() else {
+            return;
+        };
+        if boost.was_auto {
+            self.enable_auto();
+        } else if let Some(index) = boost.previous.filter(|&i| i < self.profiles.len()) {
+            let profile = self.profiles[index].clone();
+            self.switch_to(index, &profile);
+        }
+        self.info("Boost ended".to_string());
+    }
+
+    /// Aim for the battery to last `runtime`, capping the package to
+    /// the power that allows if `cap`
+    pub fn start_budget(&mut self, runtime: Duration, cap: bool) {
+        if cap && self.rapl.is_none() {
+            return self.error("Capping needs RAPL power limits".to_string());
+        }
+        // A new target keeps the PL1 to go back to
+        let restore = self.budget.take().and_then(|b| b.restore);
+        self.budget = Some(Budget {
+            restore,
+            ..Budget::new(runtime, cap)
+        });
+        if !cap {
+            self.release_budget_cap();
+        }
+        self.check_budget();
+        match self.budget.as_ref().and_then(|b| b.watts(&self.batteries)) {
+            Some(watts) => self.info(format!(
+                "{} of battery means {:.1} W",
+                format_countdown(runtime),
+                watts
+            )),
+            None => self.info(format!(
+                "Budgeting for {}; battery energy isn't reported",
+                format_countdown(runtime)
+            )),
+        }
+    }
+
+    pub fn end_budget(&mut self) {
+        if self.budget.is_none() {
+            return;
+        }
+        self.release_budget_cap();
+        self.budget = None;
+        self.info("Runtime target cleared".to_string());
+    }
+
+    /// Put PL1 back the way the budget found it
+    pub fn release_budget_cap(&mut self) {
+        let Some(watts) = self.budget.as_mut().and_then(|b| b.restore.take()) else {
+            return;
+        };
+        if let Some(ref package) = self.rapl {
+            if let Err(e) = package.set_limit(Limit::Pl1, watts) {
+                self.error(format!("Restoring PL1: {}", e));
+            }
+        }
+    }
+
+    /// Follow the budget as the battery drains: end it at the target,
+    /// and keep the package cap at what the rest of the machine leaves
+    fn check_budget(&mut self) {
+        let Some(ref budget) = self.budget else {
+            return;
+        };
+        if budget.remaining().is_zero() {
+            self.release_budget_cap();
+            self.budget = None;
+            return self.info("Runtime target reached".to_string());
+        }
+        if !budget.cap {
+            return;
+        }
+        let system = budget::system_draw(&self.batteries);
+        let (Some(watts), Some(system), Some(package_power), Some(package)) = (
+            budget.watts(&self.batteries),
+            system,
+            self.package_power,
+            self.rapl.as_ref(),
+        ) else {
+            // On AC, or nothing to measure against
+            if system.is_none() {
+                self.release_budget_cap();
+            }
+            return;
+        };
+        let cap = budget::package_cap(watts, system, package_power);
+        let current = package.limit(Limit::Pl1);
+        if current.is_some_and(|pl1| (pl1 - cap).abs() < budget::CAP_STEP) {
+            return;
+        }
+        match package.set_limit(Limit::Pl1, cap) {
+            Ok(()) => {
+                if let Some(budget) = self.budget.as_mut() {
+                    budget.restore = budget.restore.or(current);
+                }
+            }
+            Err(e) => {
+                if let Some(budget) = self.budget.as_mut() {
+                    budget.cap = false;
+                }
+                self.error(format!("Capping PL1: {}", e));
+            }
+        }
+    }
+
+    /// Fastest fan right now, the one that's heard
+    pub fn loudest_fan(&self) -> Option<u32> {
+        self.fans.iter().filter_map(|f| f.rpm).max()
+    }
+
+    /// Quietest settings that keep up with the current load
+    pub fn quiet_suggestion(&self) -> Option<Suggestion> {
+        let package = self.rapl.as_ref()?;
+        quiet::suggest(
+            &self.noise,
+            self.loudest_fan(),
+            package.max(Limit::Pl1),
+            &platform::choices(),
+            &cpufreq::available_epp(),
+        )
+    }
+
+    /// Hold the package to what's running now, with the firmware and EPP
+    /// on their quietest, or put it all back
+    pub fn toggle_quiet(&mut self) {
+        if self.quiet.is_some() {
+            return self.end_quiet();
+        }
+        if self.budget.as_ref().is_some_and(|b| b.restore.is_some()) {
+            return self.error("A runtime target is capping PL1; :budget off first".to_string());
+        }
+        let Some(package) = rapl::package() else {
+            return self.error("Quiet settings need RAPL power limits".to_string());
+        };
+        let Some(suggestion) = self.quiet_suggestion() else {
+            return self
+                .error("No package power reading yet; energy_uj may be root-only".to_string());
+        };
+        let mut quiet = Quiet {
+            limit: suggestion.limit,
+            pl1: package.limit(Limit::Pl1),
+            pl2: package.limit(Limit::Pl2),
+            platform: None,
+            epp: None,
+        };
+        let mut errors = Vec::new();
+        for limit in [Limit::Pl1, Limit::Pl2] {
+            if let Err(e) = package.set_limit(limit, suggestion.limit) {
+                errors.push(format!("{}: {}", limit.label(), e));
+            }
+        }
+        if let Some(ref profile) = suggestion.platform {
+            let current = platform::read_profile();
+            if current.as_ref() != Some(profile) {
+                match platform::set_profile(profile) {
+                    Ok(()) => quiet.platform = current,
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+        if let Some(ref hint) = suggestion.epp {
+            let current = cpufreq::read_epp();
+            if current.as_ref() != Some(hint) {
+                match cpufreq::set_epp(hint) {
+                    Ok(()) => quiet.epp = current,
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+        self.quiet = Some(quiet);
+        if !errors.is_empty() {
+            return self.error(format!("Quiet settings: {}", errors.join("; ")));
+        }
+        let mut text = format!(
+            "Quiet: package held to {:.0} W for a {:.1} W load",
+            suggestion.limit, suggestion.workload
+        );
+        if let (Some(now), Some(expected)) = (suggestion.rpm_now, suggestion.rpm_expected) {
+            text.push_str(&format!(", fans ~{:.0} RPM from {}", expected, now));
+        }
+        self.info(text);
+    }
+
+    /// Put back what the quiet settings replaced
+    pub fn end_quiet(&mut self) {
+        let Some(quiet) = self.quiet.take() else {
+            return;
+        };
+        let mut errors = Vec::new();
+        if let Some(package) = rapl::package() {
+            for (limit, watts) in [(Limit::Pl1, quiet.pl1), (Limit::Pl2, quiet.pl2)] {
+                if let Some(watts) = watts {
+                    if let Err(e) = package.set_limit(limit, watts) {
+                        errors.push(format!("{}: {}", limit.label(), e));
+                    }
+                }
+            }
+        }
+        if let Some(ref profile) = quiet.platform {
+            if let Err(e) = platform::set_profile(profile) {
+                errors.push(e);
+            }
+        }
+        if let Some(ref hint) = quiet.epp {
+            if let Err(e) = cpufreq::set_epp(hint) {
+                errors.push(e);
+            }
+        }
+        if errors.is_empty() {
+            self.info("Quiet settings undone".to_string());
+        } else {
+            self.error(format!("Undoing quiet settings: {}", errors.join("; ")));
+        }
+    }
+
+    /// Open the profile editor, on the selected profile or a blank one
+    pub fn open_editor(&mut self, edit_selected: bool) {
+        if edit_selected && self.selected >= self.profiles.len() {
+            return;
+        }
+        let governors = available_governors();
+        if governors.is_empty() {
+            self.error("No cpufreq governors found".to_string());
+            return;
+        }
+        let profile = if edit_selected {
+            self.profiles.get(self.selected)
+        } else {
+            None
+        };
+        self.editor = Some(ProfileEditor::new(
+            profile,
+            governors,
+            cpufreq::available_epp(),
+            self.turbo.is_some(),
+            Ranges {
+                freq: cpufreq::hardware_range().map(|(min, max)| (min / 1000, max / 1000)),
+                power: self
+                    .rapl
+                    .as_ref()
+                    .map(|package| {
+                        [Limit::Pl1, Limit::Pl2]
+                            .into_iter()
+                            .filter(|l| package.limit(*l).is_some())
+                            .map(|l| (l, package.max(l)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            },
+            platform::choices(),
+            self.desktop.is_some() || self.backlight.is_some(),
+        ));
+    }
+
+    pub fn handle_editor_key(&mut self, code: KeyCode) {
+        let Some(ref mut editor) = self.editor else {
+            return;
+        };
+        match editor.handle_key(code) {
+            EditorAction::None => {}
+            EditorAction::Cancel => self.editor = None,
+            EditorAction::Save => self.save_editor(),
+        }
+    }
+
+    fn save_editor(&mut self) {
+        let Some(editor) = self.editor.as_ref() else {
+            return;
+        };
+        let profile = match editor.to_profile() {
+            Ok(profile) => profile,
+            Err(e) => return self.error(e),
+        };
+        let original = editor.original.clone();
+        let mut profiles = self.profiles.clone();
+
+        let clash = profiles
+            .iter()
+            .any(|p| p.custom && p.name == profile.name && original.as_deref() != Some(&p.name));
+        if clash {
+            return self.error(format!("A profile named {} already exists", profile.name));
+        }
+
+        // Renaming a custom profile drops the old entry, bringing back
+        // the built-in one if it was an override
+        if let Some(old) = original.as_deref().filter(|o| *o != profile.name) {
+            if let Some(i) = profiles.iter().position(|p| p.custom && p.name == old) {
+                match Profile::builtin().into_iter().find(|p| p.name == old) {
+                    Some(builtin) => profiles[i] = builtin,
+                    None => {
+                        profiles.remove(i);
+                    }
+                }
+            }
+        }
+
+        match profiles.iter().position(|p| p.name == profile.name) {
+            Some(i) => profiles[i] = profile.clone(),
+            None => profiles.push(profile.clone()),
+        }
+
+        match save_custom_profiles(&profiles) {
+            Ok(()) => {
+                self.profiles = profiles;
+                self.editor = None;
+                self.current_profile = None;
+                self.refresh();
+                self.info(format!("Saved profile {}", profile.name));
+            }
+            Err(e) => self.error(format!("Could not save profiles: {}", e)),
+        }
+    }
+
+    /// Remove a custom profile, restoring the built-in one it overrode
+    pub fn delete_profile(&mut self) {
+        let Some(profile) = self.profiles.get(self.selected).cloned() else {
+            return;
+        };
+        if !profile.custom {
+            return self.error(format!("{} is built in", profile.name));
+        }
+
+        let mut profiles = self.profiles.clone();
+        match Profile::builtin()
+            .into_iter()
+            .find(|p| p.name == profile.name)
+        {
+            Some(builtin) => profiles[self.selected] = builtin,
+            None => {
+                profiles.remove(self.selected);
+            }
+        }
+
+        match save_custom_profiles(&profiles) {
+            Ok(()) => {
+                self.profiles = profiles;
+                self.current_profile = None;
+                self.selected = self.selected.min(self.profiles.len() - 1);
+                self.list_state.select(Some(self.selected));
+                self.refresh();
+                self.info(format!("Deleted profile {}", profile.name));
+            }
+            Err(e) => self.error(format!("Could not save profiles: {}", e)),
+        }
+    }
+
+    pub fn adjust_brightness(&mut self, delta: i16) {
+        if self.presentation.is_some() {
+            return;
+        }
+        let Some(current) = self.brightness() else {
+            return self.error("No desktop or backlight to set brightness".to_string());
+        };
+        self.set_brightness((current as i16 + delta).clamp(0, 100) as u8);
+    }
+
+    /// Screen brightness in percent, from the desktop where there is
+    /// one, since it keeps its own level, else the backlight
+    pub fn brightness(&self) -> Option<u8> {
+        match self.desktop {
+            Some(_) => self.desktop_settings.as_ref().and_then(|s| s.brightness),
+            None => self.backlight.as_ref().map(|b| b.percent()),
+        }
+    }
+
+    fn write_brightness(&mut self, target: u8) -> Result<(), String> {
+        if let Some(desktop) = self.desktop {
+            desktop.set_brightness(target)?;
+            if let Some(ref mut settings) = self.desktop_settings {
+                settings.brightness = Some(target);
+            }
+            return Ok(());
+        }
+        let backlight = self
+            .backlight
+            .as_mut()
+            .ok_or("No desktop or backlight to set brightness")?;
+        backlight.set_percent(target)?;
+        if !privileged::dry_run() {
+            backlight.brightness = (target as u64 * backlight.max as u64).div_ceil(100) as u32;
+        }
+        Ok(())
+    }
+
+    fn set_brightness(&mut self, target: u8) {
+        if let Err(e) = self.write_brightness(target) {
+            self.error(e);
+        }
+    }
+
+    pub fn set_outputs_power(&mut self, on: bool) {
+        let Some(dpms) = self.dpms else {
+            return;
+        };
+        match dpms.set_power(self.sway.as_ref(), on) {
+            Ok(()) => self.outputs_off = !on,
+            Err(e) => self.error(e),
+        }
+        if on {
+            self.rule_blanked = false;
+        }
+    }
+
+    /// Change the simulated machine and show the result straight away
+    fn simulate(&mut self, change: &Change) {
+        match simulate::apply(change) {
+            Ok(text) => {
+                self.info(text);
+                self.refresh();
+            }
+            Err(e) => self.error(e),
+        }
+    }
+
+    /// Step the simulated battery or temperature from where it is now
+    fn nudge_simulation(&mut self, action: Action) {
+        let percent = self.battery.as_ref().map_or(50, |b| b.capacity as i16);
+        let temp = thermal::read_cpu_temp().unwrap_or(50.0);
+        let change = match action {
+            Action::SimBatteryUp => Change::Battery((percent + 5).min(100) as u8),
+            Action::SimBatteryDown => Change::Battery((percent - 5).max(0) as u8),
+            Action::SimTempUp => Change::Temp(temp + 5.0),
+            Action::SimTempDown => Change::Temp(temp - 5.0),
+            _ => {
+                let current = self.battery.as_ref().map(|b| b.status.as_str());
+                let next = simulate::STATUSES
+                    .iter()
+                    .position(|s| Some(*s) == current)
+                    .map_or(0, |i| (i + 1) % simulate::STATUSES.len());
+                Change::Status(simulate::STATUSES[next].to_string())
+            }
+        };
+        self.simulate(&change);
+    }
+
+    /// Allow or forbid turbo until the next profile that sets it
+    pub fn toggle_turbo(&mut self) {
+        let Some(on) = self.turbo else {
+            return;
+        };
+        match cpufreq::set_turbo(!on) {
+            Ok(()) if privileged::dry_run() => {}
+            Ok(()) => {
+                self.turbo = Some(!on);
+                self.info(if on {
+                    "Turbo off: the CPU stays at its base clock".to_string()
+                } else {
+                    "Turbo on".to_string()
+                });
+            }
+            Err(e) => self.error(e),
+        }
+    }
+
+    /// Keep Panel Self Refresh off, for panels that flicker or freeze
+    /// with it, or hand it back to the driver
+    pub fn toggle_psr(&mut self) {
+        let Some(ref graphics) = self.graphics else {
+            return;
+        };
+        let off = !self.psr_off;
+        match i915::set_psr(graphics, !off) {
+            Ok(()) => {
+                self.psr_off = off;
+                self.info(if off {
+                    "PSR off until re-enabled or reboot (costs around 0.5–1 W)".to_string()
+                } else {
+                    "PSR back under driver control".to_string()
+                });
+            }
+            Err(e) => self.error(e),
+        }
+    }
+
+    /// Step through the battery's charge behaviours. Anything but
+    /// `auto` stops the battery charging, so each mode says so loudly.
+    pub fn cycle_charge_behaviour(&mut self) {
+        let Some(ref bat) = self.battery else {
+            return;
+        };
+        let Some(next) = bat
+            .charge_behaviour
+            .as_ref()
+            .and_then(|c| c.next())
+            .cloned()
+        else {
+            return;
+        };
+        if next == "force-discharge" && bat.capacity <= FORCE_DISCHARGE_FLOOR {
+            return self.error(format!(
+                "Not forcing discharge at {}%; charge above {}% first",
+                bat.capacity, FORCE_DISCHARGE_FLOOR
+            ));
+        }
+        if let Err(e) = battery::set_charge_behaviour(bat, &next) {
+            return self.error(e);
+        }
+        self.battery = read_battery_info();
+        match next.as_str() {
+            "inhibit-charge" => self.info(
+                "Charging inhibited: running from AC without charging the battery".to_string(),
+            ),
+            // Sticky, since the battery now drains while plugged in
+            "force-discharge" => self.error(format!(
+                "Forcing discharge: battery drains even on AC. Stops at {}%",
+                FORCE_DISCHARGE_FLOOR
+            )),
+            _ => self.info(format!("Charge behaviour: {}", next)),
+        }
+    }
+
+    /// Return to normal charging before a forced discharge empties the
+    /// battery
+    fn guard_force_discharge(&mut self) {
+        let Some(result) = self
+            .battery
+            .as_ref()
+            .and_then(battery::guard_force_discharge)
+        else {
+            return;
+        };
+        match result {
+            Ok(()) => {
+                self.battery = read_battery_info();
+                self.info(format!(
+                    "Battery at {}%: stopped forced discharge",
+                    FORCE_DISCHARGE_FLOOR
+                ));
+            }
+            Err(e) => self.error(e),
+        }
+    }
+
+    /// Stop a forced discharge when quitting, so the battery doesn't
+    /// drain to empty on AC with nothing watching it
+    pub fn end_force_discharge(&mut self) {
+        let Some(ref bat) = self.battery else {
+            return;
+        };
+        if bat.behaviour() == Some("force-discharge") {
+            let _ = battery::set_charge_behaviour(bat, "auto");
+        }
+    }
+
+    /// Enter or leave presentation mode. Entering holds an idle and
+    /// sleep inhibitor, freezes brightness and the profile, and keeps
+    /// toasts off the screen.
+    pub fn toggle_presentation(&mut self) {
+        if let Some(presentation) = self.presentation.take() {
+            // Dropping it releases the inhibitor
+            let was_auto = presentation.was_auto;
+            drop(presentation);
+            self.info("Presentation mode off".to_string());
+            if was_auto {
+                self.enable_auto();
+            }
+            return;
+        }
+        let idle = match IdleBlock::hold(self.desktop, "Presentation mode") {
+            Ok(idle) => idle,
+            Err(e) => return self.error(e),
+        };
+        // A boost would end mid-talk and switch profiles, so end it now
+        self.end_boost();
+        let was_auto = self.auto;
+        self.auto = false;
+        self.info("Presentation mode on".to_string());
+        self.presentation = Some(Presentation {
+            _idle: idle,
+            brightness: self.brightness(),
+            was_auto,
+        });
+    }
+
+    /// Notice calls starting and ending, holding off suspend for their
+    /// length if asked to
+    fn check_call(&mut self) {
+        if !self.calls.enabled() {
+            return;
+        }
+        let captures = calls::read_captures();
+        if captures.is_empty() {
+            if self.call.take().is_some() {
+                // Dropping it releases the inhibitor
+                self.info("Call ended".to_string());
+            }
+            return;
+        }
+        let apps = calls::describe(&captures);
+        if let Some(call) = self.call.as_mut() {
+            call.apps = apps;
+            return;
+        }
+        let idle = if self.calls.no_suspend {
+            IdleBlock::hold(self.desktop, "On a call")
+                .map_err(|e| self.error(e))
+                .ok()
+        } else {
+            None
+        };
+        let mut held = Vec::new();
+        if self.calls.hold_profile {
+            held.push("profile held");
+        }
+        if idle.is_some() {
+            held.push("suspend blocked");
+        }
+        let mut text = format!("Call started: {}", apps);
+        if !held.is_empty() {
+            text.push_str(&format!("; {}", held.join(", ")));
+        }
+        self.info(text);
+        self.call = Some(Call { apps, _idle: idle });
+    }
+
+    /// Put brightness back if something (like auto-dimming) moved it
+    /// while presenting
+    fn hold_brightness(&mut self) {
+        let Some(target) = self.presentation.as_ref().and_then(|p| p.brightness) else {
+            return;
+        };
+        if self.brightness().is_some_and(|b| b != target) {
+            let _ = self.write_brightness(target);
+        }
+    }
+
+    /// Hold every battery with charge thresholds at `[storage] level` for
+    /// putting the machine away, remembering the thresholds to restore
+    fn start_storage(&mut self) {
+        if self.storage.is_some() {
+            return self.error("Already in storage mode; :storage off to leave".to_string());
+        }
+        let level = self.storage_config.level;
+        let mut previous = Vec::new();
+        let mut failures = Vec::new();
+        for battery in self.batteries.iter().filter(|b| b.end_threshold.is_some()) {
+            // A little room below the level where charging can resume
+            let start = battery.start_threshold.map(|_| level.saturating_sub(5));
+            match battery::set_thresholds(battery, start, level) {
+                Ok(()) => previous.push(storage::Previous {
+                    battery: battery.name.clone(),
+                    start: battery.start_threshold,
+                    end: battery.end_threshold,
+                }),
+                Err(e) => failures.push(format!("{}: {}", battery.name, e)),
+            }
+        }
+        for e in failures {
+            self.error(e);
+        }
+        if previous.is_empty() {
+            if !self.batteries.iter().any(|b| b.end_threshold.is_some()) {
+                self.error("No battery supports charge thresholds".to_string());
+            }
+            return;
+        }
+        let stored = Storage {
+            at: Local::now().timestamp(),
+            level,
+            previous,
+        };
+        if let Err(e) = stored.save() {
+            self.error(format!("Storage mode won't survive a restart: {}", e));
+        }
+        let mut text = format!("Storage mode on: charging stops at {}%", level);
+        if let Some(capacity) = self
+            .combined
+            .as_ref()
+            .map(|b| b.capacity)
+            .filter(|c| *c > level)
+        {
+            text.push_str(&format!(
+                "; run it down from {}% before putting it away",
+                capacity
+            ));
+        }
+        self.info(text);
+        self.storage = Some(stored);
+        self.storage_reminded = false;
+        self.refresh();
+    }
+
+    fn end_storage(&mut self) {
+        let Some(stored) = self.storage.take() else {
+            return self.error("Not in storage mode".to_string());
+        };
+        for previous in &stored.previous {
+            let Some(end) = previous.end else {
+                continue;
+            };
+            let Some(battery) = self.batteries.iter().find(|b| b.name == previous.battery) else {
+                continue;
+            };
+            if let Err(e) = battery::set_thresholds(battery, previous.start, end) {
+                self.error(format!("{}: {}", previous.battery, e));
+            }
+        }
+        storage::clear();
+        self.info(format!(
+            "Storage mode off after {} weeks; thresholds restored",
+            stored.weeks()
+        ));
+        self.refresh();
+    }
+
+    /// Once a session, when the machine has been stored long enough to
+    /// have lost charge
+    fn check_storage(&mut self) {
+        if self.storage_reminded {
+            return;
+        }
+        let Some(text) = self
+            .storage
+            .as_ref()
+            .and_then(|s| s.reminder(self.storage_config.remind_weeks))
+        else {
+            return;
+        };
+        self.storage_reminded = true;
+        self.alert("storage", text);
+    }
+
+    /// Enter or leave travel mode: Power Saver, every radio blocked,
+    /// a dimmer screen and short idle suspend, undone on the way out
+    pub fn toggle_travel(&mut self) {
+        if let Some(travel) = self.travel.take() {
+            return self.leave_travel(travel);
+        }
+        if self.presentation.is_some() {
+            return self.error("Profile is pinned while presenting".to_string());
+        }
+        let Some(index) = self
+            .profiles
+            .iter()
+            .position(|p| p.name == "Power Saver")
+            .or_else(|| self.profiles.iter().position(|p| p.governor == "powersave"))
+        else {
+            return self.error("No profile uses the powersave governor".to_string());
+        };
+
+        // Leaving travel mode goes back to what was there before any boost
+        let (previous, was_auto) = match self.boost.take() {
+            Some(boost) => (boost.previous, boost.was_auto),
+            None => (self.current_profile, self.auto),
+        };
+        self.auto = false;
+        if self.current_profile != Some(index) {
+            let profile = self.profiles[index].clone();
+            self.switch_to(index, &profile);
+        }
+
+        let mut radios = Vec::new();
+        for mut radio in rfkill::read_radios()
+            .into_iter()
+            .filter(|r| !r.soft_blocked)
+        {
+            match rfkill::set_blocked(&radio, true) {
+                Ok(()) => {
+                    radio.soft_blocked = true;
+                    radios.push(radio);
+                }
+                Err(e) => self.error(format!("{}: {}", radio.name, e)),
+            }
+        }
+
+        let mut brightness = None;
+        let mut suspend = Vec::new();
+        let mut failures = Vec::new();
+        if let Some(b) = self.brightness().filter(|&b| b > TRAVEL_BRIGHTNESS) {
+            match self.write_brightness(TRAVEL_BRIGHTNESS) {
+                Ok(()) => brightness = Some(b),
+                Err(e) => failures.push(e),
+            }
+        }
+        if let (Some(desktop), Some(settings)) = (self.desktop, self.desktop_settings.as_ref()) {
+            let shortest = SUSPEND_PRESETS[0];
+            for (on_battery, current) in [
+                (true, settings.suspend_battery),
+                (false, settings.suspend_ac),
+            ] {
+                // 0 is never, which is the longest of all
+                if current.is_some_and(|c| c > 0 && c <= shortest) {
+                    continue;
+                }
+                match desktop.set_suspend_timeout(on_battery, shortest) {
+                    Ok(()) => suspend.push((on_battery, current)),
+                    Err(e) => failures.push(e),
+                }
+            }
+        }
+        for e in failures {
+            self.error(e);
+        }
+
+        self.info(format!(
+            "Travel mode on: {} radio{} off",
+            radios.len(),
+            if radios.len() == 1 { "" } else { "s" }
+        ));
+        self.travel = Some(Travel {
+            previous,
+            was_auto,
+            radios,
+            brightness,
+            suspend,
+        });
+        self.refresh();
+    }
+
+    fn leave_travel(&mut self, travel: Travel) {
+        for radio in &travel.radios {
+            if let Err(e) = rfkill::set_blocked(radio, false) {
+                self.error(format!("{}: {}", radio.name, e));
+            }
+        }
+        if let Some(b) = travel.brightness {
+            self.set_brightness(b);
+        }
+        if let Some(desktop) = self.desktop {
+            for (on_battery, timeout) in travel.suspend {
+                // An unreadable timeout can't be restored exactly
+                let Some(timeout) = timeout else {
+                    continue;
+                };
+                if let Err(e) = desktop.set_suspend_timeout(on_battery, timeout) {
+                    self.error(e);
+                }
+            }
+        }
+        if travel.was_auto {
+            self.enable_auto();
+        } else if let Some(index) = travel.previous.filter(|&i| i < self.profiles.len()) {
+            let profile = self.profiles[index].clone();
+            self.switch_to(index, &profile);
+        }
+        self.info("Travel mode off".to_string());
+        self.refresh();
+    }
+
+    /// Switch between s2idle and deep suspend
+    pub fn cycle_sleep_mode(&mut self) {
+        let Some(next) = self.mem_sleep.as_ref().and_then(|m| m.next()).cloned() else {
+            return;
+        };
+        match sleep::set_mem_sleep(&next) {
+            Ok(()) => {
+                self.mem_sleep = sleep::read_mem_sleep();
+                self.info(format!("Suspend mode: {}", next));
+            }
+            Err(e) => self.error(e),
+        }
+    }
+
+    pub fn cycle_suspend_timeout(&mut self) {
+        let Some(desktop) = self.desktop else {
+            return;
+        };
+        let current = self
+            .desktop_settings
+            .as_ref()
+            .and_then(|s| s.suspend_battery);
+        let next = SUSPEND_PRESETS
+            .iter()
+            .position(|&p| Some(p) == current)
+            .map(|i| SUSPEND_PRESETS[(i + 1) % SUSPEND_PRESETS.len()])
+            .unwrap_or(SUSPEND_PRESETS[0]);
+        match desktop.set_suspend_timeout(true, next) {
+            Ok(()) => {
+                if let Some(ref mut settings) = self.desktop_settings {
+                    settings.suspend_battery = Some(next);
+                }
+                self.info(format!(
+                    "Suspend on battery: {}",
+                    desktop::format_timeout(Some(next))
+                ));
+            }
+            Err(e) => self.error(e),
+        }
+    }
+}
+
+fn push_sample(history: &mut VecDeque<f64>, watts: f64) {
+    if history.len() == POWER_HISTORY {
+        history.pop_front();
+    }
+    history.push_back(watts);
+}
+
+/// Whether `every` has passed since `last`, starting the next wait if so
+fn due(last: &mut Option<Instant>, every: Duration) -> bool {
+    if last.is_some_and(|t| t.elapsed() < every) {
+        return false;
+    }
+    *last = Some(Instant::now());
+    true
+}
+
+/// `12:34`, or `1:02:03` past an hour
+pub fn format_countdown(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,58 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/// One privileged operation, as stored in the audit file
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub time: String,
+    pub setting: String,
+    pub command: String,
+    pub old: Option<String>,
+    pub new: String,
+    pub outcome: String,
+}
+
+fn audit_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("audit.jsonl"))
+}
+
+/// Append an entry to the audit file, one JSON object per line.
+/// Best effort: failing to audit must not block the operation's result.
+pub fn record(setting: &str, command: &str, old: Option<String>, new: &str, outcome: &str) {
+    let entry = Entry {
+        time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        setting: setting.to_string(),
+        command: command.to_string(),
+        old,
+        new: new.to_string(),
+        outcome: outcome.to_string(),
+    };
+    let (Some(path), Ok(line)) = (audit_path(), serde_json::to_string(&entry)) else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// The whole audit trail, oldest first
+pub fn read_entries() -> Vec<Entry> {
+    audit_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,321 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::dbus::{self, Bus};
+use crate::desktop::command_exists;
+use crate::doctor::{Check, Severity};
+use crate::privileged;
+use crate::profile::{available_governors, read_current_governor};
+use crate::sysfs;
+
+const CPUFREQ: &str = "/sys/devices/system/cpu/cpufreq";
+
+/// Something that can switch the CPU between governors. Profiles name
+/// governors; each backend turns that into whatever it controls.
+pub trait PowerBackend: Send + Sync {
+    /// Shown in the UI and by doctor
+    fn name(&self) -> &'static str;
+
+    /// Whether this backend can work on this machine
+    fn available(&self) -> bool;
+
+    /// Governors it can switch between
+    fn governors(&self) -> Vec<String>;
+
+    /// The governor in effect, in the same terms
+    fn current(&self) -> Option<String>;
+
+    fn set_governor(&self, governor: &str) -> Result<(), String>;
+
+    /// Whether switching will actually go through, for the startup checks
+    fn check(&self) -> Check;
+
+    /// Whether it sets EPP and the platform profile itself, undoing
+    /// anyone else's hints
+    fn owns_epp(&self) -> bool {
+        false
+    }
+}
+
+/// What `name` returns for each backend, for the config
+pub const NAMES: [&str; 3] = ["power-profiles-daemon", "sysfs", "cpupower"];
+
+/// Tried in order. power-profiles-daemon comes first because while it
+/// runs it undoes governor changes made behind its back. Writing sysfs
+/// needs nothing installed, so cpupower is only picked when configured
+/// or when it's the one allowed root, as older sudoers setups have it.
+fn backends() -> Vec<Box<dyn PowerBackend>> {
+    vec![
+        Box::new(PowerProfilesDaemon),
+        Box::new(SysfsWrite),
+        Box::new(Cpupower),
+    ]
+}
+
+static ACTIVE: OnceLock<Box<dyn PowerBackend>> = OnceLock::new();
+
+/// Backend from the config to try before the others
+static PREFERRED: OnceLock<String> = OnceLock::new();
+
+/// Try `name` first; only takes effect before the first `active`
+pub fn prefer(name: &str) {
+    let _ = PREFERRED.set(name.to_string());
+}
+
+/// The preferred backend, if one is configured
+pub fn preferred() -> Option<&'static str> {
+    PREFERRED.get().map(String::as_str)
+}
+
+/// The first backend available here, the preferred one first, probed
+/// once per run
+pub fn active() -> &'static dyn PowerBackend {
+    ACTIVE
+        .get_or_init(|| {
+            let mut backends = backends();
+            if let Some(i) = backends.iter().position(|b| Some(b.name()) == preferred()) {
+                let first = backends.remove(i);
+                backends.insert(0, first);
+            }
+            backends
+                .into_iter()
+                .find(|b| b.available())
+                // Failing to get root at least explains itself
+                .unwrap_or_else(|| Box::new(SysfsWrite))
+        })
+        .as_ref()
+}
+
+fn root_check(program: &str, name: &str) -> Check {
+    match privileged::allowed(program) {
+        Some(how) => Check::new(
+            "Governor",
+            Severity::Ok,
+            format!("writable through {} ({})", name, how.name()),
+        ),
+        None => Check::new("Governor", Severity::Warn, privileged::no_root(program)),
+    }
+}
+
+/// `cpupower frequency-set -g` as root
+pub struct Cpupower;
+
+impl PowerBackend for Cpupower {
+    fn name(&self) -> &'static str {
+        "cpupower"
+    }
+
+    fn available(&self) -> bool {
+        command_exists("cpupower") && read_current_governor().is_some()
+    }
+
+    fn governors(&self) -> Vec<String> {
+        available_governors()
+    }
+
+    fn current(&self) -> Option<String> {
+        read_current_governor()
+    }
+
+    fn set_governor(&self, governor: &str) -> Result<(), String> {
+        privileged::run(
+            "governor",
+            read_current_governor(),
+            governor,
+            "cpupower",
+            &["frequency-set", "-g", governor],
+        )
+    }
+
+    fn check(&self) -> Check {
+        root_check("cpupower", "cpupower")
+    }
+}
+
+/// `scaling_governor` of every cpufreq policy, written as root one
+/// policy at a time so a failure names the policies it hit
+pub struct SysfsWrite;
+
+impl SysfsWrite {
+    /// `policy*` directories with a governor
+    fn policies() -> Vec<PathBuf> {
+        let mut policies: Vec<PathBuf> = fs::read_dir(sysfs::path(CPUFREQ))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.join("scaling_governor").exists())
+            .collect();
+        policies.sort();
+        policies
+    }
+}
+
+impl PowerBackend for SysfsWrite {
+    fn name(&self) -> &'static str {
+        "sysfs"
+    }
+
+    fn available(&self) -> bool {
+        !SysfsWrite::policies().is_empty() && privileged::allowed(privileged::HELPER).is_some()
+    }
+
+    fn governors(&self) -> Vec<String> {
+        available_governors()
+    }
+
+    fn current(&self) -> Option<String> {
+        read_current_governor()
+    }
+
+    fn set_governor(&self, governor: &str) -> Result<(), String> {
+        let policies = SysfsWrite::policies();
+        if policies.is_empty() {
+            return Err("No cpufreq policies to set a governor on".to_string());
+        }
+        // Policies already on it are skipped, and one that doesn't take
+        // it doesn't stop the rest
+        let mut failed = Vec::new();
+        for policy in &policies {
+            let name = policy
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let path = policy.join("scaling_governor");
+            let old = sysfs::read_string(&path);
+            if old.as_deref() == Some(governor) {
+                continue;
+            }
+            let offered = sysfs::read_string(policy.join("scaling_available_governors"));
+            if offered.is_some_and(|o| !o.split_whitespace().any(|g| g == governor)) {
+                failed.push(format!("{} (not offered)", name));
+                continue;
+            }
+            if let Err(e) = privileged::write("governor", old, &path.to_string_lossy(), governor) {
+                failed.push(format!("{} ({})", name, e));
+            }
+        }
+        if failed.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "Governor {} failed on {} of {} policies: {}",
+            governor,
+            failed.len(),
+            policies.len(),
+            failed.join(", ")
+        ))
+    }
+
+    fn check(&self) -> Check {
+        if !self.available() {
+            return Check::new("Governor", Severity::Fail, "no cpufreq driver loaded");
+        }
+        root_check(privileged::HELPER, "sysfs")
+    }
+}
+
+/// power-profiles-daemon over D-Bus. Its polkit policy lets any active
+/// session set `ActiveProfile`. It switches platform profiles and EPP
+/// rather than governors, so the three governors stand for its three
+/// profiles.
+pub struct PowerProfilesDaemon;
+
+const PPD_NAME: &str = "net.hadess.PowerProfiles";
+const PPD_PATH: &str = "/net/hadess/PowerProfiles";
+
+/// Governors as power-profiles-daemon profiles
+const PPD_PROFILES: [(&str, &str); 3] = [
+    ("powersave", "power-saver"),
+    ("schedutil", "balanced"),
+    ("performance", "performance"),
+];
+
+impl PowerProfilesDaemon {
+    fn property(name: &str) -> Option<Vec<String>> {
+        dbus::get_properties(Bus::System, PPD_NAME, PPD_PATH, PPD_NAME, &[name])
+    }
+
+    /// `s "balanced"`
+    fn get() -> Option<String> {
+        PowerProfilesDaemon::property("ActiveProfile")?
+            .get(1)
+            .cloned()
+    }
+
+    /// Profiles the daemon offers here; `performance` needs platform
+    /// support. `aa{sv} 3 3 "Profile" s "power-saver" "Driver" s ...`
+    fn profiles() -> Vec<String> {
+        let tokens = PowerProfilesDaemon::property("Profiles").unwrap_or_default();
+        tokens
+            .windows(3)
+            .filter(|w| w[0] == "Profile" && w[1] == "s")
+            .map(|w| w[2].clone())
+            .collect()
+    }
+}
+
+impl PowerBackend for PowerProfilesDaemon {
+    fn name(&self) -> &'static str {
+        "power-profiles-daemon"
+    }
+
+    fn available(&self) -> bool {
+        // Replayed snapshots shouldn't pick up the live daemon
+        !sysfs::replaying() && PowerProfilesDaemon::get().is_some()
+    }
+
+    fn governors(&self) -> Vec<String> {
+        let offered = PowerProfilesDaemon::profiles();
+        PPD_PROFILES
+            .iter()
+            // An unreadable list shouldn't leave nothing to pick
+            .filter(|(_, p)| offered.is_empty() || offered.iter().any(|o| o == p))
+            .map(|(g, _)| g.to_string())
+            .collect()
+    }
+
+    fn current(&self) -> Option<String> {
+        let profile = PowerProfilesDaemon::get()?;
+        PPD_PROFILES
+            .iter()
+            .find(|(_, p)| *p == profile)
+            .map(|(g, _)| g.to_string())
+    }
+
+    fn set_governor(&self, governor: &str) -> Result<(), String> {
+        let (_, profile) = PPD_PROFILES
+            .iter()
+            .find(|(g, _)| *g == governor)
+            .ok_or_else(|| format!("power-profiles-daemon has no match for {}", governor))?;
+        privileged::run_unprivileged(
+            "platform profile",
+            PowerProfilesDaemon::get(),
+            profile,
+            "busctl",
+            &[
+                "--system",
+                "set-property",
+                PPD_NAME,
+                PPD_PATH,
+                PPD_NAME,
+                "ActiveProfile",
+                "s",
+                profile,
+            ],
+        )
+    }
+
+    fn check(&self) -> Check {
+        Check::new(
+            "Governor",
+            Severity::Ok,
+            "switched through power-profiles-daemon",
+        )
+    }
+
+    fn owns_epp(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::dbus::{self, Bus};
+use crate::privileged;
+use crate::sysfs;
+
+const BACKLIGHT: &str = "/sys/class/backlight";
+
+/// Interface types in the order systemd prefers them: the firmware's
+/// own control, then the platform driver's, then the GPU's raw register
+const TYPES: [&str; 3] = ["firmware", "platform", "raw"];
+
+/// The panel backlight, for when no desktop sets brightness for us
+pub struct Backlight {
+    /// `intel_backlight`, `amdgpu_bl0`
+    pub name: String,
+    path: PathBuf,
+    pub brightness: u32,
+    pub max: u32,
+}
+
+impl Backlight {
+    pub fn percent(&self) -> u8 {
+        (self.brightness as u64 * 100 / self.max as u64) as u8
+    }
+
+    /// Through logind when it's there, which lets the seat's user set
+    /// it without root, else a privileged write. Simulated and dry-run
+    /// writes never reach logind, which would change the real panel.
+    pub fn set_percent(&self, percent: u8) -> Result<(), String> {
+        let value = (percent.min(100) as u64 * self.max as u64).div_ceil(100) as u32;
+        if !privileged::dry_run() && !sysfs::replaying() {
+            let value = value.to_string();
+            let done = dbus::call(
+                Bus::System,
+                "org.freedesktop.login1",
+                "/org/freedesktop/login1/session/auto",
+                "org.freedesktop.login1.Session",
+                "SetBrightness",
+                &["ssu", "backlight", &self.name, &value],
+            );
+            if done.is_some() {
+                return Ok(());
+            }
+        }
+        privileged::write(
+            "Brightness",
+            Some(format!("{}%", self.percent())),
+            &self.path.join("brightness").to_string_lossy(),
+            &value.to_string(),
+        )
+    }
+}
+
+/// The backlight systemd would pick: by interface type, then by name
+pub fn read() -> Option<Backlight> {
+    let mut devices: Vec<PathBuf> = fs::read_dir(sysfs::path(BACKLIGHT))
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    devices.sort();
+    let path = devices.into_iter().min_by_key(|dev| {
+        let kind = sysfs::read_string(dev.join("type")).unwrap_or_default();
+        TYPES.iter().position(|t| *t == kind).unwrap_or(TYPES.len())
+    })?;
+    let max = sysfs::read_value(path.join("max_brightness")).filter(|m| *m > 0)?;
+    // actual_brightness is what the hardware reports, where it does
+    let brightness = sysfs::read_value(path.join("actual_brightness"))
+        .or_else(|| sysfs::read_value(path.join("brightness")))?;
+    Some(Backlight {
+        name: path.file_name()?.to_string_lossy().into_owned(),
+        path,
+        brightness,
+        max,
+    })
+}
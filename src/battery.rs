@@ -0,0 +1,343 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::calibration;
+use crate::locale;
+use crate::privileged;
+use crate::sysfs::{self, Choice};
+
+/// Below this, forced discharge is refused or stopped
+pub const FORCE_DISCHARGE_FLOOR: u8 = 20;
+
+#[derive(Clone)]
+pub struct BatteryInfo {
+    pub path: PathBuf,
+    /// `BAT0`
+    pub name: String,
+    /// Charge in percent, through the calibration curve if one is set
+    pub capacity: u8,
+    /// What the firmware says; thresholds and alarms are in these terms
+    pub reported: u8,
+    pub status: String,
+    pub health: Option<u8>,
+    /// Charge cycles, where the firmware counts them
+    pub cycles: Option<u32>,
+    pub time_remaining: Option<String>,
+    /// Instantaneous draw (or charge rate) in watts
+    pub power: Option<f64>,
+    pub energy_full: Option<f64>,
+    pub energy_full_design: Option<f64>,
+    /// `auto`, `inhibit-charge` or `force-discharge`, where supported
+    pub charge_behaviour: Option<Choice>,
+    /// Firmware low-battery alarm as a percentage of full, on ACPI
+    /// batteries that have one (0 means disabled)
+    pub alarm: Option<u8>,
+    /// Charging starts below `start` and stops at `end`, where the
+    /// driver supports thresholds
+    pub start_threshold: Option<u8>,
+    pub end_threshold: Option<u8>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Threshold {
+    Start,
+    End,
+}
+
+impl Threshold {
+    /// The standard attribute, then the name older ThinkPad kernels
+    /// (and some vendor drivers) use for the same thing
+    fn attributes(&self) -> [&'static str; 2] {
+        match self {
+            Threshold::Start => ["charge_control_start_threshold", "charge_start_threshold"],
+            Threshold::End => ["charge_control_end_threshold", "charge_stop_threshold"],
+        }
+    }
+
+    /// Whichever of the attributes this battery has, the standard one
+    /// when it has neither
+    pub fn path(&self, battery: &Path) -> PathBuf {
+        let [standard, vendor] = self.attributes();
+        let vendor = battery.join(vendor);
+        if !battery.join(standard).exists() && vendor.exists() {
+            return vendor;
+        }
+        battery.join(standard)
+    }
+}
+
+impl BatteryInfo {
+    /// `75–80%`, `≤80%`, or None without thresholds
+    pub fn thresholds(&self) -> Option<String> {
+        match (self.start_threshold, self.end_threshold) {
+            (Some(start), Some(end)) => Some(format!("{}–{}%", start, end)),
+            (None, Some(end)) => Some(format!("≤{}%", end)),
+            (Some(start), None) => Some(format!("from {}%", start)),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether `charge_behaviour` offers `mode`
+    pub fn supports(&self, mode: &str) -> bool {
+        self.charge_behaviour
+            .as_ref()
+            .is_some_and(|c| c.options.iter().any(|o| o == mode))
+    }
+
+    pub fn behaviour(&self) -> Option<&str> {
+        self.charge_behaviour.as_ref()?.current.as_deref()
+    }
+}
+
+/// `2h 10m remaining` or `40m until full`, from energy and power in
+/// matching units
+fn time_remaining(
+    status: &str,
+    energy_now: Option<f64>,
+    energy_full: Option<f64>,
+    power: Option<f64>,
+) -> Option<String> {
+    let power = power.filter(|p| *p > 0.0)?;
+    let energy = if status == "Charging" {
+        energy_full? - energy_now?
+    } else {
+        energy_now?
+    };
+    let duration = locale::duration((energy / power * 3600.0) as u64);
+    if status == "Charging" {
+        Some(format!("{} until full", duration))
+    } else {
+        Some(format!("{} remaining", duration))
+    }
+}
+
+/// All batteries as one: charge and time left from the energy they hold
+/// together, health from their combined capacity, and the draw of those
+/// going the same way. A lone battery comes back as it is; otherwise
+/// per-battery settings (thresholds, behaviour, alarm) are left out.
+pub fn combined(batteries: &[BatteryInfo]) -> Option<BatteryInfo> {
+    let [first, rest @ ..] = batteries else {
+        return None;
+    };
+    if rest.is_empty() {
+        return Some(first.clone());
+    }
+    let sum = |value: fn(&BatteryInfo) -> Option<f64>| -> Option<f64> {
+        batteries.iter().map(value).sum()
+    };
+    let full = sum(|b| b.energy_full);
+    let now = sum(|b| Some(b.energy_full? * b.capacity as f64 / 100.0));
+    let average = |value: fn(&BatteryInfo) -> u8| {
+        (batteries.iter().map(|b| value(b) as u32).sum::<u32>() / batteries.len() as u32) as u8
+    };
+    let capacity = match (now, full) {
+        (Some(now), Some(full)) if full > 0.0 => (now / full * 100.0).round() as u8,
+        _ => average(|b| b.capacity),
+    };
+    // Firmware charges and drains packs one at a time, so the set is
+    // doing whatever any of them is doing
+    let any = |status: &str| batteries.iter().any(|b| b.status == status);
+    let status = if any("Charging") {
+        "Charging"
+    } else if any("Discharging") {
+        "Discharging"
+    } else if batteries.iter().all(|b| b.status == "Full") {
+        "Full"
+    } else {
+        first.status.as_str()
+    }
+    .to_string();
+    let draws: Vec<f64> = batteries
+        .iter()
+        .filter(|b| b.status == status)
+        .filter_map(|b| b.power)
+        .collect();
+    let power = (!draws.is_empty()).then(|| draws.iter().sum());
+    let health = full
+        .zip(sum(|b| b.energy_full_design))
+        .map(|(full, design)| (full / design * 100.0) as u8);
+    Some(BatteryInfo {
+        name: "Total".to_string(),
+        capacity,
+        reported: average(|b| b.reported),
+        health,
+        cycles: None,
+        time_remaining: time_remaining(&status, now, full, power),
+        status,
+        power,
+        energy_full: full,
+        energy_full_design: sum(|b| b.energy_full_design),
+        charge_behaviour: None,
+        alarm: None,
+        start_threshold: None,
+        end_threshold: None,
+        path: first.path.clone(),
+    })
+}
+
+/// The first system battery (usually BAT0, or macsmc-battery on Asahi)
+pub fn read_battery_info() -> Option<BatteryInfo> {
+    read_batteries().into_iter().next()
+}
+
+/// Every system battery, by name. Power banks and peripherals are
+/// batteries too, but scoped to a device.
+pub fn read_batteries() -> Vec<BatteryInfo> {
+    let Ok(entries) = fs::read_dir(sysfs::path("/sys/class/power_supply")) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let scope = sysfs::read_string(path.join("scope"));
+            sysfs::read_string(path.join("type")).as_deref() == Some("Battery")
+                && scope.as_deref() != Some("Device")
+        })
+        .collect();
+    paths.sort();
+    paths.iter().filter_map(|path| read_battery(path)).collect()
+}
+
+fn read_battery(battery_path: &Path) -> Option<BatteryInfo> {
+    let reported: u8 = sysfs::read_value(battery_path.join("capacity"))?;
+    let capacity = calibration::apply(reported as f64).round() as u8;
+
+    let status =
+        sysfs::read_string(battery_path.join("status")).unwrap_or_else(|| "Unknown".to_string());
+
+    // sysfs reports energy in µWh and power in µW
+    let energy_full: Option<f64> = sysfs::read_value(battery_path.join("energy_full"));
+    let energy_full_design: Option<f64> =
+        sysfs::read_value(battery_path.join("energy_full_design"));
+    // Firmware that gets the percentage wrong usually derives energy_now
+    // from it too, so a calibrated percentage stands in for it
+    let energy_now: Option<f64> = match energy_full {
+        Some(full) if calibration::active() => Some(full * capacity as f64 / 100.0),
+        _ => sysfs::read_value(battery_path.join("energy_now")),
+    };
+    // Batteries that report charge rather than energy (charge_now and
+    // friends) have current_now instead; µV × µA / 10⁶ is µW
+    let power_now: Option<f64> = sysfs::read_value(battery_path.join("power_now")).or_else(|| {
+        let volts: f64 = sysfs::read_value(battery_path.join("voltage_now"))?;
+        let amps: f64 = sysfs::read_value(battery_path.join("current_now"))?;
+        Some((volts * amps).abs() / 1_000_000.0)
+    });
+
+    // Calculate health from energy_full vs energy_full_design
+    let health = (|| Some(((energy_full? / energy_full_design?) * 100.0) as u8))();
+
+    let time_remaining = time_remaining(&status, energy_now, energy_full, power_now);
+
+    Some(BatteryInfo {
+        path: battery_path.to_path_buf(),
+        capacity,
+        reported,
+        status,
+        health,
+        // Firmware without a counter reports 0
+        cycles: sysfs::read_value(battery_path.join("cycle_count")).filter(|c| *c > 0),
+        time_remaining,
+        power: power_now.map(|p| p / 1_000_000.0),
+        energy_full: energy_full.map(|e| e / 1_000_000.0),
+        energy_full_design: energy_full_design.map(|e| e / 1_000_000.0),
+        charge_behaviour: sysfs::read_choice(battery_path.join("charge_behaviour")),
+        alarm: read_alarm(battery_path),
+        start_threshold: sysfs::read_value(Threshold::Start.path(battery_path)),
+        end_threshold: sysfs::read_value(Threshold::End.path(battery_path)),
+        name: battery_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    })
+}
+
+/// Full capacity in the units `alarm` uses: µWh, or µAh on batteries
+/// that report charge instead of energy
+fn full_raw(battery_path: &Path) -> Option<f64> {
+    sysfs::read_value(battery_path.join("energy_full"))
+        .or_else(|| sysfs::read_value(battery_path.join("charge_full")))
+}
+
+fn read_alarm(battery_path: &Path) -> Option<u8> {
+    let alarm: f64 = sysfs::read_value(battery_path.join("alarm"))?;
+    let full = full_raw(battery_path).filter(|f| *f > 0.0)?;
+    Some((alarm / full * 100.0).round() as u8)
+}
+
+/// Set the firmware alarm to `percent` of full capacity
+pub fn set_alarm(battery: &BatteryInfo, percent: u8) -> Result<(), String> {
+    let full = full_raw(&battery.path).ok_or("Battery reports no full capacity")?;
+    let value = (full * percent.min(100) as f64 / 100.0) as u64;
+    let path = battery.path.join("alarm");
+    let old = sysfs::read_string(&path);
+    privileged::write(
+        "battery alarm",
+        old,
+        &path.to_string_lossy(),
+        &value.to_string(),
+    )
+}
+
+pub fn set_threshold(
+    battery: &BatteryInfo,
+    threshold: Threshold,
+    percent: u8,
+) -> Result<(), String> {
+    let path = threshold.path(&battery.path);
+    let old = sysfs::read_string(&path);
+    let attribute = path.file_name().unwrap_or_default().to_string_lossy();
+    privileged::write(
+        &format!("{} {}", battery.name, attribute),
+        old,
+        &path.to_string_lossy(),
+        &percent.min(100).to_string(),
+    )
+}
+
+/// Write both charge thresholds, skipping values already set. Drivers
+/// reject a start at or above the current end, so the order depends on
+/// which way they move.
+pub fn set_thresholds(battery: &BatteryInfo, start: Option<u8>, end: u8) -> Result<(), String> {
+    if battery.end_threshold.is_none() {
+        return Err(format!(
+            "{} doesn't support charge thresholds",
+            battery.name
+        ));
+    }
+    let mut writes = vec![(Threshold::End, Some(end))];
+    let start_first = start.is_some_and(|s| battery.end_threshold.is_some_and(|e| s < e));
+    if start_first {
+        writes.insert(0, (Threshold::Start, start));
+    } else {
+        writes.push((Threshold::Start, start));
+    }
+    let current = |t: Threshold| match t {
+        Threshold::Start => battery.start_threshold,
+        Threshold::End => battery.end_threshold,
+    };
+    for (threshold, value) in writes {
+        if let Some(value) = value.filter(|v| Some(*v) != current(threshold)) {
+            set_threshold(battery, threshold, value)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn set_charge_behaviour(battery: &BatteryInfo, mode: &str) -> Result<(), String> {
+    let old = battery
+        .charge_behaviour
+        .as_ref()
+        .and_then(|c| c.current.clone());
+    let path = battery.path.join("charge_behaviour");
+    privileged::write("charge_behaviour", old, &path.to_string_lossy(), mode)
+}
+
+/// Back to `auto` when a forced discharge has reached the floor, before
+/// it empties the battery. None when there's nothing to stop.
+pub fn guard_force_discharge(battery: &BatteryInfo) -> Option<Result<(), String>> {
+    if battery.behaviour() != Some("force-discharge") || battery.capacity > FORCE_DISCHARGE_FLOOR {
+        return None;
+    }
+    Some(set_charge_behaviour(battery, "auto"))
+}
@@ -0,0 +1,196 @@
+//! The one program powertui runs as root through pkexec or sudo. It
+//! writes a value to, or reads bytes from, the attributes powertui
+//! changes and nothing else, so the polkit policy and a sudoers rule
+//! can allow it without handing out `tee` or `dd`.
+//!
+//!     echo <value> | powertui-helper write <path>
+//!     powertui-helper read <path> <offset> <length>
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::FileExt;
+use std::process::ExitCode;
+
+/// Attributes powertui writes. `*` stands for any part of one path
+/// component.
+const WRITABLE: [&str; 35] = [
+    "/sys/devices/system/cpu/cpufreq/policy*/scaling_governor",
+    "/sys/devices/system/cpu/cpufreq/policy*/scaling_min_freq",
+    "/sys/devices/system/cpu/cpufreq/policy*/scaling_max_freq",
+    "/sys/devices/system/cpu/cpufreq/policy*/energy_performance_preference",
+    "/sys/devices/system/cpu/cpufreq/boost",
+    "/sys/devices/system/cpu/intel_pstate/no_turbo",
+    "/sys/devices/system/cpu/intel_pstate/min_perf_pct",
+    "/sys/devices/system/cpu/intel_pstate/max_perf_pct",
+    "/sys/class/power_supply/*/charge_control_start_threshold",
+    "/sys/class/power_supply/*/charge_control_end_threshold",
+    "/sys/class/power_supply/*/charge_start_threshold",
+    "/sys/class/power_supply/*/charge_stop_threshold",
+    "/sys/class/power_supply/*/charge_behaviour",
+    "/sys/class/power_supply/*/alarm",
+    "/sys/class/powercap/intel-rapl:*/constraint_*_power_limit_uw",
+    "/sys/class/backlight/*/brightness",
+    "/sys/class/drm/card*/gt_min_freq_mhz",
+    "/sys/class/drm/card*/gt_max_freq_mhz",
+    "/sys/class/drm/card*/device/power_dpm_force_performance_level",
+    "/sys/kernel/debug/dri/*/i915_edp_psr_debug",
+    "/sys/class/hwmon/hwmon*/pwm*",
+    "/sys/class/rfkill/rfkill*/soft",
+    "/sys/firmware/acpi/platform_profile",
+    "/sys/power/mem_sleep",
+    "/sys/module/zswap/parameters/enabled",
+    "/sys/module/zswap/parameters/compressor",
+    "/sys/module/zswap/parameters/max_pool_percent",
+    "/sys/module/pcie_aspm/parameters/policy",
+    "/sys/module/snd_hda_intel/parameters/power_save",
+    "/sys/bus/pci/devices/*/power/control",
+    "/sys/bus/usb/devices/*/power/control",
+    "/sys/class/scsi_host/host*/link_power_management_policy",
+    "/proc/sys/vm/swappiness",
+    "/proc/sys/vm/dirty_writeback_centisecs",
+    "/proc/sys/kernel/nmi_watchdog",
+];
+
+/// Files powertui reads as root
+const READABLE: [&str; 1] = ["/dev/cpu/*/msr"];
+
+/// Where an allowed path may lead once its links are followed. Nobody
+/// can make links in these, so a path that matches stays what it says.
+const ROOTS: [&str; 3] = ["/sys/", "/proc/sys/", "/dev/cpu/"];
+
+/// The longest value written, far more than any attribute takes
+const MAX_VALUE: u64 = 4096;
+
+/// The most bytes read at once, a few registers' worth
+const MAX_READ: usize = 64;
+
+/// `pattern` with `*` matching anything within the component, but
+/// never stepping out of the directory
+fn component_matches(pattern: &str, component: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            !matches!(component, "" | "." | "..")
+                && component.len() >= prefix.len() + suffix.len()
+                && component.starts_with(prefix)
+                && component.ends_with(suffix)
+        }
+        None => pattern == component,
+    }
+}
+
+fn matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    pattern.len() == path.len()
+        && pattern
+            .iter()
+            .zip(&path)
+            .all(|(p, c)| component_matches(p, c))
+}
+
+/// `path` if one of `allowed` covers it, through links included
+fn check(path: &str, allowed: &[&str]) -> Result<(), String> {
+    if !allowed.iter().any(|pattern| matches(pattern, path)) {
+        return Err(format!("{} isn't a setting powertui changes", path));
+    }
+    let real = fs::canonicalize(path).map_err(|e| format!("{}: {}", path, e))?;
+    let real = real.to_string_lossy();
+    if !ROOTS.iter().any(|root| real.starts_with(root)) {
+        return Err(format!("{} leads outside sysfs to {}", path, real));
+    }
+    Ok(())
+}
+
+fn write(path: &str) -> Result<(), String> {
+    check(path, &WRITABLE)?;
+    let mut value = Vec::new();
+    io::stdin()
+        .take(MAX_VALUE)
+        .read_to_end(&mut value)
+        .map_err(|e| e.to_string())?;
+    // Never created: only attributes that exist are written
+    OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&value))
+        .map_err(|e| format!("{}: {}", path, e))
+}
+
+fn read(path: &str, offset: &str, len: &str) -> Result<(), String> {
+    check(path, &READABLE)?;
+    let offset: u64 = offset
+        .parse()
+        .map_err(|_| format!("Bad offset {}", offset))?;
+    let len: usize = len
+        .parse()
+        .ok()
+        .filter(|len| (1..=MAX_READ).contains(len))
+        .ok_or_else(|| format!("Bad length {}", len))?;
+    let mut bytes = vec![0u8; len];
+    fs::File::open(path)
+        .and_then(|file| file.read_exact_at(&mut bytes, offset))
+        .map_err(|e| format!("{} at {:#x}: {}", path, offset, e))?;
+    io::stdout().write_all(&bytes).map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["write", path] => write(path),
+        ["read", path, offset, len] => read(path, offset, len),
+        _ => Err("usage: powertui-helper write <path> | read <path> <offset> <length>".to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowed_attributes() {
+        let allowed = |path: &str| WRITABLE.iter().any(|p| matches(p, path));
+        assert!(allowed(
+            "/sys/devices/system/cpu/cpufreq/policy3/scaling_governor"
+        ));
+        assert!(allowed(
+            "/sys/class/power_supply/BAT0/charge_control_end_threshold"
+        ));
+        assert!(allowed("/sys/class/hwmon/hwmon4/pwm1_enable"));
+        assert!(allowed("/proc/sys/vm/swappiness"));
+    }
+
+    #[test]
+    fn nothing_outside_the_patterns() {
+        let allowed = |path: &str| WRITABLE.iter().any(|p| matches(p, path));
+        assert!(!allowed("/etc/shadow"));
+        assert!(!allowed(
+            "/sys/class/power_supply/BAT0/../../../../etc/shadow"
+        ));
+        assert!(!allowed("/sys/class/power_supply/../charge_behaviour"));
+        assert!(!allowed("/sys/class/power_supply//charge_behaviour"));
+        assert!(!allowed(
+            "/sys/class/power_supply/BAT0/sub/charge_behaviour"
+        ));
+        assert!(!allowed(
+            "/sys/devices/system/cpu/cpufreq/policy0/scaling_setspeed"
+        ));
+    }
+
+    #[test]
+    fn star_stays_in_its_component() {
+        assert!(component_matches("policy*", "policy12"));
+        assert!(!component_matches("policy*", "cpu0"));
+        assert!(component_matches("*", "BAT0"));
+        for name in ["", ".", ".."] {
+            assert!(!component_matches("*", name));
+        }
+    }
+}
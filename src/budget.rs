@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+
+use crate::battery::BatteryInfo;
+
+/// Lowest PL1 the budget sets; below this most packages stutter
+/// without saving much more
+pub const MIN_PACKAGE: f64 = 4.0;
+
+/// Changes smaller than this aren't worth a write
+pub const CAP_STEP: f64 = 1.0;
+
+/// A runtime the battery has to last, `:budget 4h`
+pub struct Budget {
+    pub until: Instant,
+    /// Hold the package to what's left of the budget through PL1
+    pub cap: bool,
+    /// PL1 before the budget first changed it, to put back afterwards
+    pub restore: Option<f64>,
+}
+
+impl Budget {
+    pub fn new(runtime: Duration, cap: bool) -> Self {
+        Budget {
+            until: Instant::now() + runtime,
+            cap,
+            restore: None,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.until.saturating_duration_since(Instant::now())
+    }
+
+    /// Watts the whole machine may draw to last until the target
+    pub fn watts(&self, batteries: &[BatteryInfo]) -> Option<f64> {
+        let hours = self.remaining().as_secs_f64() / 3600.0;
+        let energy = remaining_energy(batteries)?;
+        (hours > 0.0).then(|| energy / hours)
+    }
+}
+
+/// Watt-hours left across all batteries
+pub fn remaining_energy(batteries: &[BatteryInfo]) -> Option<f64> {
+    let energy: Vec<f64> = batteries
+        .iter()
+        .filter_map(|b| Some(b.energy_full? * b.capacity as f64 / 100.0))
+        .collect();
+    (!energy.is_empty()).then(|| energy.iter().sum())
+}
+
+/// Draw across all discharging batteries, in watts
+pub fn system_draw(batteries: &[BatteryInfo]) -> Option<f64> {
+    let draw: Vec<f64> = batteries
+        .iter()
+        .filter(|b| b.status == "Discharging")
+        .filter_map(|b| b.power)
+        .collect();
+    (!draw.is_empty()).then(|| draw.iter().sum())
+}
+
+/// PL1 that keeps the machine within `budget`: whatever the rest of the
+/// system isn't drawing, measured as total draw less the package's
+pub fn package_cap(budget: f64, system: f64, package: f64) -> f64 {
+    let rest = (system - package).max(0.0);
+    (budget - rest).max(MIN_PACKAGE)
+}
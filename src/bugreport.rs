@@ -0,0 +1,311 @@
+use std::env;
+use std::fs;
+use std::io::{stdin, stdout, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+use chrono::Local;
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+
+use crate::config;
+use crate::journal;
+use crate::snapshot;
+
+/// Keys in config.toml whose values can point at the owner: webhook
+/// URLs with tokens, a smart plug's address
+const SECRET_KEYS: [&str; 4] = ["url", "host", "token", "password"];
+
+/// State files worth having, and how many lines of each from the end
+const STATE_FILES: [(&str, usize); 6] = [
+    ("audit.jsonl", 500),
+    ("daemon.json", 1),
+    ("profile.json", 1),
+    ("storage.json", 1),
+    ("history.csv", 2000),
+    ("sessions.jsonl", 200),
+];
+
+/// One part of the bundle, included or left out as a whole
+struct Part {
+    /// Directory or file name inside the bundle
+    name: &'static str,
+    description: &'static str,
+}
+
+const PARTS: [Part; 5] = [
+    Part {
+        name: "doctor.txt",
+        description: "capability matrix and setup checks",
+    },
+    Part {
+        name: "journal.txt",
+        description: "this boot's thermal, ACPI, battery and suspend kernel messages",
+    },
+    Part {
+        name: "snapshot",
+        description: "the sysfs files powertui reads, without serials, asset tags, UUIDs, MAC addresses or the host name",
+    },
+    Part {
+        name: "config",
+        description: "config.toml and profiles.toml, with URLs and hosts redacted",
+    },
+    Part {
+        name: "state",
+        description: "the audit trail, daemon state, battery log and sessions",
+    },
+];
+
+/// `powertui bugreport [--yes] [-o FILE]`: gather what a bug report
+/// needs into one tarball, asking about each part before it's added
+pub fn run(args: &[String]) -> Result<()> {
+    let mut review = true;
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--yes" | "-y" => review = false,
+            "-o" | "--output" => {
+                let Some(path) = args.next() else {
+                    bail!("{} needs a file name", arg);
+                };
+                output = Some(PathBuf::from(path));
+            }
+            _ => bail!("unknown bugreport option: {}", arg),
+        }
+    }
+    if review && !stdin().is_terminal() {
+        bail!("Nothing to review on: run it in a terminal, or pass --yes to include everything");
+    }
+    let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let name = format!("powertui-bugreport-{}", stamp);
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", name)));
+
+    let staging = env::temp_dir().join(format!("powertui-bugreport-{}", process::id()));
+    let bundle = staging.join(&name);
+    fs::create_dir_all(&bundle)?;
+    let result = gather(&bundle)
+        .and_then(|()| pick(&bundle, review))
+        .and_then(|kept| {
+            if kept == 0 {
+                bail!("Nothing left to bundle");
+            }
+            pack(&staging, &name, &output)
+        });
+    let _ = fs::remove_dir_all(&staging);
+    result?;
+    println!("Wrote {}; attach it to the issue", output.display());
+    Ok(())
+}
+
+/// Write every part into `bundle`
+fn gather(bundle: &Path) -> Result<()> {
+    println!("Collecting...");
+    let mut doctor = String::new();
+    for (title, args) in [
+        ("powertui doctor --capabilities", &["--capabilities"][..]),
+        ("powertui doctor", &[][..]),
+    ] {
+        doctor.push_str(&format!("$ {}\n{}\n", title, run_doctor(args)));
+    }
+    doctor.push_str(&format!(
+        "powertui {}\nkernel {}\n",
+        env!("CARGO_PKG_VERSION"),
+        fs::read_to_string("/proc/sys/kernel/osrelease")
+            .unwrap_or_default()
+            .trim()
+    ));
+    if let Some(name) = config::config_profile() {
+        doctor.push_str(&format!("config profile {}\n", name));
+    }
+    fs::write(bundle.join("doctor.txt"), scrub(&doctor))?;
+
+    let journal = journal::recent(300)
+        .unwrap_or_else(|| "journalctl isn't available or returned nothing\n".to_string());
+    fs::write(bundle.join("journal.txt"), scrub(&journal))?;
+
+    let snapshot = bundle.join("snapshot");
+    snapshot::record(&snapshot)?;
+    for file in files(&snapshot) {
+        if let Ok(text) = fs::read_to_string(&file) {
+            fs::write(&file, scrub(&text))?;
+        }
+    }
+
+    let config_out = bundle.join("config");
+    fs::create_dir_all(&config_out)?;
+    if let Some(dir) = config::config_dir() {
+        for file in ["config.toml", "profiles.toml"] {
+            if let Ok(text) = fs::read_to_string(dir.join(file)) {
+                fs::write(config_out.join(file), scrub(&redact(&text)))?;
+            }
+        }
+    }
+
+    let state_out = bundle.join("state");
+    fs::create_dir_all(&state_out)?;
+    if let Some(dir) = config::state_dir() {
+        for (file, lines) in STATE_FILES {
+            if let Ok(text) = fs::read_to_string(dir.join(file)) {
+                fs::write(state_out.join(file), scrub(&tail(&text, lines)))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// This binary's own doctor output, uncolored
+fn run_doctor(args: &[&str]) -> String {
+    let Ok(exe) = env::current_exe() else {
+        return "couldn't find the powertui binary\n".to_string();
+    };
+    let mut command = Command::new(exe);
+    command.arg("doctor").args(args).arg("--no-color");
+    if let Some(name) = config::config_profile() {
+        command.args(["--config-profile", name]);
+    }
+    match command.output() {
+        Ok(output) => {
+            String::from_utf8_lossy(&output.stdout).into_owned()
+                + &String::from_utf8_lossy(&output.stderr)
+        }
+        Err(e) => format!("doctor failed to run: {}\n", e),
+    }
+}
+
+/// Replace the values of `SECRET_KEYS` line by line, so comments and
+/// layout survive and a broken file still comes through
+fn redact(toml: &str) -> String {
+    toml.lines()
+        .map(|line| {
+            let Some((key, _)) = line.split_once('=') else {
+                return line.to_string();
+            };
+            if SECRET_KEYS.contains(&key.trim()) {
+                format!("{}= \"<redacted>\"", key)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+/// The host name and home directory, wherever they turn up. Host names
+/// too short to be told apart from ordinary words are left.
+fn scrub(text: &str) -> String {
+    let mut text = text.to_string();
+    if let Ok(host) = fs::read_to_string("/proc/sys/kernel/hostname") {
+        let host = host.trim();
+        if host.len() > 3 {
+            text = text.replace(host, "<host>");
+        }
+    }
+    if let Some(home) = env::var_os("HOME").filter(|h| h.len() > 1) {
+        text = text.replace(&*home.to_string_lossy(), "~");
+    }
+    text
+}
+
+fn tail(text: &str, lines: usize) -> String {
+    let all: Vec<&str> = text.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    all[start..].join("\n") + "\n"
+}
+
+/// Walk through the parts, dropping the ones the user doesn't want.
+/// Returns how many are left.
+fn pick(bundle: &Path, review: bool) -> Result<usize> {
+    let mut kept = 0;
+    for part in &PARTS {
+        let path = bundle.join(part.name);
+        let files = files(&path);
+        if files.is_empty() {
+            let _ = fs::remove_dir_all(&path);
+            continue;
+        }
+        let bytes: u64 = files
+            .iter()
+            .filter_map(|f| fs::metadata(f).ok())
+            .map(|m| m.len())
+            .sum();
+        println!(
+            "{:<12} {} ({} file{}, {} KiB)",
+            part.name,
+            part.description,
+            files.len(),
+            if files.len() == 1 { "" } else { "s" },
+            bytes.div_ceil(1024)
+        );
+        if !review || ask(bundle, &files)? {
+            kept += 1;
+            continue;
+        }
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+        println!("             left out");
+    }
+    Ok(kept)
+}
+
+/// `Y`, `n`, or `v` to look first: a file's contents, or a directory's
+/// file names
+fn ask(bundle: &Path, files: &[PathBuf]) -> Result<bool> {
+    loop {
+        print!("             include? [Y/n/v]iew ");
+        stdout().flush()?;
+        let mut answer = String::new();
+        if stdin().read_line(&mut answer)? == 0 {
+            bail!("Cancelled");
+        }
+        match answer.trim() {
+            "" | "y" | "Y" => return Ok(true),
+            "n" | "N" => return Ok(false),
+            "v" | "V" => {
+                for file in files {
+                    let shown = file.strip_prefix(bundle).unwrap_or(file);
+                    if files.len() == 1 {
+                        println!("--- {}", shown.display());
+                        println!("{}", fs::read_to_string(file).unwrap_or_default());
+                    } else {
+                        println!("  {}", shown.display());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Every file at or under `path`
+fn files(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        return vec![path.to_path_buf()];
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+        files.extend(self::files(&entry.path()));
+    }
+    files.sort();
+    files
+}
+
+fn pack(staging: &Path, name: &str, output: &Path) -> Result<()> {
+    let output = env::current_dir()?.join(output);
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&output)
+        .arg("-C")
+        .arg(staging)
+        .arg(name)
+        .status()
+        .map_err(|e| eyre!("tar: {}", e))?;
+    if !status.success() {
+        bail!("tar couldn't write {}", output.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::sync::OnceLock;
+
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+
+use crate::charging::{self, ChargeSample};
+
+/// Reported and actual percentage pairs, sorted, from `[battery]
+/// calibration`. Empty leaves readings alone.
+static CURVE: OnceLock<Vec<(f64, f64)>> = OnceLock::new();
+
+/// A discharge has to cover this many points to learn from
+const MIN_SPAN: f64 = 40.0;
+
+/// Settle the curve for this run from the config
+pub fn init(points: &[[f64; 2]]) {
+    let mut curve: Vec<(f64, f64)> = points
+        .iter()
+        .map(|[reported, actual]| (reported.clamp(0.0, 100.0), actual.clamp(0.0, 100.0)))
+        .collect();
+    if !curve.is_empty() {
+        curve.sort_by(|a, b| a.0.total_cmp(&b.0));
+        // Empty and full mean the same on both sides unless said otherwise
+        if curve[0].0 > 0.0 {
+            curve.insert(0, (0.0, 0.0));
+        }
+        if curve[curve.len() - 1].0 < 100.0 {
+            curve.push((100.0, 100.0));
+        }
+    }
+    let _ = CURVE.set(curve);
+}
+
+pub fn active() -> bool {
+    CURVE.get().is_some_and(|c| !c.is_empty())
+}
+
+/// The actual charge for a reported percentage, interpolated between
+/// the nearest points of the curve
+pub fn apply(reported: f64) -> f64 {
+    let Some(curve) = CURVE.get().filter(|c| !c.is_empty()) else {
+        return reported;
+    };
+    let reported = reported.clamp(0.0, 100.0);
+    curve
+        .windows(2)
+        .find(|w| reported <= w[1].0)
+        .map(|w| {
+            let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+            if x1 > x0 {
+                y0 + (y1 - y0) * (reported - x0) / (x1 - x0)
+            } else {
+                y1
+            }
+        })
+        .unwrap_or(reported)
+}
+
+/// The longest run of discharging samples, oldest first
+fn longest_discharge(history: &[ChargeSample]) -> &[ChargeSample] {
+    let mut best: &[ChargeSample] = &[];
+    for run in history.split(|s| s.state != "discharging") {
+        let span = |r: &[ChargeSample]| match (r.first(), r.last()) {
+            (Some(a), Some(b)) => a.percent - b.percent,
+            _ => 0.0,
+        };
+        if span(run) > span(best) {
+            best = run;
+        }
+    }
+    best
+}
+
+/// Draw in watts at `time`: the last rate sample before it
+fn rate_at(rates: &[ChargeSample], time: i64) -> Option<f64> {
+    rates
+        .iter()
+        .take_while(|r| r.time <= time)
+        .last()
+        .map(|r| r.percent)
+}
+
+/// A curve from one long discharge in UPower's history. Energy drawn is
+/// integrated from the rate history; where the reported percentage
+/// falls out of step with it, the firmware isn't linear. The ends of
+/// the discharge are taken as reported, so this corrects the shape of
+/// the curve but not an offset.
+fn learn(history: &[ChargeSample], rates: &[ChargeSample]) -> Result<Vec<(f64, f64)>> {
+    let session = longest_discharge(history);
+    let (Some(first), Some(last)) = (session.first(), session.last()) else {
+        bail!("UPower has no discharge history");
+    };
+    let span = first.percent - last.percent;
+    if span < MIN_SPAN {
+        bail!(
+            "the longest recorded discharge covers only {:.0}%; run the battery down from full to below {:.0}% first",
+            span,
+            100.0 - MIN_SPAN
+        );
+    }
+
+    // Watt-seconds drawn by each sample's time
+    let mut drawn = vec![0.0];
+    for pair in session.windows(2) {
+        let rate = rate_at(rates, pair[0].time).unwrap_or(0.0);
+        let energy = rate * (pair[1].time - pair[0].time) as f64;
+        drawn.push(drawn.last().copied().unwrap_or(0.0) + energy);
+    }
+    let total = drawn.last().copied().unwrap_or(0.0);
+    if total <= 0.0 {
+        bail!("UPower recorded no discharge rate for that session");
+    }
+
+    // Average actual charge at each ten-point step of the reported one
+    let mut curve = Vec::new();
+    for step in (0..=100).step_by(10).map(f64::from) {
+        let near: Vec<f64> = session
+            .iter()
+            .zip(&drawn)
+            .filter(|(s, _)| (s.percent - step).abs() <= 2.0)
+            .map(|(_, e)| first.percent - span * e / total)
+            .collect();
+        if !near.is_empty() {
+            curve.push((step, near.iter().sum::<f64>() / near.len() as f64));
+        }
+    }
+    Ok(curve)
+}
+
+/// `powertui calibrate`: learn a curve and print it for the config
+pub fn run(args: &[String]) -> Result<()> {
+    if let Some(arg) = args.first() {
+        bail!("unknown calibrate option: {}", arg);
+    }
+    let curve = learn(&charging::read_history(), &charging::read_rate_history())?;
+    println!("# Reported and actual percentage, learned from UPower's history");
+    println!("[battery]");
+    let points: Vec<String> = curve
+        .iter()
+        .map(|(reported, actual)| format!("[{:.0}, {:.1}]", reported, actual))
+        .collect();
+    println!("calibration = [{}]", points.join(", "));
+    Ok(())
+}
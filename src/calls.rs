@@ -0,0 +1,167 @@
+use std::fs;
+use std::process::Command;
+
+use serde_json::Value;
+
+use crate::desktop::command_exists;
+use crate::sysfs;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Device {
+    Microphone,
+    Camera,
+}
+
+impl Device {
+    pub fn name(self) -> &'static str {
+        match self {
+            Device::Microphone => "microphone",
+            Device::Camera => "camera",
+        }
+    }
+}
+
+/// An application recording from a microphone or camera
+pub struct Capture {
+    pub app: String,
+    pub device: Device,
+}
+
+/// The media server opens cameras on behalf of its clients; those show
+/// up as PipeWire streams instead
+const MEDIA_SERVERS: [&str; 2] = ["pipewire", "wireplumber"];
+
+/// Whatever is recording right now. PipeWire knows which application
+/// owns each capture stream; without it, ALSA capture devices that are
+/// running and cameras held open in /proc are the best we can do.
+pub fn read_captures() -> Vec<Capture> {
+    let mut captures = pipewire_streams().unwrap_or_else(alsa_captures);
+    for app in camera_users() {
+        if !captures
+            .iter()
+            .any(|c| c.device == Device::Camera && c.app.eq_ignore_ascii_case(&app))
+        {
+            captures.push(Capture {
+                app,
+                device: Device::Camera,
+            });
+        }
+    }
+    captures
+}
+
+/// Capture streams PipeWire is running, or `None` without PipeWire
+fn pipewire_streams() -> Option<Vec<Capture>> {
+    if !command_exists("pw-dump") {
+        return None;
+    }
+    let output = Command::new("pw-dump").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let objects: Vec<Value> = serde_json::from_slice(&output.stdout).ok()?;
+    Some(
+        objects
+            .iter()
+            .filter_map(|object| {
+                let info = object.get("info")?;
+                if info.get("state")?.as_str()? != "running" {
+                    return None;
+                }
+                let props = info.get("props")?;
+                let prop = |key: &str| props.get(key).and_then(Value::as_str);
+                let device = match prop("media.class")? {
+                    "Stream/Input/Audio" => Device::Microphone,
+                    "Stream/Input/Video" => Device::Camera,
+                    _ => return None,
+                };
+                // Level meters in mixers record too, but aren't calls
+                if props.get("stream.monitor").and_then(Value::as_bool) == Some(true) {
+                    return None;
+                }
+                let app = prop("application.name")
+                    .or_else(|| prop("application.process.binary"))
+                    .or_else(|| prop("node.name"))?;
+                Some(Capture {
+                    app: app.to_string(),
+                    device,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// ALSA capture substreams in the RUNNING state, by owning process
+fn alsa_captures() -> Vec<Capture> {
+    let mut captures = Vec::new();
+    for card in fs::read_dir("/proc/asound").into_iter().flatten().flatten() {
+        for pcm in fs::read_dir(card.path()).into_iter().flatten().flatten() {
+            let name = pcm.file_name().to_string_lossy().into_owned();
+            if !(name.starts_with("pcm") && name.ends_with('c')) {
+                continue;
+            }
+            for sub in fs::read_dir(pcm.path()).into_iter().flatten().flatten() {
+                let Ok(status) = fs::read_to_string(sub.path().join("status")) else {
+                    continue;
+                };
+                if !status.lines().any(|l| l.trim() == "state: RUNNING") {
+                    continue;
+                }
+                let app = status
+                    .lines()
+                    .find_map(|l| l.trim().strip_prefix("owner_pid"))
+                    .and_then(|l| l.trim_start_matches([' ', ':']).parse::<u32>().ok())
+                    .and_then(|pid| sysfs::read_string(format!("/proc/{}/comm", pid)))
+                    .unwrap_or_else(|| "unknown".to_string());
+                captures.push(Capture {
+                    app,
+                    device: Device::Microphone,
+                });
+            }
+        }
+    }
+    captures
+}
+
+/// Processes with a V4L2 capture device open, by name. Only our own
+/// processes are visible without root, which covers a desktop session.
+fn camera_users() -> Vec<String> {
+    let mut users: Vec<String> = fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter(|e| {
+            fs::read_dir(e.path().join("fd"))
+                .into_iter()
+                .flatten()
+                .flatten()
+                .any(|fd| {
+                    fs::read_link(fd.path())
+                        .is_ok_and(|t| t.to_string_lossy().starts_with("/dev/video"))
+                })
+        })
+        .filter_map(|e| sysfs::read_string(e.path().join("comm")))
+        .filter(|name| !MEDIA_SERVERS.contains(&name.as_str()))
+        .collect();
+    users.sort();
+    users.dedup();
+    users
+}
+
+/// `Firefox (camera, microphone)`, one entry per application
+pub fn describe(captures: &[Capture]) -> String {
+    let mut apps: Vec<(&str, Vec<&str>)> = Vec::new();
+    for capture in captures {
+        let device = capture.device.name();
+        match apps.iter_mut().find(|(app, _)| *app == capture.app) {
+            Some((_, devices)) if !devices.contains(&device) => devices.push(device),
+            Some(_) => {}
+            None => apps.push((&capture.app, vec![device])),
+        }
+    }
+    apps.iter()
+        .map(|(app, devices)| format!("{} ({})", app, devices.join(", ")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
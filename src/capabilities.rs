@@ -0,0 +1,91 @@
+use std::fs;
+
+use crate::battery::{read_battery_info, Threshold};
+use crate::desktop;
+use crate::hwp;
+use crate::lid;
+use crate::rapl;
+use crate::rfkill;
+use crate::ryzen;
+use crate::sleep;
+use crate::sysfs;
+
+const CPU0: &str = "/sys/devices/system/cpu/cpu0/cpufreq";
+
+/// What this machine's kernel, firmware and installed tools support,
+/// probed at startup so the UI only offers what can work
+pub struct Capabilities {
+    pub governors: bool,
+    pub cpupower: bool,
+    pub epp: bool,
+    pub boost: bool,
+    pub hwp: bool,
+    pub platform_profile: bool,
+    pub charge_thresholds: bool,
+    pub charge_behaviour: bool,
+    pub battery_alarm: bool,
+    pub rapl: bool,
+    pub ryzenadj: bool,
+    /// More than one suspend mode to choose from
+    pub mem_sleep: bool,
+    pub hibernate: bool,
+    pub typec: bool,
+    pub rfkill: bool,
+    pub lid: bool,
+}
+
+impl Capabilities {
+    pub fn probe() -> Capabilities {
+        let exists = |path: &str| sysfs::path(path).exists();
+        let battery = read_battery_info();
+        let pstate = hwp::read_status();
+        Capabilities {
+            governors: exists(&format!("{}/scaling_governor", CPU0)),
+            cpupower: desktop::command_exists("cpupower"),
+            epp: exists(&format!("{}/energy_performance_preference", CPU0)),
+            // acpi-cpufreq and amd-pstate expose boost; intel_pstate no_turbo
+            boost: exists("/sys/devices/system/cpu/cpufreq/boost")
+                || exists("/sys/devices/system/cpu/intel_pstate/no_turbo"),
+            hwp: pstate.is_some_and(|s| s.hwp),
+            platform_profile: exists("/sys/firmware/acpi/platform_profile"),
+            charge_thresholds: battery
+                .as_ref()
+                .is_some_and(|b| Threshold::End.path(&b.path).exists()),
+            charge_behaviour: battery
+                .as_ref()
+                .is_some_and(|b| b.charge_behaviour.is_some()),
+            battery_alarm: battery.as_ref().is_some_and(|b| b.alarm.is_some()),
+            rapl: rapl::package().is_some(),
+            ryzenadj: ryzen::available(),
+            mem_sleep: sleep::read_mem_sleep().is_some_and(|m| m.options.len() > 1),
+            hibernate: sysfs::read_string(sysfs::path("/sys/power/state"))
+                .is_some_and(|s| s.split_whitespace().any(|s| s == "disk")),
+            typec: fs::read_dir(sysfs::path("/sys/class/typec"))
+                .is_ok_and(|mut entries| entries.next().is_some()),
+            rfkill: !rfkill::read_radios().is_empty(),
+            lid: lid::read_lid_closed().is_some(),
+        }
+    }
+
+    /// Every capability with its name, for `doctor --capabilities`
+    pub fn matrix(&self) -> [(&'static str, bool); 16] {
+        [
+            ("Governors", self.governors),
+            ("cpupower", self.cpupower),
+            ("EPP", self.epp),
+            ("Boost", self.boost),
+            ("HWP", self.hwp),
+            ("platform_profile", self.platform_profile),
+            ("Charge thresholds", self.charge_thresholds),
+            ("Charge behaviour", self.charge_behaviour),
+            ("Battery alarm", self.battery_alarm),
+            ("RAPL limits", self.rapl),
+            ("ryzenadj", self.ryzenadj),
+            ("Sleep modes", self.mem_sleep),
+            ("Hibernate", self.hibernate),
+            ("USB-C ports", self.typec),
+            ("rfkill", self.rfkill),
+            ("Lid switch", self.lid),
+        ]
+    }
+}
@@ -0,0 +1,137 @@
+use std::fs;
+
+use crate::battery::BatteryInfo;
+use crate::sysfs;
+
+/// An online external power source
+pub struct Charger {
+    pub name: String,
+    /// Negotiated wattage, known for USB-PD sources
+    pub watts: Option<f64>,
+}
+
+/// The first online charger. USB sources report negotiated voltage and
+/// current; plain mains adapters usually report neither.
+pub fn read_charger() -> Option<Charger> {
+    let mut chargers: Vec<Charger> = fs::read_dir(sysfs::path("/sys/class/power_supply"))
+        .ok()?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let kind = sysfs::read_string(path.join("type"))?;
+            if kind != "USB" && kind != "Mains" {
+                return None;
+            }
+            if sysfs::read_value::<u8>(path.join("online"))? != 1 {
+                return None;
+            }
+            // µV × µA
+            let volts: Option<f64> = sysfs::read_value(path.join("voltage_max"))
+                .or_else(|| sysfs::read_value(path.join("voltage_now")));
+            let amps: Option<f64> = sysfs::read_value(path.join("current_max"));
+            let watts = match (volts, amps) {
+                (Some(v), Some(a)) if v > 0.0 && a > 0.0 => Some(v * a / 1e12),
+                _ => None,
+            };
+            Some(Charger {
+                name: path.file_name()?.to_string_lossy().into_owned(),
+                watts,
+            })
+        })
+        .collect();
+    // Prefer a source that knows its wattage
+    chargers.sort_by_key(|c| c.watts.is_none());
+    chargers.into_iter().next()
+}
+
+/// Warn when the battery drains even though a charger is plugged in,
+/// e.g. "65 W charger can't keep up under load (−12.3 W)". Not while
+/// one of `batteries` is told to discharge or not to charge, since then
+/// draining on AC is the point.
+pub fn shortfall_warning(
+    charger: &Charger,
+    battery: &BatteryInfo,
+    batteries: &[BatteryInfo],
+) -> Option<String> {
+    if battery.status != "Discharging" {
+        return None;
+    }
+    if batteries
+        .iter()
+        .any(|b| matches!(b.behaviour(), Some("force-discharge" | "inhibit-charge")))
+    {
+        return None;
+    }
+    let deficit = battery.power.filter(|p| *p > 0.0)?;
+    let charger = match charger.watts {
+        Some(w) => format!("{:.0} W charger", w),
+        None => "Charger".to_string(),
+    };
+    Some(format!(
+        "{} can't keep up under load (−{:.1} W)",
+        charger, deficit
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::slice;
+
+    use super::*;
+    use crate::sysfs::Choice;
+
+    fn battery(status: &str, behaviour: &str) -> BatteryInfo {
+        BatteryInfo {
+            path: PathBuf::from("/sys/class/power_supply/BAT0"),
+            name: "BAT0".to_string(),
+            capacity: 80,
+            reported: 80,
+            status: status.to_string(),
+            health: None,
+            cycles: None,
+            time_remaining: None,
+            power: Some(12.3),
+            energy_full: None,
+            energy_full_design: None,
+            charge_behaviour: Some(Choice {
+                options: ["auto", "inhibit-charge", "force-discharge"]
+                    .map(String::from)
+                    .to_vec(),
+                current: Some(behaviour.to_string()),
+            }),
+            alarm: None,
+            start_threshold: None,
+            end_threshold: None,
+        }
+    }
+
+    const CHARGER: Charger = Charger {
+        name: String::new(),
+        watts: Some(65.0),
+    };
+
+    #[test]
+    fn shortfall_under_load() {
+        let bat = battery("Discharging", "auto");
+        assert_eq!(
+            shortfall_warning(&CHARGER, &bat, slice::from_ref(&bat)).as_deref(),
+            Some("65 W charger can't keep up under load (−12.3 W)")
+        );
+        let bat = battery("Charging", "auto");
+        assert_eq!(
+            shortfall_warning(&CHARGER, &bat, slice::from_ref(&bat)),
+            None
+        );
+    }
+
+    #[test]
+    fn no_shortfall_when_told_not_to_charge() {
+        for behaviour in ["force-discharge", "inhibit-charge"] {
+            let bat = battery("Discharging", behaviour);
+            assert_eq!(
+                shortfall_warning(&CHARGER, &bat, slice::from_ref(&bat)),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::battery::BatteryInfo;
+use crate::charger::Charger;
+
+/// One entry of UPower's charge history
+pub struct ChargeSample {
+    /// Unix timestamp
+    pub time: i64,
+    pub percent: f64,
+    /// `charging`, `discharging`, `fully-charged`, ...
+    pub state: String,
+}
+
+/// The battery's charge history file, `history-charge-<id>.dat`
+fn history_path() -> Option<PathBuf> {
+    fs::read_dir("/var/lib/upower")
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|p| {
+            p.file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with("history-charge-"))
+        })
+}
+
+/// `time value state` lines of a UPower history file
+fn read_samples(path: PathBuf) -> Vec<ChargeSample> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(ChargeSample {
+                time: fields.next()?.parse().ok()?,
+                percent: fields.next()?.parse().ok()?,
+                state: fields.next().unwrap_or("unknown").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The samples UPower records while running, oldest first
+pub fn read_history() -> Vec<ChargeSample> {
+    history_path().map(read_samples).unwrap_or_default()
+}
+
+/// UPower's record of the same battery's draw, oldest first, with the
+/// rate in watts in place of the percentage
+pub fn read_rate_history() -> Vec<ChargeSample> {
+    let Some(path) = history_path() else {
+        return Vec::new();
+    };
+    let name = path
+        .file_name()
+        .map(|n| {
+            n.to_string_lossy()
+                .replacen("history-charge-", "history-rate-", 1)
+        })
+        .unwrap_or_default();
+    read_samples(path.with_file_name(name))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AnomalyKind {
+    Slow,
+    Stuck,
+    Flapping,
+}
+
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    pub text: String,
+}
+
+/// Adapters at least this strong should charge visibly fast
+const HIGH_WATTAGE: f64 = 45.0;
+
+/// How long charge may sit still, or crawl, before it's worth a mention
+const WINDOW: i64 = 30 * 60;
+
+/// Plug/unplug transitions within an hour that suggest a loose cable
+const FLAPS_PER_HOUR: usize = 6;
+
+/// Charging that doesn't look right, from the current readings and the
+/// charging session recorded so far
+pub fn anomalies(
+    history: &[ChargeSample],
+    battery: &BatteryInfo,
+    charger: Option<&Charger>,
+    now: i64,
+) -> Vec<Anomaly> {
+    let mut found = Vec::new();
+    // Near the top, or at a configured limit, charging slows down on purpose
+    let limit = battery.end_threshold.map_or(100.0, f64::from).min(80.0);
+    let session = current_session(history);
+    let charging = battery.status == "Charging";
+
+    let stuck = charging
+        .then(|| stuck_minutes(session, now, limit))
+        .flatten();
+    if let Some(minutes) = stuck {
+        found.push(Anomaly {
+            kind: AnomalyKind::Stuck,
+            text: format!(
+                "Charge stuck at {:.0}% for {} min",
+                session.last().map(|s| s.percent).unwrap_or_default(),
+                minutes
+            ),
+        });
+    }
+
+    if charging && (battery.reported as f64) < limit {
+        let watts = charger.and_then(|c| c.watts);
+        let slow_now = match (watts, battery.power) {
+            (Some(w), Some(p)) if w >= HIGH_WATTAGE => p < w * 0.1,
+            _ => false,
+        };
+        if slow_now {
+            found.push(Anomaly {
+                kind: AnomalyKind::Slow,
+                text: format!(
+                    "Charging at only {:.1} W on a {:.0} W adapter",
+                    battery.power.unwrap_or_default(),
+                    watts.unwrap_or_default()
+                ),
+            });
+        } else if let Some(gained) = gained_over(session, now).filter(|_| stuck.is_none()) {
+            if gained < 2.0 {
+                found.push(Anomaly {
+                    kind: AnomalyKind::Slow,
+                    text: format!(
+                        "Charged only {:.0}% in the last {} min",
+                        gained,
+                        WINDOW / 60
+                    ),
+                });
+            }
+        }
+    }
+
+    let flaps = flaps_in_last_hour(history, now);
+    if flaps >= FLAPS_PER_HOUR {
+        found.push(Anomaly {
+            kind: AnomalyKind::Flapping,
+            text: format!(
+                "Switched between charging and discharging {} times in the last hour (loose cable?)",
+                flaps
+            ),
+        });
+    }
+    found
+}
+
+/// The trailing run of charging samples
+fn current_session(history: &[ChargeSample]) -> &[ChargeSample] {
+    let start = history
+        .iter()
+        .rposition(|s| s.state != "charging")
+        .map_or(0, |i| i + 1);
+    &history[start..]
+}
+
+/// Percent gained over the last window, if the session is that long
+fn gained_over(session: &[ChargeSample], now: i64) -> Option<f64> {
+    let first = session.first()?;
+    if now - first.time < WINDOW {
+        return None;
+    }
+    let before = session.iter().rev().find(|s| s.time <= now - WINDOW)?;
+    Some(session.last()?.percent - before.percent)
+}
+
+/// Minutes the charge has sat at one percentage below the limit
+fn stuck_minutes(session: &[ChargeSample], now: i64, limit: f64) -> Option<i64> {
+    let last = session.last()?;
+    if last.percent >= limit {
+        return None;
+    }
+    let since = session
+        .iter()
+        .rev()
+        .take_while(|s| (s.percent - last.percent).abs() < 1.0)
+        .last()?
+        .time;
+    let minutes = (now - since) / 60;
+    (now - since >= WINDOW).then_some(minutes)
+}
+
+fn flaps_in_last_hour(history: &[ChargeSample], now: i64) -> usize {
+    let recent: Vec<&str> = history
+        .iter()
+        .filter(|s| now - s.time <= 3600)
+        .map(|s| s.state.as_str())
+        .filter(|s| *s == "charging" || *s == "discharging")
+        .collect();
+    recent.windows(2).filter(|w| w[0] != w[1]).count()
+}
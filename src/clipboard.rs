@@ -0,0 +1,39 @@
+use std::env;
+use std::io::{stdout, Write};
+
+/// Copy text to the system clipboard with an OSC 52 escape sequence.
+/// The terminal does the work, so this also works over SSH.
+pub fn copy(text: &str) -> std::io::Result<()> {
+    let mut seq = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+
+    // tmux swallows OSC 52 unless it is wrapped in a passthrough
+    if env::var_os("TMUX").is_some() {
+        seq = format!("\x1bPtmux;{}\x1b\\", seq.replace('\x1b', "\x1b\x1b"));
+    }
+
+    let mut out = stdout();
+    out.write_all(seq.as_bytes())?;
+    out.flush()
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
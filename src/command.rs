@@ -0,0 +1,343 @@
+use std::time::Duration;
+
+use crossterm::event::KeyCode;
+
+use crate::gpu::Level;
+use crate::rapl::Limit;
+use crate::simulate::Change;
+
+pub enum Command {
+    Profile(String),
+    Boost(Duration),
+    /// Runtime the battery has to last, optionally capping the package
+    /// to the power that allows
+    Budget {
+        runtime: Duration,
+        cap: bool,
+    },
+    EndBudget,
+    /// Hold the batteries at the `[storage]` level, or put them back
+    Storage,
+    EndStorage,
+    SetRefresh(Duration),
+    SetAlarm(u8),
+    /// Charge thresholds for one battery; the start is optional
+    SetThresholds {
+        battery: String,
+        start: Option<u8>,
+        end: u8,
+    },
+    /// Battery to charge first, or None to lift the priority
+    SetChargeFirst(Option<String>),
+    SetDischargeFirst(Option<String>),
+    /// Frequency limits in MHz for every policy, `None` for the
+    /// hardware's own bound
+    SetMinFreq(Option<u64>),
+    SetMaxFreq(Option<u64>),
+    /// A RAPL package limit in watts, for the session
+    SetPowerLimit(Limit, f64),
+    /// Force every GPU to its lowest or highest clocks, or hand it back
+    SetGpuLevel(Level),
+    SetBrightness(u8),
+    Export {
+        format: String,
+        path: Option<String>,
+    },
+    /// Adjust the simulated machine
+    Simulate(Change),
+    Refresh,
+    Log,
+    Help,
+    Quit,
+}
+
+const COMMANDS: [&str; 11] = [
+    "profile", "boost", "budget", "storage", "set", "export", "sim", "refresh", "log", "help",
+    "quit",
+];
+const SETTINGS: [&str; 11] = [
+    "refresh",
+    "alarm",
+    "threshold",
+    "charge-first",
+    "discharge-first",
+    "min-freq",
+    "max-freq",
+    "pl1",
+    "pl2",
+    "gpu",
+    "brightness",
+];
+const EXPORT_FORMATS: [&str; 2] = ["csv", "sources"];
+const SIM_SETTINGS: [&str; 3] = ["battery", "status", "temp"];
+
+pub fn parse(input: &str) -> Result<Command, String> {
+    let input = input.trim();
+    let (name, rest) = input.split_once(' ').unwrap_or((input, ""));
+    let rest = rest.trim();
+
+    match name {
+        "profile" | "p" if !rest.is_empty() => Ok(Command::Profile(rest.to_string())),
+        "profile" | "p" => Err("usage: profile <name>".to_string()),
+        "boost" if rest.is_empty() => Err("usage: boost <duration>".to_string()),
+        "boost" => parse_duration(rest)
+            .map(Command::Boost)
+            .ok_or_else(|| format!("invalid duration: {}", rest)),
+        "budget" => {
+            let mut args = rest.split_whitespace();
+            match (args.next(), args.next(), args.next()) {
+                (Some("off"), None, _) => Ok(Command::EndBudget),
+                (Some(runtime), cap, None) if cap.is_none_or(|c| c == "cap") => {
+                    parse_duration(runtime)
+                        .map(|runtime| Command::Budget {
+                            runtime,
+                            cap: cap.is_some(),
+                        })
+                        .ok_or_else(|| format!("invalid duration: {}", runtime))
+                }
+                _ => Err("usage: budget <runtime> [cap] | off".to_string()),
+            }
+        }
+        "storage" => match rest {
+            "" => Ok(Command::Storage),
+            "off" => Ok(Command::EndStorage),
+            _ => Err("usage: storage [off]".to_string()),
+        },
+        "set" => {
+            let (key, value) = rest.split_once(' ').unwrap_or((rest, ""));
+            match key {
+                "refresh" => parse_duration(value.trim())
+                    .map(Command::SetRefresh)
+                    .ok_or_else(|| format!("invalid duration: {}", value.trim())),
+                "alarm" => parse_percent(value.trim())
+                    .map(Command::SetAlarm)
+                    .ok_or_else(|| format!("invalid percentage: {}", value.trim())),
+                "threshold" => parse_thresholds(value.trim()),
+                "charge-first" | "discharge-first" => {
+                    let battery = match value.trim() {
+                        "" => return Err(format!("usage: set {} <battery>|none", key)),
+                        "none" => None,
+                        name => Some(name.to_string()),
+                    };
+                    Ok(if key == "charge-first" {
+                        Command::SetChargeFirst(battery)
+                    } else {
+                        Command::SetDischargeFirst(battery)
+                    })
+                }
+                "min-freq" | "max-freq" => {
+                    let mhz = match value.trim() {
+                        "" => return Err(format!("usage: set {} <frequency>|default", key)),
+                        "default" => None,
+                        freq => Some(
+                            parse_frequency(freq)
+                                .ok_or_else(|| format!("invalid frequency: {}", freq))?,
+                        ),
+                    };
+                    Ok(if key == "min-freq" {
+                        Command::SetMinFreq(mhz)
+                    } else {
+                        Command::SetMaxFreq(mhz)
+                    })
+                }
+                "pl1" | "pl2" => {
+                    let limit = if key == "pl1" { Limit::Pl1 } else { Limit::Pl2 };
+                    parse_power(value.trim())
+                        .map(|watts| Command::SetPowerLimit(limit, watts))
+                        .ok_or_else(|| format!("invalid power: {}", value.trim()))
+                }
+                "gpu" => Level::parse(value.trim())
+                    .map(Command::SetGpuLevel)
+                    .ok_or_else(|| "usage: set gpu low|auto|high".to_string()),
+                "brightness" => parse_percent(value.trim())
+                    .map(Command::SetBrightness)
+                    .ok_or_else(|| format!("invalid percentage: {}", value.trim())),
+                "" => Err("usage: set <option> <value>".to_string()),
+                _ => Err(format!("unknown option: {}", key)),
+            }
+        }
+        "export" => {
+            let mut args = rest.split_whitespace();
+            let format = args.next().unwrap_or("csv");
+            if !EXPORT_FORMATS.contains(&format) {
+                return Err(format!("unknown export format: {}", format));
+            }
+            Ok(Command::Export {
+                format: format.to_string(),
+                path: args.next().map(String::from),
+            })
+        }
+        "sim" => {
+            let (key, value) = rest.split_once(' ').unwrap_or((rest, ""));
+            let value = value.trim();
+            match key {
+                "battery" => parse_percent(value)
+                    .map(|p| Command::Simulate(Change::Battery(p)))
+                    .ok_or_else(|| format!("invalid percentage: {}", value)),
+                "status" if !value.is_empty() => {
+                    Ok(Command::Simulate(Change::Status(value.to_string())))
+                }
+                "temp" => value
+                    .trim_end_matches("°C")
+                    .parse()
+                    .ok()
+                    .map(|t| Command::Simulate(Change::Temp(t)))
+                    .ok_or_else(|| format!("invalid temperature: {}", value)),
+                _ => Err("usage: sim battery <percent> | status <state> | temp <°C>".to_string()),
+            }
+        }
+        "refresh" | "r" => Ok(Command::Refresh),
+        "log" => Ok(Command::Log),
+        "help" | "h" => Ok(Command::Help),
+        "quit" | "q" | "q!" => Ok(Command::Quit),
+        _ => Err(format!("unknown command: {}", name)),
+    }
+}
+
+/// `BAT0 80` or `BAT0 75 80`
+fn parse_thresholds(value: &str) -> Result<Command, String> {
+    let usage = || "usage: set threshold <battery> [start] <end>".to_string();
+    let percent = |s: &str| parse_percent(s).ok_or_else(|| format!("invalid percentage: {}", s));
+    let args: Vec<&str> = value.split_whitespace().collect();
+    let (battery, start, end) = match args[..] {
+        [battery, end] => (battery, None, percent(end)?),
+        [battery, start, end] => (battery, Some(percent(start)?), percent(end)?),
+        _ => return Err(usage()),
+    };
+    if start.is_some_and(|s| s >= end) {
+        return Err("start threshold must be below the end".to_string());
+    }
+    Ok(Command::SetThresholds {
+        battery: battery.to_string(),
+        start,
+        end,
+    })
+}
+
+/// Durations like `500ms`, `2s` or `1m`; a bare number means seconds
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let secs = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return None,
+    };
+    (secs > 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+/// Frequencies like `2.4GHz` or `1800MHz` in MHz; a bare number means MHz
+pub fn parse_frequency(s: &str) -> Option<u64> {
+    let s = s.to_lowercase();
+    let (number, scale) = if let Some(n) = s.strip_suffix("ghz") {
+        (n, 1000.0)
+    } else {
+        (s.strip_suffix("mhz").unwrap_or(&s), 1.0)
+    };
+    let mhz = number.trim().parse::<f64>().ok()? * scale;
+    (mhz > 0.0).then_some(mhz.round() as u64)
+}
+
+/// Power like `15W`, `15.5 W` or `9000mW` in watts; a bare number means
+/// watts
+pub fn parse_power(s: &str) -> Option<f64> {
+    let s = s.to_lowercase();
+    let (number, scale) = if let Some(n) = s.strip_suffix("mw") {
+        (n, 0.001)
+    } else {
+        (s.strip_suffix('w').unwrap_or(&s), 1.0)
+    };
+    let watts = number.trim().parse::<f64>().ok()? * scale;
+    (watts > 0.0).then_some(watts)
+}
+
+/// `80%` or `80`, up to 100
+pub fn parse_percent(s: &str) -> Option<u8> {
+    s.trim_end_matches('%')
+        .trim()
+        .parse()
+        .ok()
+        .filter(|p| *p <= 100)
+}
+
+pub enum LineAction {
+    None,
+    Submit(String),
+    Cancel,
+}
+
+/// The `:` prompt, with tab completion cycling through candidates
+#[derive(Default)]
+pub struct CommandLine {
+    pub input: String,
+    completions: Vec<String>,
+    completion: usize,
+}
+
+impl CommandLine {
+    pub fn handle_key(&mut self, code: KeyCode, profiles: &[String]) -> LineAction {
+        if code != KeyCode::Tab && code != KeyCode::BackTab {
+            self.completions.clear();
+        }
+        match code {
+            KeyCode::Esc => return LineAction::Cancel,
+            KeyCode::Enter => return LineAction::Submit(self.input.clone()),
+            // Backspace on an empty prompt leaves command mode, like vim
+            KeyCode::Backspace if self.input.is_empty() => return LineAction::Cancel,
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Tab => self.complete(profiles, 1),
+            KeyCode::BackTab => self.complete(profiles, -1),
+            KeyCode::Char(c) => self.input.push(c),
+            _ => {}
+        }
+        LineAction::None
+    }
+
+    fn complete(&mut self, profiles: &[String], step: isize) {
+        if self.completions.is_empty() {
+            self.completions = completions(&self.input, profiles);
+            self.completion = 0;
+            if step < 0 && !self.completions.is_empty() {
+                self.completion = self.completions.len() - 1;
+            }
+        } else {
+            let len = self.completions.len() as isize;
+            self.completion = (self.completion as isize + step).rem_euclid(len) as usize;
+        }
+        if let Some(line) = self.completions.get(self.completion) {
+            self.input = line.clone();
+        }
+    }
+}
+
+/// Full command lines the current input could complete to
+fn completions(input: &str, profiles: &[String]) -> Vec<String> {
+    let Some((command, arg)) = input.split_once(' ') else {
+        return COMMANDS
+            .iter()
+            .filter(|c| c.starts_with(input))
+            .map(|c| c.to_string())
+            .collect();
+    };
+
+    let values: Vec<&str> = match command {
+        "profile" | "p" => profiles.iter().map(String::as_str).collect(),
+        "set" => SETTINGS.to_vec(),
+        "export" => EXPORT_FORMATS.to_vec(),
+        "sim" => SIM_SETTINGS.to_vec(),
+        _ => Vec::new(),
+    };
+    let arg = arg.to_lowercase();
+    values
+        .into_iter()
+        .filter(|v| v.to_lowercase().starts_with(&arg))
+        .map(|v| format!("{} {}", command, v))
+        .collect()
+}
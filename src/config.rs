@@ -0,0 +1,760 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use chrono::{Local, NaiveTime};
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::app::Page;
+use crate::backend;
+use crate::battery;
+use crate::command::parse_duration;
+use crate::notifications::NotificationsConfig;
+use crate::plug::PlugConfig;
+use crate::rules::{self, Conditions, Rule};
+use crate::storage::StorageConfig;
+use crate::webhook::WebhookConfig;
+
+/// `~/.config/powertui/config.toml`. Every field has a default, so a
+/// missing file or section behaves like an empty one.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// How profiles set the governor, tried first when it works here
+    pub backend: Option<BackendName>,
+    pub ui: UiConfig,
+    pub battery: BatteryConfig,
+    pub peripherals: PeripheralsConfig,
+    pub steering: SteeringConfig,
+    pub calls: CallsConfig,
+    pub refresh: RefreshConfig,
+    pub overview: OverviewConfig,
+    pub gauges: GaugesConfig,
+    /// Smart plug measuring wall power
+    pub plug: Option<PlugConfig>,
+    /// UPS to watch through NUT
+    pub ups: Option<UpsConfig>,
+    /// Desktop notifications for battery events
+    pub notifications: Option<NotificationsConfig>,
+    /// Post alerts to ntfy, Slack, Gotify or anything taking JSON
+    pub webhook: Option<WebhookConfig>,
+    /// Suspend or hibernate a closed laptop that heats up on battery
+    pub bag: Option<BagConfig>,
+    /// Profiles `powertui daemon` switches between
+    pub daemon: DaemonConfig,
+    /// What to do when the machine wakes from suspend
+    pub resume: ResumeConfig,
+    /// The battery log behind the History page's charge charts
+    pub history: HistoryConfig,
+    /// Charge level and reminder for `:storage`
+    pub storage: StorageConfig,
+    /// When notifications stay in the event log
+    pub quiet_hours: Option<QuietHours>,
+    /// Rules for the Automatic profile, tried in order
+    #[serde(rename = "rule")]
+    pub rules: Vec<Rule>,
+    /// Keys by action name, replacing that action's defaults
+    pub keys: BTreeMap<String, Vec<String>>,
+}
+
+/// One of the profile backends, by the name it goes by in the UI
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct BackendName(pub String);
+
+impl TryFrom<String> for BackendName {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        if backend::NAMES.contains(&s.as_str()) {
+            Ok(BackendName(s))
+        } else {
+            Err(format!(
+                "unknown backend {}, expected one of {}",
+                s,
+                backend::NAMES.join(", ")
+            ))
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// Smooth gauge transitions. Off by default.
+    pub animations: bool,
+    /// Disable all motion, overriding `animations`
+    pub reduced_motion: bool,
+    /// 12 or 24 hour times; `auto` follows the locale
+    pub clock: Clock,
+    /// Page shown at startup
+    pub page: Page,
+    /// Add the MSR page, which reads model-specific registers as root
+    pub msr: bool,
+    /// Just the battery, profile and draw in a couple of rows, for a
+    /// small terminal kept open on the side
+    pub minimal: bool,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+pub enum Clock {
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "12h")]
+    TwelveHour,
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+}
+
+impl UiConfig {
+    pub fn animate(&self) -> bool {
+        self.animations && !self.reduced_motion
+    }
+}
+
+/// How worrying a reading is, before it's turned into a color
+#[derive(Clone, Copy)]
+pub enum Band {
+    Good,
+    Warn,
+    Critical,
+}
+
+/// A color name (`red`, `lightblue`), index (`208`) or `#rrggbb`
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct GaugeColor(pub Color);
+
+impl TryFrom<String> for GaugeColor {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Color::from_str(&s)
+            .map(GaugeColor)
+            .map_err(|_| format!("invalid color: {}", s))
+    }
+}
+
+/// A time of day, `22:00`
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ClockTime(pub NaiveTime);
+
+impl TryFrom<String> for ClockTime {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        NaiveTime::parse_from_str(&s, "%H:%M")
+            .map(ClockTime)
+            .map_err(|_| format!("invalid time, expected HH:MM: {}", s))
+    }
+}
+
+/// Do-not-disturb hours. Desktop notifications and webhook alerts are
+/// held back, except the critical battery warning and alerts about to
+/// act on the machine; nothing stops alerts from acting.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuietHours {
+    pub start: ClockTime,
+    /// May be earlier than `start`, for hours across midnight
+    pub end: ClockTime,
+}
+
+impl QuietHours {
+    /// Whether it's quiet right now
+    pub fn now(&self) -> bool {
+        self.contains(Local::now().time())
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let (start, end) = (self.start.0, self.end.0);
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// Where battery, temperature and power readings change color. The
+/// defaults suit a laptop; a UPS or an SBC wants different bands.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GaugesConfig {
+    /// Battery percentage at or below which the gauge turns yellow, then red
+    pub battery_low: u8,
+    pub battery_critical: u8,
+    /// °C from which temperatures turn yellow, then red
+    pub temp_warm: f64,
+    pub temp_hot: f64,
+    /// Watts from which power draw turns yellow, then red
+    pub power_medium: f64,
+    pub power_high: f64,
+    pub good: GaugeColor,
+    pub warn: GaugeColor,
+    pub critical: GaugeColor,
+}
+
+impl Default for GaugesConfig {
+    fn default() -> Self {
+        GaugesConfig {
+            battery_low: 50,
+            battery_critical: 20,
+            temp_warm: 70.0,
+            temp_hot: 85.0,
+            power_medium: 15.0,
+            power_high: 30.0,
+            good: GaugeColor(Color::Green),
+            warn: GaugeColor(Color::Yellow),
+            critical: GaugeColor(Color::Red),
+        }
+    }
+}
+
+impl GaugesConfig {
+    pub fn battery(&self, percent: u8) -> Band {
+        if percent <= self.battery_critical {
+            Band::Critical
+        } else if percent <= self.battery_low {
+            Band::Warn
+        } else {
+            Band::Good
+        }
+    }
+
+    pub fn temp(&self, celsius: f64) -> Band {
+        rising(celsius, self.temp_warm, self.temp_hot)
+    }
+
+    pub fn power(&self, watts: f64) -> Band {
+        rising(watts, self.power_medium, self.power_high)
+    }
+
+    pub fn color(&self, band: Band) -> Color {
+        match band {
+            Band::Good => self.good.0,
+            Band::Warn => self.warn.0,
+            Band::Critical => self.critical.0,
+        }
+    }
+}
+
+/// Bands for readings where higher is worse
+fn rising(value: f64, warn: f64, critical: f64) -> Band {
+    if value >= critical {
+        Band::Critical
+    } else if value >= warn {
+        Band::Warn
+    } else {
+        Band::Good
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatteryConfig {
+    /// Percentage at or below which to warn while discharging
+    pub low: u8,
+    /// Set the firmware alarm to `low` on startup
+    pub sync_alarm: bool,
+    /// `[reported, actual]` percentage points for firmware that
+    /// misreports charge; `powertui calibrate` suggests some
+    pub calibration: Vec<[f64; 2]>,
+    /// Per-battery policy on machines with more than one
+    #[serde(rename = "pack")]
+    pub packs: Vec<PackConfig>,
+}
+
+/// Charge policy for one battery, by its power_supply name
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PackConfig {
+    /// e.g. `BAT1`
+    pub name: String,
+    /// Charge thresholds, applied on startup
+    pub start_threshold: Option<u8>,
+    pub end_threshold: Option<u8>,
+    /// Hold the other batteries' charging until this one is full
+    pub charge_first: bool,
+    /// Drain this battery before the others, where the EC can force it
+    pub discharge_first: bool,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        BatteryConfig {
+            low: 10,
+            sync_alarm: false,
+            calibration: Vec::new(),
+            packs: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeripheralsConfig {
+    /// Notify when a mouse, keyboard or headset runs low
+    pub alerts: bool,
+    /// Percentage at or below which to notify
+    pub low_battery: u8,
+}
+
+impl Default for PeripheralsConfig {
+    fn default() -> Self {
+        PeripheralsConfig {
+            alerts: true,
+            low_battery: 15,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SteeringConfig {
+    /// Process name patterns to keep on efficiency cores while on battery
+    pub patterns: Vec<String>,
+}
+
+/// What to do while the microphone or camera is in use. Both off by
+/// default, which also skips looking for calls.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CallsConfig {
+    /// Keep the current profile; the Automatic rules wait until the call ends
+    pub hold_profile: bool,
+    /// Block idle suspend for the length of the call
+    pub no_suspend: bool,
+}
+
+impl CallsConfig {
+    pub fn enabled(&self) -> bool {
+        self.hold_profile || self.no_suspend
+    }
+}
+
+/// A duration like `500ms`, `2s` or `1m`
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Interval(pub Duration);
+
+impl TryFrom<String> for Interval {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        parse_duration(s.trim())
+            .map(Interval)
+            .ok_or_else(|| format!("invalid interval, expected e.g. 500ms or 2s: {}", s))
+    }
+}
+
+/// How often each kind of reading is taken. Cheap, fast-moving ones
+/// are only read while something on screen shows them.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RefreshConfig {
+    /// Battery, charger, profile and everything else; `:set refresh`
+    /// changes it for the session
+    pub battery: Interval,
+    /// Per-core frequencies, on the Cores page and the `cores` widget
+    pub cores: Interval,
+    /// Thermal zones, for the `temps` widget
+    pub thermal: Interval,
+    /// Battery draw, for the `chart` widget
+    pub power: Interval,
+    /// Longest wait for a key before the screen is redrawn and the
+    /// intervals above are checked
+    pub redraw: Interval,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        RefreshConfig {
+            battery: Interval(Duration::from_secs(5)),
+            cores: Interval(Duration::from_millis(500)),
+            thermal: Interval(Duration::from_secs(2)),
+            power: Interval(Duration::from_secs(1)),
+            redraw: Interval(Duration::from_millis(250)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpsConfig {
+    /// NUT name, `ups@host`
+    pub name: String,
+    /// Seconds of runtime on battery at which to warn
+    pub low_runtime: u32,
+    /// Power off when the low-runtime warning fires
+    pub shutdown: bool,
+}
+
+impl Default for UpsConfig {
+    fn default() -> Self {
+        UpsConfig {
+            name: "ups@localhost".to_string(),
+            low_runtime: 300,
+            shutdown: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DaemonConfig {
+    /// Profile applied when a charger comes online
+    pub ac: String,
+    /// Profile applied when the last charger goes offline
+    pub battery: String,
+    /// How often the power supplies are checked
+    pub interval: Interval,
+    /// Keep a profile picked by hand in the TUI across reboots: the
+    /// daemon applies it at startup and holds it until Automatic is
+    /// picked again
+    pub persist: bool,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        DaemonConfig {
+            ac: "Balanced".to_string(),
+            battery: "Power Saver".to_string(),
+            interval: Interval(Duration::from_secs(2)),
+            persist: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResumeConfig {
+    /// Apply the active profile and charge thresholds again, as
+    /// firmware often resets them across suspend
+    pub reapply: bool,
+    /// Shell commands run as the user after every resume
+    pub hooks: Vec<String>,
+}
+
+impl Default for ResumeConfig {
+    fn default() -> Self {
+        ResumeConfig {
+            reapply: true,
+            hooks: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Append charge, status and draw to `history.csv` while powertui
+    /// or its daemon runs
+    pub log: bool,
+    /// Time between samples
+    pub interval: Interval,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            log: true,
+            interval: Interval(Duration::from_secs(60)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BagConfig {
+    /// CPU temperature in °C at which a closed laptop counts as cooking
+    pub temp: f64,
+    /// Degrees it has to have climbed since the lid closed
+    pub rise: f64,
+    pub action: BagAction,
+}
+
+impl Default for BagConfig {
+    fn default() -> Self {
+        BagConfig {
+            temp: 60.0,
+            rise: 10.0,
+            action: BagAction::Hibernate,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BagAction {
+    Hibernate,
+    /// Suspend to RAM, switching `mem_sleep` to `deep` first where the
+    /// machine has it, since s2idle can keep the CPU warm
+    Suspend,
+}
+
+/// Blocks the Overview page can show
+#[derive(Clone, Copy, PartialEq)]
+pub enum Widget {
+    Battery,
+    Charger,
+    Lid,
+    Graphics,
+    Swap,
+    Profiles,
+    Power,
+    Temps,
+    Cores,
+    Desktop,
+    Sway,
+    Inhibitors,
+    Devices,
+    Ups,
+    Sources,
+    Chart,
+}
+
+/// A widget and an optional height in rows, written `"power:8"`
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct WidgetSlot {
+    pub widget: Widget,
+    pub height: Option<u16>,
+}
+
+impl TryFrom<String> for WidgetSlot {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let (name, height) = match s.split_once(':') {
+            Some((name, height)) => (
+                name,
+                Some(
+                    height
+                        .parse()
+                        .map_err(|_| format!("invalid widget height: {}", s))?,
+                ),
+            ),
+            None => (s.as_str(), None),
+        };
+        let widget = match name {
+            "battery" => Widget::Battery,
+            "charger" => Widget::Charger,
+            "lid" => Widget::Lid,
+            "graphics" => Widget::Graphics,
+            "swap" => Widget::Swap,
+            "profiles" => Widget::Profiles,
+            "power" => Widget::Power,
+            "temps" => Widget::Temps,
+            "cores" => Widget::Cores,
+            "desktop" => Widget::Desktop,
+            "sway" => Widget::Sway,
+            "inhibitors" => Widget::Inhibitors,
+            "devices" => Widget::Devices,
+            "ups" => Widget::Ups,
+            "sources" => Widget::Sources,
+            "chart" => Widget::Chart,
+            _ => return Err(format!("unknown widget: {}", name)),
+        };
+        Ok(WidgetSlot { widget, height })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OverviewConfig {
+    /// Top to bottom
+    pub widgets: Vec<WidgetSlot>,
+    /// How far back the `chart` widget goes
+    pub chart_window: Interval,
+}
+
+impl Default for OverviewConfig {
+    fn default() -> Self {
+        let widgets = [
+            Widget::Battery,
+            Widget::Charger,
+            Widget::Lid,
+            Widget::Graphics,
+            Widget::Swap,
+            Widget::Profiles,
+            Widget::Chart,
+            Widget::Desktop,
+            Widget::Sway,
+            Widget::Inhibitors,
+            Widget::Devices,
+            Widget::Ups,
+            Widget::Sources,
+        ];
+        OverviewConfig {
+            widgets: widgets
+                .into_iter()
+                .map(|widget| WidgetSlot {
+                    widget,
+                    height: None,
+                })
+                .collect(),
+            chart_window: Interval(Duration::from_secs(10 * 60)),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/powertui`, falling back to `~/.config/powertui`
+fn base_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("powertui"))
+}
+
+/// Where config.toml and profiles.toml are read: the selected config
+/// profile's directory, or the base one
+pub fn config_dir() -> Option<PathBuf> {
+    match CONFIG_PROFILE.get() {
+        Some(name) => Some(config_profiles_dir()?.join(name)),
+        None => base_dir(),
+    }
+}
+
+/// The config profile this run uses, set once at startup
+static CONFIG_PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Named sets of config.toml and profiles.toml, one directory each,
+/// for a laptop that serves very different roles
+fn config_profiles_dir() -> Option<PathBuf> {
+    Some(base_dir()?.join("config-profiles"))
+}
+
+/// `config-profiles/rules.toml`: `[[rule]]`s like the Automatic ones,
+/// whose `profile` names a config profile
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigProfileRules {
+    rule: Vec<Rule>,
+}
+
+/// The config profile in use, if any
+pub fn config_profile() -> Option<&'static str> {
+    CONFIG_PROFILE.get().map(String::as_str)
+}
+
+/// Use the named config profile for this run; only takes effect before
+/// the first `load`
+pub fn select_config_profile(name: &str) -> Result<(), String> {
+    // A name, not a path out of the profiles directory
+    if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+        return Err(format!("Bad config profile name {:?}", name));
+    }
+    let dir = config_profiles_dir()
+        .ok_or("Cannot locate config directory")?
+        .join(name);
+    if !dir.is_dir() {
+        return Err(format!(
+            "No config profile {}: {} doesn't exist",
+            name,
+            dir.display()
+        ));
+    }
+    let _ = CONFIG_PROFILE.set(name.to_string());
+    Ok(())
+}
+
+/// Select the config profile of the first rule in rules.toml that
+/// matches right now, if there is one. Rules only decide at startup.
+pub fn pick_config_profile() -> Result<(), String> {
+    let Some(path) = config_profiles_dir().map(|d| d.join("rules.toml")) else {
+        return Ok(());
+    };
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let file: ConfigProfileRules =
+        toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if let Some(rule) = file.rule.iter().find(|r| r.suspend || r.blank) {
+        return Err(format!(
+            "{}: rule {} can't suspend or blank, it only picks a config profile",
+            path.display(),
+            rule.name
+        ));
+    }
+    let conditions = Conditions::read(battery::read_battery_info().as_ref());
+    match rules::evaluate(&file.rule, &conditions) {
+        Some(rule) => select_config_profile(&rule.profile),
+        None => Ok(()),
+    }
+}
+
+/// `$XDG_STATE_HOME/powertui`, falling back to `~/.local/state/powertui`
+pub fn state_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))?;
+    Some(base.join("powertui"))
+}
+
+pub fn load() -> Result<Config, String> {
+    let Some(path) = config_dir().map(|d| d.join("config.toml")) else {
+        return Ok(Config::default());
+    };
+    match fs::read_to_string(&path) {
+        Ok(contents) => toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(_) => Ok(Config::default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet(start: &str, end: &str) -> QuietHours {
+        QuietHours {
+            start: ClockTime::try_from(start.to_string()).unwrap(),
+            end: ClockTime::try_from(end.to_string()).unwrap(),
+        }
+    }
+
+    fn at(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let hours = quiet("13:00", "14:30");
+        assert!(!hours.contains(at("12:59")));
+        assert!(hours.contains(at("13:00")));
+        assert!(hours.contains(at("14:29")));
+        assert!(!hours.contains(at("14:30")));
+    }
+
+    #[test]
+    fn quiet_hours_across_midnight() {
+        let hours = quiet("22:00", "07:00");
+        assert!(hours.contains(at("22:00")));
+        assert!(hours.contains(at("00:00")));
+        assert!(hours.contains(at("06:59")));
+        assert!(!hours.contains(at("07:00")));
+        assert!(!hours.contains(at("12:00")));
+    }
+
+    #[test]
+    fn bad_clock_time() {
+        assert!(ClockTime::try_from("25:00".to_string()).is_err());
+        assert!(ClockTime::try_from("7pm".to_string()).is_err());
+    }
+
+    #[test]
+    fn config_profile_names_stay_in_the_directory() {
+        for name in ["", ".", "..", "../work", "/etc"] {
+            assert!(select_config_profile(name).is_err(), "{:?}", name);
+        }
+    }
+}
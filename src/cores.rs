@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use crate::sysfs;
+
+/// How far back the Cores page keeps samples
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Current and maximum frequency of one CPU, in kHz
+pub struct CoreFreq {
+    pub cpu: usize,
+    pub cur: u64,
+    pub max: Option<u64>,
+}
+
+impl CoreFreq {
+    /// How hard the core is clocked, 0.0 to 1.0
+    pub fn load(&self) -> f64 {
+        match self.max {
+            Some(max) if max > 0 => (self.cur as f64 / max as f64).min(1.0),
+            _ => 0.0,
+        }
+    }
+}
+
+/// Every online CPU with cpufreq, in CPU order
+pub fn read_frequencies() -> Vec<CoreFreq> {
+    let Ok(entries) = fs::read_dir(sysfs::path("/sys/devices/system/cpu")) else {
+        return Vec::new();
+    };
+    let mut cores: Vec<CoreFreq> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let cpu = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("cpu")?
+                .parse()
+                .ok()?;
+            let cpufreq = entry.path().join("cpufreq");
+            Some(CoreFreq {
+                cpu,
+                cur: sysfs::read_value(cpufreq.join("scaling_cur_freq"))?,
+                max: sysfs::read_value(cpufreq.join("cpuinfo_max_freq")),
+            })
+        })
+        .collect();
+    cores.sort_by_key(|c| c.cpu);
+    cores
+}
+
+/// Recent frequencies of one CPU, oldest first, in kHz
+pub struct CoreTrace {
+    pub cpu: usize,
+    pub max: Option<u64>,
+    pub samples: VecDeque<u64>,
+}
+
+impl CoreTrace {
+    pub fn current(&self) -> Option<u64> {
+        self.samples.back().copied()
+    }
+
+    /// Lowest, highest and average over the kept samples
+    pub fn stats(&self) -> Option<(u64, u64, u64)> {
+        let min = *self.samples.iter().min()?;
+        let max = *self.samples.iter().max()?;
+        let avg = self.samples.iter().sum::<u64>() / self.samples.len() as u64;
+        Some((min, max, avg))
+    }
+}
+
+/// Per-core frequency over the last minute, sampled at `[refresh]
+/// cores` while the Cores page is open
+pub struct CoreHistory {
+    pub cores: Vec<CoreTrace>,
+    every: Duration,
+    last: Option<Instant>,
+}
+
+impl CoreHistory {
+    pub fn new(every: Duration) -> Self {
+        CoreHistory {
+            cores: Vec::new(),
+            every,
+            last: None,
+        }
+    }
+
+    pub fn every(&self) -> Duration {
+        self.every
+    }
+
+    /// Samples covering the window
+    fn capacity(&self) -> usize {
+        (WINDOW.as_millis() / self.every.as_millis().max(1)).max(1) as usize
+    }
+
+    pub fn sample(&mut self) {
+        if self.last.is_some_and(|t| t.elapsed() < self.every) {
+            return;
+        }
+        self.last = Some(Instant::now());
+        let capacity = self.capacity();
+        let now = read_frequencies();
+        // CPUs taken offline drop out; ones brought online start fresh
+        self.cores.retain(|t| now.iter().any(|c| c.cpu == t.cpu));
+        for core in now {
+            let trace = match self.cores.iter().position(|t| t.cpu == core.cpu) {
+                Some(i) => &mut self.cores[i],
+                None => {
+                    self.cores.push(CoreTrace {
+                        cpu: core.cpu,
+                        max: core.max,
+                        samples: VecDeque::new(),
+                    });
+                    self.cores.last_mut().expect("just pushed")
+                }
+            };
+            if trace.samples.len() >= capacity {
+                trace.samples.pop_front();
+            }
+            trace.samples.push_back(core.cur);
+        }
+        self.cores.sort_by_key(|t| t.cpu);
+    }
+}
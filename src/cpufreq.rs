@@ -0,0 +1,371 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::privileged;
+use crate::sysfs;
+
+const CPUFREQ: &str = "/sys/devices/system/cpu/cpufreq";
+
+/// The kernel's CPUFREQ_ETERNAL, meaning the driver doesn't know
+const UNKNOWN_LATENCY: u64 = 4_294_967_295;
+
+const EPP: &str = "energy_performance_preference";
+
+/// Turbo switches: intel_pstate's inverted one, or the generic one of
+/// acpi-cpufreq and amd-pstate
+const NO_TURBO: &str = "/sys/devices/system/cpu/intel_pstate/no_turbo";
+const BOOST: &str = "/sys/devices/system/cpu/cpufreq/boost";
+
+/// A cpufreq policy, or several identical ones merged
+pub struct Policy {
+    pub cpus: Vec<usize>,
+    pub driver: String,
+    pub governor: Option<String>,
+    pub governors: Vec<String>,
+    /// Discrete steps in kHz; empty when the driver picks freely
+    /// (intel_pstate, amd-pstate)
+    pub frequencies: Vec<u64>,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    /// Limits the governor works within, from `scaling_min_freq` and
+    /// `scaling_max_freq`
+    pub limit_min: Option<u64>,
+    pub limit_max: Option<u64>,
+    /// Time to switch frequency, in nanoseconds
+    pub latency: Option<u64>,
+    /// Energy Performance Preference hint on intel_pstate and
+    /// amd-pstate in active mode, e.g. `balance_power`
+    pub epp: Option<String>,
+}
+
+impl Policy {
+    /// `0-3,8`
+    pub fn cpu_list(&self) -> String {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for &cpu in &self.cpus {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == cpu => *end = cpu,
+                _ => ranges.push((cpu, cpu)),
+            }
+        }
+        ranges
+            .iter()
+            .map(|&(start, end)| {
+                if start == end {
+                    start.to_string()
+                } else {
+                    format!("{}-{}", start, end)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// `400–4800 MHz`
+    pub fn range(&self) -> String {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => format!("{}–{} MHz", min / 1000, max / 1000),
+            _ => "?".to_string(),
+        }
+    }
+
+    /// `800–2000 MHz`, or `full` while the limits are the hardware range
+    pub fn limits(&self) -> String {
+        match (self.limit_min, self.limit_max) {
+            (Some(min), Some(max)) if (Some(min), Some(max)) == (self.min, self.max) => {
+                "full".to_string()
+            }
+            (Some(min), Some(max)) => format!("{}–{} MHz", min / 1000, max / 1000),
+            _ => "?".to_string(),
+        }
+    }
+
+    /// `12 steps`, or `continuous` for drivers without a frequency table
+    pub fn steps(&self) -> String {
+        if self.frequencies.is_empty() {
+            "continuous".to_string()
+        } else {
+            format!("{} steps", self.frequencies.len())
+        }
+    }
+
+    pub fn format_latency(&self) -> String {
+        match self.latency {
+            Some(ns) if ns >= 1000 => format!("{} µs", ns / 1000),
+            Some(ns) => format!("{} ns", ns),
+            None => "?".to_string(),
+        }
+    }
+
+    /// Same settings, so the two can be shown as one row
+    fn same_as(&self, other: &Policy) -> bool {
+        self.driver == other.driver
+            && self.governor == other.governor
+            && self.governors == other.governors
+            && self.frequencies == other.frequencies
+            && (self.min, self.max, self.latency) == (other.min, other.max, other.latency)
+            && (self.limit_min, self.limit_max) == (other.limit_min, other.limit_max)
+            && self.epp == other.epp
+    }
+}
+
+/// Every cpufreq policy, with identical ones (common on intel_pstate,
+/// which has one per CPU) merged, in CPU order
+pub fn read_policies() -> Vec<Policy> {
+    let Ok(entries) = fs::read_dir(sysfs::path(CPUFREQ)) else {
+        return Vec::new();
+    };
+    let mut policies: Vec<Policy> = entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with("policy"))
+        .filter_map(|entry| {
+            let path = entry.path();
+            let list = |name: &str| -> Vec<String> {
+                sysfs::read_string(path.join(name))
+                    .map(|s| s.split_whitespace().map(String::from).collect())
+                    .unwrap_or_default()
+            };
+            Some(Policy {
+                cpus: list("related_cpus")
+                    .iter()
+                    .filter_map(|c| c.parse().ok())
+                    .collect(),
+                driver: sysfs::read_string(path.join("scaling_driver"))?,
+                governor: sysfs::read_string(path.join("scaling_governor")),
+                governors: list("scaling_available_governors"),
+                frequencies: list("scaling_available_frequencies")
+                    .iter()
+                    .filter_map(|f| f.parse().ok())
+                    .collect(),
+                min: sysfs::read_value(path.join("cpuinfo_min_freq")),
+                max: sysfs::read_value(path.join("cpuinfo_max_freq")),
+                limit_min: sysfs::read_value(path.join("scaling_min_freq")),
+                limit_max: sysfs::read_value(path.join("scaling_max_freq")),
+                latency: sysfs::read_value(path.join("cpuinfo_transition_latency"))
+                    .filter(|&l| l != UNKNOWN_LATENCY),
+                epp: sysfs::read_string(path.join(EPP)),
+            })
+        })
+        .collect();
+    policies.sort_by_key(|p| p.cpus.first().copied());
+
+    let mut merged: Vec<Policy> = Vec::new();
+    for policy in policies {
+        match merged.iter_mut().find(|m| m.same_as(&policy)) {
+            Some(existing) => existing.cpus.extend(&policy.cpus),
+            None => merged.push(policy),
+        }
+    }
+    for policy in &mut merged {
+        policy.cpus.sort_unstable();
+    }
+    merged
+}
+
+/// Every policy directory, in order
+fn policy_dirs() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(sysfs::path(CPUFREQ))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with("policy"))
+        .map(|e| e.path())
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Policy directories with an EPP hint, in order
+fn epp_policies() -> Vec<PathBuf> {
+    policy_dirs()
+        .into_iter()
+        .filter(|p| p.join(EPP).exists())
+        .collect()
+}
+
+/// The hint in effect, from the first policy
+pub fn read_epp() -> Option<String> {
+    sysfs::read_string(epp_policies().first()?.join(EPP))
+}
+
+/// Hints the driver accepts, usually `default performance
+/// balance_performance balance_power power`
+pub fn available_epp() -> Vec<String> {
+    epp_policies()
+        .first()
+        .and_then(|p| sysfs::read_string(p.join("energy_performance_available_preferences")))
+        .map(|s| s.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Set the hint on every policy. intel_pstate refuses anything but
+/// `performance` while the performance governor is in charge.
+pub fn set_epp(hint: &str) -> Result<(), String> {
+    let policies = epp_policies();
+    if policies.is_empty() {
+        return Err("This cpufreq driver has no EPP".to_string());
+    }
+    let old = read_epp();
+    for path in policies {
+        privileged::write("EPP", old.clone(), &path.join(EPP).to_string_lossy(), hint)?;
+    }
+    Ok(())
+}
+
+/// Lowest and highest frequency any policy's hardware allows, in kHz
+pub fn hardware_range() -> Option<(u64, u64)> {
+    let ranges: Vec<(u64, u64)> = policy_dirs()
+        .iter()
+        .filter_map(|p| {
+            Some((
+                sysfs::read_value(p.join("cpuinfo_min_freq"))?,
+                sysfs::read_value(p.join("cpuinfo_max_freq"))?,
+            ))
+        })
+        .collect();
+    let min = ranges.iter().map(|r| r.0).min()?;
+    let max = ranges.iter().map(|r| r.1).max()?;
+    Some((min, max))
+}
+
+/// Limits in effect on the first policy, in kHz
+pub fn read_limits() -> Option<(u64, u64)> {
+    let policy = policy_dirs().into_iter().next()?;
+    Some((
+        sysfs::read_value(policy.join("scaling_min_freq"))?,
+        sysfs::read_value(policy.join("scaling_max_freq"))?,
+    ))
+}
+
+/// Set `scaling_min_freq` and `scaling_max_freq` on every policy, in
+/// kHz, leaving a bound alone when `None`. Each policy clamps to its own
+/// hardware range, so on hybrid CPUs a cap above the efficiency cores'
+/// top clock only limits the performance cores.
+pub fn set_limits(min: Option<u64>, max: Option<u64>) -> Result<(), String> {
+    let policies = policy_dirs();
+    if policies.is_empty() {
+        return Err("No cpufreq policies".to_string());
+    }
+    let mhz = |khz: u64| format!("{} MHz", khz / 1000);
+    for policy in policies {
+        let (Some(low), Some(high)) = (
+            sysfs::read_value::<u64>(policy.join("cpuinfo_min_freq")),
+            sysfs::read_value::<u64>(policy.join("cpuinfo_max_freq")),
+        ) else {
+            continue;
+        };
+        let current_min = sysfs::read_value::<u64>(policy.join("scaling_min_freq"));
+        let current_max = sysfs::read_value::<u64>(policy.join("scaling_max_freq"));
+        let writes = [
+            (
+                "Min frequency",
+                "scaling_min_freq",
+                current_min,
+                min.map(|f| f.clamp(low, high)),
+            ),
+            (
+                "Max frequency",
+                "scaling_max_freq",
+                current_max,
+                max.map(|f| f.clamp(low, high)),
+            ),
+        ];
+        // The kernel refuses a minimum above the maximum in effect, so
+        // raise the maximum first when the minimum goes above it
+        let raise_max_first = min
+            .zip(current_max)
+            .is_some_and(|(min, current)| min > current);
+        let order = if raise_max_first { [1, 0] } else { [0, 1] };
+        for i in order {
+            let (setting, attribute, old, new) = writes[i];
+            let Some(new) = new.filter(|n| Some(*n) != old) else {
+                continue;
+            };
+            privileged::write(
+                setting,
+                old.map(mhz),
+                &policy.join(attribute).to_string_lossy(),
+                &new.to_string(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Drivers that pick frequencies themselves (or let the hardware) and
+/// only take the governor as a hint
+fn driver_active(driver: &str) -> bool {
+    matches!(driver, "intel_pstate" | "amd-pstate-epp")
+}
+
+/// What a governor actually does under this cpufreq driver; the same
+/// name means different things in active and passive mode
+pub fn governor_semantics(driver: &str, governor: &str) -> String {
+    if driver_active(driver) {
+        return match governor {
+            "performance" => format!(
+                "{} picks the clocks itself; performance makes it favor speed and forces the EPP to performance",
+                driver
+            ),
+            "powersave" => format!(
+                "Not a fixed low clock: {} still ramps up under load, steered by the EPP hint",
+                driver
+            ),
+            _ => format!("{} only offers performance and powersave", driver),
+        };
+    }
+    let what = match governor {
+        "performance" => "Holds every CPU at its maximum frequency",
+        "powersave" => "Holds every CPU at its minimum frequency, even under load",
+        "schedutil" => "Scales with the scheduler's measure of CPU utilization; the usual default",
+        "ondemand" => "Jumps to the top frequency once load crosses a threshold, then steps down",
+        "conservative" => {
+            "Like ondemand but steps up and down gradually, trading latency for power"
+        }
+        "userspace" => "Leaves the frequency to whatever a program writes to scaling_setspeed",
+        _ => return format!("{} is specific to {}", governor, driver),
+    };
+    format!("{} ({})", what, driver)
+}
+
+/// What an EPP hint asks of the hardware
+pub fn epp_semantics(driver: &str, hint: &str) -> String {
+    let what = match hint {
+        "performance" => "Favor speed: clocks rise eagerly and stay up",
+        "balance_performance" => "Lean toward speed; the usual default on AC",
+        "balance_power" => "Lean toward saving power: clocks rise more slowly",
+        "power" => "Favor saving power: clocks stay low unless load persists",
+        "default" => "Whatever the firmware set at boot",
+        _ => return format!("{} is specific to {}", hint, driver),
+    };
+    if driver == "intel_pstate" {
+        format!(
+            "{}; under the performance governor intel_pstate only accepts performance",
+            what
+        )
+    } else {
+        what.to_string()
+    }
+}
+
+/// Whether the CPU may turbo, or `None` without a switch for it
+pub fn read_turbo() -> Option<bool> {
+    match sysfs::read_value::<u8>(sysfs::path(NO_TURBO)) {
+        Some(no_turbo) => Some(no_turbo == 0),
+        None => sysfs::read_value::<u8>(sysfs::path(BOOST)).map(|boost| boost == 1),
+    }
+}
+
+/// Allow or forbid turbo. Takes effect at once and lasts until reboot.
+pub fn set_turbo(on: bool) -> Result<(), String> {
+    let old = read_turbo().map(|t| if t { "on" } else { "off" }.to_string());
+    if sysfs::path(NO_TURBO).exists() {
+        let value = if on { "0" } else { "1" };
+        privileged::write("turbo", old, NO_TURBO, value)
+    } else if sysfs::path(BOOST).exists() {
+        let value = if on { "1" } else { "0" };
+        privileged::write("turbo", old, BOOST, value)
+    } else {
+        Err("This CPU has no turbo switch".to_string())
+    }
+}
@@ -0,0 +1,331 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+
+use chrono::{DateTime, Local};
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::backlight;
+use crate::battery;
+use crate::charger;
+use crate::config::{self, PackConfig, QuietHours};
+use crate::history;
+use crate::notifications::Notifier;
+use crate::privileged;
+use crate::profile::{self, load_profiles, Profile};
+use crate::resume::{self, SleepMonitor};
+use crate::sessions;
+use crate::storage;
+use crate::stream::{self, Message};
+use crate::thermal;
+use crate::usage::Tracker;
+use crate::webhook::{self, WebhookConfig};
+
+/// What a running daemon last did, for the TUI to show
+#[derive(Clone, Serialize, Deserialize)]
+pub struct State {
+    pub pid: u32,
+    /// `On AC` or `On battery`
+    pub rule: Option<String>,
+    pub profile: Option<String>,
+    /// Unix time the rule last fired
+    pub at: Option<i64>,
+    /// Why the last switch failed
+    pub error: Option<String>,
+}
+
+impl State {
+    /// Local time the rule last fired
+    pub fn fired(&self) -> Option<DateTime<Local>> {
+        Some(DateTime::from_timestamp(self.at?, 0)?.with_timezone(&Local))
+    }
+
+    fn save(&self) {
+        let (Some(path), Ok(text)) = (state_path(), serde_json::to_string(self)) else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = fs::write(path, text);
+        stream::publish(&Message::State(self.clone()));
+    }
+}
+
+fn state_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("daemon.json"))
+}
+
+/// A profile picked by hand, kept for the daemon to apply after a
+/// reboot
+#[derive(Serialize, Deserialize)]
+struct Choice {
+    profile: String,
+    /// Unix time it was picked
+    at: i64,
+}
+
+fn choice_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("profile.json"))
+}
+
+/// Remember `profile` as the one to hold from now on
+pub fn save_choice(profile: &str) -> Result<(), String> {
+    let path = choice_path().ok_or("No state directory")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let choice = Choice {
+        profile: profile.to_string(),
+        at: Local::now().timestamp(),
+    };
+    let text = serde_json::to_string(&choice).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// Go back to following the charger
+pub fn clear_choice() {
+    if let Some(path) = choice_path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn read_choice() -> Option<String> {
+    let text = fs::read_to_string(choice_path()?).ok()?;
+    serde_json::from_str::<Choice>(&text)
+        .ok()
+        .map(|c| c.profile)
+}
+
+/// The running daemon's state. A daemon that was killed leaves its file
+/// behind, so the pid has to still be a powertui process.
+pub fn read_state() -> Option<State> {
+    let text = fs::read_to_string(state_path()?).ok()?;
+    let state: State = serde_json::from_str(&text).ok()?;
+    let comm = fs::read_to_string(Path::new("/proc").join(state.pid.to_string()).join("comm"));
+    (state.pid != process::id() && comm.ok()?.trim() == "powertui").then_some(state)
+}
+
+/// `powertui daemon [--dry-run]`: switch profiles on AC plug and unplug,
+/// or hold the one saved with `[daemon] persist`, apply them again after
+/// resume, keep the battery log and post low battery and thermal
+/// alerts to the webhook, without the TUI open. Polls the power
+/// supplies' `online` attribute; run it as a systemd user service.
+pub fn run(args: &[String]) -> Result<()> {
+    for arg in args {
+        match arg.as_str() {
+            "--dry-run" => privileged::set_dry_run(true),
+            _ => bail!("unknown daemon option: {}", arg),
+        }
+    }
+    let config = config::load().map_err(|e| eyre!(e))?;
+    let settings = config.daemon;
+    let profiles = load_profiles().map_err(|e| eyre!(e))?;
+    for name in [&settings.ac, &settings.battery] {
+        if !profiles.iter().any(|p| p.name == *name) {
+            bail!("[daemon] names unknown profile {}", name);
+        }
+    }
+    // Two would fight over profiles, thresholds and the state file
+    if stream::running() {
+        bail!("Another powertui daemon is already running");
+    }
+    if let Some(name) = config::config_profile() {
+        println!("Using config profile {}", name);
+    }
+    println!(
+        "Switching to {} on AC and {} on battery, checking every {:?}",
+        settings.ac, settings.battery, settings.interval.0
+    );
+
+    if !stream::serve() {
+        eprintln!("Can't listen on the daemon socket; open TUIs will only see changes on their next refresh");
+    }
+    let mut state = State {
+        pid: process::id(),
+        rule: None,
+        profile: None,
+        at: None,
+        error: None,
+    };
+    state.save();
+    let resume = config.resume;
+    let sleep_monitor = (resume.reapply || !resume.hooks.is_empty())
+        .then(SleepMonitor::start)
+        .flatten();
+    let mut battery_log = config
+        .history
+        .log
+        .then(|| history::Logger::new(config.history.interval.0));
+    let mut session_log = config.history.log.then(sessions::Recorder::default);
+    let mut online = None;
+    // The saved profile being held instead of following the charger
+    let mut held: Option<String> = None;
+    let mut low_warned = false;
+    let mut running_hot = false;
+    let mut storage_reminded = false;
+    let quiet_hours = config.quiet_hours;
+    let mut notifier = config.notifications.map(|n| Notifier::new(n, quiet_hours));
+    // For the History page's time in profile, which is the rules at work
+    // when nobody has the TUI open
+    let mut profile_time = Tracker::default();
+    loop {
+        // Credit the time since the last round before switching
+        profile_time.tick(state.profile.as_deref());
+        let now = charger::read_charger().is_some();
+        let resumed = sleep_monitor.as_ref().is_some_and(|m| m.resumed());
+        if resumed {
+            println!("Resumed from suspend");
+        }
+        // Read every time, since the TUI saves and clears it
+        let choice = settings.persist.then(read_choice).flatten();
+        if choice != held {
+            // Cleared: follow the charger again from this round
+            if choice.is_none() {
+                online = None;
+            }
+            held = choice;
+            if let Some(ref name) = held {
+                switch(&mut state, "Saved", name, &profiles);
+            }
+        } else if let (Some(ref name), true) = (&held, resumed && resume.reapply) {
+            switch(&mut state, "Saved", name, &profiles);
+        }
+        if held.is_none() && (online != Some(now) || (resumed && resume.reapply)) {
+            online = Some(now);
+            let (rule, name) = if now {
+                ("On AC", &settings.ac)
+            } else {
+                ("On battery", &settings.battery)
+            };
+            switch(&mut state, rule, name, &profiles);
+        }
+        if resumed && resume.reapply {
+            apply_pack_thresholds(&config.battery.packs);
+        }
+        if resumed {
+            for e in resume::run_hooks(&resume.hooks) {
+                eprintln!("Resume hook failed: {}", e);
+            }
+        }
+        let batteries = battery::read_batteries();
+        // Same floor as the TUI, for a forced discharge left running
+        // after it quit
+        for bat in &batteries {
+            match battery::guard_force_discharge(bat) {
+                Some(Ok(())) => println!("{}: stopped forced discharge", bat.name),
+                Some(Err(e)) => eprintln!("{}: {}", bat.name, e),
+                None => {}
+            }
+        }
+        let combined = battery::combined(&batteries);
+        if let Some(ref mut log) = battery_log {
+            log.record(combined.as_ref());
+        }
+        if let Some(ref mut recorder) = session_log {
+            recorder.update(combined.as_ref(), state.profile.as_deref());
+        }
+        if let Some(ref mut notifier) = notifier {
+            notifier.update(combined.as_ref(), now, config.battery.low);
+        }
+        // Same as the TUI: once per discharge, and once per time the CPU
+        // gets hot
+        match combined {
+            Some(ref bat) if !now && bat.capacity <= config.battery.low => {
+                if !low_warned {
+                    low_warned = true;
+                    let text = format!("Battery low: {}%", bat.capacity);
+                    alert(config.webhook.as_ref(), quiet_hours, "battery_low", &text);
+                }
+            }
+            _ => low_warned = false,
+        }
+        if let Some(temp) = thermal::read_cpu_temp() {
+            if temp < config.gauges.temp_warm {
+                running_hot = false;
+            } else if temp >= config.gauges.temp_hot && !running_hot {
+                running_hot = true;
+                let text = format!("CPU running hot: {:.0}°C", temp);
+                alert(config.webhook.as_ref(), quiet_hours, "thermal", &text);
+            }
+        }
+        if !storage_reminded {
+            let reminder = storage::read().and_then(|s| s.reminder(config.storage.remind_weeks));
+            if let Some(text) = reminder {
+                storage_reminded = true;
+                alert(config.webhook.as_ref(), quiet_hours, "storage", &text);
+            }
+        }
+        for failure in webhook::drain_failed() {
+            eprintln!("{}", failure);
+        }
+        thread::sleep(settings.interval.0);
+    }
+}
+
+/// Print it, and post it unless it's quiet hours and it can wait
+fn alert(
+    webhook: Option<&WebhookConfig>,
+    quiet_hours: Option<QuietHours>,
+    event: &str,
+    text: &str,
+) {
+    println!("{}", text);
+    let quiet = quiet_hours.is_some_and(|q| q.now()) && !webhook::URGENT.contains(&event);
+    if let (Some(webhook), false) = (webhook, quiet) {
+        webhook.send(event, text);
+    }
+}
+
+/// Apply the rule's profile and record it for the TUI
+fn switch(state: &mut State, rule: &str, name: &str, profiles: &[Profile]) {
+    // Read again so profile edits apply without a restart
+    let profiles = load_profiles().unwrap_or_else(|_| profiles.to_vec());
+    let result = match profiles.iter().find(|p| p.name == name) {
+        Some(profile) => profile::apply(profile).and_then(|substitute| {
+            // No desktop to ask from here, so straight to the backlight
+            if let (Some(percent), Some(backlight)) = (profile.brightness, backlight::read()) {
+                backlight.set_percent(percent)?;
+            }
+            Ok(substitute)
+        }),
+        None => Err(format!("Unknown profile {}", name)),
+    };
+    state.error = match result {
+        Ok(substitute) => {
+            let using = substitute
+                .map(|g| format!(" (using the {} governor)", g))
+                .unwrap_or_default();
+            println!("{}: switched to {}{}", rule, name, using);
+            None
+        }
+        Err(e) => {
+            eprintln!("{}: {}", rule, e);
+            Some(e)
+        }
+    };
+    state.rule = Some(rule.to_string());
+    state.profile = Some(name.to_string());
+    state.at = Some(Local::now().timestamp());
+    state.save();
+}
+
+/// Charge thresholds from `[[battery.pack]]`, which firmware may have
+/// reset while asleep
+fn apply_pack_thresholds(packs: &[PackConfig]) {
+    let batteries = battery::read_batteries();
+    for pack in packs {
+        let Some(end) = pack.end_threshold else {
+            continue;
+        };
+        let Some(battery) = batteries.iter().find(|b| b.name == pack.name) else {
+            continue;
+        };
+        if let Err(e) = battery::set_thresholds(battery, pack.start_threshold, end) {
+            eprintln!("{}: {}", pack.name, e);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::process::Command;
+
+#[derive(Clone, Copy)]
+pub enum Bus {
+    System,
+    Session,
+}
+
+/// Call a D-Bus method through `busctl` and return the reply as tokens.
+///
+/// busctl prints replies as a signature followed by values, e.g.
+/// `a(ss) 1 "firefox" "video playing"`; strings come back unquoted.
+pub fn call(
+    bus: Bus,
+    dest: &str,
+    path: &str,
+    iface: &str,
+    method: &str,
+    args: &[&str],
+) -> Option<Vec<String>> {
+    let mut cmd = Command::new("busctl");
+    cmd.arg(bus_flag(bus))
+        .args(["call", dest, path, iface, method])
+        .args(args);
+    run(cmd)
+}
+
+/// Read several properties in one `busctl get-property` call. Each value
+/// comes back as its signature followed by the value, e.g. `d 85 s "MX"`.
+pub fn get_properties(
+    bus: Bus,
+    dest: &str,
+    path: &str,
+    iface: &str,
+    props: &[&str],
+) -> Option<Vec<String>> {
+    let mut cmd = Command::new("busctl");
+    cmd.arg(bus_flag(bus))
+        .args(["get-property", dest, path, iface])
+        .args(props);
+    run(cmd)
+}
+
+/// Set one property with `busctl set-property`; `signature` is the
+/// D-Bus type code, e.g. `i` for int32
+pub fn set_property(
+    bus: Bus,
+    dest: &str,
+    path: &str,
+    iface: &str,
+    prop: &str,
+    signature: &str,
+    value: &str,
+) -> Option<()> {
+    let mut cmd = Command::new("busctl");
+    cmd.arg(bus_flag(bus))
+        .args(["set-property", dest, path, iface, prop, signature, value]);
+    run(cmd).map(|_| ())
+}
+
+fn bus_flag(bus: Bus) -> &'static str {
+    match bus {
+        Bus::System => "--system",
+        Bus::Session => "--user",
+    }
+}
+
+fn run(mut cmd: Command) -> Option<Vec<String>> {
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(tokenize(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut token = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => {
+                        if let Some(escaped) = chars.next() {
+                            token.push(escaped);
+                        }
+                    }
+                    '"' => break,
+                    _ => token.push(c),
+                }
+            }
+            tokens.push(token);
+        } else {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+
+    tokens
+}
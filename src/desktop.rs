@@ -0,0 +1,306 @@
+use std::env;
+use std::process::Command;
+
+/// Desktop environments whose settings daemons own brightness and idle
+/// suspend. Writing sysfs behind their back gets undone on the next
+/// session restore, so when one is running we go through its settings store.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Desktop {
+    Gnome,
+    Kde,
+}
+
+pub struct DesktopSettings {
+    pub brightness: Option<u8>,
+    pub suspend_battery: Option<u32>,
+    pub suspend_ac: Option<u32>,
+}
+
+/// Battery suspend timeouts (seconds) cycled through from the TUI, 0 = never
+pub const SUSPEND_PRESETS: [u32; 5] = [300, 600, 900, 1800, 0];
+
+impl Desktop {
+    pub fn detect() -> Option<Desktop> {
+        let current = env::var("XDG_CURRENT_DESKTOP").ok()?.to_uppercase();
+        let desktop = if current.contains("GNOME") {
+            Desktop::Gnome
+        } else if current.contains("KDE") {
+            Desktop::Kde
+        } else {
+            return None;
+        };
+
+        // The bridge is useless without the tools to talk to the DE
+        let tool = match desktop {
+            Desktop::Gnome => "gsettings",
+            Desktop::Kde => "kwriteconfig6",
+        };
+        if !command_exists(tool) || !command_exists("gdbus") {
+            return None;
+        }
+        Some(desktop)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Desktop::Gnome => "GNOME",
+            Desktop::Kde => "KDE Plasma",
+        }
+    }
+
+    pub fn read_settings(&self) -> DesktopSettings {
+        DesktopSettings {
+            brightness: self.brightness(),
+            suspend_battery: self.suspend_timeout(true),
+            suspend_ac: self.suspend_timeout(false),
+        }
+    }
+
+    pub fn brightness(&self) -> Option<u8> {
+        match self {
+            Desktop::Gnome => {
+                let out = gdbus_call(
+                    "org.gnome.SettingsDaemon.Power",
+                    "/org/gnome/SettingsDaemon/Power",
+                    "org.freedesktop.DBus.Properties.Get",
+                    &["org.gnome.SettingsDaemon.Power.Screen", "Brightness"],
+                )?;
+                let value = parse_gvariant_int(&out)?;
+                // -1 means the daemon found no controllable backlight
+                (0..=100).contains(&value).then_some(value as u8)
+            }
+            Desktop::Kde => {
+                let path = "/org/kde/Solid/PowerManagement/Actions/BrightnessControl";
+                let iface = "org.kde.Solid.PowerManagement.Actions.BrightnessControl";
+                let now = gdbus_call(
+                    "org.kde.Solid.PowerManagement",
+                    path,
+                    &format!("{}.brightness", iface),
+                    &[],
+                )?;
+                let max = gdbus_call(
+                    "org.kde.Solid.PowerManagement",
+                    path,
+                    &format!("{}.brightnessMax", iface),
+                    &[],
+                )?;
+                let now = parse_gvariant_int(&now)? as f64;
+                let max = parse_gvariant_int(&max)? as f64;
+                if max <= 0.0 {
+                    return None;
+                }
+                Some(((now / max) * 100.0).round() as u8)
+            }
+        }
+    }
+
+    pub fn set_brightness(&self, percent: u8) -> Result<(), String> {
+        let percent = percent.min(100);
+        match self {
+            Desktop::Gnome => {
+                let value = format!("<int32 {}>", percent);
+                gdbus_call(
+                    "org.gnome.SettingsDaemon.Power",
+                    "/org/gnome/SettingsDaemon/Power",
+                    "org.freedesktop.DBus.Properties.Set",
+                    &[
+                        "org.gnome.SettingsDaemon.Power.Screen",
+                        "Brightness",
+                        &value,
+                    ],
+                )
+                .map(|_| ())
+                .ok_or_else(|| "GNOME settings daemon rejected brightness".to_string())
+            }
+            Desktop::Kde => {
+                let path = "/org/kde/Solid/PowerManagement/Actions/BrightnessControl";
+                let iface = "org.kde.Solid.PowerManagement.Actions.BrightnessControl";
+                let max = gdbus_call(
+                    "org.kde.Solid.PowerManagement",
+                    path,
+                    &format!("{}.brightnessMax", iface),
+                    &[],
+                )
+                .and_then(|s| parse_gvariant_int(&s))
+                .ok_or_else(|| "PowerDevil is not running".to_string())?;
+                let value = (max * percent as i64 / 100).to_string();
+                gdbus_call(
+                    "org.kde.Solid.PowerManagement",
+                    path,
+                    &format!("{}.setBrightness", iface),
+                    &[&value],
+                )
+                .map(|_| ())
+                .ok_or_else(|| "PowerDevil rejected brightness".to_string())
+            }
+        }
+    }
+
+    /// Idle suspend timeout in seconds, 0 when suspend is disabled
+    pub fn suspend_timeout(&self, on_battery: bool) -> Option<u32> {
+        match self {
+            Desktop::Gnome => {
+                let out = run(
+                    "gsettings",
+                    &["get", GNOME_POWER_SCHEMA, gnome_suspend_key(on_battery)],
+                )?;
+                parse_gvariant_int(&out).map(|v| v.max(0) as u32)
+            }
+            Desktop::Kde => {
+                let group = kde_group(on_battery);
+                let enabled = run(
+                    "kreadconfig6",
+                    &[
+                        "--file",
+                        "powerdevilrc",
+                        "--group",
+                        group,
+                        "--group",
+                        "SuspendAndShutdown",
+                        "--key",
+                        "AutoSuspendAction",
+                    ],
+                )?;
+                if enabled.trim() == "0" {
+                    return Some(0);
+                }
+                let out = run(
+                    "kreadconfig6",
+                    &[
+                        "--file",
+                        "powerdevilrc",
+                        "--group",
+                        group,
+                        "--group",
+                        "SuspendAndShutdown",
+                        "--key",
+                        "AutoSuspendIdleTimeoutSec",
+                    ],
+                )?;
+                out.trim().parse().ok()
+            }
+        }
+    }
+
+    pub fn set_suspend_timeout(&self, on_battery: bool, secs: u32) -> Result<(), String> {
+        match self {
+            Desktop::Gnome => {
+                let key = gnome_suspend_key(on_battery);
+                let value = secs.to_string();
+                run("gsettings", &["set", GNOME_POWER_SCHEMA, key, &value])
+                    .ok_or_else(|| "gsettings failed".to_string())?;
+                // GNOME keeps a separate "nothing" action for disabled suspend
+                let kind = if on_battery {
+                    "sleep-inactive-battery-type"
+                } else {
+                    "sleep-inactive-ac-type"
+                };
+                let action = if secs == 0 { "nothing" } else { "suspend" };
+                run("gsettings", &["set", GNOME_POWER_SCHEMA, kind, action])
+                    .map(|_| ())
+                    .ok_or_else(|| "gsettings failed".to_string())
+            }
+            Desktop::Kde => {
+                let group = kde_group(on_battery);
+                let action = if secs == 0 { "0" } else { "1" };
+                for (key, value) in [
+                    ("AutoSuspendAction", action.to_string()),
+                    ("AutoSuspendIdleTimeoutSec", secs.to_string()),
+                ] {
+                    run(
+                        "kwriteconfig6",
+                        &[
+                            "--file",
+                            "powerdevilrc",
+                            "--group",
+                            group,
+                            "--group",
+                            "SuspendAndShutdown",
+                            "--key",
+                            key,
+                            &value,
+                        ],
+                    )
+                    .ok_or_else(|| "kwriteconfig6 failed".to_string())?;
+                }
+                // PowerDevil only picks up config changes when asked to
+                gdbus_call(
+                    "org.kde.Solid.PowerManagement",
+                    "/org/kde/Solid/PowerManagement",
+                    "org.kde.Solid.PowerManagement.reparseConfiguration",
+                    &[],
+                )
+                .map(|_| ())
+                .ok_or_else(|| "PowerDevil did not reload its config".to_string())
+            }
+        }
+    }
+}
+
+const GNOME_POWER_SCHEMA: &str = "org.gnome.settings-daemon.plugins.power";
+
+fn gnome_suspend_key(on_battery: bool) -> &'static str {
+    if on_battery {
+        "sleep-inactive-battery-timeout"
+    } else {
+        "sleep-inactive-ac-timeout"
+    }
+}
+
+fn kde_group(on_battery: bool) -> &'static str {
+    if on_battery {
+        "Battery"
+    } else {
+        "AC"
+    }
+}
+
+pub fn command_exists(name: &str) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(name).is_file()))
+        .unwrap_or(false)
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        None
+    }
+}
+
+fn gdbus_call(dest: &str, path: &str, method: &str, args: &[&str]) -> Option<String> {
+    let mut full = vec![
+        "call",
+        "--session",
+        "--dest",
+        dest,
+        "--object-path",
+        path,
+        "--method",
+        method,
+    ];
+    full.extend_from_slice(args);
+    run("gdbus", &full)
+}
+
+/// Pull the integer out of GVariant text like `(<int32 60>,)` or `uint32 900`
+fn parse_gvariant_int(s: &str) -> Option<i64> {
+    s.trim()
+        .trim_matches(|c| matches!(c, '(' | ')' | ',' | '<' | '>'))
+        .split_whitespace()
+        .last()?
+        .trim_matches(|c| matches!(c, '(' | ')' | ',' | '<' | '>'))
+        .parse()
+        .ok()
+}
+
+pub fn format_timeout(secs: Option<u32>) -> String {
+    match secs {
+        None => "?".to_string(),
+        Some(0) => "never".to_string(),
+        Some(s) if s % 3600 == 0 => format!("{}h", s / 3600),
+        Some(s) => format!("{}m", s / 60),
+    }
+}
@@ -0,0 +1,114 @@
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+
+use crate::backend;
+use crate::battery::read_battery_info;
+use crate::capabilities::Capabilities;
+use crate::health;
+use crate::hibernate;
+use crate::status::{Painter, BOLD, DIM, GREEN, RED, YELLOW};
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Severity {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// The outcome of one diagnostic
+pub struct Check {
+    pub name: &'static str,
+    pub severity: Severity,
+    pub detail: String,
+}
+
+impl Check {
+    pub fn new(name: &'static str, severity: Severity, detail: impl Into<String>) -> Check {
+        Check {
+            name,
+            severity,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Worst severity among the checks
+pub fn verdict(checks: &[Check]) -> Severity {
+    checks
+        .iter()
+        .map(|c| c.severity)
+        .fold(Severity::Ok, |a, b| if b > a { b } else { a })
+}
+
+/// `powertui doctor [--capabilities] [--no-color]`: check whether power
+/// features will actually work on this machine
+pub fn run(args: &[String]) -> Result<()> {
+    let mut p = Painter::detect();
+    let mut capabilities = false;
+    for arg in args {
+        match arg.as_str() {
+            "--no-color" => p.color = false,
+            "--capabilities" => capabilities = true,
+            _ => bail!("unknown doctor option: {}", arg),
+        }
+    }
+    if capabilities {
+        print_capabilities(&p);
+        return Ok(());
+    }
+
+    let checks = hibernate::check();
+    let ready = match verdict(&checks) {
+        Severity::Ok => p.paint(GREEN, "ready"),
+        Severity::Warn => p.paint(YELLOW, "probably works"),
+        Severity::Fail => p.paint(RED, "will not work"),
+    };
+    println!("{} {}", p.paint(BOLD, "Hibernate"), ready);
+    for check in &checks {
+        print_check(&p, check);
+    }
+
+    // The same checks the TUI runs on launch
+    let checks: Vec<Check> = health::check(read_battery_info().as_ref())
+        .into_iter()
+        .map(|f| f.check)
+        .collect();
+    let setup = match verdict(&checks) {
+        Severity::Ok => p.paint(GREEN, "looks good"),
+        Severity::Warn => p.paint(YELLOW, "has warnings"),
+        Severity::Fail => p.paint(RED, "has problems"),
+    };
+    println!("{} {}", p.paint(BOLD, "Setup"), setup);
+    for check in &checks {
+        print_check(&p, check);
+    }
+    Ok(())
+}
+
+/// What the machine supports, one line each, for pasting into bug reports
+fn print_capabilities(p: &Painter) {
+    println!("{}", p.paint(BOLD, "Capabilities"));
+    for (name, supported) in Capabilities::probe().matrix() {
+        let answer = if supported {
+            p.paint(GREEN, "yes")
+        } else {
+            p.paint(DIM, "no")
+        };
+        println!("  {:<18} {}", name, answer);
+    }
+    println!("  {:<18} {}", "Profile backend", backend::active().name());
+}
+
+fn print_check(p: &Painter, check: &Check) {
+    let mark = match check.severity {
+        Severity::Ok => p.paint(GREEN, "✓"),
+        Severity::Warn => p.paint(YELLOW, "!"),
+        Severity::Fail => p.paint(RED, "✗"),
+    };
+    println!(
+        "  {} {:<10} {}",
+        mark,
+        check.name,
+        p.paint(DIM, &check.detail)
+    );
+}
@@ -0,0 +1,88 @@
+use std::env;
+use std::process::Command;
+
+use crate::dbus::{self, Bus};
+use crate::desktop::command_exists;
+use crate::sway::Sway;
+
+/// How displays get forced off, by what runs the session. Each one
+/// blanks every output at once; input wakes them as usual.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Dpms {
+    /// `output * power off` over IPC
+    Sway,
+    /// Mutter's `PowerSaveMode`
+    Gnome,
+    /// `kscreen-doctor --dpms`
+    Kde,
+    /// `wlopm`, for wlroots compositors with output power management
+    Wlroots,
+    /// `xset dpms force`, for X11 sessions including i3
+    X11,
+}
+
+impl Dpms {
+    pub fn detect(sway: Option<&Sway>) -> Option<Dpms> {
+        if sway.is_some_and(|s| s.is_sway()) {
+            return Some(Dpms::Sway);
+        }
+        let desktop = env::var("XDG_CURRENT_DESKTOP")
+            .unwrap_or_default()
+            .to_uppercase();
+        let wayland = env::var_os("WAYLAND_DISPLAY").is_some();
+        if desktop.contains("GNOME") && wayland && command_exists("busctl") {
+            Some(Dpms::Gnome)
+        } else if desktop.contains("KDE") && command_exists("kscreen-doctor") {
+            Some(Dpms::Kde)
+        } else if wayland && command_exists("wlopm") {
+            Some(Dpms::Wlroots)
+        } else if env::var_os("DISPLAY").is_some() && command_exists("xset") {
+            Some(Dpms::X11)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dpms::Sway => "Sway",
+            Dpms::Gnome => "Mutter",
+            Dpms::Kde => "kscreen-doctor",
+            Dpms::Wlroots => "wlopm",
+            Dpms::X11 => "xset",
+        }
+    }
+
+    /// Turn every output off or back on
+    pub fn set_power(&self, sway: Option<&Sway>, on: bool) -> Result<(), String> {
+        match self {
+            Dpms::Sway => sway.ok_or("Sway IPC is gone")?.set_outputs_power(on),
+            // 0 is on, 3 is off
+            Dpms::Gnome => dbus::set_property(
+                Bus::Session,
+                "org.gnome.Mutter.DisplayConfig",
+                "/org/gnome/Mutter/DisplayConfig",
+                "org.gnome.Mutter.DisplayConfig",
+                "PowerSaveMode",
+                "i",
+                if on { "0" } else { "3" },
+            )
+            .ok_or_else(|| "Mutter rejected PowerSaveMode".to_string()),
+            Dpms::Kde => run("kscreen-doctor", &["--dpms", if on { "on" } else { "off" }]),
+            Dpms::Wlroots => run("wlopm", &[if on { "--on" } else { "--off" }, "*"]),
+            Dpms::X11 => run("xset", &["dpms", "force", if on { "on" } else { "off" }]),
+        }
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} failed", program))
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Power draw over the last few minutes, sampled at `[refresh] power`
+/// while the `chart` widget is on screen
+pub struct DrawHistory {
+    samples: VecDeque<(Instant, f64)>,
+    window: Duration,
+    every: Duration,
+    last: Option<Instant>,
+}
+
+impl DrawHistory {
+    pub fn new(window: Duration, every: Duration) -> Self {
+        DrawHistory {
+            samples: VecDeque::new(),
+            window,
+            every,
+            last: None,
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Whether the next sample is due
+    pub fn due(&self) -> bool {
+        self.last.is_none_or(|t| t.elapsed() >= self.every)
+    }
+
+    /// Record a reading, dropping ones older than the window. `None` still
+    /// counts as a sample so a missing reading isn't retried every frame.
+    pub fn push(&mut self, watts: Option<f64>) {
+        let now = Instant::now();
+        self.last = Some(now);
+        while self
+            .samples
+            .front()
+            .is_some_and(|(t, _)| now.duration_since(*t) > self.window)
+        {
+            self.samples.pop_front();
+        }
+        if let Some(watts) = watts {
+            self.samples.push_back((now, watts));
+        }
+    }
+
+    /// `(seconds ago as a negative number, watts)`, oldest first
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        let now = Instant::now();
+        self.samples
+            .iter()
+            .map(|(t, w)| (-now.duration_since(*t).as_secs_f64(), *w))
+            .collect()
+    }
+
+    pub fn latest(&self) -> Option<f64> {
+        self.samples.back().map(|(_, w)| *w)
+    }
+
+    /// Highest draw in the window
+    pub fn peak(&self) -> Option<f64> {
+        self.samples.iter().map(|(_, w)| *w).reduce(f64::max)
+    }
+}
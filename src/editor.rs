@@ -0,0 +1,662 @@
+use crossterm::event::KeyCode;
+
+use crate::battery::BatteryInfo;
+use crate::command;
+use crate::fans::{Fan, Pwm, MIN_PERCENT};
+use crate::profile::Profile;
+use crate::rapl::Limit;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Field {
+    Name,
+    Governor,
+    Epp,
+    Turbo,
+    MinFreq,
+    MaxFreq,
+    Pl1,
+    Pl2,
+    Platform,
+    Brightness,
+}
+
+impl Field {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Field::Name => "Name",
+            Field::Governor => "Governor",
+            Field::Epp => "EPP",
+            Field::Turbo => "Turbo",
+            Field::MinFreq => "Min freq",
+            Field::MaxFreq => "Max freq",
+            Field::Pl1 => "PL1",
+            Field::Pl2 => "PL2",
+            Field::Platform => "Platform",
+            Field::Brightness => "Brightness",
+        }
+    }
+
+    /// Fields that take a typed value as well as ←/→
+    fn numeric(&self) -> bool {
+        matches!(
+            self,
+            Field::MinFreq | Field::MaxFreq | Field::Pl1 | Field::Pl2 | Field::Brightness
+        )
+    }
+}
+
+/// Frequency limits change in steps of this many MHz
+const FREQ_STEP: u64 = 100;
+
+/// Brightness changes in steps of this many percent
+const BRIGHTNESS_STEP: u8 = 10;
+
+/// Power limits change in steps of this many watts, and go no lower
+const POWER_STEP: f64 = 1.0;
+
+pub enum EditorAction {
+    None,
+    Save,
+    Cancel,
+}
+
+/// What the hardware accepts for the form's limits
+pub struct Ranges {
+    /// Lowest and highest frequency, in MHz
+    pub freq: Option<(u64, u64)>,
+    /// RAPL limits the package has, with the most the firmware takes
+    pub power: Vec<(Limit, Option<f64>)>,
+}
+
+/// Modal form for creating or editing a custom profile. Choice fields
+/// only offer values discovered on this machine.
+pub struct ProfileEditor {
+    /// Name of the profile being edited, `None` when creating
+    pub original: Option<String>,
+    pub name: String,
+    pub governors: Vec<String>,
+    pub governor: usize,
+    /// EPP hints the driver accepts; empty without EPP
+    pub epps: Vec<String>,
+    /// Index into `epps` plus one, 0 leaving the hint alone
+    pub epp: usize,
+    /// Whether this CPU has a turbo switch to offer
+    turbo_switch: bool,
+    pub turbo: Option<bool>,
+    /// Lowest and highest frequency the hardware allows, in MHz
+    freq_range: Option<(u64, u64)>,
+    pub min_freq: Option<u64>,
+    pub max_freq: Option<u64>,
+    /// RAPL limits this package has, with the most the firmware accepts
+    power_limits: Vec<(Limit, Option<f64>)>,
+    pub pl1: Option<f64>,
+    pub pl2: Option<f64>,
+    /// Platform profiles the firmware offers; empty without one
+    pub platforms: Vec<String>,
+    /// Index into `platforms` plus one, 0 leaving it alone
+    pub platform: usize,
+    /// Whether powertui can set brightness, through the desktop or backlight
+    brightness_control: bool,
+    pub brightness: Option<u8>,
+    pub field: Field,
+    /// A value being typed into the focused field, unit and all
+    pub typed: Option<String>,
+    /// Why the typed value was refused
+    pub error: Option<String>,
+    /// Settings the form doesn't edit, carried over unchanged
+    base: Option<Profile>,
+}
+
+impl ProfileEditor {
+    pub fn new(
+        profile: Option<&Profile>,
+        governors: Vec<String>,
+        epps: Vec<String>,
+        turbo_switch: bool,
+        ranges: Ranges,
+        platforms: Vec<String>,
+        brightness_control: bool,
+    ) -> ProfileEditor {
+        let governor = profile
+            .and_then(|p| governors.iter().position(|g| *g == p.governor))
+            .unwrap_or(0);
+        let epp = profile
+            .and_then(|p| p.epp.as_ref())
+            .and_then(|e| epps.iter().position(|h| h == e))
+            .map_or(0, |i| i + 1);
+        let platform = profile
+            .and_then(|p| p.platform_profile.as_ref())
+            .and_then(|wanted| platforms.iter().position(|c| c == wanted))
+            .map_or(0, |i| i + 1);
+        ProfileEditor {
+            original: profile.map(|p| p.name.clone()),
+            name: profile.map(|p| p.name.clone()).unwrap_or_default(),
+            governors,
+            governor,
+            epps,
+            epp,
+            turbo_switch,
+            turbo: profile.and_then(|p| p.turbo),
+            freq_range: ranges.freq,
+            min_freq: profile.and_then(|p| p.min_freq),
+            max_freq: profile.and_then(|p| p.max_freq),
+            power_limits: ranges.power,
+            pl1: profile.and_then(|p| p.pl1),
+            pl2: profile.and_then(|p| p.pl2),
+            platforms,
+            platform,
+            brightness_control,
+            brightness: profile.and_then(|p| p.brightness),
+            field: Field::Name,
+            typed: None,
+            error: None,
+            base: profile.cloned(),
+        }
+    }
+
+    /// The form's fields; the rest only where this machine has them
+    pub fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![Field::Name, Field::Governor];
+        if !self.epps.is_empty() {
+            fields.push(Field::Epp);
+        }
+        if self.turbo_switch {
+            fields.push(Field::Turbo);
+        }
+        if self.freq_range.is_some() {
+            fields.extend([Field::MinFreq, Field::MaxFreq]);
+        }
+        for (limit, _) in &self.power_limits {
+            fields.push(match limit {
+                Limit::Pl1 => Field::Pl1,
+                Limit::Pl2 => Field::Pl2,
+            });
+        }
+        if !self.platforms.is_empty() {
+            fields.push(Field::Platform);
+        }
+        if self.brightness_control {
+            fields.push(Field::Brightness);
+        }
+        fields
+    }
+
+    pub fn value(&self, field: Field) -> String {
+        match field {
+            Field::Name => self.name.clone(),
+            Field::Governor => self
+                .governors
+                .get(self.governor)
+                .cloned()
+                .unwrap_or_else(|| "(none available)".to_string()),
+            Field::Epp => match self.epp {
+                0 => "(unchanged)".to_string(),
+                i => self.epps[i - 1].clone(),
+            },
+            Field::Turbo => match self.turbo {
+                None => "(unchanged)".to_string(),
+                Some(true) => "on".to_string(),
+                Some(false) => "off".to_string(),
+            },
+            Field::MinFreq | Field::MaxFreq => {
+                let freq = if field == Field::MinFreq {
+                    self.min_freq
+                } else {
+                    self.max_freq
+                };
+                match freq {
+                    None => "(unchanged)".to_string(),
+                    Some(mhz) => format!("{} MHz", mhz),
+                }
+            }
+            Field::Pl1 | Field::Pl2 => match self.power(field) {
+                None => "(unchanged)".to_string(),
+                Some(watts) => format!("{:.1} W", watts),
+            },
+            Field::Platform => match self.platform {
+                0 => "(unchanged)".to_string(),
+                i => self.platforms[i - 1].clone(),
+            },
+            Field::Brightness => match self.brightness {
+                None => "(unchanged)".to_string(),
+                Some(percent) => format!("{}%", percent),
+            },
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> EditorAction {
+        // A typed value has to be valid before leaving the field
+        let leaving = matches!(
+            code,
+            KeyCode::Enter | KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down
+        );
+        if leaving && !self.commit_typed() {
+            return EditorAction::None;
+        }
+        match code {
+            KeyCode::Esc if self.typed.is_some() => {
+                self.typed = None;
+                self.error = None;
+            }
+            KeyCode::Esc => return EditorAction::Cancel,
+            KeyCode::Enter => return EditorAction::Save,
+            KeyCode::Tab | KeyCode::Down => self.move_field(1),
+            KeyCode::BackTab | KeyCode::Up => self.move_field(-1),
+            KeyCode::Left => self.cycle(-1),
+            KeyCode::Right => self.cycle(1),
+            KeyCode::Backspace if self.field == Field::Name => {
+                self.name.pop();
+            }
+            KeyCode::Char(c) if self.field == Field::Name => self.name.push(c),
+            KeyCode::Backspace if self.field.numeric() => {
+                let typed = self.typed.get_or_insert_with(String::new);
+                typed.pop();
+            }
+            KeyCode::Char(c)
+                if self.field.numeric() && (c.is_alphanumeric() || ".%".contains(c)) =>
+            {
+                self.typed.get_or_insert_with(String::new).push(c);
+            }
+            _ => {}
+        }
+        EditorAction::None
+    }
+
+    /// Check and take the typed value; empty means unchanged
+    fn commit_typed(&mut self) -> bool {
+        let Some(typed) = self.typed.take() else {
+            return true;
+        };
+        match self.parse(self.field, typed.trim()) {
+            Ok(()) => {
+                self.error = None;
+                true
+            }
+            Err(e) => {
+                self.error = Some(e);
+                self.typed = Some(typed);
+                false
+            }
+        }
+    }
+
+    /// Set `field` from text like `2.4GHz`, `15W` or `80%`, within what
+    /// the hardware allows
+    fn parse(&mut self, field: Field, text: &str) -> Result<(), String> {
+        let empty = text.is_empty();
+        match field {
+            Field::MinFreq | Field::MaxFreq => {
+                let (low, high) = self.freq_range.ok_or("No frequency range")?;
+                let mhz = match empty {
+                    true => None,
+                    false => Some(
+                        command::parse_frequency(text)
+                            .filter(|f| (low..=high).contains(f))
+                            .ok_or_else(|| format!("Frequency must be {}–{} MHz", low, high))?,
+                    ),
+                };
+                if field == Field::MinFreq {
+                    self.min_freq = mhz;
+                } else {
+                    self.max_freq = mhz;
+                }
+            }
+            Field::Pl1 | Field::Pl2 => {
+                let (limit, max) = self.power_limit(field).ok_or("No power limits")?;
+                let range = match max {
+                    Some(max) => format!("{:.0}–{:.0} W", POWER_STEP, max),
+                    None => format!("at least {:.0} W", POWER_STEP),
+                };
+                let watts = match empty {
+                    true => None,
+                    false => Some(
+                        command::parse_power(text)
+                            .filter(|w| *w >= POWER_STEP && max.is_none_or(|max| *w <= max))
+                            .ok_or_else(|| format!("{} must be {}", limit.label(), range))?,
+                    ),
+                };
+                self.set_power(field, watts);
+            }
+            Field::Brightness => {
+                self.brightness = match empty {
+                    true => None,
+                    false => Some(
+                        command::parse_percent(text)
+                            .filter(|p| *p > 0)
+                            .ok_or("Brightness must be 1–100%")?,
+                    ),
+                };
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The RAPL limit behind a field, with the most the firmware takes
+    fn power_limit(&self, field: Field) -> Option<(Limit, Option<f64>)> {
+        let limit = match field {
+            Field::Pl1 => Limit::Pl1,
+            Field::Pl2 => Limit::Pl2,
+            _ => return None,
+        };
+        self.power_limits.iter().find(|(l, _)| *l == limit).copied()
+    }
+
+    fn power(&self, field: Field) -> Option<f64> {
+        if field == Field::Pl1 {
+            self.pl1
+        } else {
+            self.pl2
+        }
+    }
+
+    fn set_power(&mut self, field: Field, watts: Option<f64>) {
+        if field == Field::Pl1 {
+            self.pl1 = watts;
+        } else {
+            self.pl2 = watts;
+        }
+    }
+
+    fn move_field(&mut self, delta: isize) {
+        let fields = self.fields();
+        let i = fields.iter().position(|f| *f == self.field).unwrap_or(0) as isize;
+        let next = (i + delta).rem_euclid(fields.len() as isize) as usize;
+        self.field = fields[next];
+    }
+
+    fn cycle(&mut self, delta: isize) {
+        if self.field == Field::Governor && !self.governors.is_empty() {
+            let len = self.governors.len() as isize;
+            self.governor = (self.governor as isize + delta).rem_euclid(len) as usize;
+        }
+        if self.field == Field::Epp {
+            let len = self.epps.len() as isize + 1;
+            self.epp = (self.epp as isize + delta).rem_euclid(len) as usize;
+        }
+        if self.field == Field::Turbo {
+            let choices = [None, Some(true), Some(false)];
+            let i = choices.iter().position(|c| *c == self.turbo).unwrap_or(0) as isize;
+            self.turbo = choices[(i + delta).rem_euclid(3) as usize];
+        }
+        if self.field == Field::Platform {
+            let len = self.platforms.len() as isize + 1;
+            self.platform = (self.platform as isize + delta).rem_euclid(len) as usize;
+        }
+        if self.field == Field::Brightness {
+            // Unchanged sits below the lowest step
+            self.brightness = match (self.brightness, delta > 0) {
+                (None, true) => Some(BRIGHTNESS_STEP),
+                (None, false) => Some(100),
+                (Some(b), true) if b < 100 => Some(b.saturating_add(BRIGHTNESS_STEP).min(100)),
+                (Some(b), false) if b > BRIGHTNESS_STEP => Some(b - BRIGHTNESS_STEP),
+                _ => None,
+            };
+        }
+        if let Some((_, max)) = self.power_limit(self.field) {
+            let stepped = step_power(self.power(self.field), max, delta);
+            self.set_power(self.field, stepped);
+        }
+        if let Some(range) = self.freq_range {
+            match self.field {
+                Field::MinFreq => self.min_freq = step_freq(self.min_freq, range, delta),
+                Field::MaxFreq => self.max_freq = step_freq(self.max_freq, range, delta),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn to_profile(&self) -> Result<Profile, String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Profile needs a name".to_string());
+        }
+        let governor = self
+            .governors
+            .get(self.governor)
+            .ok_or("No governors available on this system")?;
+        let mut profile = self
+            .base
+            .clone()
+            .unwrap_or_else(|| Profile::new(name, governor));
+        profile.name = name.to_string();
+        profile.governor = governor.clone();
+        // Without EPP here, keep whatever the profile had for elsewhere
+        if !self.epps.is_empty() {
+            profile.epp = self.epp.checked_sub(1).map(|i| self.epps[i].clone());
+        }
+        if self.turbo_switch {
+            profile.turbo = self.turbo;
+        }
+        if self.freq_range.is_some() {
+            if let (Some(min), Some(max)) = (self.min_freq, self.max_freq) {
+                if min > max {
+                    return Err("Min frequency is above the max".to_string());
+                }
+            }
+            profile.min_freq = self.min_freq;
+            profile.max_freq = self.max_freq;
+        }
+        if let (Some(pl1), Some(pl2)) = (self.pl1, self.pl2) {
+            if pl1 > pl2 {
+                return Err("PL1 is above PL2".to_string());
+            }
+        }
+        for (limit, _) in &self.power_limits {
+            match limit {
+                Limit::Pl1 => profile.pl1 = self.pl1,
+                Limit::Pl2 => profile.pl2 = self.pl2,
+            }
+        }
+        if !self.platforms.is_empty() {
+            profile.platform_profile = self
+                .platform
+                .checked_sub(1)
+                .map(|i| self.platforms[i].clone());
+        }
+        if self.brightness_control {
+            profile.brightness = self.brightness;
+        }
+        profile.custom = true;
+        Ok(profile)
+    }
+}
+
+/// The next power limit in whole watts, with "unchanged" below the
+/// lowest and past the firmware's maximum
+fn step_power(watts: Option<f64>, max: Option<f64>, delta: isize) -> Option<f64> {
+    let Some(watts) = watts else {
+        return if delta > 0 { Some(POWER_STEP) } else { max };
+    };
+    let next = if delta > 0 {
+        watts.floor() + POWER_STEP
+    } else {
+        watts.ceil() - POWER_STEP
+    };
+    (next >= POWER_STEP && max.is_none_or(|max| next <= max)).then_some(next)
+}
+
+/// The next frequency limit: through the hardware range in steps, with
+/// "unchanged" past either end
+fn step_freq(freq: Option<u64>, (low, high): (u64, u64), delta: isize) -> Option<u64> {
+    let Some(freq) = freq else {
+        return Some(if delta > 0 { low } else { high });
+    };
+    // Limits typed into profiles.toml may be off the steps; move to
+    // the neighbouring step either way
+    let mut steps = (low..high).step_by(FREQ_STEP as usize).chain([high]);
+    if delta > 0 {
+        steps.find(|f| *f > freq)
+    } else {
+        steps.filter(|f| *f < freq).last()
+    }
+}
+
+/// Numeric form for one battery's charge thresholds
+pub struct ThresholdEditor {
+    pub battery: String,
+    /// Whether the battery has a start threshold besides the end
+    pub has_start: bool,
+    /// Digits as typed; an empty start leaves it as it is
+    pub start: String,
+    pub end: String,
+    /// Editing the end, otherwise the start
+    pub on_end: bool,
+}
+
+impl ThresholdEditor {
+    pub fn new(battery: &BatteryInfo) -> Self {
+        let digits = |v: Option<u8>| v.map(|v| v.to_string()).unwrap_or_default();
+        ThresholdEditor {
+            battery: battery.name.clone(),
+            has_start: battery.start_threshold.is_some(),
+            start: digits(battery.start_threshold),
+            end: digits(battery.end_threshold.or(Some(100))),
+            on_end: true,
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> EditorAction {
+        match code {
+            KeyCode::Esc => return EditorAction::Cancel,
+            KeyCode::Enter => return EditorAction::Save,
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down if self.has_start => {
+                self.on_end = !self.on_end
+            }
+            KeyCode::Left => self.step(-1),
+            KeyCode::Right => self.step(1),
+            KeyCode::Backspace => {
+                self.focused().pop();
+            }
+            KeyCode::Char(c @ '0'..='9') if self.focused().len() < 3 => self.focused().push(c),
+            _ => {}
+        }
+        EditorAction::None
+    }
+
+    fn focused(&mut self) -> &mut String {
+        if self.on_end {
+            &mut self.end
+        } else {
+            &mut self.start
+        }
+    }
+
+    fn step(&mut self, delta: i16) {
+        let field = self.focused();
+        let value = field
+            .parse::<i16>()
+            .unwrap_or(if delta > 0 { 0 } else { 100 });
+        *field = (value + delta).clamp(0, 100).to_string();
+    }
+
+    /// Start (if set) and end as percentages, start below end
+    pub fn values(&self) -> Result<(Option<u8>, u8), String> {
+        let percent = |s: &str| s.parse::<u8>().ok().filter(|v| *v <= 100);
+        let end = percent(&self.end).ok_or("End threshold must be 1–100%")?;
+        if end == 0 {
+            return Err("End threshold must be 1–100%".to_string());
+        }
+        let start = match self.start.as_str() {
+            "" => None,
+            s => Some(percent(s).ok_or("Start threshold must be 0–100%")?),
+        };
+        if start.is_some_and(|s| s >= end) {
+            return Err("Start threshold must be below the end".to_string());
+        }
+        Ok((start, end))
+    }
+}
+
+/// Percent per ←/→ step of a fan's duty cycle
+const FAN_STEP: u8 = 5;
+
+/// What one fan should be set to
+pub struct FanSetting {
+    pub name: String,
+    /// As it was read when the editor opened
+    pub pwm: Pwm,
+    pub manual: bool,
+    pub percent: u8,
+}
+
+impl FanSetting {
+    pub fn changed(&self) -> bool {
+        self.manual != self.pwm.manual() || (self.manual && self.percent != self.pwm.percent())
+    }
+}
+
+/// Automatic or a fixed duty cycle for each controllable fan. Taking a
+/// fan off its curve has to be confirmed.
+pub struct FanEditor {
+    pub fans: Vec<FanSetting>,
+    pub selected: usize,
+    /// Asking before applying a switch to manual
+    pub confirming: bool,
+}
+
+impl FanEditor {
+    pub fn new(fans: &[Fan]) -> Self {
+        FanEditor {
+            fans: fans
+                .iter()
+                .filter_map(|fan| {
+                    let pwm = fan.pwm.as_ref().filter(|p| p.writable)?;
+                    Some(FanSetting {
+                        name: fan.name.clone(),
+                        manual: pwm.manual(),
+                        percent: pwm.percent(),
+                        pwm: pwm.clone(),
+                    })
+                })
+                .collect(),
+            selected: 0,
+            confirming: false,
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> EditorAction {
+        if self.confirming {
+            self.confirming = false;
+            return match code {
+                KeyCode::Char('y') => EditorAction::Save,
+                _ => EditorAction::None,
+            };
+        }
+        let count = self.fans.len();
+        match code {
+            KeyCode::Esc => return EditorAction::Cancel,
+            KeyCode::Enter if self.takes_manual() => self.confirming = true,
+            KeyCode::Enter => return EditorAction::Save,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab if count > 0 => {
+                self.selected = (self.selected + 1) % count
+            }
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab if count > 0 => {
+                self.selected = (self.selected + count - 1) % count
+            }
+            KeyCode::Char('m') | KeyCode::Char(' ') => {
+                if let Some(fan) = self.fans.get_mut(self.selected) {
+                    fan.manual = !fan.manual;
+                }
+            }
+            KeyCode::Left => self.step(-(FAN_STEP as i16)),
+            KeyCode::Right => self.step(FAN_STEP as i16),
+            _ => {}
+        }
+        EditorAction::None
+    }
+
+    /// Stepping the speed of an automatic fan makes it manual. Never
+    /// down to a stop.
+    fn step(&mut self, delta: i16) {
+        if let Some(fan) = self.fans.get_mut(self.selected) {
+            fan.manual = true;
+            fan.percent = (fan.percent as i16 + delta).clamp(MIN_PERCENT as i16, 100) as u8;
+        }
+    }
+
+    /// Whether applying would take a fan off the chip's curve
+    fn takes_manual(&self) -> bool {
+        self.fans.iter().any(|f| f.manual && !f.pwm.manual())
+    }
+}
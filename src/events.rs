@@ -0,0 +1,43 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+pub struct Event {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub text: String,
+}
+
+/// Oldest entries are dropped past this many
+const CAPACITY: usize = 500;
+
+/// Everything the app told the user this session, newest last
+#[derive(Default)]
+pub struct EventLog {
+    entries: VecDeque<Event>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, level: Level, text: String) {
+        self.push_at(Local::now(), level, text);
+    }
+
+    /// Record something that happened earlier, like a kernel message
+    pub fn push_at(&mut self, time: DateTime<Local>, level: Level, text: String) {
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Event { time, level, text });
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Event> {
+        self.entries.iter()
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::privileged;
+use crate::sysfs;
+
+/// `pwm*_enable` value handing the fan to software
+const MANUAL: u8 = 1;
+
+/// `pwm*_enable` value for the chip's own fan curve, where nothing
+/// better is known: nct6775 and it87 use higher values for theirs
+const AUTOMATIC: u8 = 2;
+
+/// Lowest duty a fan may be held at, since 0% stops it
+pub const MIN_PERCENT: u8 = 20;
+
+/// One `fan*_input` of an hwmon chip, with the PWM channel of the same
+/// number when the chip has one
+pub struct Fan {
+    /// Chip name and label (`thinkpad fan1`)
+    pub name: String,
+    pub rpm: Option<u32>,
+    pub pwm: Option<Pwm>,
+}
+
+/// A fan's PWM channel
+#[derive(Clone)]
+pub struct Pwm {
+    enable_path: PathBuf,
+    duty_path: PathBuf,
+    /// 0 full speed, 1 manual, 2 and up automatic
+    pub enable: u8,
+    /// 0–255
+    pub duty: u8,
+    /// Root can write `pwm*_enable`; some drivers only report it
+    pub writable: bool,
+}
+
+impl Pwm {
+    pub fn manual(&self) -> bool {
+        self.enable == MANUAL
+    }
+
+    /// The same channel, read at another time
+    pub fn is(&self, other: &Pwm) -> bool {
+        self.enable_path == other.enable_path
+    }
+
+    pub fn percent(&self) -> u8 {
+        (self.duty as u32 * 100 / 255) as u8
+    }
+
+    /// `auto`, `manual` or `full`
+    pub fn mode(&self) -> &'static str {
+        match self.enable {
+            0 => "full",
+            MANUAL => "manual",
+            _ => "auto",
+        }
+    }
+}
+
+/// Every fan the hwmon chips report, by chip
+pub fn read_fans() -> Vec<Fan> {
+    let mut chips: Vec<PathBuf> = fs::read_dir(sysfs::path("/sys/class/hwmon"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    chips.sort();
+    let mut fans = Vec::new();
+    for chip in chips {
+        let Some(chip_name) = sysfs::read_string(chip.join("name")) else {
+            continue;
+        };
+        let mut inputs: Vec<u32> = fs::read_dir(&chip)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.strip_prefix("fan")?
+                    .strip_suffix("_input")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        inputs.sort();
+        for n in inputs {
+            let label = sysfs::read_string(chip.join(format!("fan{}_label", n)))
+                .unwrap_or_else(|| format!("fan{}", n));
+            fans.push(Fan {
+                name: format!("{} {}", chip_name, label),
+                rpm: sysfs::read_value(chip.join(format!("fan{}_input", n))),
+                pwm: read_pwm(&chip, n),
+            });
+        }
+    }
+    fans
+}
+
+fn read_pwm(chip: &Path, n: u32) -> Option<Pwm> {
+    let enable_path = chip.join(format!("pwm{}_enable", n));
+    let duty_path = chip.join(format!("pwm{}", n));
+    let writable = fs::metadata(&enable_path).is_ok_and(|m| m.permissions().mode() & 0o200 != 0);
+    Some(Pwm {
+        enable: sysfs::read_value(&enable_path)?,
+        duty: sysfs::read_value(&duty_path)?,
+        enable_path,
+        duty_path,
+        writable,
+    })
+}
+
+/// Take the fan off the chip's curve and run it at `percent`, never
+/// below `MIN_PERCENT`
+pub fn set_manual(pwm: &Pwm, percent: u8) -> Result<(), String> {
+    if pwm.enable != MANUAL {
+        write(&pwm.enable_path, pwm.enable, MANUAL)?;
+    }
+    let duty = (percent.clamp(MIN_PERCENT, 100) as u32 * 255 / 100) as u8;
+    write(&pwm.duty_path, pwm.duty, duty)
+}
+
+/// Give the fan back to the chip, for a fan that was manual before
+/// powertui saw it
+pub fn set_automatic(pwm: &Pwm) -> Result<(), String> {
+    write(&pwm.enable_path, pwm.enable, AUTOMATIC)
+}
+
+/// Put a fan powertui made manual back the way it was, as `original`
+/// read before: its chip's own mode, or full speed
+pub fn restore(original: &Pwm) -> Result<(), String> {
+    write(&original.enable_path, MANUAL, original.enable)
+}
+
+fn write(path: &Path, old: u8, value: u8) -> Result<(), String> {
+    privileged::write(
+        "fan",
+        Some(old.to_string()),
+        &path.to_string_lossy(),
+        &value.to_string(),
+    )
+}
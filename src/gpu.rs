@@ -0,0 +1,253 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::privileged;
+use crate::sysfs;
+
+const DRM: &str = "/sys/class/drm";
+
+/// A GPU's power state forced one way, or left to the driver
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    Low,
+    Auto,
+    High,
+}
+
+impl Level {
+    pub const ALL: [Level; 3] = [Level::Low, Level::Auto, Level::High];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Low => "low",
+            Level::Auto => "auto",
+            Level::High => "high",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Level> {
+        Level::ALL.into_iter().find(|l| l.name() == s)
+    }
+
+    /// The one after this, wrapping round
+    pub fn next(&self) -> Level {
+        match self {
+            Level::Low => Level::Auto,
+            Level::Auto => Level::High,
+            Level::High => Level::Low,
+        }
+    }
+}
+
+/// What each driver reports about its clocks
+pub enum Clocks {
+    /// amdgpu: `power_dpm_force_performance_level`, and the `pp_dpm_sclk`
+    /// shader clock states in MHz with the one in use
+    Amd {
+        level: String,
+        states: Vec<u32>,
+        current: Option<usize>,
+    },
+    /// i915: the GT clock now, the range it may use, and the range the
+    /// hardware has (`RPn` to `RP0`), all in MHz
+    Intel {
+        current: Option<u32>,
+        min: Option<u32>,
+        max: Option<u32>,
+        floor: Option<u32>,
+        ceiling: Option<u32>,
+    },
+}
+
+/// A DRM card whose clocks powertui can read
+pub struct Gpu {
+    /// `card0`
+    pub card: String,
+    path: PathBuf,
+    pub clocks: Clocks,
+}
+
+impl Gpu {
+    pub fn driver(&self) -> &'static str {
+        match self.clocks {
+            Clocks::Amd { .. } => "amdgpu",
+            Clocks::Intel { .. } => "i915",
+        }
+    }
+
+    /// Current clock in MHz
+    pub fn clock(&self) -> Option<u32> {
+        match self.clocks {
+            Clocks::Amd {
+                ref states,
+                current,
+                ..
+            } => states.get(current?).copied(),
+            Clocks::Intel { current, .. } => current,
+        }
+    }
+
+    /// The lowest and highest clock the GPU may run at now
+    pub fn range(&self) -> Option<(u32, u32)> {
+        match self.clocks {
+            Clocks::Amd { ref states, .. } => Some((*states.iter().min()?, *states.iter().max()?)),
+            Clocks::Intel { min, max, .. } => Some((min?, max?)),
+        }
+    }
+
+    /// The forced level, or None when it's something else: amdgpu's
+    /// `manual` and `profile_*` modes, or an i915 range set by hand
+    pub fn level(&self) -> Option<Level> {
+        match self.clocks {
+            Clocks::Amd { ref level, .. } => Level::parse(level),
+            Clocks::Intel {
+                min,
+                max,
+                floor,
+                ceiling,
+                ..
+            } => {
+                let (min, max, floor, ceiling) = (min?, max?, floor?, ceiling?);
+                if max == floor {
+                    Some(Level::Low)
+                } else if min == ceiling {
+                    Some(Level::High)
+                } else if min == floor && max == ceiling {
+                    Some(Level::Auto)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// The level as the driver puts it, `manual` included
+    pub fn level_name(&self) -> String {
+        match self.clocks {
+            Clocks::Amd { ref level, .. } => level.clone(),
+            Clocks::Intel { .. } => self
+                .level()
+                .map(|l| l.name().to_string())
+                .unwrap_or_else(|| "custom".to_string()),
+        }
+    }
+}
+
+/// Every amdgpu and i915 card, by card number
+pub fn read_gpus() -> Vec<Gpu> {
+    let mut cards: Vec<String> = fs::read_dir(sysfs::path(DRM))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|n| n.starts_with("card") && !n.contains('-'))
+        .collect();
+    cards.sort();
+    cards
+        .into_iter()
+        .filter_map(|card| {
+            let path = sysfs::path(DRM).join(&card);
+            // Told apart by their files, which replayed snapshots keep
+            // where the driver link isn't
+            let clocks = read_amd(&path.join("device")).or_else(|| read_intel(&path))?;
+            Some(Gpu { card, path, clocks })
+        })
+        .collect()
+}
+
+fn read_amd(device: &Path) -> Option<Clocks> {
+    let level = sysfs::read_string(device.join("power_dpm_force_performance_level"))?;
+    // `0: 500Mhz`, `1: 1200Mhz *`
+    let mut states = Vec::new();
+    let mut current = None;
+    for line in sysfs::read_string(device.join("pp_dpm_sclk"))
+        .unwrap_or_default()
+        .lines()
+    {
+        let Some((_, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(mhz) = rest
+            .split_whitespace()
+            .next()
+            .and_then(|f| f.to_lowercase().strip_suffix("mhz")?.parse().ok())
+        else {
+            continue;
+        };
+        if rest.trim_end().ends_with('*') {
+            current = Some(states.len());
+        }
+        states.push(mhz);
+    }
+    Some(Clocks::Amd {
+        level,
+        states,
+        current,
+    })
+}
+
+fn read_intel(card: &Path) -> Option<Clocks> {
+    let mhz = |name: &str| sysfs::read_value(card.join(format!("gt_{}_freq_mhz", name)));
+    Some(Clocks::Intel {
+        current: Some(mhz("cur")?),
+        min: mhz("min"),
+        max: mhz("max"),
+        floor: mhz("RPn"),
+        ceiling: mhz("RP0"),
+    })
+}
+
+/// Hold the GPU at its lowest or highest clocks, or hand it back to the
+/// driver. On i915 that's the GT range pinned to one end; each bound is
+/// written in the order that keeps min at or below max.
+pub fn set_level(gpu: &Gpu, level: Level) -> Result<(), String> {
+    match gpu.clocks {
+        Clocks::Amd { level: ref old, .. } if old == level.name() => Ok(()),
+        Clocks::Amd { level: ref old, .. } => privileged::write(
+            "GPU level",
+            Some(old.clone()),
+            &gpu.path
+                .join("device/power_dpm_force_performance_level")
+                .to_string_lossy(),
+            level.name(),
+        ),
+        Clocks::Intel {
+            min,
+            max,
+            floor,
+            ceiling,
+            ..
+        } => {
+            let (Some(floor), Some(ceiling)) = (floor, ceiling) else {
+                return Err(format!("{} doesn't report its clock range", gpu.card));
+            };
+            let write = |name: &str, old: Option<u32>, mhz: u32| {
+                if old == Some(mhz) {
+                    return Ok(());
+                }
+                privileged::write(
+                    &format!("GPU {} clock", name),
+                    old.map(|m| format!("{} MHz", m)),
+                    &gpu.path
+                        .join(format!("gt_{}_freq_mhz", name))
+                        .to_string_lossy(),
+                    &mhz.to_string(),
+                )
+            };
+            match level {
+                Level::Low => {
+                    write("min", min, floor)?;
+                    write("max", max, floor)
+                }
+                Level::Auto => {
+                    write("min", min, floor)?;
+                    write("max", max, ceiling)
+                }
+                Level::High => {
+                    write("max", max, ceiling)?;
+                    write("min", min, ceiling)
+                }
+            }
+        }
+    }
+}
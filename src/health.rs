@@ -0,0 +1,120 @@
+use std::process::Command;
+
+use crate::app::Page;
+use crate::backend;
+use crate::battery::BatteryInfo;
+use crate::doctor::{Check, Severity};
+use crate::sleep;
+
+/// Daemons that switch governors or EPP on their own and undo ours
+const CONFLICTING: [&str; 5] = [
+    "tlp",
+    "auto-cpufreq",
+    "power-profiles-daemon",
+    "tuned",
+    "system76-power",
+];
+
+/// A check and the page where the thing it's about lives
+pub struct Finding {
+    pub check: Check,
+    pub page: Page,
+}
+
+/// Quick checks worth running on every launch, cheap enough not to
+/// hold up the first frame
+pub fn check(battery: Option<&BatteryInfo>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if let Some(battery) = battery {
+        findings.push(Finding {
+            check: check_battery(battery),
+            page: Page::Overview,
+        });
+    }
+    findings.push(Finding {
+        check: check_governor(),
+        page: Page::Cpu,
+    });
+    findings.push(Finding {
+        check: check_daemons(),
+        page: Page::Overview,
+    });
+    findings.push(Finding {
+        check: check_last_suspend(),
+        page: Page::Sleep,
+    });
+    findings
+}
+
+/// Firmware and drivers sometimes report nonsense, which makes health
+/// and time estimates meaningless
+fn check_battery(battery: &BatteryInfo) -> Check {
+    if battery.reported > 100 {
+        return Check::new(
+            "Battery",
+            Severity::Warn,
+            format!("driver reports {}% charge", battery.reported),
+        );
+    }
+    match battery.health {
+        None => Check::new(
+            "Battery",
+            Severity::Warn,
+            "no design capacity, health is unknown",
+        ),
+        Some(health) if !(10..=110).contains(&health) => Check::new(
+            "Battery",
+            Severity::Warn,
+            format!("implausible health {}%", health),
+        ),
+        Some(_) => Check::new("Battery", Severity::Ok, "driver readings look sane"),
+    }
+}
+
+fn check_governor() -> Check {
+    backend::active().check()
+}
+
+fn check_daemons() -> Check {
+    let Ok(output) = Command::new("systemctl")
+        .arg("is-active")
+        .args(CONFLICTING)
+        .output()
+    else {
+        return Check::new("Daemons", Severity::Ok, "systemctl unavailable");
+    };
+    // One line per unit, in the order asked
+    let backend = backend::active().name();
+    let active: Vec<&str> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .zip(CONFLICTING)
+        // Not a conflict when profiles go through it
+        .filter(|(state, unit)| *state == "active" && *unit != backend)
+        .map(|(_, unit)| unit)
+        .collect();
+    if active.is_empty() {
+        Check::new("Daemons", Severity::Ok, "nothing else manages power")
+    } else {
+        Check::new(
+            "Daemons",
+            Severity::Warn,
+            format!("{} may override profile changes", active.join(", ")),
+        )
+    }
+}
+
+fn check_last_suspend() -> Check {
+    match sleep::read_cycles().last() {
+        Some(cycle) if cycle.abnormal() => Check::new(
+            "Suspend",
+            Severity::Warn,
+            format!(
+                "last {} suspend drained {:.1}%/h",
+                cycle.mode,
+                cycle.drain_per_hour().unwrap_or_default()
+            ),
+        ),
+        Some(_) => Check::new("Suspend", Severity::Ok, "last suspend drained normally"),
+        None => Check::new("Suspend", Severity::Ok, "no recent suspends"),
+    }
+}
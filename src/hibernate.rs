@@ -0,0 +1,135 @@
+use crate::doctor::{Check, Severity};
+use crate::sysfs;
+
+/// Everything hibernate depends on, so it can be trusted before a
+/// critical battery relies on it
+pub fn check() -> Vec<Check> {
+    vec![
+        check_kernel(),
+        check_swap(),
+        check_resume(),
+        check_lockdown(),
+    ]
+}
+
+fn check_kernel() -> Check {
+    let supported = sysfs::read_string("/sys/power/state")
+        .is_some_and(|s| s.split_whitespace().any(|s| s == "disk"));
+    if supported {
+        Check::new("Kernel", Severity::Ok, "supports suspend to disk")
+    } else {
+        Check::new("Kernel", Severity::Fail, "no \"disk\" in /sys/power/state")
+    }
+}
+
+/// A disk-backed swap entry from /proc/swaps
+struct Swap {
+    name: String,
+    is_file: bool,
+    kib: u64,
+}
+
+fn read_swaps() -> Vec<Swap> {
+    sysfs::read_string("/proc/swaps")
+        .unwrap_or_default()
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let name = fields.first()?.to_string();
+            // zram lives in RAM and can't hold a hibernation image
+            if name.starts_with("/dev/zram") {
+                return None;
+            }
+            Some(Swap {
+                name,
+                is_file: *fields.get(1)? == "file",
+                kib: fields.get(2)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+fn mem_total_kib() -> Option<u64> {
+    sysfs::read_string("/proc/meminfo")?
+        .lines()
+        .find_map(|l| l.strip_prefix("MemTotal:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn check_swap() -> Check {
+    let swaps = read_swaps();
+    let swap: u64 = swaps.iter().map(|s| s.kib).sum();
+    if swap == 0 {
+        return Check::new("Swap", Severity::Fail, "no disk-backed swap");
+    }
+    let gib = |kib: u64| kib as f64 / 1024.0 / 1024.0;
+    let Some(ram) = mem_total_kib() else {
+        return Check::new("Swap", Severity::Warn, format!("{:.1} GiB", gib(swap)));
+    };
+    // The kernel compresses the image down to image_size (2/5 of RAM by
+    // default), so less swap than RAM may work if memory isn't full
+    let image = sysfs::read_value::<u64>("/sys/power/image_size")
+        .map(|b| b / 1024)
+        .unwrap_or(ram * 2 / 5);
+    let detail = format!("{:.1} GiB swap for {:.1} GiB RAM", gib(swap), gib(ram));
+    if swap >= ram {
+        Check::new("Swap", Severity::Ok, detail)
+    } else if swap >= image {
+        Check::new(
+            "Swap",
+            Severity::Warn,
+            format!("{}; fails if memory is busy", detail),
+        )
+    } else {
+        Check::new("Swap", Severity::Fail, format!("{}; too small", detail))
+    }
+}
+
+fn check_resume() -> Check {
+    let cmdline = sysfs::read_string("/proc/cmdline").unwrap_or_default();
+    let param = |key: &str| {
+        cmdline
+            .split_whitespace()
+            .find_map(|arg| arg.strip_prefix(key))
+            .map(String::from)
+    };
+    // Set by resume= or by systemd-hibernate-resume at boot
+    let device = sysfs::read_string("/sys/power/resume").filter(|d| d != "0:0");
+    let Some(target) = param("resume=").or(device) else {
+        return Check::new("Resume", Severity::Fail, "no resume= device configured");
+    };
+    let swap_file = read_swaps().into_iter().find(|s| s.is_file);
+    match swap_file {
+        Some(file) if param("resume_offset=").is_none() => Check::new(
+            "Resume",
+            Severity::Fail,
+            format!("{} is a swap file but resume_offset= is missing", file.name),
+        ),
+        _ => Check::new("Resume", Severity::Ok, format!("resume={}", target)),
+    }
+}
+
+fn check_lockdown() -> Check {
+    // e.g. "none [integrity] confidentiality"
+    let Some(lockdown) = sysfs::read_string("/sys/kernel/security/lockdown") else {
+        return Check::new("Lockdown", Severity::Ok, "not enforced");
+    };
+    let active = lockdown
+        .split_whitespace()
+        .find_map(|m| m.strip_prefix('[')?.strip_suffix(']'))
+        .unwrap_or("none");
+    if active == "none" {
+        Check::new("Lockdown", Severity::Ok, "not enforced")
+    } else {
+        Check::new(
+            "Lockdown",
+            Severity::Fail,
+            format!("{} mode (Secure Boot) disables hibernation", active),
+        )
+    }
+}
@@ -0,0 +1,195 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDate};
+
+use crate::battery::BatteryInfo;
+use crate::config;
+
+/// How far back samples are kept and charted, in seconds
+pub const KEEP: i64 = 7 * 24 * 3600;
+
+const HEADER: &str = "timestamp,capacity,status,power";
+
+const HEALTH_HEADER: &str = "date,health";
+
+/// One line of the battery log
+pub struct Sample {
+    /// Unix time
+    pub at: i64,
+    pub capacity: u8,
+    pub status: String,
+    /// Draw or charge rate in watts
+    pub power: Option<f64>,
+}
+
+fn history_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("history.csv"))
+}
+
+/// The last week of samples, oldest first
+pub fn read_samples() -> Vec<Sample> {
+    let Some(text) = history_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    let since = Local::now().timestamp() - KEEP;
+    text.lines()
+        .filter_map(parse)
+        .filter(|s| s.at >= since)
+        .collect()
+}
+
+/// Battery health on one day. Wear shows over months, not the week
+/// `history.csv` keeps, so these are kept for good.
+pub struct HealthSample {
+    pub date: NaiveDate,
+    /// Percent of design capacity
+    pub health: u8,
+}
+
+fn health_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("health.csv"))
+}
+
+/// Every day health was recorded, oldest first
+pub fn read_health() -> Vec<HealthSample> {
+    let Some(text) = health_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    let mut samples: Vec<HealthSample> = text.lines().filter_map(parse_health).collect();
+    // The TUI and the daemon may both have logged a day
+    samples.dedup_by_key(|s| s.date);
+    samples
+}
+
+fn parse_health(line: &str) -> Option<HealthSample> {
+    let (date, health) = line.split_once(',')?;
+    Some(HealthSample {
+        date: NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?,
+        health: health.parse().ok()?,
+    })
+}
+
+/// `timestamp,capacity,status,power`; the header and damaged lines
+/// don't parse
+fn parse(line: &str) -> Option<Sample> {
+    let mut fields = line.split(',');
+    Some(Sample {
+        at: fields.next()?.parse().ok()?,
+        capacity: fields.next()?.parse().ok()?,
+        status: fields.next()?.to_string(),
+        power: fields.next().and_then(|p| p.parse().ok()),
+    })
+}
+
+/// Appends a sample to `history.csv` at most every `every`, and the
+/// battery's health to `health.csv` once a day. Samples older than a
+/// week are dropped when it starts.
+pub struct Logger {
+    every: Duration,
+    last: Option<Instant>,
+    /// The last day in `health.csv`
+    health_logged: Option<NaiveDate>,
+}
+
+impl Logger {
+    pub fn new(every: Duration) -> Self {
+        prune();
+        Logger {
+            every,
+            last: None,
+            health_logged: read_health().last().map(|s| s.date),
+        }
+    }
+
+    pub fn record(&mut self, battery: Option<&BatteryInfo>) {
+        if self.last.is_some_and(|t| t.elapsed() < self.every) {
+            return;
+        }
+        let Some(battery) = battery else {
+            return;
+        };
+        self.last = Some(Instant::now());
+        let line = format!(
+            "{},{},{},{}\n",
+            Local::now().timestamp(),
+            battery.capacity,
+            battery.status,
+            battery
+                .power
+                .map(|p| format!("{:.2}", p))
+                .unwrap_or_default()
+        );
+        let _ = append(history_path(), HEADER, &line);
+
+        let today = Local::now().date_naive();
+        if let Some(health) = battery.health.filter(|_| self.health_logged != Some(today)) {
+            self.health_logged = Some(today);
+            let line = format!("{},{}\n", today.format("%Y-%m-%d"), health);
+            let _ = append(health_path(), HEALTH_HEADER, &line);
+        }
+    }
+}
+
+fn append(path: Option<PathBuf>, header: &str, line: &str) -> Option<()> {
+    let path = path?;
+    fs::create_dir_all(path.parent()?).ok()?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .ok()?;
+    if file.metadata().ok()?.len() == 0 {
+        writeln!(file, "{}", header).ok()?;
+    }
+    file.write_all(line.as_bytes()).ok()
+}
+
+fn prune() {
+    let Some(path) = history_path() else {
+        return;
+    };
+    let Ok(text) = fs::read_to_string(&path) else {
+        return;
+    };
+    let since = Local::now().timestamp() - KEEP;
+    let kept: Vec<&str> = text
+        .lines()
+        .filter(|l| parse(l).is_some_and(|s| s.at >= since))
+        .collect();
+    if kept.len() + 1 < text.lines().count() {
+        let mut text = format!("{}\n", HEADER);
+        for line in kept {
+            text.push_str(line);
+            text.push('\n');
+        }
+        let _ = fs::write(path, text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn battery_log_lines() {
+        let sample = parse("1718000000,73,Discharging,8.25").unwrap();
+        assert_eq!((sample.at, sample.capacity), (1718000000, 73));
+        assert_eq!(sample.status, "Discharging");
+        assert_eq!(sample.power, Some(8.25));
+        assert_eq!(parse("1718000000,100,Full,").unwrap().power, None);
+        assert!(parse(HEADER).is_none());
+        assert!(parse("1718000000,7").is_none());
+    }
+
+    #[test]
+    fn health_log_lines() {
+        let sample = parse_health("2024-06-10,91").unwrap();
+        assert_eq!(sample.date, NaiveDate::from_ymd_opt(2024, 6, 10).unwrap());
+        assert_eq!(sample.health, 91);
+        assert!(parse_health(HEALTH_HEADER).is_none());
+        assert!(parse_health("2024-06-10,").is_none());
+    }
+}
@@ -0,0 +1,69 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// A power supply appearing, disappearing or changing state
+pub struct SupplyEvent {
+    /// `add`, `remove` or `change`
+    pub action: String,
+    /// Name under /sys/class/power_supply, e.g. `BAT1`
+    pub name: String,
+}
+
+/// Follows udev's power_supply events, so swapped packs and power banks
+/// show up without waiting for the next refresh
+pub struct SupplyMonitor {
+    child: Child,
+    events: Receiver<SupplyEvent>,
+}
+
+impl SupplyMonitor {
+    pub fn start() -> Option<SupplyMonitor> {
+        let mut child = Command::new("udevadm")
+            .args(["monitor", "--udev", "--subsystem-match=power_supply"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let stdout = child.stdout.take()?;
+        let (tx, events) = mpsc::channel();
+        // The reader ends with udevadm, when the monitor is dropped
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                // UDEV  [1234.567890] add      /devices/.../power_supply/BAT1 (power_supply)
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let [source, _, action, devpath, ..] = fields[..] else {
+                    continue;
+                };
+                if source != "UDEV" {
+                    continue;
+                }
+                let Some(name) = devpath.rsplit('/').next() else {
+                    continue;
+                };
+                let event = SupplyEvent {
+                    action: action.to_string(),
+                    name: name.to_string(),
+                };
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        Some(SupplyMonitor { child, events })
+    }
+
+    /// Events since the last call
+    pub fn drain(&self) -> Vec<SupplyEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+impl Drop for SupplyMonitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,82 @@
+use std::fs::File;
+
+use crate::msr;
+use crate::sysfs;
+
+const PSTATE: &str = "/sys/devices/system/cpu/intel_pstate";
+const CPUFREQ: &str = "/sys/devices/system/cpu/cpu0/cpufreq";
+
+const MSR_HWP_CAPABILITIES: u64 = 0x771;
+const MSR_HWP_REQUEST: u64 = 0x774;
+
+/// intel_pstate and Hardware P-state (Speed Shift) state of CPU 0
+pub struct HwpStatus {
+    /// `active`, `passive` or `off`
+    pub mode: String,
+    pub hwp: bool,
+    pub dynamic_boost: Option<bool>,
+    pub no_turbo: Option<bool>,
+    /// Limits in kHz as cpufreq sees them
+    pub min_freq: Option<u64>,
+    pub max_freq: Option<u64>,
+    pub epp: Option<String>,
+    /// Raw performance levels from the MSRs, when readable (root)
+    pub request: Option<HwpRequest>,
+}
+
+/// IA32_HWP_REQUEST next to the range IA32_HWP_CAPABILITIES allows
+pub struct HwpRequest {
+    pub min: u8,
+    pub max: u8,
+    /// 0 means the hardware picks freely
+    pub desired: u8,
+    pub lowest: u8,
+    pub highest: u8,
+}
+
+/// `None` unless intel_pstate drives this machine
+pub fn read_status() -> Option<HwpStatus> {
+    let pstate = sysfs::path(PSTATE);
+    let cpufreq = sysfs::path(CPUFREQ);
+    let mode = sysfs::read_string(pstate.join("status"))?;
+    let flag = |name: &str| sysfs::read_value::<u8>(pstate.join(name)).map(|v| v == 1);
+    // hwp_dynamic_boost only exists while HWP is on
+    let dynamic_boost = flag("hwp_dynamic_boost");
+    let hwp = dynamic_boost.is_some() || cpu_has_flag("hwp");
+    Some(HwpStatus {
+        mode,
+        hwp,
+        dynamic_boost,
+        no_turbo: flag("no_turbo"),
+        min_freq: sysfs::read_value(cpufreq.join("scaling_min_freq")),
+        max_freq: sysfs::read_value(cpufreq.join("scaling_max_freq")),
+        epp: sysfs::read_string(cpufreq.join("energy_performance_preference")),
+        request: if hwp && !sysfs::replaying() {
+            read_request()
+        } else {
+            None
+        },
+    })
+}
+
+fn cpu_has_flag(flag: &str) -> bool {
+    sysfs::read_string(sysfs::path("/proc/cpuinfo")).is_some_and(|info| {
+        info.lines()
+            .find(|l| l.starts_with("flags"))
+            .is_some_and(|l| l.split_whitespace().any(|f| f == flag))
+    })
+}
+
+fn read_request() -> Option<HwpRequest> {
+    let device = File::open(msr::DEVICE).ok()?;
+    let request = msr::read_at(&device, MSR_HWP_REQUEST).ok()?;
+    let caps = msr::read_at(&device, MSR_HWP_CAPABILITIES).ok()?;
+    let byte = |value: u64, n: u32| (value >> (n * 8)) as u8;
+    Some(HwpRequest {
+        min: byte(request, 0),
+        max: byte(request, 1),
+        desired: byte(request, 2),
+        highest: byte(caps, 0),
+        lowest: byte(caps, 3),
+    })
+}
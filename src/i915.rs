@@ -0,0 +1,184 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::privileged;
+use crate::sysfs;
+
+const PARAMETERS: &str = "/sys/module/i915/parameters";
+const DRM: &str = "/sys/class/drm";
+const DEBUGFS: &str = "/sys/kernel/debug/dri";
+
+/// `i915_edp_psr_debug` values: the driver's own choice, or PSR off.
+/// The force modes are left alone, panels that don't advertise them
+/// can hang.
+const PSR_DEBUG_DEFAULT: &str = "0";
+const PSR_DEBUG_DISABLE: &str = "1";
+
+/// Display and firmware power features of an Intel GPU. debugfs only
+/// opens up to root, so without it the module parameters say what the
+/// driver was asked to do rather than what it did.
+pub struct GraphicsStatus {
+    /// DRM minor, which names the card's debugfs directory
+    minor: u32,
+    /// Whether an eDP panel is connected; PSR and FBC only matter there
+    pub panel: bool,
+    /// `enable_psr`: -1 per-platform default, 0 off, 1+ allowed up to
+    /// that PSR version
+    pub psr_param: Option<i32>,
+    /// `PSR1 enabled`, `disabled` and so on, from debugfs
+    pub psr: Option<String>,
+    /// Whether the panel supports PSR at all, from debugfs
+    pub psr_sink: Option<bool>,
+    pub fbc_param: Option<i32>,
+    /// Whether FBC is on, and why not when it isn't
+    pub fbc: Option<(bool, Option<String>)>,
+    /// `enable_guc` bits: 1 GuC submission, 2 HuC loading; -1 default
+    pub guc_param: Option<i32>,
+    /// Firmware states like `RUNNING`, from debugfs
+    pub guc: Option<String>,
+    pub huc: Option<String>,
+}
+
+impl GraphicsStatus {
+    /// PSR, FBC and GuC/HuC in a few words each
+    pub fn summary(&self) -> Vec<(&'static str, String)> {
+        let param = |value: Option<i32>| match value {
+            Some(-1) => "default".to_string(),
+            Some(0) => "off".to_string(),
+            Some(_) => "on".to_string(),
+            None => "?".to_string(),
+        };
+        let psr = match (&self.psr, self.psr_sink) {
+            (_, Some(false)) => "panel lacks it".to_string(),
+            (Some(mode), _) => mode.clone(),
+            (None, _) => format!("{} (param)", param(self.psr_param)),
+        };
+        let fbc = match self.fbc {
+            Some((true, _)) => "enabled".to_string(),
+            Some((false, Some(ref reason))) => format!("off: {}", reason),
+            Some((false, None)) => "disabled".to_string(),
+            None => format!("{} (param)", param(self.fbc_param)),
+        };
+        let firmware = |state: &Option<String>, bit: i32| match (state, self.guc_param) {
+            (Some(state), _) => state.to_lowercase(),
+            (None, Some(-1)) => "default (param)".to_string(),
+            (None, Some(v)) if v & bit != 0 => "on (param)".to_string(),
+            (None, Some(_)) => "off (param)".to_string(),
+            (None, None) => "?".to_string(),
+        };
+        vec![
+            ("PSR", psr),
+            ("FBC", fbc),
+            ("GuC", firmware(&self.guc, 1)),
+            ("HuC", firmware(&self.huc, 2)),
+        ]
+    }
+
+    /// PSR can be turned off at runtime where the driver would use it;
+    /// with `enable_psr=0` there's nothing to toggle
+    pub fn psr_toggleable(&self) -> bool {
+        self.panel && self.psr_param != Some(0) && self.psr_sink != Some(false)
+    }
+
+    fn debugfs(&self, name: &str) -> PathBuf {
+        PathBuf::from(DEBUGFS)
+            .join(self.minor.to_string())
+            .join(name)
+    }
+}
+
+/// `None` unless an i915 card is present
+pub fn read_status() -> Option<GraphicsStatus> {
+    let card = find_card()?;
+    let minor: u32 = card.strip_prefix("card")?.parse().ok()?;
+    let panel = fs::read_dir(sysfs::path(DRM))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .any(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.starts_with(&format!("{}-eDP", card))
+                && sysfs::read_string(e.path().join("status")).as_deref() == Some("connected")
+        });
+    let param = |name: &str| sysfs::read_value::<i32>(sysfs::path(PARAMETERS).join(name));
+
+    let mut status = GraphicsStatus {
+        minor,
+        panel,
+        psr_param: param("enable_psr"),
+        psr: None,
+        psr_sink: None,
+        fbc_param: param("enable_fbc"),
+        fbc: None,
+        guc_param: param("enable_guc"),
+        guc: None,
+        huc: None,
+    };
+    // Replayed snapshots carry no debugfs
+    if sysfs::replaying() {
+        return Some(status);
+    }
+    if let Ok(psr) = fs::read_to_string(status.debugfs("i915_edp_psr_status")) {
+        status.psr_sink = field(&psr, "Sink support").map(|s| {
+            // `yes [0x03]`, or `PSR = yes [0x03], Panel Replay = no`
+            let s = s.strip_prefix("PSR = ").unwrap_or(&s);
+            s.starts_with("yes")
+        });
+        status.psr = field(&psr, "PSR mode");
+    }
+    if let Ok(fbc) = fs::read_to_string(status.debugfs("i915_fbc_status")) {
+        let first = fbc.lines().next().unwrap_or_default();
+        status.fbc = Some(match first.strip_prefix("FBC disabled:") {
+            Some(reason) => (false, Some(reason.trim().to_string())),
+            None => (first.starts_with("FBC enabled"), None),
+        });
+    }
+    status.guc = fs::read_to_string(status.debugfs("gt/uc/guc_info"))
+        .ok()
+        .and_then(|s| field(&s, "status"));
+    status.huc = fs::read_to_string(status.debugfs("gt/uc/huc_info"))
+        .ok()
+        .and_then(|s| field(&s, "status"));
+    Some(status)
+}
+
+/// The first DRM card driven by i915, e.g. `card1`
+fn find_card() -> Option<String> {
+    let mut cards: Vec<String> = fs::read_dir(sysfs::path(DRM))
+        .ok()?
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|n| n.starts_with("card") && !n.contains('-'))
+        .collect();
+    cards.sort();
+    cards.into_iter().find(|card| {
+        let driver = sysfs::path(DRM).join(card).join("device/driver");
+        fs::read_link(driver).is_ok_and(|d| d.ends_with("i915"))
+    })
+}
+
+/// The value after `name:` on the first line that has it
+fn field(text: &str, name: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once(':')?;
+        (key.trim() == name).then(|| value.trim().to_string())
+    })
+}
+
+/// Let the driver use PSR as it normally would, or keep it off. Takes
+/// effect on the next frame; nothing survives a reboot.
+pub fn set_psr(status: &GraphicsStatus, enabled: bool) -> Result<(), String> {
+    if !status.psr_toggleable() {
+        return Err("PSR isn't available to toggle on this panel".to_string());
+    }
+    privileged::write(
+        "PSR",
+        status.psr.clone(),
+        &status.debugfs("i915_edp_psr_debug").to_string_lossy(),
+        if enabled {
+            PSR_DEBUG_DEFAULT
+        } else {
+            PSR_DEBUG_DISABLE
+        },
+    )
+}
@@ -0,0 +1,177 @@
+use std::process::{Child, Command, Stdio};
+
+use crate::dbus::{self, Bus};
+use crate::desktop::{self, Desktop};
+use crate::sway::Sway;
+
+/// Something currently preventing the screen from blanking or the
+/// machine from suspending
+pub struct Inhibitor {
+    pub who: String,
+    pub why: String,
+    pub source: &'static str,
+}
+
+// GNOME session inhibitor flags (see gnome-session's Inhibit docs)
+const GNOME_INHIBIT_SUSPEND: u32 = 4;
+const GNOME_INHIBIT_IDLE: u32 = 8;
+
+/// Collect idle/sleep inhibitors from every place we know how to ask.
+///
+/// Wayland clients using idle-inhibit end up in the compositor, which on
+/// GNOME and KDE forwards them to the session manager, so the session
+/// lists cover those too. Sway keeps them to itself, so ask its tree.
+pub fn read_inhibitors(sway: Option<&Sway>) -> Vec<Inhibitor> {
+    let mut inhibitors = logind_inhibitors();
+    inhibitors.extend(gnome_inhibitors());
+    inhibitors.extend(kde_inhibitors());
+    if let Some(sway) = sway {
+        inhibitors.extend(
+            sway.idle_inhibiting_views()
+                .into_iter()
+                .map(|who| Inhibitor {
+                    who,
+                    why: String::new(),
+                    source: sway.name(),
+                }),
+        );
+    }
+    inhibitors
+}
+
+fn logind_inhibitors() -> Vec<Inhibitor> {
+    let Some(tokens) = dbus::call(
+        Bus::System,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+        "ListInhibitors",
+        &[],
+    ) else {
+        return Vec::new();
+    };
+
+    // a(ssssuu) N what who why mode uid pid ...
+    tokens
+        .get(2..)
+        .unwrap_or_default()
+        .chunks_exact(6)
+        .filter(|entry| {
+            let what = &entry[0];
+            let mode = &entry[3];
+            mode == "block" && (what.contains("idle") || what.contains("sleep"))
+        })
+        .map(|entry| Inhibitor {
+            who: entry[1].clone(),
+            why: entry[2].clone(),
+            source: "logind",
+        })
+        .collect()
+}
+
+fn gnome_inhibitors() -> Vec<Inhibitor> {
+    let Some(tokens) = dbus::call(
+        Bus::Session,
+        "org.gnome.SessionManager",
+        "/org/gnome/SessionManager",
+        "org.gnome.SessionManager",
+        "GetInhibitors",
+        &[],
+    ) else {
+        return Vec::new();
+    };
+
+    // ao N path...
+    tokens
+        .get(2..)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| {
+            let query = |method| {
+                dbus::call(
+                    Bus::Session,
+                    "org.gnome.SessionManager",
+                    path,
+                    "org.gnome.SessionManager.Inhibitor",
+                    method,
+                    &[],
+                )
+                .and_then(|t| t.get(1).cloned())
+            };
+            let flags: u32 = query("GetFlags")?.parse().ok()?;
+            if flags & (GNOME_INHIBIT_IDLE | GNOME_INHIBIT_SUSPEND) == 0 {
+                return None;
+            }
+            Some(Inhibitor {
+                who: query("GetAppId").unwrap_or_else(|| "unknown".to_string()),
+                why: query("GetReason").unwrap_or_default(),
+                source: "gnome-session",
+            })
+        })
+        .collect()
+}
+
+fn kde_inhibitors() -> Vec<Inhibitor> {
+    let Some(tokens) = dbus::call(
+        Bus::Session,
+        "org.freedesktop.PowerManagement",
+        "/org/freedesktop/PowerManagement/Inhibit",
+        "org.freedesktop.PowerManagement.Inhibit",
+        "ListInhibitions",
+        &[],
+    ) else {
+        return Vec::new();
+    };
+
+    // a(ss) N who why ...
+    tokens
+        .get(2..)
+        .unwrap_or_default()
+        .chunks_exact(2)
+        .map(|entry| Inhibitor {
+            who: entry[0].clone(),
+            why: entry[1].clone(),
+            source: "powerdevil",
+        })
+        .collect()
+}
+
+/// An idle and sleep inhibitor held by powertui until dropped
+pub struct IdleBlock {
+    child: Child,
+}
+
+impl IdleBlock {
+    /// GNOME ignores logind idle inhibitors, so ask its session manager
+    /// there; everywhere else logind is the common ground
+    pub fn hold(desktop: Option<Desktop>, why: &str) -> Result<IdleBlock, String> {
+        let mut cmd = if desktop == Some(Desktop::Gnome)
+            && desktop::command_exists("gnome-session-inhibit")
+        {
+            let mut cmd = Command::new("gnome-session-inhibit");
+            cmd.args(["--inhibit", "idle:suspend", "--reason", why]);
+            cmd
+        } else {
+            let mut cmd = Command::new("systemd-inhibit");
+            cmd.args(["--what=idle:sleep", "--who=powertui"])
+                .arg(format!("--why={}", why));
+            cmd
+        };
+        // `cat` runs until we close its stdin, and takes the lock with it
+        let child = cmd
+            .arg("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Could not inhibit idle: {}", e))?;
+        Ok(IdleBlock { child })
+    }
+}
+
+impl Drop for IdleBlock {
+    fn drop(&mut self) {
+        drop(self.child.stdin.take());
+        let _ = self.child.wait();
+    }
+}
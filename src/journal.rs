@@ -0,0 +1,106 @@
+use std::process::Command;
+
+use chrono::{DateTime, Local, TimeZone};
+use serde_json::Value;
+
+use crate::events::Level;
+
+/// Kernel messages worth surfacing, as a journalctl `--grep` pattern.
+/// All lowercase, which journalctl takes as case-insensitive.
+const PATTERN: &str =
+    "thermal|throttl|temperature|acpi|battery|power_supply|ac adapter|charger|pm: |suspend|resume|hibernat";
+
+/// Entries shown from before powertui started, so a recent throttle
+/// is still there to find
+const BACKLOG: &str = "50";
+
+/// This boot's power-related kernel messages as journalctl prints them,
+/// the last `lines` of them
+pub fn recent(lines: usize) -> Option<String> {
+    let output = Command::new("journalctl")
+        .args(["-k", "-b", "-o", "short-iso", "--no-pager", "-g", PATTERN])
+        .args(["-n", &lines.to_string()])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub struct KernelEvent {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub text: String,
+}
+
+/// Follows the kernel journal across refreshes by cursor
+#[derive(Default)]
+pub struct JournalTail {
+    cursor: Option<String>,
+}
+
+impl JournalTail {
+    /// Power-related kernel messages logged since the last poll, oldest
+    /// first. The first poll returns the tail of this boot's.
+    pub fn poll(&mut self) -> Vec<KernelEvent> {
+        let mut cmd = Command::new("journalctl");
+        cmd.args(["-k", "-o", "json", "--no-pager", "-g", PATTERN]);
+        match self.cursor {
+            Some(ref cursor) => cmd.arg(format!("--after-cursor={}", cursor)),
+            None => cmd.args(["-b", "-n", BACKLOG]),
+        };
+        let Ok(output) = cmd.output() else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let Ok(entry) = serde_json::from_str::<Value>(line) else {
+                continue;
+            };
+            if let Some(cursor) = entry["__CURSOR"].as_str() {
+                self.cursor = Some(cursor.to_string());
+            }
+            let Some(message) = entry["MESSAGE"].as_str() else {
+                continue;
+            };
+            let Some(time) = entry["__REALTIME_TIMESTAMP"]
+                .as_str()
+                .and_then(|t| t.parse::<i64>().ok())
+                .and_then(|us| Local.timestamp_micros(us).single())
+            else {
+                continue;
+            };
+            // syslog priorities: 0-3 are errors, 4 warnings
+            let level = match entry["PRIORITY"]
+                .as_str()
+                .and_then(|p| p.parse::<u8>().ok())
+            {
+                Some(0..=3) => Level::Error,
+                Some(4) => Level::Warning,
+                _ => Level::Info,
+            };
+            events.push(KernelEvent {
+                time,
+                level,
+                text: format!("kernel {}: {}", category(message), message),
+            });
+        }
+        events
+    }
+}
+
+fn category(message: &str) -> &'static str {
+    let message = message.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| message.contains(w));
+    if has(&["thermal", "throttl", "temperature"]) {
+        "thermal"
+    } else if has(&["pm: ", "suspend", "resume", "hibernat"]) {
+        "suspend"
+    } else if has(&["battery", "power_supply", "ac adapter", "charger"]) {
+        "battery"
+    } else {
+        "acpi"
+    }
+}
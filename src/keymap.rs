@@ -0,0 +1,418 @@
+use std::collections::BTreeMap;
+
+use crossterm::event::KeyCode;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Context {
+    General,
+    Profiles,
+    Battery,
+    Display,
+    Sleep,
+    Simulation,
+}
+
+impl Context {
+    pub const ALL: [Context; 6] = [
+        Context::General,
+        Context::Profiles,
+        Context::Battery,
+        Context::Display,
+        Context::Sleep,
+        Context::Simulation,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Context::General => "General",
+            Context::Profiles => "Profiles",
+            Context::Battery => "Battery",
+            Context::Display => "Display",
+            Context::Sleep => "Sleep",
+            Context::Simulation => "Simulation",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    Quit,
+    Help,
+    Tour,
+    CommandMode,
+    NextPage,
+    PrevPage,
+    Refresh,
+    ShowLog,
+    DismissErrors,
+    CopyStatus,
+    CopyRow,
+    Down,
+    Up,
+    Select,
+    NewProfile,
+    EditProfile,
+    DeleteProfile,
+    Boost,
+    ToggleTurbo,
+    CycleGpuLevel,
+    CycleChargeBehaviour,
+    EditThresholds,
+    Fans,
+    Quiet,
+    Presentation,
+    Travel,
+    BrightnessUp,
+    BrightnessDown,
+    CycleSuspend,
+    BlankOutputs,
+    TogglePsr,
+    CycleSleepMode,
+    SimBatteryUp,
+    SimBatteryDown,
+    SimCycleStatus,
+    SimTempUp,
+    SimTempDown,
+}
+
+impl Action {
+    pub const ALL: [Action; 37] = [
+        Action::Quit,
+        Action::Help,
+        Action::Tour,
+        Action::CommandMode,
+        Action::NextPage,
+        Action::PrevPage,
+        Action::Refresh,
+        Action::ShowLog,
+        Action::DismissErrors,
+        Action::CopyStatus,
+        Action::CopyRow,
+        Action::Down,
+        Action::Up,
+        Action::Select,
+        Action::NewProfile,
+        Action::EditProfile,
+        Action::DeleteProfile,
+        Action::Boost,
+        Action::ToggleTurbo,
+        Action::CycleGpuLevel,
+        Action::CycleChargeBehaviour,
+        Action::EditThresholds,
+        Action::Fans,
+        Action::Quiet,
+        Action::Presentation,
+        Action::Travel,
+        Action::BrightnessUp,
+        Action::BrightnessDown,
+        Action::CycleSuspend,
+        Action::BlankOutputs,
+        Action::TogglePsr,
+        Action::CycleSleepMode,
+        Action::SimBatteryUp,
+        Action::SimBatteryDown,
+        Action::SimCycleStatus,
+        Action::SimTempUp,
+        Action::SimTempDown,
+    ];
+
+    pub fn context(&self) -> Context {
+        match self {
+            Action::Quit
+            | Action::Help
+            | Action::Tour
+            | Action::CommandMode
+            | Action::NextPage
+            | Action::PrevPage
+            | Action::Refresh
+            | Action::ShowLog
+            | Action::DismissErrors
+            | Action::CopyStatus => Context::General,
+            Action::Down
+            | Action::Up
+            | Action::Select
+            | Action::CopyRow
+            | Action::NewProfile
+            | Action::EditProfile
+            | Action::DeleteProfile
+            | Action::Boost
+            | Action::ToggleTurbo
+            | Action::CycleGpuLevel
+            | Action::Fans
+            | Action::Quiet => Context::Profiles,
+            Action::CycleChargeBehaviour | Action::EditThresholds => Context::Battery,
+            Action::Presentation
+            | Action::Travel
+            | Action::BrightnessUp
+            | Action::BrightnessDown
+            | Action::CycleSuspend
+            | Action::BlankOutputs
+            | Action::TogglePsr => Context::Display,
+            Action::CycleSleepMode => Context::Sleep,
+            Action::SimBatteryUp
+            | Action::SimBatteryDown
+            | Action::SimCycleStatus
+            | Action::SimTempUp
+            | Action::SimTempDown => Context::Simulation,
+        }
+    }
+
+    /// Name in the `[keys]` config table
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "help",
+            Action::Tour => "tour",
+            Action::CommandMode => "command",
+            Action::NextPage => "next_page",
+            Action::PrevPage => "prev_page",
+            Action::Refresh => "refresh",
+            Action::ShowLog => "log",
+            Action::DismissErrors => "dismiss",
+            Action::CopyStatus => "copy_status",
+            Action::CopyRow => "copy_row",
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::Select => "select",
+            Action::NewProfile => "new_profile",
+            Action::EditProfile => "edit_profile",
+            Action::DeleteProfile => "delete_profile",
+            Action::Boost => "boost",
+            Action::ToggleTurbo => "turbo",
+            Action::CycleGpuLevel => "gpu_level",
+            Action::CycleChargeBehaviour => "charge_behaviour",
+            Action::EditThresholds => "charge_thresholds",
+            Action::Fans => "fans",
+            Action::Quiet => "quiet",
+            Action::Presentation => "presentation",
+            Action::Travel => "travel",
+            Action::BrightnessUp => "brightness_up",
+            Action::BrightnessDown => "brightness_down",
+            Action::CycleSuspend => "suspend_timeout",
+            Action::BlankOutputs => "blank",
+            Action::TogglePsr => "psr",
+            Action::CycleSleepMode => "sleep_mode",
+            Action::SimBatteryUp => "sim_battery_up",
+            Action::SimBatteryDown => "sim_battery_down",
+            Action::SimCycleStatus => "sim_status",
+            Action::SimTempUp => "sim_temp_up",
+            Action::SimTempDown => "sim_temp_down",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Help => "Show this help",
+            Action::Tour => "Guided tour of the pages and controls",
+            Action::CommandMode => "Command line (:profile, :boost, :set, :export)",
+            Action::NextPage => "Next page",
+            Action::PrevPage => "Previous page",
+            Action::Refresh => "Refresh",
+            Action::ShowLog => "Event log",
+            Action::DismissErrors => "Dismiss errors and startup warnings",
+            Action::CopyStatus => "Copy status summary to clipboard",
+            Action::CopyRow => "Copy selected row to clipboard",
+            Action::Down => "Move down",
+            Action::Up => "Move up",
+            Action::Select => "Preview and apply selected profile",
+            Action::NewProfile => "New custom profile",
+            Action::EditProfile => "Edit selected profile",
+            Action::DeleteProfile => "Delete selected custom profile",
+            Action::Boost => "Boost to Performance for 15 minutes (again to cancel)",
+            Action::ToggleTurbo => "CPU turbo on/off",
+            Action::CycleGpuLevel => "GPU power level (low/auto/high)",
+            Action::CycleChargeBehaviour => "Charge behaviour (auto/inhibit/force discharge)",
+            Action::EditThresholds => "Edit charge thresholds",
+            Action::Fans => "Fan control (automatic or fixed speed)",
+            Action::Quiet => "Quietest settings that keep up with the current load (again to undo)",
+            Action::Presentation => "Presentation mode: no idle, fixed brightness and profile",
+            Action::Travel => "Travel mode: Power Saver, radios off, dim, short idle",
+            Action::BrightnessUp => "Brightness up",
+            Action::BrightnessDown => "Brightness down",
+            Action::CycleSuspend => "Cycle suspend-on-battery timeout",
+            Action::BlankOutputs => "Turn displays off (any key wakes)",
+            Action::TogglePsr => "Panel Self Refresh on/off (Intel)",
+            Action::CycleSleepMode => "Switch suspend mode (s2idle/deep)",
+            Action::SimBatteryUp => "Simulated battery +5%",
+            Action::SimBatteryDown => "Simulated battery −5%",
+            Action::SimCycleStatus => {
+                "Simulated charging state (discharging/charging/full/not charging)"
+            }
+            Action::SimTempUp => "Simulated temperature +5°C",
+            Action::SimTempDown => "Simulated temperature −5°C",
+        }
+    }
+}
+
+/// Entries of the one-line help at the bottom of the screen. Actions
+/// sharing an entry have their keys joined with `/`.
+pub const HELP_LINE: &[(&[Action], &str)] = &[
+    (&[Action::Down, Action::Up], "navigate"),
+    (&[Action::Select], "select"),
+    (&[Action::EditProfile], "edit"),
+    (
+        &[Action::BrightnessUp, Action::BrightnessDown],
+        "brightness",
+    ),
+    (&[Action::NextPage], "page"),
+    (&[Action::ShowLog], "log"),
+    (&[Action::Help], "help"),
+    (&[Action::Quit], "quit"),
+];
+
+pub struct Keymap {
+    bindings: Vec<(KeyCode, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode::*;
+        Keymap {
+            bindings: vec![
+                (Char('q'), Action::Quit),
+                (Esc, Action::Quit),
+                (Char('?'), Action::Help),
+                (Char('T'), Action::Tour),
+                (Char(':'), Action::CommandMode),
+                (Tab, Action::NextPage),
+                (BackTab, Action::PrevPage),
+                (Char('r'), Action::Refresh),
+                (Char('e'), Action::ShowLog),
+                (Char('x'), Action::DismissErrors),
+                (Char('y'), Action::CopyStatus),
+                (Char('Y'), Action::CopyRow),
+                (Char('j'), Action::Down),
+                (Down, Action::Down),
+                (Char('k'), Action::Up),
+                (Up, Action::Up),
+                (Enter, Action::Select),
+                (Char(' '), Action::Select),
+                (Char('n'), Action::NewProfile),
+                (Char('E'), Action::EditProfile),
+                (Char('D'), Action::DeleteProfile),
+                (Char('B'), Action::Boost),
+                (Char('t'), Action::ToggleTurbo),
+                (Char('g'), Action::CycleGpuLevel),
+                (Char('c'), Action::CycleChargeBehaviour),
+                (Char('C'), Action::EditThresholds),
+                (Char('F'), Action::Fans),
+                (Char('Q'), Action::Quiet),
+                (Char('p'), Action::Presentation),
+                (Char('f'), Action::Travel),
+                (Char('+'), Action::BrightnessUp),
+                (Char('='), Action::BrightnessUp),
+                (Char('-'), Action::BrightnessDown),
+                (Char('s'), Action::CycleSuspend),
+                (Char('b'), Action::BlankOutputs),
+                (Char('P'), Action::TogglePsr),
+                (Char('m'), Action::CycleSleepMode),
+                (Char('>'), Action::SimBatteryUp),
+                (Char('<'), Action::SimBatteryDown),
+                (Char('~'), Action::SimCycleStatus),
+                (Char(']'), Action::SimTempUp),
+                (Char('['), Action::SimTempDown),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    /// The defaults with actions rebound from the `[keys]` table. An
+    /// action listed there loses its default keys, and a key taken by
+    /// it is dropped from whatever action had it before.
+    pub fn with_overrides(overrides: &BTreeMap<String, Vec<String>>) -> Result<Keymap, String> {
+        let mut keymap = Keymap::default();
+        for (name, keys) in overrides {
+            let action = Action::ALL
+                .into_iter()
+                .find(|a| a.name() == name)
+                .ok_or_else(|| format!("unknown action in [keys]: {}", name))?;
+            let codes = keys
+                .iter()
+                .map(|k| parse_key(k).ok_or_else(|| format!("unknown key for {}: {}", name, k)))
+                .collect::<Result<Vec<_>, _>>()?;
+            keymap
+                .bindings
+                .retain(|(code, a)| *a != action && !codes.contains(code));
+            keymap
+                .bindings
+                .extend(codes.into_iter().map(|c| (c, action)));
+        }
+        Ok(keymap)
+    }
+
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(key, _)| *key == code)
+            .map(|(_, action)| *action)
+    }
+
+    pub fn keys(&self, action: Action) -> Vec<KeyCode> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    /// Primary key of each action in an entry, e.g. `j/k`
+    fn entry_keys(&self, actions: &[Action]) -> Option<String> {
+        let keys: Vec<String> = actions
+            .iter()
+            .filter_map(|a| self.keys(*a).first().map(|k| key_name(*k)))
+            .collect();
+        (!keys.is_empty()).then(|| keys.join("/"))
+    }
+
+    pub fn help_line(&self, available: impl Fn(Action) -> bool) -> String {
+        HELP_LINE
+            .iter()
+            .filter(|(actions, _)| actions.iter().any(|a| available(*a)))
+            .filter_map(|(actions, label)| Some(format!("{} {}", self.entry_keys(actions)?, label)))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+/// A key as `key_name` writes it: a character, `Space`, `Enter`, `S-Tab`,
+/// `F5` and so on
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match name.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "tab" => KeyCode::Tab,
+        "s-tab" | "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        other => KeyCode::F(other.strip_prefix('f')?.parse().ok()?),
+    };
+    Some(code)
+}
+
+pub fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "S-Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
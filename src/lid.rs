@@ -0,0 +1,70 @@
+use std::fs;
+
+use crate::dbus::{self, Bus};
+use crate::sway;
+use crate::sysfs;
+
+/// A display connected to a GPU connector
+pub struct Display {
+    /// Connector name, e.g. `eDP-1` or `HDMI-A-1`
+    pub name: String,
+    pub internal: bool,
+}
+
+/// Whether the lid is closed, or None on machines without one.
+///
+/// ACPI exposes the switch under /proc; elsewhere (Apple silicon, some
+/// ARM laptops) logind knows it as long as there is a lid switch device.
+pub fn read_lid_closed() -> Option<bool> {
+    if let Ok(entries) = fs::read_dir(sysfs::path("/proc/acpi/button/lid")) {
+        for entry in entries.flatten() {
+            // "state:      open"
+            if let Some(state) = sysfs::read_string(entry.path().join("state")) {
+                return Some(state.ends_with("closed"));
+            }
+        }
+    }
+    if !has_lid_switch() || sysfs::replaying() {
+        return None;
+    }
+    let tokens = dbus::get_properties(
+        Bus::System,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+        &["LidClosed"],
+    )?;
+    Some(tokens.get(1)? == "true")
+}
+
+fn has_lid_switch() -> bool {
+    fs::read_dir(sysfs::path("/sys/class/input")).is_ok_and(|entries| {
+        entries.flatten().any(|entry| {
+            sysfs::read_string(entry.path().join("name")).is_some_and(|n| n.contains("Lid Switch"))
+        })
+    })
+}
+
+/// Connected displays from DRM, whatever the compositor is doing with them
+pub fn connected_displays() -> Vec<Display> {
+    let Ok(entries) = fs::read_dir(sysfs::path("/sys/class/drm")) else {
+        return Vec::new();
+    };
+    let mut displays: Vec<Display> = entries
+        .flatten()
+        .filter_map(|entry| {
+            // card0-HDMI-A-1
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let (_, name) = file_name.split_once('-')?;
+            if sysfs::read_string(entry.path().join("status"))? != "connected" {
+                return None;
+            }
+            Some(Display {
+                name: name.to_string(),
+                internal: sway::is_internal_panel(name),
+            })
+        })
+        .collect();
+    displays.sort_by(|a, b| a.name.cmp(&b.name));
+    displays
+}
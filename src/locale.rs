@@ -0,0 +1,109 @@
+use std::env;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+
+use crate::config::Clock;
+
+/// How dates and times are written, shared by the TUI, CLI and reports
+#[derive(Clone, Copy)]
+struct TimeStyle {
+    twelve_hour: bool,
+    date: DateOrder,
+}
+
+#[derive(Clone, Copy)]
+enum DateOrder {
+    /// ISO 8601, also what the C locale gets
+    Ymd,
+    /// Day first, with the locale's separator
+    Dmy(char),
+    Mdy,
+}
+
+static STYLE: OnceLock<TimeStyle> = OnceLock::new();
+
+/// Settle the style for this run from the locale and the `clock` setting
+pub fn init(clock: Clock) {
+    let _ = STYLE.set(TimeStyle::detect(clock));
+}
+
+fn style() -> TimeStyle {
+    *STYLE.get_or_init(|| TimeStyle::detect(Clock::Auto))
+}
+
+impl TimeStyle {
+    fn detect(clock: Clock) -> TimeStyle {
+        // LC_ALL overrides LC_TIME, which overrides LANG
+        let locale = ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .find_map(|var| env::var(var).ok().filter(|v| !v.is_empty()))
+            .unwrap_or_default();
+        // en_US.UTF-8@euro
+        let name = locale.split(['.', '@']).next().unwrap_or_default();
+        let (lang, territory) = name.split_once('_').unwrap_or((name, ""));
+
+        let twelve_hour = match clock {
+            Clock::TwelveHour => true,
+            Clock::TwentyFourHour => false,
+            Clock::Auto => matches!(
+                (lang, territory),
+                ("en", "US" | "CA" | "AU" | "NZ" | "IN" | "PH") | (_, "PK" | "EG" | "SA" | "KR")
+            ),
+        };
+        let date = match territory {
+            "" => DateOrder::Ymd,
+            "US" => DateOrder::Mdy,
+            "CN" | "JP" | "KR" | "TW" | "HU" | "LT" | "SE" | "CA" => DateOrder::Ymd,
+            "DE" | "AT" | "CH" | "RU" | "PL" | "FI" | "NO" | "DK" | "CZ" | "SK" | "TR" | "UA"
+            | "BY" | "RO" | "BG" | "HR" | "RS" | "SI" | "EE" | "LV" | "IS" | "KZ" => {
+                DateOrder::Dmy('.')
+            }
+            _ => DateOrder::Dmy('/'),
+        };
+        TimeStyle { twelve_hour, date }
+    }
+}
+
+/// `14:05` or `2:05 PM`
+pub fn clock(time: &DateTime<Local>) -> String {
+    if style().twelve_hour {
+        time.format("%-I:%M %p").to_string()
+    } else {
+        time.format("%H:%M").to_string()
+    }
+}
+
+/// `14:05:09` or `2:05:09 PM`
+pub fn clock_seconds(time: &DateTime<Local>) -> String {
+    if style().twelve_hour {
+        time.format("%-I:%M:%S %p").to_string()
+    } else {
+        time.format("%H:%M:%S").to_string()
+    }
+}
+
+/// `2024-03-09`, `09.03.2024`, `09/03/2024` or `03/09/2024`
+pub fn date(time: &DateTime<Local>) -> String {
+    let format = match style().date {
+        DateOrder::Ymd => "%Y-%m-%d".to_string(),
+        DateOrder::Dmy(sep) => format!("%d{sep}%m{sep}%Y"),
+        DateOrder::Mdy => "%m/%d/%Y".to_string(),
+    };
+    time.format(&format).to_string()
+}
+
+/// Date and time to the minute
+pub fn date_time(time: &DateTime<Local>) -> String {
+    format!("{} {}", date(time), clock(time))
+}
+
+/// `1h 05m`, or `23m` under an hour
+pub fn duration(secs: u64) -> String {
+    let mins = secs / 60;
+    if mins >= 60 {
+        format!("{}h {:02}m", mins / 60, mins % 60)
+    } else {
+        format!("{}m", mins)
+    }
+}
@@ -1,364 +1,298 @@
-use std::fs;
+mod anim;
+mod app;
+mod audit;
+mod backend;
+mod backlight;
+mod battery;
+mod budget;
+mod bugreport;
+mod calibration;
+mod calls;
+mod capabilities;
+mod charger;
+mod charging;
+mod clipboard;
+mod command;
+mod config;
+mod cores;
+mod cpufreq;
+mod daemon;
+mod dbus;
+mod desktop;
+mod doctor;
+mod dpms;
+mod draw;
+mod editor;
+mod events;
+mod fans;
+mod gpu;
+mod health;
+mod hibernate;
+mod history;
+mod hotplug;
+mod hwp;
+mod i915;
+mod inhibit;
+mod journal;
+mod keymap;
+mod lid;
+mod locale;
+mod msr;
+mod netpower;
+mod notifications;
+mod peripherals;
+mod platform;
+mod plug;
+mod preview;
+mod privileged;
+mod profile;
+mod quiet;
+mod rapl;
+mod rehearse;
+mod report;
+mod resume;
+mod rfkill;
+mod rules;
+mod ryzen;
+mod sessions;
+mod settings;
+mod simulate;
+mod sleep;
+mod snapshot;
+mod soak;
+mod sources;
+mod status;
+mod steering;
+mod storage;
+mod stream;
+mod swap;
+mod sway;
+mod sysfs;
+mod thermal;
+mod toast;
+mod tour;
+mod typec;
+mod ui;
+mod ups;
+mod usage;
+mod usb;
+mod wake;
+mod webhook;
+
+use std::env;
 use std::io::stdout;
-use std::process::Command;
-use std::time::Duration;
+use std::path::Path;
 
+use color_eyre::eyre::{bail, eyre};
 use color_eyre::Result;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
-use ratatui::{
-    prelude::*,
-    widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph},
-};
+use ratatui::prelude::*;
 
-#[derive(Clone, Copy, PartialEq)]
-enum Profile {
-    PowerSaver,
-    Balanced,
-    Performance,
-}
+use app::App;
+use keymap::Action;
+use ui::ui;
 
-impl Profile {
-    fn all() -> [Profile; 3] {
-        [Profile::PowerSaver, Profile::Balanced, Profile::Performance]
-    }
+fn main() -> Result<()> {
+    color_eyre::install()?;
 
-    fn name(&self) -> &'static str {
-        match self {
-            Profile::PowerSaver => "Power Saver",
-            Profile::Balanced => "Balanced",
-            Profile::Performance => "Performance",
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    match take_config_profile(&mut args)? {
+        Some(name) => config::select_config_profile(&name).map_err(|e| eyre!(e))?,
+        // Before the TUI takes the screen, so it's still there after
+        None => {
+            if let Err(e) = config::pick_config_profile() {
+                eprintln!("powertui: using the base config: {}", e);
+            }
         }
     }
-
-    fn governor(&self) -> &'static str {
-        match self {
-            Profile::PowerSaver => "powersave",
-            Profile::Balanced => "schedutil",
-            Profile::Performance => "performance",
-        }
+    // A broken config is reported by the TUI; times just use the locale
+    // and batteries read as reported
+    let config = config::load().ok();
+    locale::init(config.as_ref().map(|c| c.ui.clock).unwrap_or_default());
+    calibration::init(config.as_ref().map_or(&[], |c| &c.battery.calibration));
+    if let Some(name) = config.as_ref().and_then(|c| c.backend.as_ref()) {
+        backend::prefer(&name.0);
     }
-
-    fn from_governor(gov: &str) -> Option<Profile> {
-        match gov.trim() {
-            "powersave" => Some(Profile::PowerSaver),
-            "schedutil" => Some(Profile::Balanced),
-            "performance" => Some(Profile::Performance),
-            _ => None,
-        }
+    match args.first().map(String::as_str) {
+        None => run_tui(&args),
+        Some(flag) if flag.starts_with('-') => run_tui(&args),
+        Some("report") => report::run(&args[1..]),
+        Some("status") => status::run(&args[1..]),
+        Some("doctor") => doctor::run(&args[1..]),
+        Some("snapshot") => snapshot::run(&args[1..]),
+        Some("soak") => soak::run(&args[1..]),
+        Some("calibrate") => calibration::run(&args[1..]),
+        Some("settings") => settings::run(&args[1..]),
+        Some("daemon") => daemon::run(&args[1..]),
+        Some("rehearse") => rehearse::run(&args[1..]),
+        Some("bugreport") => bugreport::run(&args[1..]),
+        Some(other) => bail!("unknown command: {}", other),
     }
 }
 
-struct BatteryInfo {
-    capacity: u8,
-    status: String,
-    health: Option<u8>,
-    time_remaining: Option<String>,
-}
-
-struct App {
-    battery: Option<BatteryInfo>,
-    current_profile: Option<Profile>,
-    selected: usize,
-    list_state: ListState,
-    message: Option<String>,
-}
-
-impl App {
-    fn new() -> Self {
-        let mut app = Self {
-            battery: None,
-            current_profile: None,
-            selected: 0,
-            list_state: ListState::default(),
-            message: None,
-        };
-        app.list_state.select(Some(0));
-        app.refresh();
-        app
-    }
-
-    fn refresh(&mut self) {
-        self.battery = read_battery_info();
-        self.current_profile = read_current_governor();
-
-        // Set selection to current profile
-        if let Some(current) = self.current_profile {
-            for (i, profile) in Profile::all().iter().enumerate() {
-                if *profile == current {
-                    self.selected = i;
-                    self.list_state.select(Some(i));
-                    break;
-                }
-            }
-        }
+/// Take `--config-profile <name>` out of the arguments, wherever it is,
+/// so it works with every subcommand
+fn take_config_profile(args: &mut Vec<String>) -> Result<Option<String>> {
+    let Some(i) = args
+        .iter()
+        .position(|a| a == "--config-profile" || a.starts_with("--config-profile="))
+    else {
+        return Ok(None);
+    };
+    let arg = args.remove(i);
+    if let Some(name) = arg.strip_prefix("--config-profile=") {
+        return Ok(Some(name.to_string()));
     }
-
-    fn move_up(&mut self) {
-        if self.selected > 0 {
-            self.selected -= 1;
-            self.list_state.select(Some(self.selected));
-        }
+    if i >= args.len() {
+        bail!("--config-profile needs a name, e.g. --config-profile travel");
     }
+    Ok(Some(args.remove(i)))
+}
 
-    fn move_down(&mut self) {
-        if self.selected < Profile::all().len() - 1 {
-            self.selected += 1;
-            self.list_state.select(Some(self.selected));
-        }
+/// `--page cpu`: open on that page, by its tab title. Asking for the
+/// MSR page turns it on.
+fn set_page(config: &mut config::Config, page: &str) -> Result<()> {
+    let page = app::Page::try_from(page.to_string()).map_err(|e| eyre!(e))?;
+    if page == app::Page::Msr {
+        config.ui.msr = true;
     }
+    config.ui.page = page;
+    Ok(())
+}
 
-    fn select_profile(&mut self) {
-        let profile = Profile::all()[self.selected];
-        match set_governor(profile.governor()) {
-            Ok(()) => {
-                self.current_profile = Some(profile);
-                self.message = Some(format!("Switched to {}", profile.name()));
+fn run_tui(args: &[String]) -> Result<()> {
+    let (mut config, config_error) = match config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (config::Config::default(), Some(e)),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--reduced-motion" => config.ui.reduced_motion = true,
+            "--minimal" => config.ui.minimal = true,
+            "--page" => {
+                let Some(page) = args.next() else {
+                    bail!("--page needs a page, e.g. --page cpu");
+                };
+                set_page(&mut config, page)?;
             }
-            Err(e) => {
-                self.message = Some(format!("Error: {}", e));
+            "--dry-run" => privileged::set_dry_run(true),
+            "--simulate" => simulate::start(None).map_err(|e| eyre!(e))?,
+            _ => {
+                if let Some(dir) = arg.strip_prefix("--simulate=") {
+                    simulate::start(Some(Path::new(dir))).map_err(|e| eyre!(e))?;
+                } else if let Some(page) = arg.strip_prefix("--page=") {
+                    set_page(&mut config, page)?;
+                } else {
+                    bail!("unknown option: {}", arg);
+                }
             }
         }
     }
-}
-
-fn read_battery_info() -> Option<BatteryInfo> {
-    let base = "/sys/class/power_supply";
-
-    // Find battery (usually BAT0 or macsmc-battery on Asahi)
-    let battery_path = fs::read_dir(base).ok()?.find_map(|entry| {
-        let entry = entry.ok()?;
-        let type_path = entry.path().join("type");
-        let bat_type = fs::read_to_string(type_path).ok()?;
-        if bat_type.trim() == "Battery" {
-            Some(entry.path())
-        } else {
-            None
-        }
-    })?;
-
-    let capacity = fs::read_to_string(battery_path.join("capacity"))
-        .ok()?
-        .trim()
-        .parse()
-        .ok()?;
-
-    let status = fs::read_to_string(battery_path.join("status"))
-        .ok()
-        .map(|s| s.trim().to_string())
-        .unwrap_or_else(|| "Unknown".to_string());
-
-    // Calculate health from energy_full vs energy_full_design
-    let health = (|| {
-        let full: f64 = fs::read_to_string(battery_path.join("energy_full"))
-            .ok()?
-            .trim()
-            .parse()
-            .ok()?;
-        let design: f64 = fs::read_to_string(battery_path.join("energy_full_design"))
-            .ok()?
-            .trim()
-            .parse()
-            .ok()?;
-        Some(((full / design) * 100.0) as u8)
-    })();
-
-    // Calculate time remaining
-    let time_remaining = (|| {
-        let power_now: f64 = fs::read_to_string(battery_path.join("power_now"))
-            .ok()?
-            .trim()
-            .parse()
-            .ok()?;
-
-        if power_now <= 0.0 {
-            return None;
-        }
-
-        let energy: f64 = if status == "Charging" {
-            let full: f64 = fs::read_to_string(battery_path.join("energy_full"))
-                .ok()?
-                .trim()
-                .parse()
-                .ok()?;
-            let now: f64 = fs::read_to_string(battery_path.join("energy_now"))
-                .ok()?
-                .trim()
-                .parse()
-                .ok()?;
-            full - now
-        } else {
-            fs::read_to_string(battery_path.join("energy_now"))
-                .ok()?
-                .trim()
-                .parse()
-                .ok()?
-        };
-
-        let hours = energy / power_now;
-        let h = hours as u32;
-        let m = ((hours - h as f64) * 60.0) as u32;
-
-        if status == "Charging" {
-            Some(format!("{}h {}m until full", h, m))
-        } else {
-            Some(format!("{}h {}m remaining", h, m))
-        }
-    })();
-
-    Some(BatteryInfo {
-        capacity,
-        status,
-        health,
-        time_remaining,
-    })
-}
-
-fn read_current_governor() -> Option<Profile> {
-    let gov = fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor").ok()?;
-    Profile::from_governor(&gov)
-}
-
-fn set_governor(governor: &str) -> Result<(), String> {
-    let output = Command::new("sudo")
-        .args(["-n", "cpupower", "frequency-set", "-g", governor])
-        .output()
-        .map_err(|e| e.to_string())?;
-
-    if output.status.success() {
-        Ok(())
-    } else {
-        Err("Need passwordless sudo for cpupower".to_string())
-    }
-}
-
-fn main() -> Result<()> {
-    color_eyre::install()?;
 
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
 
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-    let mut app = App::new();
+    let mut app = App::new(config);
+    if let Some(e) = config_error {
+        app.error(format!("Ignoring config: {}", e));
+    }
 
     loop {
+        app.tick();
+        app.toasts.expire();
         terminal.draw(|f| ui(f, &mut app))?;
 
-        if event::poll(Duration::from_millis(250))? {
+        if event::poll(app.poll_timeout())? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => break,
-                        KeyCode::Char('j') | KeyCode::Down => app.move_down(),
-                        KeyCode::Char('k') | KeyCode::Up => app.move_up(),
-                        KeyCode::Enter | KeyCode::Char(' ') => app.select_profile(),
-                        KeyCode::Char('r') => app.refresh(),
-                        _ => {}
+                    // Any key wakes blanked outputs without acting on it
+                    if app.outputs_off {
+                        app.set_outputs_power(true);
+                        continue;
+                    }
+                    if app.command_line.is_some() {
+                        app.handle_command_key(key.code);
+                        continue;
+                    }
+                    if app.preview.is_some() {
+                        app.handle_preview_key(key.code);
+                        continue;
+                    }
+                    if app.editor.is_some() {
+                        app.handle_editor_key(key.code);
+                        continue;
+                    }
+                    if app.threshold_editor.is_some() {
+                        app.handle_threshold_key(key.code);
+                        continue;
+                    }
+                    if app.fan_editor.is_some() {
+                        app.handle_fan_key(key.code);
+                        continue;
+                    }
+                    if app.tour.is_some() {
+                        app.handle_tour_key(key.code);
+                        continue;
+                    }
+                    if app.show_log || app.show_help {
+                        // Overlays close on Esc, q or their own key
+                        let action = app.keymap.action(key.code);
+                        // The tour is offered from help
+                        if action == Some(Action::Tour) && app.show_help {
+                            app.start_tour();
+                        } else if matches!(
+                            action,
+                            Some(Action::Quit | Action::ShowLog | Action::Help)
+                        ) {
+                            app.show_log = false;
+                            app.show_help = false;
+                        }
+                        continue;
+                    }
+                    if let Some(action) = app.keymap.action(key.code) {
+                        app.perform(action);
+                    } else if let KeyCode::Char(c @ '1'..='9') = key.code {
+                        // Number keys jump straight to a page
+                        let index = c as usize - '1' as usize;
+                        if let Some(&page) = app.pages().get(index) {
+                            app.show_page(page);
+                        }
                     }
                 }
             }
         }
+
+        if app.quit {
+            break;
+        }
+    }
+    app.release_steering();
+    app.restore_nics();
+    app.release_batteries();
+    app.end_force_discharge();
+    app.release_fans();
+    app.restore_mem_sleep();
+    app.release_budget_cap();
+    app.end_quiet();
+    app.profile_time.flush();
+    if let Some(ref mut recorder) = app.session_log {
+        recorder.finish();
+    }
+    if app.travel.is_some() {
+        app.toggle_travel();
+    }
+
+    if app.simulating {
+        simulate::stop();
     }
 
     disable_raw_mode()?;
     stdout().execute(LeaveAlternateScreen)?;
     Ok(())
 }
-
-fn ui(f: &mut Frame, app: &mut App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([
-            Constraint::Length(5), // Battery
-            Constraint::Length(5), // Profiles
-            Constraint::Length(2), // Help/message
-        ])
-        .split(f.area());
-
-    // Battery widget
-    let battery_block = Block::default()
-        .title(" Battery ")
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::DarkGray));
-
-    if let Some(ref bat) = app.battery {
-        let color = match bat.capacity {
-            0..=20 => Color::Red,
-            21..=50 => Color::Yellow,
-            _ => Color::Green,
-        };
-
-        let label = format!(
-            "{}%  {}{}",
-            bat.capacity,
-            bat.status,
-            bat.time_remaining
-                .as_ref()
-                .map(|t| format!("  ({})", t))
-                .unwrap_or_default()
-        );
-
-        let health_str = bat
-            .health
-            .map(|h| format!("  Health: {}%", h))
-            .unwrap_or_default();
-
-        let gauge = Gauge::default()
-            .block(battery_block)
-            .gauge_style(Style::default().fg(color))
-            .ratio(bat.capacity as f64 / 100.0)
-            .label(format!("{}{}", label, health_str));
-
-        f.render_widget(gauge, chunks[0]);
-    } else {
-        let no_battery = Paragraph::new("No battery found")
-            .block(battery_block)
-            .style(Style::default().fg(Color::DarkGray));
-        f.render_widget(no_battery, chunks[0]);
-    }
-
-    // Profile list
-    let profiles: Vec<ListItem> = Profile::all()
-        .iter()
-        .map(|p| {
-            let is_current = app.current_profile == Some(*p);
-            let marker = if is_current { " ● " } else { "   " };
-            let text = format!("{}{} ({})", marker, p.name(), p.governor());
-            let style = if is_current {
-                Style::default().fg(Color::Green)
-            } else {
-                Style::default()
-            };
-            ListItem::new(text).style(style)
-        })
-        .collect();
-
-    let profiles_block = Block::default()
-        .title(" Power Profile ")
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::DarkGray));
-
-    let list = List::new(profiles)
-        .block(profiles_block)
-        .highlight_style(Style::default().bg(Color::DarkGray).fg(Color::White))
-        .highlight_symbol("▶ ");
-
-    f.render_stateful_widget(list, chunks[1], &mut app.list_state);
-
-    // Help/message line
-    let help_text = if let Some(ref msg) = app.message {
-        msg.clone()
-    } else {
-        "j/k navigate  Enter select  r refresh  q quit".to_string()
-    };
-
-    let help = Paragraph::new(help_text)
-        .style(Style::default().fg(Color::DarkGray))
-        .alignment(Alignment::Center);
-
-    f.render_widget(help, chunks[2]);
-}
@@ -0,0 +1,191 @@
+use std::fs::{self, File};
+use std::io::{self, ErrorKind};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+use crate::privileged;
+
+/// Registers are per CPU; package-wide ones read the same on any
+pub const DEVICE: &str = "/dev/cpu/0/msr";
+
+const PLATFORM_INFO: u64 = 0xce;
+const PERF_STATUS: u64 = 0x198;
+const MISC_ENABLE: u64 = 0x1a0;
+const TEMPERATURE_TARGET: u64 = 0x1a2;
+const TURBO_RATIO_LIMIT: u64 = 0x1ad;
+const ENERGY_PERF_BIAS: u64 = 0x1b0;
+const RAPL_POWER_UNIT: u64 = 0x606;
+const PKG_POWER_LIMIT: u64 = 0x610;
+
+/// Bus clock every ratio is a multiple of, since Sandy Bridge
+const BCLK_MHZ: u64 = 100;
+
+/// One model-specific register and what its fields mean
+pub struct Register {
+    pub address: u64,
+    pub name: &'static str,
+    pub raw: u64,
+    /// Field and decoded value, `PL1` and `28.0 W, enabled`
+    pub fields: Vec<(String, String)>,
+}
+
+/// Turbo ratios, power limits and the temperature target from CPU 0,
+/// read directly when running as root and through `dd` as root otherwise.
+/// Registers this model doesn't have are left out.
+pub fn read_registers() -> Result<Vec<Register>, String> {
+    let vendor = fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|info| {
+            info.lines().find_map(|l| {
+                Some(
+                    l.strip_prefix("vendor_id")?
+                        .trim_start_matches([' ', '\t', ':'])
+                        .to_string(),
+                )
+            })
+        })
+        .unwrap_or_default();
+    if vendor != "GenuineIntel" {
+        return Err(format!(
+            "Only Intel registers are decoded; this CPU is {}",
+            if vendor.is_empty() {
+                "unknown"
+            } else {
+                &vendor
+            }
+        ));
+    }
+    if !Path::new(DEVICE).exists() {
+        return Err(format!(
+            "No {}; load the msr module (sudo modprobe msr)",
+            DEVICE
+        ));
+    }
+    // Every Intel CPU since Nehalem has it, so failing here is access
+    let platform = read(PLATFORM_INFO)?;
+    let mut registers = vec![Register {
+        address: PLATFORM_INFO,
+        name: "MSR_PLATFORM_INFO",
+        raw: platform,
+        fields: vec![
+            ("Base".to_string(), mhz(bits(platform, 8, 8))),
+            ("Lowest efficient".to_string(), mhz(bits(platform, 40, 8))),
+        ],
+    }];
+    let mut add =
+        |address: u64, name: &'static str, decode: &dyn Fn(u64) -> Vec<(String, String)>| {
+            if let Ok(raw) = read(address) {
+                registers.push(Register {
+                    address,
+                    name,
+                    raw,
+                    fields: decode(raw),
+                });
+            }
+        };
+    add(PERF_STATUS, "IA32_PERF_STATUS", &|raw| {
+        vec![("Current".to_string(), mhz(bits(raw, 8, 8)))]
+    });
+    add(TURBO_RATIO_LIMIT, "MSR_TURBO_RATIO_LIMIT", &|raw| {
+        // A byte per number of active cores, 1 first; 0 past the last
+        (0..8)
+            .map(|i| bits(raw, i * 8, 8))
+            .enumerate()
+            .filter(|(_, ratio)| *ratio > 0)
+            .map(|(i, ratio)| {
+                let cores = if i == 0 {
+                    "1 core".to_string()
+                } else {
+                    format!("{} cores", i + 1)
+                };
+                (format!("Turbo, {} active", cores), mhz(ratio))
+            })
+            .collect()
+    });
+    add(MISC_ENABLE, "IA32_MISC_ENABLE", &|raw| {
+        vec![
+            ("Turbo".to_string(), enabled(raw & (1 << 38) == 0)),
+            ("SpeedStep".to_string(), enabled(raw & (1 << 16) != 0)),
+        ]
+    });
+    add(TEMPERATURE_TARGET, "MSR_TEMPERATURE_TARGET", &|raw| {
+        let tjmax = bits(raw, 16, 8);
+        let offset = bits(raw, 24, 6);
+        vec![
+            ("TjMax".to_string(), format!("{} °C", tjmax)),
+            (
+                "Throttles at".to_string(),
+                format!("{} °C (offset {})", tjmax.saturating_sub(offset), offset),
+            ),
+        ]
+    });
+    add(ENERGY_PERF_BIAS, "IA32_ENERGY_PERF_BIAS", &|raw| {
+        vec![(
+            "Bias".to_string(),
+            format!("{} (0 performance, 15 power saving)", bits(raw, 0, 4)),
+        )]
+    });
+    // Power limits are in the units this register gives
+    if let Ok(units) = read(RAPL_POWER_UNIT) {
+        let watt = 1.0 / (1u64 << bits(units, 0, 4)) as f64;
+        let second = 1.0 / (1u64 << bits(units, 16, 4)) as f64;
+        add(PKG_POWER_LIMIT, "MSR_PKG_POWER_LIMIT", &|raw| {
+            let limit = |shift: u32| {
+                format!(
+                    "{:.1} W, {}",
+                    bits(raw, shift, 15) as f64 * watt,
+                    enabled(raw & (1 << (shift + 15)) != 0)
+                )
+            };
+            // 2^Y × (1 + Z/4) time units, Y in bits 17–21 and Z in 22–23
+            let window =
+                (1u64 << bits(raw, 17, 5)) as f64 * (1.0 + bits(raw, 22, 2) as f64 / 4.0) * second;
+            vec![
+                ("PL1".to_string(), limit(0)),
+                ("PL1 window".to_string(), format!("{:.2} s", window)),
+                ("PL2".to_string(), limit(32)),
+                (
+                    "Locked".to_string(),
+                    if raw & (1 << 63) != 0 {
+                        "yes, until reset"
+                    } else {
+                        "no"
+                    }
+                    .to_string(),
+                ),
+            ]
+        });
+    }
+    Ok(registers)
+}
+
+/// One register from an open MSR device; needs root
+pub fn read_at(msr: &File, address: u64) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    msr.read_exact_at(&mut buf, address)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read(address: u64) -> Result<u64, String> {
+    match File::open(DEVICE).and_then(|f| read_at(&f, address)) {
+        Ok(value) => Ok(value),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+            let bytes = privileged::read(DEVICE, address, 8)?;
+            Ok(u64::from_le_bytes(bytes.try_into().expect("read 8 bytes")))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// `width` bits starting at `shift`
+fn bits(value: u64, shift: u32, width: u32) -> u64 {
+    (value >> shift) & ((1 << width) - 1)
+}
+
+fn mhz(ratio: u64) -> String {
+    format!("{} MHz (×{})", ratio * BCLK_MHZ, ratio)
+}
+
+fn enabled(on: bool) -> String {
+    if on { "enabled" } else { "disabled" }.to_string()
+}
@@ -0,0 +1,141 @@
+use std::fs;
+use std::process::Command;
+
+use crate::privileged;
+use crate::sysfs;
+
+/// A wired network interface backed by real hardware
+pub struct Nic {
+    /// e.g. `enp0s31f6`
+    pub name: String,
+    /// Administratively up (`ip link set up`)
+    pub up: bool,
+    /// A cable is plugged in and the link negotiated
+    pub carrier: bool,
+}
+
+/// Wired interfaces, skipping virtual ones (bridges, veth, docker) and
+/// Wi-Fi, which has its own power saving
+pub fn wired_nics() -> Vec<Nic> {
+    let Ok(entries) = fs::read_dir(sysfs::path("/sys/class/net")) else {
+        return Vec::new();
+    };
+    let mut nics: Vec<Nic> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if !path.join("device").exists() || path.join("wireless").exists() {
+                return None;
+            }
+            // ARPHRD_ETHER
+            if sysfs::read_value::<u32>(path.join("type"))? != 1 {
+                return None;
+            }
+            let flags = sysfs::read_string(path.join("flags"))?;
+            let flags = u32::from_str_radix(flags.trim_start_matches("0x"), 16).ok()?;
+            Some(Nic {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                up: flags & 1 != 0,
+                // Unreadable while the interface is down
+                carrier: sysfs::read_value::<u8>(path.join("carrier")) == Some(1),
+            })
+        })
+        .collect();
+    nics.sort_by(|a, b| a.name.cmp(&b.name));
+    nics
+}
+
+/// Whether energy-efficient ethernet is enabled, None when the NIC
+/// doesn't support it or ethtool isn't installed
+pub fn read_eee(nic: &str) -> Option<bool> {
+    let output = Command::new("ethtool")
+        .args(["--show-eee", nic])
+        .output()
+        .ok()?;
+    parse_eee(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// `ethtool --show-eee` output: "EEE status: enabled - active",
+/// "disabled" or "not supported"
+fn parse_eee(stdout: &str) -> Option<bool> {
+    let status = stdout
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("EEE status:"))?
+        .trim();
+    if status.starts_with("enabled") {
+        Some(true)
+    } else if status.starts_with("disabled") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+pub fn set_eee(nic: &str, on: bool) -> Result<(), String> {
+    privileged::run(
+        &format!("EEE {}", nic),
+        read_eee(nic).map(|e| on_off(e).to_string()),
+        on_off(on),
+        "ethtool",
+        &["--set-eee", nic, "eee", on_off(on)],
+    )
+}
+
+/// Set EEE on every wired interface that supports it
+pub fn apply_eee(on: bool) -> Result<(), String> {
+    for nic in wired_nics() {
+        if read_eee(&nic.name).is_some_and(|current| current != on) {
+            set_eee(&nic.name, on)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn set_link(nic: &Nic, up: bool) -> Result<(), String> {
+    let state = if up { "up" } else { "down" };
+    let old = if nic.up { "up" } else { "down" };
+    privileged::run(
+        &format!("link {}", nic.name),
+        Some(old.to_string()),
+        state,
+        "ip",
+        &["link", "set", &nic.name, state],
+    )
+}
+
+/// Connected Bluetooth devices across all adapters, or None without an
+/// adapter. Each ACL connection shows up as `hci0:N` next to `hci0`.
+pub fn bluetooth_connections() -> Option<usize> {
+    let entries = fs::read_dir("/sys/class/bluetooth").ok()?;
+    let names: Vec<String> = entries
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    Some(names.iter().filter(|n| n.contains(':')).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eee_status() {
+        let show = |status: &str| format!("EEE settings for enp2s0:\n\tEEE status: {}\n", status);
+        assert_eq!(parse_eee(&show("enabled - active")), Some(true));
+        assert_eq!(parse_eee(&show("enabled - inactive")), Some(true));
+        assert_eq!(parse_eee(&show("disabled")), Some(false));
+        assert_eq!(parse_eee(&show("not supported")), None);
+        assert_eq!(parse_eee("netlink error: Operation not supported\n"), None);
+    }
+}
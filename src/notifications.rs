@@ -0,0 +1,137 @@
+use serde::Deserialize;
+
+use crate::battery::BatteryInfo;
+use crate::config::QuietHours;
+use crate::dbus::{self, Bus};
+
+/// `[notifications]`: desktop notifications for battery events, for
+/// when the terminal is out of sight
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationsConfig {
+    /// Percentage for the critical warning; the low one is `[battery] low`
+    pub critical: u8,
+    /// When the battery reports itself full
+    pub full: bool,
+    /// When the charger is plugged in or pulled
+    pub power: bool,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            critical: 5,
+            full: true,
+            power: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Charge {
+    Fine,
+    Low,
+    Critical,
+}
+
+/// Notifies on changes between readings: each charge warning once per
+/// discharge, full once per charge, and plug and unplug but not the
+/// first reading. During quiet hours only the critical warning goes out;
+/// the rest are dropped rather than saved for the morning.
+pub struct Notifier {
+    config: NotificationsConfig,
+    quiet_hours: Option<QuietHours>,
+    charge: Charge,
+    full: bool,
+    on_ac: Option<bool>,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationsConfig, quiet_hours: Option<QuietHours>) -> Self {
+        Notifier {
+            config,
+            quiet_hours,
+            charge: Charge::Fine,
+            full: false,
+            on_ac: None,
+        }
+    }
+
+    /// Look at a new reading; `low` is the low-battery percentage
+    pub fn update(&mut self, battery: Option<&BatteryInfo>, on_ac: bool, low: u8) {
+        let first = self.on_ac.is_none();
+        let quiet = self.quiet_hours.is_some_and(|q| q.now());
+        if self.config.power && !quiet && self.on_ac.is_some_and(|was| was != on_ac) {
+            let (summary, icon) = if on_ac {
+                ("Charger connected", "ac-adapter")
+            } else {
+                ("On battery", "battery")
+            };
+            let body = battery
+                .map(|b| format!("{}%", b.capacity))
+                .unwrap_or_default();
+            send(summary, &body, icon, 0);
+        }
+        self.on_ac = Some(on_ac);
+        let Some(battery) = battery else {
+            return;
+        };
+
+        let charge = match battery.capacity {
+            _ if battery.status != "Discharging" => Charge::Fine,
+            c if c <= self.config.critical => Charge::Critical,
+            c if c <= low => Charge::Low,
+            _ => Charge::Fine,
+        };
+        if charge > self.charge {
+            let mut body = format!("{}% left", battery.capacity);
+            if let Some(ref t) = battery.time_remaining {
+                body.push_str(&format!(", about {}", t));
+            }
+            match charge {
+                Charge::Critical => send("Battery critical", &body, "battery-caution", 2),
+                _ if quiet => {}
+                _ => send("Battery low", &body, "battery-low", 1),
+            }
+        }
+        self.charge = charge;
+
+        let full = battery.status == "Full";
+        if self.config.full && full && !self.full && !first && !quiet {
+            send(
+                "Battery full",
+                "Charging is done",
+                "battery-full-charged",
+                0,
+            );
+        }
+        self.full = full;
+    }
+}
+
+/// One notification through the session bus; `urgency` is 0 low,
+/// 1 normal, 2 critical. Nothing happens without a notification server.
+fn send(summary: &str, body: &str, icon: &str, urgency: u8) {
+    let urgency = urgency.to_string();
+    dbus::call(
+        Bus::Session,
+        "org.freedesktop.Notifications",
+        "/org/freedesktop/Notifications",
+        "org.freedesktop.Notifications",
+        "Notify",
+        &[
+            "susssasa{sv}i",
+            "powertui",
+            "0",
+            icon,
+            summary,
+            body,
+            "0",
+            "1",
+            "urgency",
+            "y",
+            &urgency,
+            "-1",
+        ],
+    );
+}
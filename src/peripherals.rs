@@ -0,0 +1,81 @@
+use crate::dbus::{self, Bus};
+
+const UPOWER: &str = "org.freedesktop.UPower";
+
+/// A battery-powered device reported by UPower, like a mouse or earbuds
+pub struct Peripheral {
+    /// UPower object path, stable while the device stays connected
+    pub path: String,
+    /// Model name, or the device type when UPower has none
+    pub name: String,
+    pub percentage: f64,
+}
+
+/// UPower device types that aren't peripherals
+const LINE_POWER: u32 = 1;
+const BATTERY: u32 = 2;
+
+pub fn read_peripherals() -> Vec<Peripheral> {
+    let Some(tokens) = dbus::call(
+        Bus::System,
+        UPOWER,
+        "/org/freedesktop/UPower",
+        UPOWER,
+        "EnumerateDevices",
+        &[],
+    ) else {
+        return Vec::new();
+    };
+
+    // ao N path...
+    tokens
+        .get(2..)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| read_device(path))
+        .collect()
+}
+
+fn read_device(path: &str) -> Option<Peripheral> {
+    let tokens = dbus::get_properties(
+        Bus::System,
+        UPOWER,
+        path,
+        "org.freedesktop.UPower.Device",
+        &["Type", "Model", "Percentage", "PowerSupply", "IsPresent"],
+    )?;
+    // Signature/value pairs in the order asked for
+    let value = |i: usize| tokens.get(i * 2 + 1).map(String::as_str);
+    let kind: u32 = value(0)?.parse().ok()?;
+    let power_supply = value(3)? == "true";
+    let present = value(4)? == "true";
+    // Laptop batteries and UPSes power the system; those are shown elsewhere
+    if power_supply || !present || kind == LINE_POWER || kind == BATTERY {
+        return None;
+    }
+    let name = value(1)
+        .filter(|m| !m.is_empty())
+        .unwrap_or(kind_name(kind));
+    Some(Peripheral {
+        path: path.to_string(),
+        name: name.to_string(),
+        percentage: value(2)?.parse().ok()?,
+    })
+}
+
+fn kind_name(kind: u32) -> &'static str {
+    match kind {
+        5 => "Mouse",
+        6 => "Keyboard",
+        8 => "Phone",
+        10 => "Tablet",
+        12 => "Controller",
+        13 => "Pen",
+        14 => "Touchpad",
+        17 => "Headset",
+        18 => "Speakers",
+        19 => "Headphones",
+        22 => "Remote",
+        _ => "Device",
+    }
+}
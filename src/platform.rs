@@ -0,0 +1,38 @@
+use crate::privileged;
+use crate::sysfs;
+
+/// The firmware's own power mode, on laptops that have one: fan curves
+/// and power limits the OS can't set directly
+const PLATFORM_PROFILE: &str = "/sys/firmware/acpi/platform_profile";
+const CHOICES: &str = "/sys/firmware/acpi/platform_profile_choices";
+
+pub fn read_profile() -> Option<String> {
+    sysfs::read_string(sysfs::path(PLATFORM_PROFILE))
+}
+
+/// Modes the firmware offers, usually `low-power balanced performance`
+pub fn choices() -> Vec<String> {
+    sysfs::read_string(sysfs::path(CHOICES))
+        .map(|s| s.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+pub fn set_profile(profile: &str) -> Result<(), String> {
+    let choices = choices();
+    if choices.is_empty() {
+        return Err("This machine has no platform profile".to_string());
+    }
+    if !choices.iter().any(|c| c == profile) {
+        return Err(format!(
+            "Platform profile {} isn't one of {}",
+            profile,
+            choices.join(", ")
+        ));
+    }
+    privileged::write(
+        "Platform profile",
+        read_profile(),
+        PLATFORM_PROFILE,
+        profile,
+    )
+}
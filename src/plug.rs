@@ -0,0 +1,159 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Smart plugs measure what the whole machine pulls from the wall, which
+/// RAPL and battery readings can't see on a desktop
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlugConfig {
+    pub kind: PlugKind,
+    /// Hostname or IP address on the local network
+    pub host: String,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlugKind {
+    Tasmota,
+    Shelly,
+    /// TP-Link Kasa (HS110, KP115)
+    Kasa,
+}
+
+/// Plugs answer on the LAN almost instantly; anything slower is offline
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Far more than any plug's reply, which is a few hundred bytes
+const MAX_REPLY: usize = 64 * 1024;
+
+/// Reads the plug on its own thread, since an offline plug takes a
+/// timeout or two to answer and the UI mustn't wait for it
+pub struct Poller {
+    readings: Receiver<Result<f64, String>>,
+}
+
+impl Poller {
+    pub fn start(plug: PlugConfig, interval: Duration) -> Poller {
+        let (tx, readings) = mpsc::channel();
+        // Ends with the next reading after the poller is dropped
+        thread::spawn(move || loop {
+            if tx.send(read_power(&plug)).is_err() {
+                break;
+            }
+            thread::sleep(interval);
+        });
+        Poller { readings }
+    }
+
+    /// Readings since the last call, oldest first
+    pub fn drain(&self) -> Vec<Result<f64, String>> {
+        self.readings.try_iter().collect()
+    }
+}
+
+/// Current wall power in watts
+pub fn read_power(plug: &PlugConfig) -> Result<f64, String> {
+    match plug.kind {
+        PlugKind::Tasmota => {
+            let status = http_json(&format!("http://{}/cm?cmnd=Status%208", plug.host))?;
+            status["StatusSNS"]["ENERGY"]["Power"]
+                .as_f64()
+                .ok_or_else(|| "Tasmota plug reports no energy readings".to_string())
+        }
+        PlugKind::Shelly => {
+            // Gen2 devices speak RPC, Gen1 only the older status endpoint
+            let gen2 = http_json(&format!("http://{}/rpc/Switch.GetStatus?id=0", plug.host));
+            if let Some(power) = gen2.ok().and_then(|s| s["apower"].as_f64()) {
+                return Ok(power);
+            }
+            let status = http_json(&format!("http://{}/status", plug.host))?;
+            status["meters"][0]["power"]
+                .as_f64()
+                .ok_or_else(|| "Shelly plug reports no power meter".to_string())
+        }
+        PlugKind::Kasa => {
+            let reply = kasa_request(&plug.host, r#"{"emeter":{"get_realtime":{}}}"#)?;
+            let realtime = &reply["emeter"]["get_realtime"];
+            // Newer firmware reports milliwatts, older watts
+            realtime["power_mw"]
+                .as_f64()
+                .map(|mw| mw / 1000.0)
+                .or_else(|| realtime["power"].as_f64())
+                .ok_or_else(|| "Kasa plug has no energy meter".to_string())
+        }
+    }
+}
+
+fn http_json(url: &str) -> Result<Value, String> {
+    let output = Command::new("curl")
+        .args(["-sf", "-m", &TIMEOUT.as_secs().to_string(), url])
+        .output()
+        .map_err(|e| format!("curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("No answer from {}", url));
+    }
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("{}: {}", url, e))
+}
+
+/// Kasa's local protocol: length-prefixed JSON over TCP 9999, obfuscated
+/// with an autokey XOR cipher starting at 171
+fn kasa_request(host: &str, request: &str) -> Result<Value, String> {
+    let addr = (host, 9999)
+        .to_socket_addrs()
+        .map_err(|e| format!("{}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("{}: no address", host))?;
+    let mut stream =
+        TcpStream::connect_timeout(&addr, TIMEOUT).map_err(|e| format!("{}: {}", host, e))?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    let mut key = 171u8;
+    let payload: Vec<u8> = request
+        .bytes()
+        .map(|b| {
+            key ^= b;
+            key
+        })
+        .collect();
+    let mut message = (payload.len() as u32).to_be_bytes().to_vec();
+    message.extend(payload);
+    stream
+        .write_all(&message)
+        .map_err(|e| format!("{}: {}", host, e))?;
+
+    let mut len = [0u8; 4];
+    stream
+        .read_exact(&mut len)
+        .map_err(|e| format!("{}: {}", host, e))?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_REPLY {
+        return Err(format!(
+            "{}: reply of {} bytes is too long for a plug",
+            host, len
+        ));
+    }
+    let mut reply = vec![0u8; len];
+    stream
+        .read_exact(&mut reply)
+        .map_err(|e| format!("{}: {}", host, e))?;
+
+    let mut key = 171u8;
+    let reply: Vec<u8> = reply
+        .into_iter()
+        .map(|c| {
+            let b = key ^ c;
+            key = c;
+            b
+        })
+        .collect();
+    serde_json::from_slice(&reply).map_err(|e| format!("{}: {}", host, e))
+}
@@ -0,0 +1,189 @@
+use crate::backend;
+use crate::cpufreq;
+use crate::netpower;
+use crate::platform;
+use crate::profile::{resolve_governor, Profile};
+use crate::rapl::{self, Limit};
+use crate::ryzen;
+use crate::swap;
+
+/// One setting a profile would touch
+pub struct Change {
+    pub setting: &'static str,
+    pub current: String,
+    pub target: String,
+}
+
+impl Change {
+    pub fn is_change(&self) -> bool {
+        self.current != self.target
+    }
+}
+
+/// What applying a profile would do, waiting for confirmation
+pub struct Preview {
+    /// Index into the app's profile list
+    pub profile: usize,
+    pub changes: Vec<Change>,
+}
+
+impl Preview {
+    /// `brightness` is the current level, `None` where powertui can't
+    /// set it
+    pub fn new(index: usize, profile: &Profile, brightness: Option<u8>) -> Preview {
+        Preview {
+            profile: index,
+            changes: diff(profile, brightness),
+        }
+    }
+}
+
+/// Current system values next to what the profile would set
+fn diff(profile: &Profile, brightness: Option<u8>) -> Vec<Change> {
+    let unknown = || "?".to_string();
+    let mut changes = vec![Change {
+        setting: "Governor",
+        current: backend::active().current().unwrap_or_else(unknown),
+        target: match resolve_governor(&profile.governor, &backend::active().governors()) {
+            Some(g) if g == profile.governor => g,
+            Some(g) => format!("{} (for {})", g, profile.governor),
+            None => format!("{} (unavailable)", profile.governor),
+        },
+    }];
+    if let Some(epp) = profile.epp_target() {
+        changes.push(Change {
+            setting: "EPP",
+            current: cpufreq::read_epp().unwrap_or_else(unknown),
+            target: epp.to_string(),
+        });
+    }
+
+    if let Some(on) = profile.turbo {
+        let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+        changes.push(Change {
+            setting: "Turbo",
+            current: cpufreq::read_turbo().map(on_off).unwrap_or_else(unknown),
+            target: on_off(on),
+        });
+    }
+
+    if let Some(wanted) = profile.platform_target() {
+        changes.push(Change {
+            setting: "Platform profile",
+            current: platform::read_profile().unwrap_or_else(unknown),
+            target: wanted.to_string(),
+        });
+    }
+    if let (Some(target), Some(current)) = (profile.brightness, brightness) {
+        changes.push(Change {
+            setting: "Brightness",
+            current: format!("{}%", current),
+            target: format!("{}%", target),
+        });
+    }
+
+    let limits = cpufreq::read_limits();
+    let bounds = [
+        ("Min frequency", profile.min_freq, limits.map(|l| l.0)),
+        ("Max frequency", profile.max_freq, limits.map(|l| l.1)),
+    ];
+    for (setting, target, current) in bounds {
+        let Some(target) = target else {
+            continue;
+        };
+        changes.push(Change {
+            setting,
+            current: current
+                .map(|khz| format!("{} MHz", khz / 1000))
+                .unwrap_or_else(unknown),
+            target: format!("{} MHz", target),
+        });
+    }
+
+    let package = rapl::package();
+    for (limit, watts) in [(Limit::Pl1, profile.pl1), (Limit::Pl2, profile.pl2)] {
+        let Some(watts) = watts else {
+            continue;
+        };
+        let current = package.as_ref().and_then(|p| p.limit(limit));
+        changes.push(Change {
+            setting: limit.label(),
+            current: current
+                .map(|w| format!("{:.0} W", w))
+                .unwrap_or_else(unknown),
+            target: format!("{:.0} W", watts),
+        });
+    }
+    let limits = profile.ryzen_limits();
+    if !limits.is_empty() {
+        let status = ryzen::read_status();
+        for (limit, watts) in limits {
+            let current = status
+                .as_ref()
+                .and_then(|s| s.limits.iter().find(|(l, _, _)| *l == limit))
+                .and_then(|(_, current, _)| *current);
+            changes.push(Change {
+                setting: limit.label(),
+                current: current
+                    .map(|w| format!("{:.0} W", w))
+                    .unwrap_or_else(unknown),
+                target: format!("{:.0} W", watts),
+            });
+        }
+    }
+    if let Some(on) = profile.eee {
+        let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+        for nic in netpower::wired_nics() {
+            // NICs without EEE support are skipped when applying
+            if let Some(current) = netpower::read_eee(&nic.name) {
+                changes.push(Change {
+                    setting: "Ethernet EEE",
+                    current: format!("{} {}", nic.name, on_off(current)),
+                    target: format!("{} {}", nic.name, on_off(on)),
+                });
+            }
+        }
+    }
+    let tuning = profile.swap_tuning();
+    if tuning.zswap.is_some() || tuning.compressor.is_some() || tuning.pool.is_some() {
+        let zswap = swap::read_zswap();
+        let zswap = zswap.as_ref();
+        if let Some(on) = tuning.zswap {
+            let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+            changes.push(Change {
+                setting: "zswap",
+                current: zswap.map(|z| on_off(z.enabled)).unwrap_or_else(unknown),
+                target: on_off(on),
+            });
+        }
+        if let Some(compressor) = tuning.compressor {
+            changes.push(Change {
+                setting: "zswap compressor",
+                current: zswap
+                    .and_then(|z| z.compressor.clone())
+                    .unwrap_or_else(unknown),
+                target: compressor.to_string(),
+            });
+        }
+        if let Some(pool) = tuning.pool {
+            changes.push(Change {
+                setting: "zswap pool",
+                current: zswap
+                    .and_then(|z| z.max_pool_percent)
+                    .map(|p| format!("{}%", p))
+                    .unwrap_or_else(unknown),
+                target: format!("{}%", pool),
+            });
+        }
+    }
+    if let Some(swappiness) = tuning.swappiness {
+        changes.push(Change {
+            setting: "Swappiness",
+            current: swap::read_swappiness()
+                .map(|s| s.to_string())
+                .unwrap_or_else(unknown),
+            target: swappiness.to_string(),
+        });
+    }
+    changes
+}
@@ -0,0 +1,318 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::audit;
+use crate::desktop;
+use crate::stream;
+
+/// Everything that needs root goes through here, so dry-run mode can
+/// intercept it in one place.
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Operations skipped in dry-run mode, waiting to be shown to the user
+static SKIPPED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+pub fn dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// Operations skipped since the last call
+pub fn drain_skipped() -> Vec<String> {
+    SKIPPED
+        .lock()
+        .map(|mut skipped| skipped.drain(..).collect())
+        .unwrap_or_default()
+}
+
+fn skip(description: String) {
+    if let Ok(mut skipped) = SKIPPED.lock() {
+        skipped.push(description);
+    }
+}
+
+/// How a command gets root, in the order they're tried
+#[derive(Clone, Copy, PartialEq)]
+pub enum Escalation {
+    /// powertui is running as root already
+    Root,
+    /// pkexec, allowed by the polkit policy powertui ships
+    Pkexec,
+    /// Passwordless sudo, set up in sudoers
+    Sudo,
+}
+
+impl Escalation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Escalation::Root => "root",
+            Escalation::Pkexec => "pkexec",
+            Escalation::Sudo => "sudo",
+        }
+    }
+
+    /// How `program args` reads in the audit log
+    fn describe(&self, program: &str, args: &[&str]) -> String {
+        let prefix = match self {
+            Escalation::Root => "",
+            // The internal agent would prompt on the TUI's terminal
+            Escalation::Pkexec => "pkexec --disable-internal-agent ",
+            Escalation::Sudo => "sudo -n ",
+        };
+        format!("{}{} {}", prefix, program, args.join(" "))
+    }
+
+    fn command(&self, program: &str) -> Command {
+        match self {
+            Escalation::Root => Command::new(program),
+            Escalation::Pkexec => {
+                let mut command = Command::new("pkexec");
+                command.args(["--disable-internal-agent", program]);
+                command
+            }
+            Escalation::Sudo => {
+                let mut command = Command::new("sudo");
+                command.args(["-n", program]);
+                command
+            }
+        }
+    }
+
+    /// Whether a failure was the escalation being refused rather than
+    /// the program failing: pkexec exits 126 when the prompt is
+    /// dismissed and 127 when not authorized or without an agent
+    fn refused(&self, status: ExitStatus, stderr: &str) -> bool {
+        match self {
+            Escalation::Root => false,
+            Escalation::Pkexec => matches!(status.code(), Some(126 | 127)),
+            Escalation::Sudo => stderr.starts_with("sudo:"),
+        }
+    }
+}
+
+/// Where the polkit policy from `polkit/` is installed
+pub const POLICY: &str = "/usr/share/polkit-1/actions/io.github.chbornman.powertui.policy";
+
+/// Writes and reads root-only files, but only those powertui uses, so
+/// the policy and sudoers can allow it where `tee` would allow anything
+pub const HELPER: &str = "powertui-helper";
+
+/// What's tried here, probed once: nothing but running directly as
+/// root, otherwise pkexec where the policy is installed, then sudo
+pub fn escalations() -> &'static [Escalation] {
+    static ESCALATIONS: OnceLock<Vec<Escalation>> = OnceLock::new();
+    ESCALATIONS.get_or_init(|| {
+        if running_as_root() {
+            return vec![Escalation::Root];
+        }
+        let mut escalations = Vec::new();
+        if Path::new(POLICY).exists() && desktop::command_exists("pkexec") {
+            escalations.push(Escalation::Pkexec);
+        }
+        escalations.push(Escalation::Sudo);
+        escalations
+    })
+}
+
+/// The effective uid in `/proc/self/status` is 0
+fn running_as_root() -> bool {
+    fs::read_to_string("/proc/self/status").is_ok_and(|status| {
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix("Uid:"))
+            .and_then(|ids| ids.split_whitespace().nth(1))
+            == Some("0")
+    })
+}
+
+/// The way `program` would get root here, if any is set up. pkexec is
+/// trusted once its policy is installed; sudo is asked with `-l`.
+pub fn allowed(program: &str) -> Option<Escalation> {
+    escalations().iter().copied().find(|how| match how {
+        Escalation::Root | Escalation::Pkexec => true,
+        Escalation::Sudo => Command::new("sudo")
+            .args(["-n", "-l", program])
+            .output()
+            .is_ok_and(|o| o.status.success()),
+    })
+}
+
+/// What to set up when nothing gets `program` root
+pub fn no_root(program: &str) -> String {
+    format!(
+        "No root for {}: install the polkit policy or allow passwordless sudo for it",
+        program
+    )
+}
+
+/// Run `program` as root, each of `escalations` in turn until one isn't
+/// refused, feeding it `input`. Its stdout on success; its own error
+/// when it ran and failed.
+fn escalate(
+    escalations: &[Escalation],
+    program: &str,
+    args: &[&str],
+    input: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    for how in escalations {
+        let mut child = how
+            .command(program)
+            .args(args)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("{}: {}", program, e))?;
+        // Closed at the end of the arm, so the child sees the end
+        let written = match (input, child.stdin.take()) {
+            (Some(input), Some(mut stdin)) => stdin.write_all(input),
+            _ => Ok(()),
+        };
+        // Reaped whatever happened to the input. One that refused
+        // without reading it leaves a broken pipe, which is no more
+        // than the refusal.
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() && how.refused(output.status, &stderr) {
+            continue;
+        }
+        if let Err(e) = written {
+            return Err(format!("{}: {}", program, e));
+        }
+        if output.status.success() {
+            return Ok(output.stdout);
+        }
+        return Err(match stderr.lines().next() {
+            Some(line) if !line.trim().is_empty() => line.trim().to_string(),
+            _ => format!("{} failed", program),
+        });
+    }
+    Err(no_root(program))
+}
+
+/// Run a command as root, changing `setting` from `old` to `new`.
+/// Every attempt lands in the audit log.
+pub fn run(
+    setting: &str,
+    old: Option<String>,
+    new: &str,
+    program: &str,
+    args: &[&str],
+) -> Result<(), String> {
+    let description = escalations()[0].describe(program, args);
+    audited(setting, old, new, description, || {
+        escalate(escalations(), program, args, None).map(|_| ())
+    })
+}
+
+/// Run a command as the user, for changes a D-Bus policy already lets
+/// the session make. Still audited, and still skipped in dry-run mode.
+pub fn run_unprivileged(
+    setting: &str,
+    old: Option<String>,
+    new: &str,
+    program: &str,
+    args: &[&str],
+) -> Result<(), String> {
+    let description = format!("{} {}", program, args.join(" "));
+    audited(setting, old, new, description, || {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!("{} failed", program))
+        }
+    })
+}
+
+/// Write `value` to a root-owned sysfs attribute: directly as root,
+/// otherwise through the helper
+pub fn write(setting: &str, old: Option<String>, path: &str, value: &str) -> Result<(), String> {
+    if escalations()[0] == Escalation::Root {
+        let description = format!("echo {} > {}", value, path);
+        return audited(setting, old, value, description, || {
+            fs::write(path, value).map_err(|e| format!("{}: {}", path, e))
+        });
+    }
+    let description = format!(
+        "echo {} | {}",
+        value,
+        escalations()[0].describe(HELPER, &["write", path])
+    );
+    audited(setting, old, value, description, || {
+        escalate(
+            escalations(),
+            HELPER,
+            &["write", path],
+            Some(value.as_bytes()),
+        )
+        .map(|_| ())
+    })
+}
+
+/// Output of a root-only command that only reads, like `ryzenadj -i`.
+/// Reads change nothing, so they skip the audit log and run in dry-run
+/// mode too. They're polled on every refresh, so pkexec, which may put
+/// up a password dialog each time, is never tried: only root or
+/// passwordless sudo.
+pub fn output(program: &str, args: &[&str]) -> Result<Vec<u8>, String> {
+    let quiet: Vec<Escalation> = escalations()
+        .iter()
+        .copied()
+        .filter(|how| *how != Escalation::Pkexec)
+        .collect();
+    escalate(&quiet, program, args, None)
+}
+
+/// Read `len` bytes at `offset` from a root-only file through the helper
+pub fn read(path: &str, offset: u64, len: usize) -> Result<Vec<u8>, String> {
+    let bytes = output(
+        HELPER,
+        &["read", path, &offset.to_string(), &len.to_string()],
+    )?;
+    if bytes.len() != len {
+        return Err(format!("Can't read {} at {:#x}", path, offset));
+    }
+    Ok(bytes)
+}
+
+/// Honor dry-run mode and record the outcome of `exec` in the audit log
+fn audited(
+    setting: &str,
+    old: Option<String>,
+    new: &str,
+    description: String,
+    exec: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String> {
+    if dry_run() {
+        audit::record(setting, &description, old, new, "dry-run");
+        skip(description);
+        return Ok(());
+    }
+
+    let result = exec();
+
+    let outcome = match result {
+        Ok(()) => "ok".to_string(),
+        Err(ref e) => format!("failed: {}", e),
+    };
+    audit::record(setting, &description, old, new, &outcome);
+    if result.is_ok() {
+        stream::changed(setting);
+    }
+    result
+}
@@ -0,0 +1,296 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend;
+use crate::config;
+use crate::cpufreq;
+use crate::netpower;
+use crate::platform;
+use crate::rapl::{self, Limit};
+use crate::ryzen::{self, RyzenLimit};
+use crate::swap::{self, Tuning};
+use crate::sysfs;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub governor: String,
+    /// Energy Performance Preference hint, where the driver has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epp: Option<String>,
+    /// Allow turbo, or hold the CPU at its base clock
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turbo: Option<bool>,
+    /// Frequency limits in MHz on every policy, clamped to what each
+    /// allows; left alone when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_freq: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_freq: Option<u64>,
+    /// Firmware power mode from `platform_profile_choices`, e.g. `low-power`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform_profile: Option<String>,
+    /// Screen brightness in percent, through the desktop or backlight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u8>,
+    /// RAPL package limits in watts, left alone when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pl1: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pl2: Option<f64>,
+    /// Ryzen STAPM/fast/slow limits in watts, via ryzenadj
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stapm: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fast: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow: Option<f64>,
+    /// Energy-efficient ethernet on wired interfaces, via ethtool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eee: Option<bool>,
+    /// Take wired interfaces without a cable down while on battery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nics_down: Option<bool>,
+    /// Soft-block Bluetooth after this many minutes with nothing connected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bluetooth_idle: Option<u64>,
+    /// zswap on or off, its compressor and the most of RAM its pool may
+    /// take in percent; a bigger pool on battery keeps swap off the disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zswap: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zswap_compressor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zswap_pool: Option<u8>,
+    /// `vm.swappiness`, higher to favour zram or zswap over dropping
+    /// file cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swappiness: Option<u8>,
+    /// Defined in profiles.toml rather than built in
+    #[serde(skip)]
+    pub custom: bool,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ProfilesFile {
+    #[serde(default)]
+    profile: Vec<Profile>,
+}
+
+impl Profile {
+    pub fn new(name: &str, governor: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            governor: governor.to_string(),
+            epp: None,
+            turbo: None,
+            min_freq: None,
+            max_freq: None,
+            platform_profile: None,
+            brightness: None,
+            pl1: None,
+            pl2: None,
+            stapm: None,
+            fast: None,
+            slow: None,
+            eee: None,
+            nics_down: None,
+            bluetooth_idle: None,
+            zswap: None,
+            zswap_compressor: None,
+            zswap_pool: None,
+            swappiness: None,
+            custom: false,
+        }
+    }
+
+    /// The same EPP hints power-profiles-daemon picks for its profiles
+    pub fn builtin() -> Vec<Profile> {
+        [
+            ("Power Saver", "powersave", "power"),
+            ("Balanced", "schedutil", "balance_performance"),
+            ("Performance", "performance", "performance"),
+        ]
+        .into_iter()
+        .map(|(name, governor, epp)| Profile {
+            epp: Some(epp.to_string()),
+            ..Profile::new(name, governor)
+        })
+        .collect()
+    }
+
+    /// The EPP hint applying this profile would set: none without EPP
+    /// support, or while power-profiles-daemon sets hints itself
+    pub fn epp_target(&self) -> Option<&str> {
+        let epp = self.epp.as_deref()?;
+        (!backend::active().owns_epp() && cpufreq::read_epp().is_some()).then_some(epp)
+    }
+
+    /// The platform profile applying this one would set; like EPP,
+    /// power-profiles-daemon sets it itself while it's the backend
+    pub fn platform_target(&self) -> Option<&str> {
+        let wanted = self.platform_profile.as_deref()?;
+        (!backend::active().owns_epp() && platform::read_profile().is_some()).then_some(wanted)
+    }
+}
+
+fn profiles_path() -> Option<PathBuf> {
+    Some(config::config_dir()?.join("profiles.toml"))
+}
+
+/// Built-in profiles followed by the user's own. A custom profile with
+/// the same name as a built-in one replaces it in place.
+pub fn load_profiles() -> Result<Vec<Profile>, String> {
+    let mut profiles = Profile::builtin();
+
+    let Some(path) = profiles_path() else {
+        return Ok(profiles);
+    };
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Ok(profiles);
+    };
+    let file: ProfilesFile =
+        toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    for mut profile in file.profile {
+        profile.custom = true;
+        match profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => profiles.push(profile),
+        }
+    }
+    Ok(profiles)
+}
+
+pub fn save_custom_profiles(profiles: &[Profile]) -> Result<(), String> {
+    let path = profiles_path().ok_or("Cannot locate config directory")?;
+    let file = ProfilesFile {
+        profile: profiles.iter().filter(|p| p.custom).cloned().collect(),
+    };
+    let contents = toml::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn read_current_governor() -> Option<String> {
+    sysfs::read_string(sysfs::path(
+        "/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor",
+    ))
+}
+
+pub fn available_governors() -> Vec<String> {
+    sysfs::read_string(sysfs::path(
+        "/sys/devices/system/cpu/cpu0/cpufreq/scaling_available_governors",
+    ))
+    .map(|s| s.split_whitespace().map(String::from).collect())
+    .unwrap_or_default()
+}
+
+/// Stand-ins for a governor the cpufreq driver doesn't offer, closest
+/// first. intel_pstate and amd-pstate in active mode only have
+/// performance and powersave, and their powersave still scales.
+fn equivalents(governor: &str) -> &'static [&'static str] {
+    match governor {
+        "schedutil" => &["ondemand", "conservative", "powersave"],
+        "ondemand" => &["schedutil", "conservative", "powersave"],
+        "conservative" => &["schedutil", "ondemand", "powersave"],
+        "powersave" => &["conservative", "schedutil", "ondemand"],
+        "performance" => &["schedutil", "ondemand"],
+        _ => &[],
+    }
+}
+
+/// The governor to set for `wanted`: itself when the driver offers it,
+/// or the list can't be read, else its nearest equivalent
+pub fn resolve_governor(wanted: &str, available: &[String]) -> Option<String> {
+    if available.is_empty() || available.iter().any(|g| g == wanted) {
+        return Some(wanted.to_string());
+    }
+    equivalents(wanted)
+        .iter()
+        .find(|g| available.iter().any(|a| a == *g))
+        .map(|g| g.to_string())
+}
+
+/// Everything a profile sets: the governor, then any power limits and
+/// network tweaks. Link state and Bluetooth follow battery and idle time,
+/// so the app handles those on refresh; brightness goes through the
+/// desktop or backlight, which the app and daemon set themselves.
+///
+/// Returns the governor set in place of the profile's own, when the
+/// driver doesn't offer that one.
+pub fn apply(profile: &Profile) -> Result<Option<String>, String> {
+    let backend = backend::active();
+    let governor = resolve_governor(&profile.governor, &backend.governors()).ok_or_else(|| {
+        format!(
+            "Governor {} isn't available and has no equivalent",
+            profile.governor
+        )
+    })?;
+    backend.set_governor(&governor)?;
+    // After the governor, which resets the hint on intel_pstate
+    if let Some(epp) = profile.epp_target() {
+        if cpufreq::read_epp().as_deref() != Some(epp) {
+            cpufreq::set_epp(epp)?;
+        }
+    }
+    if let Some(on) = profile.turbo {
+        if cpufreq::read_turbo() != Some(on) {
+            cpufreq::set_turbo(on)?;
+        }
+    }
+    if profile.min_freq.is_some() || profile.max_freq.is_some() {
+        cpufreq::set_limits(
+            profile.min_freq.map(|mhz| mhz * 1000),
+            profile.max_freq.map(|mhz| mhz * 1000),
+        )?;
+    }
+    if let Some(wanted) = profile.platform_target() {
+        if platform::read_profile().as_deref() != Some(wanted) {
+            platform::set_profile(wanted)?;
+        }
+    }
+    let limits = [(Limit::Pl1, profile.pl1), (Limit::Pl2, profile.pl2)];
+    if limits.iter().any(|(_, watts)| watts.is_some()) {
+        let package = rapl::package().ok_or("No RAPL package zone for power limits")?;
+        for (limit, watts) in limits {
+            if let Some(watts) = watts {
+                package.set_limit(limit, watts)?;
+            }
+        }
+    }
+    ryzen::set_limits(&profile.ryzen_limits())?;
+    if let Some(on) = profile.eee {
+        netpower::apply_eee(on)?;
+    }
+    swap::apply(&profile.swap_tuning())?;
+    Ok((governor != profile.governor).then_some(governor))
+}
+
+impl Profile {
+    /// The Ryzen limits this profile sets
+    pub fn ryzen_limits(&self) -> Vec<(RyzenLimit, f64)> {
+        [
+            (RyzenLimit::Stapm, self.stapm),
+            (RyzenLimit::Fast, self.fast),
+            (RyzenLimit::Slow, self.slow),
+        ]
+        .into_iter()
+        .filter_map(|(l, w)| Some((l, w?)))
+        .collect()
+    }
+
+    /// The compressed swap settings this profile sets
+    pub fn swap_tuning(&self) -> Tuning<'_> {
+        Tuning {
+            zswap: self.zswap,
+            compressor: self.zswap_compressor.as_deref(),
+            pool: self.zswap_pool,
+            swappiness: self.swappiness,
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use crate::budget::MIN_PACKAGE;
+
+/// Readings kept for the fan line, a couple of minutes at the usual rate
+const SAMPLES: usize = 120;
+
+/// Readings averaged for the load the quiet limit has to keep up with
+const WORKLOAD: usize = 10;
+
+/// Room above the current load, so a busy moment isn't cut short
+const HEADROOM: f64 = 1.25;
+
+/// Package power has to have moved this much before the fan line means
+/// anything
+const SPREAD: f64 = 3.0;
+
+/// Platform profiles that trade speed for a quieter fan curve, best first
+const QUIET_PLATFORMS: [&str; 2] = ["quiet", "low-power"];
+
+/// Package power against the loudest fan, to guess what a lower limit
+/// would do to the noise
+#[derive(Default)]
+pub struct NoiseModel {
+    samples: VecDeque<(f64, Option<u32>)>,
+}
+
+impl NoiseModel {
+    pub fn push(&mut self, watts: f64, rpm: Option<u32>) {
+        if self.samples.len() == SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((watts, rpm));
+    }
+
+    /// Package draw over the last few readings
+    pub fn workload(&self) -> Option<f64> {
+        let recent: Vec<f64> = self
+            .samples
+            .iter()
+            .rev()
+            .take(WORKLOAD)
+            .map(|(w, _)| *w)
+            .collect();
+        (!recent.is_empty()).then(|| recent.iter().sum::<f64>() / recent.len() as f64)
+    }
+
+    /// Fan speed at `watts` from a straight line through the readings;
+    /// None until the power has moved enough to draw one
+    pub fn estimate(&self, watts: f64) -> Option<f64> {
+        let points: Vec<(f64, f64)> = self
+            .samples
+            .iter()
+            .filter_map(|(w, rpm)| Some((*w, (*rpm)? as f64)))
+            .collect();
+        let low = points.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let high = points.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+        if high - low < SPREAD {
+            return None;
+        }
+        let n = points.len() as f64;
+        let mean_w = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_rpm = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let covariance: f64 = points
+            .iter()
+            .map(|(w, rpm)| (w - mean_w) * (rpm - mean_rpm))
+            .sum();
+        let variance: f64 = points.iter().map(|(w, _)| (w - mean_w).powi(2)).sum();
+        let slope = covariance / variance;
+        Some((mean_rpm + slope * (watts - mean_w)).max(0.0))
+    }
+}
+
+/// The quietest settings that still keep up with what's running
+pub struct Suggestion {
+    /// For PL1 and PL2 both, so the package can't burst past it and
+    /// spin the fans up
+    pub limit: f64,
+    /// Load it was worked out from, in watts
+    pub workload: f64,
+    pub platform: Option<String>,
+    pub epp: Option<String>,
+    /// Loudest fan now, and what the line expects at `limit`
+    pub rpm_now: Option<u32>,
+    pub rpm_expected: Option<f64>,
+}
+
+/// None until there's package power to go by. `max` is the highest PL1
+/// the package allows; `platforms` and `epps` what the machine offers.
+pub fn suggest(
+    model: &NoiseModel,
+    rpm_now: Option<u32>,
+    max: Option<f64>,
+    platforms: &[String],
+    epps: &[String],
+) -> Option<Suggestion> {
+    let workload = model.workload()?;
+    let mut limit = (workload * HEADROOM).ceil().max(MIN_PACKAGE);
+    if let Some(max) = max {
+        limit = limit.min(max);
+    }
+    Some(Suggestion {
+        limit,
+        workload,
+        platform: QUIET_PLATFORMS
+            .iter()
+            .find(|q| platforms.iter().any(|p| p == *q))
+            .map(|q| q.to_string()),
+        epp: epps.iter().find(|e| *e == "balance_power").cloned(),
+        rpm_now,
+        // Only worth showing when it's a drop
+        rpm_expected: model
+            .estimate(limit)
+            .filter(|&rpm| rpm_now.is_some_and(|now| rpm < now as f64)),
+    })
+}
+
+/// Quiet settings in force, and what they replaced
+pub struct Quiet {
+    pub limit: f64,
+    pub pl1: Option<f64>,
+    pub pl2: Option<f64>,
+    pub platform: Option<String>,
+    pub epp: Option<String>,
+}
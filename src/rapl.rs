@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::privileged;
+use crate::sysfs;
+
+const POWERCAP: &str = "/sys/class/powercap";
+
+/// Which RAPL constraint a limit applies to
+#[derive(Clone, Copy, PartialEq)]
+pub enum Limit {
+    /// Sustained limit, `long_term`
+    Pl1,
+    /// Turbo burst limit, `short_term`
+    Pl2,
+}
+
+impl Limit {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Limit::Pl1 => "PL1",
+            Limit::Pl2 => "PL2",
+        }
+    }
+
+    fn constraint_name(&self) -> &'static str {
+        match self {
+            Limit::Pl1 => "long_term",
+            Limit::Pl2 => "short_term",
+        }
+    }
+}
+
+/// Zones besides the package with their own energy counter: the cores,
+/// the uncore (integrated graphics), memory, and `psys`, the whole
+/// platform where the firmware reports it
+const DOMAINS: [&str; 4] = ["core", "uncore", "dram", "psys"];
+
+/// A powercap zone measured for its energy only, e.g. `intel-rapl:0:0`
+pub struct Domain {
+    /// `core`, `uncore`, `dram` or `psys`
+    pub name: &'static str,
+    path: PathBuf,
+}
+
+impl Domain {
+    pub fn zone(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// The domains this machine has, in `DOMAINS` order; only the first
+/// socket's on multi-socket machines
+pub fn domains() -> Vec<Domain> {
+    let zones: Vec<PathBuf> = fs::read_dir(sysfs::path(POWERCAP))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    DOMAINS
+        .iter()
+        .filter_map(|name| {
+            let mut matching: Vec<&PathBuf> = zones
+                .iter()
+                .filter(|z| sysfs::read_string(z.join("name")).as_deref() == Some(name))
+                .collect();
+            matching.sort();
+            Some(Domain {
+                name,
+                path: matching.first()?.to_path_buf(),
+            })
+        })
+        .collect()
+}
+
+/// The CPU package's powercap zone, e.g. `intel-rapl:0`
+pub struct Package {
+    path: PathBuf,
+    pub enabled: bool,
+}
+
+pub fn package() -> Option<Package> {
+    let path = fs::read_dir(sysfs::path(POWERCAP))
+        .ok()?
+        .find_map(|entry| {
+            let path = entry.ok()?.path();
+            (sysfs::read_string(path.join("name"))? == "package-0").then_some(path)
+        })?;
+    Some(Package {
+        enabled: sysfs::read_value::<u8>(path.join("enabled")) == Some(1),
+        path,
+    })
+}
+
+impl Package {
+    /// Index of the constraint behind a limit, found by name since the
+    /// numbering isn't fixed across platforms
+    fn constraint(&self, limit: Limit) -> Option<usize> {
+        (0..3).find(|i| {
+            sysfs::read_string(self.path.join(format!("constraint_{}_name", i))).as_deref()
+                == Some(limit.constraint_name())
+        })
+    }
+
+    fn attr(&self, limit: Limit, name: &str) -> Option<PathBuf> {
+        let i = self.constraint(limit)?;
+        Some(self.path.join(format!("constraint_{}_{}", i, name)))
+    }
+
+    /// Current limit in watts
+    pub fn limit(&self, limit: Limit) -> Option<f64> {
+        sysfs::read_value::<f64>(self.attr(limit, "power_limit_uw")?).map(|uw| uw / 1e6)
+    }
+
+    /// Highest limit the firmware accepts, in watts
+    pub fn max(&self, limit: Limit) -> Option<f64> {
+        sysfs::read_value::<f64>(self.attr(limit, "max_power_uw")?)
+            .filter(|uw| *uw > 0.0)
+            .map(|uw| uw / 1e6)
+    }
+
+    pub fn set_limit(&self, limit: Limit, watts: f64) -> Result<(), String> {
+        let path = self
+            .attr(limit, "power_limit_uw")
+            .ok_or_else(|| format!("No {} constraint", limit.label()))?;
+        let watts = match self.max(limit) {
+            Some(max) => watts.min(max),
+            None => watts,
+        };
+        privileged::write(
+            limit.label(),
+            self.limit(limit).map(|w| format!("{:.1} W", w)),
+            &path.to_string_lossy(),
+            &((watts * 1e6) as u64).to_string(),
+        )
+    }
+
+    pub fn zone(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the energy counter can be read; it's root-only since
+    /// Linux 5.10, as power readings can leak what the CPU is computing
+    pub fn energy_readable(&self) -> bool {
+        fs::File::open(self.path.join("energy_uj")).is_ok()
+    }
+}
+
+/// A zone's power from successive energy counter readings
+#[derive(Default)]
+pub struct EnergyMeter {
+    last: Option<(Instant, u64)>,
+}
+
+impl EnergyMeter {
+    /// Average watts since the previous sample of `zone`; `None` on the
+    /// first
+    pub fn sample(&mut self, zone: &Path) -> Option<f64> {
+        let now = Instant::now();
+        let energy: u64 = sysfs::read_value(zone.join("energy_uj"))?;
+        let previous = self.last.replace((now, energy));
+        let (then, before) = previous?;
+        let elapsed = now.duration_since(then).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        // The counter wraps at max_energy_range_uj
+        let delta = if energy >= before {
+            energy - before
+        } else {
+            sysfs::read_value::<u64>(zone.join("max_energy_range_uj"))? - before + energy
+        };
+        Some(delta as f64 / 1e6 / elapsed)
+    }
+}
@@ -0,0 +1,273 @@
+use chrono::{DateTime, Local};
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+
+use crate::config::{self, BagAction, Config};
+use crate::history;
+use crate::locale;
+use crate::profile::load_profiles;
+use crate::rules::{self, Conditions, Rule};
+
+/// Built-in scenarios, by the name `powertui rehearse` takes
+const SCENARIOS: [&str; 4] = ["drain", "unplug", "heat", "bag"];
+
+/// A moment of a scenario: the battery and what the rules see
+struct Step {
+    /// Seconds from the start, or Unix time for a recording
+    at: i64,
+    capacity: Option<u8>,
+    conditions: Conditions,
+}
+
+impl Step {
+    fn new(at: i64, capacity: u8, on_battery: bool) -> Step {
+        Step {
+            at,
+            capacity: Some(capacity),
+            conditions: Conditions {
+                on_battery,
+                load: Some(0.2),
+                temp: Some(45.0),
+                lid_closed: Some(false),
+                external_displays: 0,
+            },
+        }
+    }
+
+    /// `82% on battery · load 0.20 · 45°C · lid open`
+    fn describe(&self) -> String {
+        let c = &self.conditions;
+        let mut parts = Vec::new();
+        let power = if c.on_battery { "on battery" } else { "on AC" };
+        match self.capacity {
+            Some(capacity) => parts.push(format!("{}% {}", capacity, power)),
+            None => parts.push(power.to_string()),
+        }
+        if let Some(load) = c.load {
+            parts.push(format!("load {:.2}", load));
+        }
+        if let Some(temp) = c.temp {
+            parts.push(format!("{:.0}°C", temp));
+        }
+        match c.lid_closed {
+            Some(true) => parts.push("lid closed".to_string()),
+            Some(false) => parts.push("lid open".to_string()),
+            None => {}
+        }
+        parts.join(" · ")
+    }
+}
+
+/// A step every minute for `minutes`, shaped by `at`
+fn synthetic(minutes: i64, at: impl Fn(i64, &mut Step)) -> Vec<Step> {
+    (0..=minutes)
+        .map(|minute| {
+            let mut step = Step::new(minute * 60, 100, false);
+            at(minute, &mut step);
+            step
+        })
+        .collect()
+}
+
+fn scenario(name: &str) -> Option<Vec<Step>> {
+    let steps = match name {
+        // Full to empty at 20%/h on battery
+        "drain" => synthetic(300, |m, s| {
+            s.conditions.on_battery = true;
+            s.capacity = Some((100 - m / 3) as u8);
+        }),
+        // Half an hour on AC, then unplugged and draining 10%/h
+        "unplug" => synthetic(60, |m, s| {
+            s.conditions.on_battery = m >= 30;
+            s.capacity = Some((80 - (m - 30).max(0) / 6) as u8);
+        }),
+        // A build on AC: load and temperature climb for 20 minutes, then
+        // cool off
+        "heat" => synthetic(40, |m, s| {
+            let ramp = if m <= 20 { m } else { 40 - m } as f64 / 20.0;
+            s.conditions.load = Some(0.2 + ramp * 0.9);
+            s.conditions.temp = Some(50.0 + ramp * 48.0);
+        }),
+        // Lid closed on battery after 5 minutes, warming up in a bag
+        "bag" => synthetic(35, |m, s| {
+            s.conditions.on_battery = true;
+            s.capacity = Some((70 - m / 6) as u8);
+            s.conditions.lid_closed = Some(m >= 5);
+            s.conditions.temp = Some(45.0 + (m - 5).max(0) as f64 * 1.4);
+        }),
+        _ => return None,
+    };
+    Some(steps)
+}
+
+/// The battery log as steps; load, temperature and the lid weren't
+/// recorded, so rules on those never match
+fn recorded() -> Vec<Step> {
+    history::read_samples()
+        .into_iter()
+        .map(|s| Step {
+            at: s.at,
+            capacity: Some(s.capacity),
+            conditions: Conditions {
+                on_battery: s.status == "Discharging",
+                load: None,
+                temp: None,
+                lid_closed: None,
+                external_displays: 0,
+            },
+        })
+        .collect()
+}
+
+/// `powertui rehearse <drain|unplug|heat|bag|history>`: run a scenario
+/// through the Automatic rules, the low-battery warning and the bag
+/// check, and print what would happen when. Nothing is changed.
+pub fn run(args: &[String]) -> Result<()> {
+    let [name] = args else {
+        bail!("usage: powertui rehearse <{}|history>", SCENARIOS.join("|"));
+    };
+    let config = config::load().map_err(|e| eyre!(e))?;
+    let rules = if config.rules.is_empty() {
+        rules::default_rules()
+    } else {
+        config.rules.clone()
+    };
+    let (steps, clock) = if name == "history" {
+        let steps = recorded();
+        if steps.is_empty() {
+            bail!("The battery log is empty; it fills while powertui or its daemon runs");
+        }
+        (steps, true)
+    } else {
+        let steps = scenario(name).ok_or_else(|| {
+            eyre!(
+                "unknown scenario {}, expected one of {} or history",
+                name,
+                SCENARIOS.join(", ")
+            )
+        })?;
+        (steps, false)
+    };
+    println!(
+        "Rehearsing {} against {} rules; nothing is changed",
+        name,
+        rules.len()
+    );
+    if clock {
+        println!("The log has no load, temperature or lid, so rules on those won't match");
+    }
+    if name == "bag" && config.bag.is_none() {
+        println!("There's no [bag] section, so only the rules act on a hot closed lid");
+    }
+    let profiles: Vec<String> = load_profiles()
+        .map(|p| p.into_iter().map(|p| p.name).collect())
+        .unwrap_or_default();
+    let start = steps.first().map_or(0, |s| s.at);
+    let mut fired = 0;
+    for (step, actions) in rehearse(&steps, &rules, &config, &profiles) {
+        let time = if clock {
+            DateTime::from_timestamp(step.at, 0)
+                .map(|t| {
+                    let t = t.with_timezone(&Local);
+                    format!("{} {}", locale::date(&t), locale::clock(&t))
+                })
+                .unwrap_or_default()
+        } else {
+            let minutes = (step.at - start) / 60;
+            format!("{}:{:02}", minutes / 60, minutes % 60)
+        };
+        println!("{:>12}  {}", time, step.describe());
+        for action in actions {
+            println!("{:>12}  → {}", "", action);
+            fired += 1;
+        }
+    }
+    if fired == 0 {
+        println!("Nothing would fire");
+    }
+    Ok(())
+}
+
+/// The steps where something would happen, with what. Mirrors the TUI:
+/// a rule acts when it starts matching, the low-battery warning once per
+/// discharge, and the bag check once per closed lid.
+fn rehearse<'a>(
+    steps: &'a [Step],
+    rules: &[Rule],
+    config: &Config,
+    profiles: &[String],
+) -> Vec<(&'a Step, Vec<String>)> {
+    let mut events = Vec::new();
+    let mut active: Option<&Rule> = None;
+    let mut low_warned = false;
+    let mut bag_start: Option<f64> = None;
+    let mut bag_fired = false;
+    for step in steps {
+        let c = &step.conditions;
+        let mut actions = Vec::new();
+
+        let rule = rules::evaluate(rules, c);
+        if rule.map(|r| &r.name) != active.map(|r| &r.name) {
+            if active.is_some_and(|r| r.blank) && !rule.is_some_and(|r| r.blank) {
+                actions.push("turn the displays back on".to_string());
+            }
+            if let Some(rule) = rule {
+                if profiles.is_empty() || profiles.contains(&rule.profile) {
+                    actions.push(format!("rule {}: switch to {}", rule.name, rule.profile));
+                } else {
+                    actions.push(format!(
+                        "rule {}: wants unknown profile {}",
+                        rule.name, rule.profile
+                    ));
+                }
+                if rule.blank {
+                    actions.push(format!("rule {}: turn the displays off", rule.name));
+                }
+                if rule.suspend {
+                    actions.push(format!("rule {}: suspend", rule.name));
+                }
+            }
+            active = rule;
+        }
+
+        match step.capacity {
+            Some(capacity) if c.on_battery && capacity <= config.battery.low => {
+                if !low_warned {
+                    low_warned = true;
+                    actions.push(format!("warn: battery low ({}%)", capacity));
+                }
+            }
+            _ => low_warned = false,
+        }
+
+        if let Some(ref bag) = config.bag {
+            match (c.lid_closed, c.on_battery, c.temp) {
+                (Some(true), true, Some(temp)) => {
+                    let start = *bag_start.get_or_insert(temp);
+                    if !bag_fired && temp >= bag.temp && temp - start >= bag.rise {
+                        bag_fired = true;
+                        let verb = match bag.action {
+                            BagAction::Hibernate => "hibernate",
+                            BagAction::Suspend => "suspend",
+                        };
+                        actions.push(format!(
+                            "bag: {} (CPU at {:.0}°C, up {:.0}°C)",
+                            verb,
+                            temp,
+                            temp - start
+                        ));
+                    }
+                }
+                _ => {
+                    bag_start = None;
+                    bag_fired = false;
+                }
+            }
+        }
+
+        if !actions.is_empty() {
+            events.push((step, actions));
+        }
+    }
+    events
+}
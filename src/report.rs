@@ -0,0 +1,614 @@
+use std::fs;
+use std::io::{stderr, Write};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+
+use crate::battery::{self, read_batteries, read_battery_info, BatteryInfo};
+use crate::cpufreq::{self, Policy};
+use crate::history::{self, HealthSample, Sample};
+use crate::locale;
+use crate::profile::{load_profiles, read_current_governor, Profile};
+use crate::sessions::{self, Session};
+use crate::sysfs;
+use crate::usage;
+
+const DEFAULT_DURATION: u64 = 20;
+
+/// A longer gap between logged samples breaks the charge history line
+const GAP: i64 = 30 * 60;
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Html,
+    Markdown,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Option<Format> {
+        match s {
+            "html" => Some(Format::Html),
+            "md" | "markdown" => Some(Format::Markdown),
+            _ => None,
+        }
+    }
+
+    /// Markdown goes to stdout by default so it can be piped into a
+    /// clipboard tool or `gh issue create --body-file -`
+    fn default_output(&self) -> &'static str {
+        match self {
+            Format::Html => "powertui-report.html",
+            Format::Markdown => "-",
+        }
+    }
+}
+
+struct Report {
+    /// When sampling began and ended
+    started: DateTime<Local>,
+    generated: DateTime<Local>,
+    hostname: String,
+    kernel: String,
+    governor: Option<String>,
+    profiles: Vec<ProfileUse>,
+    battery: Option<BatteryInfo>,
+    policies: Vec<Policy>,
+    /// Power draw in watts, one sample per second
+    samples: Vec<f64>,
+    /// The last week of the battery log
+    history: Vec<Sample>,
+    health: Vec<HealthSample>,
+}
+
+/// What the logs say about one profile
+struct ProfileUse {
+    name: String,
+    governor: String,
+    /// Seconds in effect over the last week
+    week: u64,
+    /// Average draw over those hours
+    power: Option<f64>,
+    /// Hours a full charge lasts at the rate they drained it
+    runtime: Option<f64>,
+}
+
+impl ProfileUse {
+    fn new(profile: Profile, week: &[usage::Day], sessions: &[Session]) -> Self {
+        let runs: Vec<&Session> = sessions
+            .iter()
+            .filter(|s| s.profile.as_ref() == Some(&profile.name))
+            .collect();
+        let hours: f64 = runs.iter().map(|s| (s.end - s.start) as f64 / 3600.0).sum();
+        let drop: f64 = runs.iter().map(|s| (s.from - s.to) as f64).sum();
+        let power: Vec<f64> = runs.iter().filter_map(|s| s.power).collect();
+        ProfileUse {
+            week: week
+                .iter()
+                .filter_map(|day| day.totals.get(&profile.name))
+                .sum(),
+            power: (!power.is_empty()).then(|| power.iter().sum::<f64>() / power.len() as f64),
+            runtime: (drop > 0.0).then(|| hours * 100.0 / drop),
+            name: profile.name,
+            governor: profile.governor,
+        }
+    }
+
+    fn format_power(&self) -> Option<String> {
+        self.power.map(|p| format!("{:.2} W", p))
+    }
+
+    fn format_runtime(&self) -> Option<String> {
+        self.runtime.map(|h| locale::duration((h * 3600.0) as u64))
+    }
+}
+
+/// `powertui report --html|--format html|md [-o FILE] [--duration SECS]`
+pub fn run(args: &[String]) -> Result<()> {
+    let mut format = None;
+    let mut output = None;
+    let mut duration = DEFAULT_DURATION;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--html" => format = Some(Format::Html),
+            "--format" => {
+                format = Some(
+                    args.next()
+                        .and_then(|f| Format::parse(f))
+                        .ok_or_else(|| eyre!("--format needs html or md"))?,
+                );
+            }
+            "-o" | "--output" => {
+                output = Some(
+                    args.next()
+                        .ok_or_else(|| eyre!("{} needs a file name", arg))?
+                        .clone(),
+                );
+            }
+            "--duration" => {
+                duration = args
+                    .next()
+                    .and_then(|d| d.parse().ok())
+                    .ok_or_else(|| eyre!("--duration needs a number of seconds"))?;
+            }
+            _ => bail!("unknown report option: {}", arg),
+        }
+    }
+    let Some(format) = format else {
+        bail!("choose a report format: --html or --format md");
+    };
+    let output = output.unwrap_or_else(|| format.default_output().to_string());
+
+    let report = collect(duration);
+    let rendered = match format {
+        Format::Html => render_html(&report),
+        Format::Markdown => render_markdown(&report),
+    };
+    if output == "-" {
+        print!("{}", rendered);
+    } else {
+        fs::write(&output, rendered)?;
+        eprintln!("Report written to {}", output);
+    }
+    Ok(())
+}
+
+/// Sample power draw like `powertop --html` does, so the report shows
+/// what the machine does under its current load rather than one reading
+fn collect(duration: u64) -> Report {
+    let started = Local::now();
+    let mut samples = Vec::new();
+    for i in 0..duration {
+        eprint!("\rMeasuring power draw... {}/{}s", i + 1, duration);
+        let _ = stderr().flush();
+        if let Some(power) = battery::combined(&read_batteries()).and_then(|b| b.power) {
+            samples.push(power);
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+    eprintln!();
+
+    let week = usage::read_week();
+    let sessions = sessions::read_sessions();
+    Report {
+        started,
+        generated: Local::now(),
+        hostname: sysfs::read_string("/proc/sys/kernel/hostname")
+            .unwrap_or_else(|| "unknown".to_string()),
+        kernel: sysfs::read_string("/proc/sys/kernel/osrelease")
+            .unwrap_or_else(|| "unknown".to_string()),
+        governor: read_current_governor(),
+        profiles: load_profiles()
+            .unwrap_or_else(|_| Profile::builtin())
+            .into_iter()
+            .map(|p| ProfileUse::new(p, &week, &sessions))
+            .collect(),
+        battery: read_battery_info(),
+        policies: cpufreq::read_policies(),
+        samples,
+        history: history::read_samples(),
+        health: history::read_health(),
+    }
+}
+
+fn render_html(report: &Report) -> String {
+    let mut body = String::new();
+
+    body.push_str(&format!(
+        "<h1>powertui report</h1>\n<p class=\"meta\">{} &middot; Linux {} &middot; {}</p>\n",
+        escape(&report.hostname),
+        escape(&report.kernel),
+        format_timestamp(&report.generated)
+    ));
+
+    // Battery
+    body.push_str("<h2>Battery</h2>\n");
+    match report.battery {
+        Some(ref bat) => {
+            body.push_str("<table>\n");
+            row(&mut body, "Charge", &format!("{}%", bat.capacity));
+            row(&mut body, "Status", &escape(&bat.status));
+            if let Some(ref t) = bat.time_remaining {
+                row(&mut body, "Time", &escape(t));
+            }
+            if let Some(h) = bat.health {
+                row(&mut body, "Health", &format!("{}%", h));
+            }
+            if let (Some(full), Some(design)) = (bat.energy_full, bat.energy_full_design) {
+                row(
+                    &mut body,
+                    "Capacity",
+                    &format!("{:.1} Wh of {:.1} Wh design", full, design),
+                );
+            }
+            body.push_str("</table>\n");
+            if let Some(h) = bat.health {
+                body.push_str(&format!(
+                    "<div class=\"bar\"><div style=\"width:{}%\"></div></div>\n",
+                    h.min(100)
+                ));
+            }
+        }
+        None => body.push_str("<p>No battery found.</p>\n"),
+    }
+
+    // From the logs the TUI and daemon keep
+    body.push_str("<h2>Charge history</h2>\n");
+    if report.history.len() < 2 {
+        body.push_str("<p>Nothing logged in the last week.</p>\n");
+    } else {
+        body.push_str(&charge_chart(report));
+    }
+
+    body.push_str("<h2>Health trend</h2>\n");
+    match (report.health.first(), report.health.last()) {
+        (Some(first), Some(last)) if report.health.len() > 1 => {
+            body.push_str(&health_chart(&report.health));
+            body.push_str(&format!(
+                "<p>{}% on {} &middot; {}% on {}</p>\n",
+                first.health,
+                format_day(first.date),
+                last.health,
+                format_day(last.date)
+            ));
+        }
+        _ => body.push_str("<p>Health is logged once a day; there's no trend yet.</p>\n"),
+    }
+
+    // Power draw over the measurement window
+    body.push_str("<h2>Power draw</h2>\n");
+    if report.samples.is_empty() {
+        body.push_str("<p>No power readings available.</p>\n");
+    } else {
+        body.push_str(&power_chart(report));
+        let (min, avg, max) = stats(&report.samples);
+        body.push_str(&format!(
+            "<p>{} samples &middot; min {:.2} W &middot; avg {:.2} W &middot; max {:.2} W</p>\n",
+            report.samples.len(),
+            min,
+            avg,
+            max
+        ));
+    }
+
+    // Consumption per profile, from the recorded sessions on battery
+    body.push_str("<h2>Consumption by profile</h2>\n<table>\n");
+    body.push_str(
+        "<tr><th>Profile</th><th>Governor</th><th>Time this week</th>\
+         <th>Average draw</th><th>Full charge lasts</th></tr>\n",
+    );
+    let most_time = report
+        .profiles
+        .iter()
+        .map(|p| p.week)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let most_power = report
+        .profiles
+        .iter()
+        .filter_map(|p| p.power)
+        .fold(0.0, f64::max)
+        .max(f64::EPSILON);
+    for profile in &report.profiles {
+        let active = report.governor.as_ref() == Some(&profile.governor);
+        let draw = match profile.format_power() {
+            Some(power) => format!(
+                "{}{}",
+                bar(profile.power.unwrap_or(0.0) / most_power),
+                power
+            ),
+            None => "&ndash;".to_string(),
+        };
+        body.push_str(&format!(
+            "<tr><td>{}{}</td><td>{}</td><td>{}{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&profile.name),
+            if active { " (active)" } else { "" },
+            escape(&profile.governor),
+            bar(profile.week as f64 / most_time as f64),
+            locale::duration(profile.week),
+            draw,
+            profile
+                .format_runtime()
+                .unwrap_or_else(|| "&ndash;".to_string())
+        ));
+    }
+    body.push_str("</table>\n");
+
+    // Driver context, so a report answers "which cpufreq driver?"
+    body.push_str("<h2>CPU frequency scaling</h2>\n");
+    if report.policies.is_empty() {
+        body.push_str("<p>No cpufreq driver loaded.</p>\n");
+    } else {
+        body.push_str(
+            "<table>\n<tr><th>CPUs</th><th>Driver</th><th>Governor</th>\
+             <th>Range</th><th>Limits</th><th>Steps</th><th>Latency</th></tr>\n",
+        );
+        for p in &report.policies {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                p.cpu_list(),
+                escape(&p.driver),
+                escape(p.governor.as_deref().unwrap_or("?")),
+                p.range(),
+                p.limits(),
+                p.steps(),
+                p.format_latency()
+            ));
+        }
+        body.push_str(&format!(
+            "</table>\n<p>Available governors: {}</p>\n",
+            escape(&report.policies[0].governors.join(" "))
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>powertui report - {}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(&report.hostname),
+        STYLE,
+        body
+    )
+}
+
+fn render_markdown(report: &Report) -> String {
+    let mut md = format!(
+        "## powertui report\n\n{} · Linux {} · {}\n\n",
+        report.hostname,
+        report.kernel,
+        format_timestamp(&report.generated)
+    );
+
+    md.push_str("### Battery\n\n");
+    match report.battery {
+        Some(ref bat) => {
+            md.push_str("| | |\n|---|---|\n");
+            md.push_str(&format!("| Charge | {}% |\n", bat.capacity));
+            md.push_str(&format!("| Status | {} |\n", bat.status));
+            if let Some(ref t) = bat.time_remaining {
+                md.push_str(&format!("| Time | {} |\n", t));
+            }
+            if let Some(h) = bat.health {
+                md.push_str(&format!("| Health | {}% |\n", h));
+            }
+            if let (Some(full), Some(design)) = (bat.energy_full, bat.energy_full_design) {
+                md.push_str(&format!(
+                    "| Capacity | {:.1} Wh of {:.1} Wh design |\n",
+                    full, design
+                ));
+            }
+            if let (Some(first), Some(last)) = (report.health.first(), report.health.last()) {
+                if report.health.len() > 1 {
+                    md.push_str(&format!(
+                        "| Health trend | {}% on {} → {}% on {} |\n",
+                        first.health,
+                        format_day(first.date),
+                        last.health,
+                        format_day(last.date)
+                    ));
+                }
+            }
+        }
+        None => md.push_str("No battery found.\n"),
+    }
+
+    md.push_str("\n### Power draw\n\n");
+    if report.samples.is_empty() {
+        md.push_str("No power readings available.\n");
+    } else {
+        let (min, avg, max) = stats(&report.samples);
+        md.push_str("| Samples | Min | Avg | Max |\n|---|---|---|---|\n");
+        md.push_str(&format!(
+            "| {} | {:.2} W | {:.2} W | {:.2} W |\n",
+            report.samples.len(),
+            min,
+            avg,
+            max
+        ));
+    }
+
+    md.push_str("\n### Consumption by profile\n\n");
+    md.push_str("| Profile | Governor | Time this week | Average draw | Full charge lasts |\n");
+    md.push_str("|---|---|---|---|---|\n");
+    for profile in &report.profiles {
+        let active = report.governor.as_ref() == Some(&profile.governor);
+        md.push_str(&format!(
+            "| {}{} | `{}` | {} | {} | {} |\n",
+            profile.name,
+            if active { " (active)" } else { "" },
+            profile.governor,
+            locale::duration(profile.week),
+            profile.format_power().unwrap_or_else(|| "–".to_string()),
+            profile.format_runtime().unwrap_or_else(|| "–".to_string())
+        ));
+    }
+
+    md.push_str("\n### CPU frequency scaling\n\n");
+    if report.policies.is_empty() {
+        md.push_str("No cpufreq driver loaded.\n");
+    } else {
+        md.push_str("| CPUs | Driver | Governor | Range | Limits | Steps | Latency |\n");
+        md.push_str("|---|---|---|---|---|---|---|\n");
+        for p in &report.policies {
+            md.push_str(&format!(
+                "| {} | `{}` | `{}` | {} | {} | {} | {} |\n",
+                p.cpu_list(),
+                p.driver,
+                p.governor.as_deref().unwrap_or("?"),
+                p.range(),
+                p.limits(),
+                p.steps(),
+                p.format_latency()
+            ));
+        }
+        md.push_str(&format!(
+            "\nAvailable governors: `{}`\n",
+            report.policies[0].governors.join(" ")
+        ));
+    }
+
+    md
+}
+
+pub const STYLE: &str = "body{font-family:sans-serif;max-width:760px;margin:2em auto;color:#222}\
+h1{margin-bottom:0}.meta{color:#777;margin-top:.2em}\
+table{border-collapse:collapse;margin:.5em 0}td,th{padding:.25em 1em .25em 0;text-align:left}\
+th{border-bottom:1px solid #ccc}\
+.bar{background:#eee;height:10px;width:300px;border-radius:5px;overflow:hidden}\
+.bar div{background:#4caf50;height:100%}\
+td .bar{display:inline-block;width:100px;margin-right:.5em}\
+svg{background:#fafafa;border:1px solid #ddd}polyline{fill:none;stroke:#1e88e5;stroke-width:2}";
+
+fn row(body: &mut String, label: &str, value: &str) {
+    body.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", label, value));
+}
+
+/// A short bar for a table cell, `fraction` of the way full
+fn bar(fraction: f64) -> String {
+    format!(
+        "<div class=\"bar\"><div style=\"width:{:.0}%\"></div></div>",
+        (fraction * 100.0).clamp(0.0, 100.0)
+    )
+}
+
+/// Lines through points given as fractions of the plot, 0,0 bottom
+/// left, with the top of the scale and the times at either end
+fn line_chart(lines: &[Vec<(f64, f64)>], top: &str, start: &str, end: &str) -> String {
+    const WIDTH: f64 = 720.0;
+    const HEIGHT: f64 = 200.0;
+    const AXIS: f64 = 16.0;
+
+    let polylines: String = lines
+        .iter()
+        .map(|line| {
+            let points: Vec<String> = line
+                .iter()
+                .map(|(x, y)| {
+                    format!(
+                        "{:.1},{:.1}",
+                        x * WIDTH,
+                        HEIGHT - y.clamp(0.0, 1.0) * (HEIGHT - 10.0)
+                    )
+                })
+                .collect();
+            format!("<polyline points=\"{}\"/>", points.join(" "))
+        })
+        .collect();
+
+    format!(
+        "<svg width=\"{w}\" height=\"{svg_h}\" viewBox=\"0 0 {w} {svg_h}\">\
+         <text x=\"4\" y=\"14\" font-size=\"12\" fill=\"#777\">{top}</text>\
+         {polylines}\
+         <text x=\"4\" y=\"{axis_y}\" font-size=\"12\" fill=\"#777\">{start}</text>\
+         <text x=\"{end_x}\" y=\"{axis_y}\" font-size=\"12\" fill=\"#777\" \
+         text-anchor=\"end\">{end}</text></svg>\n",
+        w = WIDTH,
+        svg_h = HEIGHT + AXIS,
+        top = top,
+        polylines = polylines,
+        axis_y = HEIGHT + AXIS - 4.0,
+        start = start,
+        end_x = WIDTH - 4.0,
+        end = end,
+    )
+}
+
+/// Draw over the sampling window, with its start and end times below
+fn power_chart(report: &Report) -> String {
+    let samples = &report.samples;
+    let (_, _, max) = stats(samples);
+    let max = max.max(1.0);
+    let step = if samples.len() > 1 {
+        1.0 / (samples.len() - 1) as f64
+    } else {
+        0.0
+    };
+    let points = samples
+        .iter()
+        .enumerate()
+        .map(|(i, w)| (i as f64 * step, w / max))
+        .collect();
+    line_chart(
+        &[points],
+        &format!("{:.1} W", max),
+        &locale::clock_seconds(&report.started),
+        &locale::clock_seconds(&report.generated),
+    )
+}
+
+/// Charge over the week the battery log keeps, broken where nothing
+/// was logged
+fn charge_chart(report: &Report) -> String {
+    let end = report.generated.timestamp();
+    let start = end - history::KEEP;
+    let mut lines: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut last = None;
+    for sample in &report.history {
+        if last.is_none_or(|at| sample.at - at > GAP) {
+            lines.push(Vec::new());
+        }
+        last = Some(sample.at);
+        if let Some(line) = lines.last_mut() {
+            line.push((
+                (sample.at - start) as f64 / history::KEEP as f64,
+                sample.capacity as f64 / 100.0,
+            ));
+        }
+    }
+    let start = DateTime::from_timestamp(start, 0).map(|t| t.with_timezone(&Local));
+    line_chart(
+        &lines,
+        "100%",
+        &start.map(|t| locale::date(&t)).unwrap_or_default(),
+        &locale::date(&report.generated),
+    )
+}
+
+/// Health from the first logged day to the last, on a 0-100% scale so
+/// a percent or two of wear looks like what it is
+fn health_chart(health: &[HealthSample]) -> String {
+    let (first, last) = (health[0].date, health[health.len() - 1].date);
+    let days = (last - first).num_days().max(1) as f64;
+    let points = health
+        .iter()
+        .map(|s| {
+            (
+                (s.date - first).num_days() as f64 / days,
+                s.health as f64 / 100.0,
+            )
+        })
+        .collect();
+    line_chart(&[points], "100%", &format_day(first), &format_day(last))
+}
+
+fn stats(samples: &[f64]) -> (f64, f64, f64) {
+    let min = samples.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let avg = samples.iter().sum::<f64>() / samples.len() as f64;
+    (min, avg, max)
+}
+
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A logged day in the user's date format
+fn format_day(date: NaiveDate) -> String {
+    date.and_time(NaiveTime::MIN)
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|t| locale::date(&t))
+        .unwrap_or_else(|| date.to_string())
+}
+
+/// Local time with its UTC offset, since reports get passed around
+pub fn format_timestamp(time: &DateTime<Local>) -> String {
+    format!("{} {}", locale::date_time(time), time.format("%:z"))
+}
@@ -0,0 +1,65 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::privileged;
+
+/// Follows logind's `PrepareForSleep` signal, sent with `true` before
+/// suspend or hibernation and `false` once the machine is back
+pub struct SleepMonitor {
+    child: Child,
+    resumes: Receiver<()>,
+}
+
+impl SleepMonitor {
+    /// gdbus subscribes to the signal like any client, where busctl and
+    /// dbus-monitor would need root to watch the system bus
+    pub fn start() -> Option<SleepMonitor> {
+        let mut child = Command::new("gdbus")
+            .args(["monitor", "--system", "--dest", "org.freedesktop.login1"])
+            .args(["--object-path", "/org/freedesktop/login1"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let stdout = child.stdout.take()?;
+        let (tx, resumes) = mpsc::channel();
+        // The reader ends with gdbus, when the monitor is dropped
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                // /org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (false,)
+                if line.contains(".PrepareForSleep (false") && tx.send(()).is_err() {
+                    break;
+                }
+            }
+        });
+        Some(SleepMonitor { child, resumes })
+    }
+
+    /// Whether the machine woke up since the last call
+    pub fn resumed(&self) -> bool {
+        self.resumes.try_iter().count() > 0
+    }
+}
+
+impl Drop for SleepMonitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Run the `[resume] hooks` through `sh -c` as the user, each audited
+/// like any other change. Returns what failed.
+pub fn run_hooks(hooks: &[String]) -> Vec<String> {
+    hooks
+        .iter()
+        .filter_map(|hook| {
+            privileged::run_unprivileged("Resume hook", None, hook, "sh", &["-c", hook])
+                .map_err(|e| format!("{}: {}", hook, e))
+                .err()
+        })
+        .collect()
+}
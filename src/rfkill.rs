@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::privileged;
+use crate::sysfs;
+
+/// A radio switch from /sys/class/rfkill
+pub struct Radio {
+    pub path: PathBuf,
+    pub name: String,
+    pub kind: String,
+    pub soft_blocked: bool,
+}
+
+pub fn read_radios() -> Vec<Radio> {
+    let Ok(entries) = fs::read_dir(sysfs::path("/sys/class/rfkill")) else {
+        return Vec::new();
+    };
+    let mut radios: Vec<Radio> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            Some(Radio {
+                name: sysfs::read_string(path.join("name"))?,
+                kind: sysfs::read_string(path.join("type"))?,
+                soft_blocked: sysfs::read_value::<u8>(path.join("soft"))? == 1,
+                path,
+            })
+        })
+        .collect();
+    radios.sort_by(|a, b| a.path.cmp(&b.path));
+    radios
+}
+
+/// Soft-block or unblock a radio, the same switch `rfkill block` flips
+pub fn set_blocked(radio: &Radio, blocked: bool) -> Result<(), String> {
+    let old = if radio.soft_blocked { "1" } else { "0" };
+    let new = if blocked { "1" } else { "0" };
+    privileged::write(
+        &format!("rfkill {} ({})", radio.name, radio.kind),
+        Some(old.to_string()),
+        &radio.path.join("soft").to_string_lossy(),
+        new,
+    )
+}
@@ -0,0 +1,118 @@
+use std::thread;
+
+use serde::Deserialize;
+
+use crate::battery::BatteryInfo;
+use crate::lid;
+use crate::sysfs;
+use crate::thermal;
+
+/// A `[[rule]]` from config.toml. Every condition given must hold for
+/// the rule to match; rules are tried in order and the first match wins.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub name: String,
+    /// Name of the profile to switch to
+    pub profile: String,
+    pub on_battery: Option<bool>,
+    /// 1-minute load average per CPU, e.g. 0.75
+    pub min_load: Option<f64>,
+    /// CPU temperature in °C
+    pub min_temp: Option<f64>,
+    pub lid_closed: Option<bool>,
+    /// Whether any display besides the built-in panel is connected
+    pub external_display: Option<bool>,
+    /// Suspend once when this rule starts matching
+    #[serde(default)]
+    pub suspend: bool,
+    /// Turn the displays off once when this rule starts matching, and
+    /// back on when it stops
+    #[serde(default)]
+    pub blank: bool,
+}
+
+impl Rule {
+    fn new(name: &str, profile: &str) -> Rule {
+        Rule {
+            name: name.to_string(),
+            profile: profile.to_string(),
+            on_battery: None,
+            min_load: None,
+            min_temp: None,
+            lid_closed: None,
+            external_display: None,
+            suspend: false,
+            blank: false,
+        }
+    }
+
+    fn matches(&self, c: &Conditions) -> bool {
+        self.on_battery.is_none_or(|b| b == c.on_battery)
+            && self
+                .min_load
+                .is_none_or(|min| c.load.is_some_and(|l| l >= min))
+            && self
+                .min_temp
+                .is_none_or(|min| c.temp.is_some_and(|t| t >= min))
+            && self.lid_closed.is_none_or(|l| c.lid_closed == Some(l))
+            && self
+                .external_display
+                .is_none_or(|e| e == (c.external_displays > 0))
+    }
+}
+
+/// Used when config.toml defines no rules
+pub fn default_rules() -> Vec<Rule> {
+    vec![
+        Rule {
+            min_temp: Some(90.0),
+            ..Rule::new("Running hot", "Power Saver")
+        },
+        Rule {
+            on_battery: Some(true),
+            ..Rule::new("On battery", "Power Saver")
+        },
+        Rule {
+            min_load: Some(0.75),
+            ..Rule::new("Heavy load on AC", "Performance")
+        },
+        Rule::new("On AC", "Balanced"),
+    ]
+}
+
+/// What the rules are evaluated against
+pub struct Conditions {
+    pub on_battery: bool,
+    pub load: Option<f64>,
+    pub temp: Option<f64>,
+    /// None without a lid
+    pub lid_closed: Option<bool>,
+    pub external_displays: usize,
+}
+
+impl Conditions {
+    pub fn read(battery: Option<&BatteryInfo>) -> Conditions {
+        Conditions {
+            on_battery: battery.is_some_and(|b| b.status == "Discharging"),
+            load: read_load(),
+            temp: thermal::read_cpu_temp(),
+            lid_closed: lid::read_lid_closed(),
+            external_displays: lid::connected_displays()
+                .iter()
+                .filter(|d| !d.internal)
+                .count(),
+        }
+    }
+}
+
+fn read_load() -> Option<f64> {
+    let loadavg = sysfs::read_string("/proc/loadavg")?;
+    let one: f64 = loadavg.split_whitespace().next()?.parse().ok()?;
+    let cpus = thread::available_parallelism().ok()?.get();
+    Some(one / cpus as f64)
+}
+
+pub fn evaluate<'a>(rules: &'a [Rule], conditions: &Conditions) -> Option<&'a Rule> {
+    rules.iter().find(|r| r.matches(conditions))
+}
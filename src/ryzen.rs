@@ -0,0 +1,116 @@
+use std::process::Command;
+
+use crate::desktop;
+use crate::privileged;
+use crate::sysfs;
+
+/// Mobile Ryzen platform power limits, set through `ryzenadj`. The
+/// governor barely moves power on these chips; these limits do.
+#[derive(Clone, Copy, PartialEq)]
+pub enum RyzenLimit {
+    /// Skin-temperature-aware sustained limit
+    Stapm,
+    /// Short boost (PPT fast)
+    Fast,
+    /// Longer boost (PPT slow)
+    Slow,
+}
+
+impl RyzenLimit {
+    pub const ALL: [RyzenLimit; 3] = [RyzenLimit::Stapm, RyzenLimit::Fast, RyzenLimit::Slow];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RyzenLimit::Stapm => "STAPM",
+            RyzenLimit::Fast => "Fast",
+            RyzenLimit::Slow => "Slow",
+        }
+    }
+
+    /// `ryzenadj` option, taking milliwatts
+    fn param(&self) -> &'static str {
+        match self {
+            RyzenLimit::Stapm => "stapm-limit",
+            RyzenLimit::Fast => "fast-limit",
+            RyzenLimit::Slow => "slow-limit",
+        }
+    }
+
+    /// Rows of `ryzenadj -i` holding the limit and the current value
+    fn rows(&self) -> (&'static str, &'static str) {
+        match self {
+            RyzenLimit::Stapm => ("STAPM LIMIT", "STAPM VALUE"),
+            RyzenLimit::Fast => ("PPT LIMIT FAST", "PPT VALUE FAST"),
+            RyzenLimit::Slow => ("PPT LIMIT SLOW", "PPT VALUE SLOW"),
+        }
+    }
+}
+
+/// Limit and current value in watts for each limit
+pub struct RyzenStatus {
+    pub limits: Vec<(RyzenLimit, Option<f64>, Option<f64>)>,
+}
+
+/// An AMD CPU with `ryzenadj` installed
+pub fn available() -> bool {
+    desktop::command_exists("ryzenadj")
+        && sysfs::read_string("/proc/cpuinfo").is_some_and(|i| i.contains("AuthenticAMD"))
+}
+
+/// Reading the SMU needs root, so escalate when not running as root.
+/// `None` when the table can't be read.
+pub fn read_status() -> Option<RyzenStatus> {
+    Some(parse_status(&read_table()?))
+}
+
+/// Raw `ryzenadj -i` output
+pub fn read_table() -> Option<String> {
+    let output = Command::new("ryzenadj")
+        .arg("-i")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| o.stdout)
+        .or_else(|| privileged::output("ryzenadj", &["-i"]).ok())?;
+    Some(String::from_utf8_lossy(&output).into_owned())
+}
+
+pub fn parse_status(table: &str) -> RyzenStatus {
+    // | STAPM LIMIT         |    15.000 | stapm-limit        |
+    let row = |name: &str| {
+        table.lines().find_map(|line| {
+            let mut cells = line.split('|').map(str::trim).skip(1);
+            (cells.next()? == name).then_some(())?;
+            cells.next()?.parse::<f64>().ok()
+        })
+    };
+    RyzenStatus {
+        limits: RyzenLimit::ALL
+            .iter()
+            .map(|l| {
+                let (limit, value) = l.rows();
+                (*l, row(limit), row(value))
+            })
+            .collect(),
+    }
+}
+
+/// Apply the given limits in one `ryzenadj` call
+pub fn set_limits(limits: &[(RyzenLimit, f64)]) -> Result<(), String> {
+    if limits.is_empty() {
+        return Ok(());
+    }
+    let args: Vec<String> = limits
+        .iter()
+        .map(|(l, watts)| format!("--{}={}", l.param(), (watts * 1000.0) as u64))
+        .collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let new = limits
+        .iter()
+        .map(|(l, w)| format!("{} {:.0} W", l.label(), w))
+        .collect::<Vec<_>>()
+        .join(", ");
+    // Reading the old values would need another root call; the audit
+    // log has the previous write
+    privileged::run("ryzen limits", None, &new, "ryzenadj", &args)
+}
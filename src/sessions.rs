@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::battery::BatteryInfo;
+use crate::config;
+
+/// Shorter runs say more about the moment than the kernel
+const MIN_SECONDS: i64 = 15 * 60;
+const MIN_DROP: u8 = 5;
+
+/// A longer gap between readings means the machine slept or powertui
+/// wasn't running, and the session ends at the last reading
+const GAP: i64 = 10 * 60;
+
+/// One stretch on battery, tagged with what it ran
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    /// Unix times
+    pub start: i64,
+    pub end: i64,
+    /// Charge at either end, in percent
+    pub from: u8,
+    pub to: u8,
+    /// Average draw in watts, where the battery reports it
+    pub power: Option<f64>,
+    /// `uname -r`
+    pub kernel: String,
+    /// Profile in effect for most of it
+    pub profile: Option<String>,
+}
+
+impl Session {
+    fn hours(&self) -> f64 {
+        (self.end - self.start) as f64 / 3600.0
+    }
+}
+
+fn sessions_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("sessions.jsonl"))
+}
+
+/// Every recorded session, oldest first
+pub fn read_sessions() -> Vec<Session> {
+    let Some(text) = sessions_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn append(session: &Session) -> Option<()> {
+    let path = sessions_path()?;
+    fs::create_dir_all(path.parent()?).ok()?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .ok()?;
+    writeln!(file, "{}", serde_json::to_string(session).ok()?).ok()
+}
+
+fn kernel() -> String {
+    fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|k| k.trim().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// A session still running
+struct Open {
+    start: i64,
+    from: u8,
+    /// The latest reading
+    at: i64,
+    capacity: u8,
+    power: Vec<f64>,
+    /// Seconds in each profile
+    profiles: BTreeMap<String, i64>,
+}
+
+/// Follows the battery from reading to reading and appends each
+/// discharge long enough to go by to `sessions.jsonl`
+#[derive(Default)]
+pub struct Recorder {
+    open: Option<Open>,
+}
+
+impl Recorder {
+    pub fn update(&mut self, battery: Option<&BatteryInfo>, profile: Option<&str>) {
+        let now = Local::now().timestamp();
+        if self.open.as_ref().is_some_and(|o| now - o.at > GAP) {
+            self.finish();
+        }
+        let Some(battery) = battery.filter(|b| b.status == "Discharging") else {
+            return self.finish();
+        };
+        let open = self.open.get_or_insert_with(|| Open {
+            start: now,
+            from: battery.capacity,
+            at: now,
+            capacity: battery.capacity,
+            power: Vec::new(),
+            profiles: BTreeMap::new(),
+        });
+        if let Some(profile) = profile {
+            *open.profiles.entry(profile.to_string()).or_default() += now - open.at;
+        }
+        open.at = now;
+        open.capacity = battery.capacity;
+        open.power.extend(battery.power);
+    }
+
+    /// End the running session, keeping it if it's long enough
+    pub fn finish(&mut self) {
+        let Some(open) = self.open.take() else {
+            return;
+        };
+        if open.at - open.start < MIN_SECONDS || open.from < open.capacity + MIN_DROP {
+            return;
+        }
+        let _ = append(&Session {
+            start: open.start,
+            end: open.at,
+            from: open.from,
+            to: open.capacity,
+            power: (!open.power.is_empty())
+                .then(|| open.power.iter().sum::<f64>() / open.power.len() as f64),
+            kernel: kernel(),
+            profile: open
+                .profiles
+                .into_iter()
+                .max_by_key(|(_, seconds)| *seconds)
+                .map(|(name, _)| name),
+        });
+    }
+
+    /// Forget the running session without recording it, while another
+    /// process records instead
+    pub fn abandon(&mut self) {
+        self.open = None;
+    }
+}
+
+/// Sessions of one kernel and profile taken together
+pub struct Group {
+    pub kernel: String,
+    pub profile: Option<String>,
+    pub sessions: usize,
+    pub hours: f64,
+    /// Hours a full charge lasts at the rate these sessions drained it
+    pub runtime: f64,
+    pub power: Option<f64>,
+    /// Against the same profile on the kernel before, as a fraction
+    pub change: Option<f64>,
+}
+
+/// One group per kernel and profile, newest kernel first
+pub fn compare(sessions: &[Session]) -> Vec<Group> {
+    // Kernels in the order they were last used
+    let mut kernels: Vec<(&str, i64)> = Vec::new();
+    for session in sessions {
+        match kernels.iter_mut().find(|(k, _)| *k == session.kernel) {
+            Some((_, last)) => *last = (*last).max(session.end),
+            None => kernels.push((&session.kernel, session.end)),
+        }
+    }
+    kernels.sort_by_key(|(_, last)| -last);
+
+    let mut groups: Vec<Group> = Vec::new();
+    for (kernel, _) in &kernels {
+        let mut profiles: Vec<Option<&String>> = sessions
+            .iter()
+            .filter(|s| s.kernel == *kernel)
+            .map(|s| s.profile.as_ref())
+            .collect();
+        profiles.sort();
+        profiles.dedup();
+        for profile in profiles {
+            let runs: Vec<&Session> = sessions
+                .iter()
+                .filter(|s| s.kernel == *kernel && s.profile.as_ref() == profile)
+                .collect();
+            let hours: f64 = runs.iter().map(|s| s.hours()).sum();
+            let drop: f64 = runs.iter().map(|s| (s.from - s.to) as f64).sum();
+            let power: Vec<f64> = runs.iter().filter_map(|s| s.power).collect();
+            groups.push(Group {
+                kernel: kernel.to_string(),
+                profile: profile.cloned(),
+                sessions: runs.len(),
+                hours,
+                runtime: hours * 100.0 / drop,
+                power: (!power.is_empty()).then(|| power.iter().sum::<f64>() / power.len() as f64),
+                change: None,
+            });
+        }
+    }
+    // Groups are newest kernel first, so the one to compare with is
+    // the next of the same profile further down
+    for i in 0..groups.len() {
+        let before = groups[i + 1..]
+            .iter()
+            .find(|g| g.profile == groups[i].profile && g.kernel != groups[i].kernel)
+            .map(|g| g.runtime);
+        groups[i].change = before.map(|before| groups[i].runtime / before - 1.0);
+    }
+    groups
+}
@@ -0,0 +1,275 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::Local;
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::backend;
+use crate::privileged;
+use crate::sysfs;
+
+/// Single attributes, by the name shown when restoring
+const FIXED: [(&str, &str); 10] = [
+    ("Turbo off", "/sys/devices/system/cpu/intel_pstate/no_turbo"),
+    (
+        "Min perf",
+        "/sys/devices/system/cpu/intel_pstate/min_perf_pct",
+    ),
+    (
+        "Max perf",
+        "/sys/devices/system/cpu/intel_pstate/max_perf_pct",
+    ),
+    ("Boost", "/sys/devices/system/cpu/cpufreq/boost"),
+    ("Platform profile", "/sys/firmware/acpi/platform_profile"),
+    ("Sleep mode", "/sys/power/mem_sleep"),
+    ("PCIe ASPM", "/sys/module/pcie_aspm/parameters/policy"),
+    (
+        "Audio power save",
+        "/sys/module/snd_hda_intel/parameters/power_save",
+    ),
+    ("Dirty writeback", "/proc/sys/vm/dirty_writeback_centisecs"),
+    ("NMI watchdog", "/proc/sys/kernel/nmi_watchdog"),
+];
+
+/// An attribute of every entry in a directory whose name starts with
+/// the prefix: name, directory, prefix, attribute
+const PER_DEVICE: [(&str, &str, &str, &str); 11] = [
+    (
+        "EPP",
+        "/sys/devices/system/cpu/cpufreq",
+        "policy",
+        "energy_performance_preference",
+    ),
+    (
+        "Min frequency",
+        "/sys/devices/system/cpu/cpufreq",
+        "policy",
+        "scaling_min_freq",
+    ),
+    (
+        "Max frequency",
+        "/sys/devices/system/cpu/cpufreq",
+        "policy",
+        "scaling_max_freq",
+    ),
+    (
+        "Charge start",
+        "/sys/class/power_supply",
+        "BAT",
+        "charge_control_start_threshold",
+    ),
+    (
+        "Charge end",
+        "/sys/class/power_supply",
+        "BAT",
+        "charge_control_end_threshold",
+    ),
+    (
+        "Charge behaviour",
+        "/sys/class/power_supply",
+        "BAT",
+        "charge_behaviour",
+    ),
+    (
+        "RAPL limit",
+        "/sys/class/powercap",
+        "intel-rapl:",
+        "constraint_0_power_limit_uw",
+    ),
+    (
+        "RAPL limit",
+        "/sys/class/powercap",
+        "intel-rapl:",
+        "constraint_1_power_limit_uw",
+    ),
+    ("Runtime PM", "/sys/bus/pci/devices", "", "power/control"),
+    ("Runtime PM", "/sys/bus/usb/devices", "", "power/control"),
+    (
+        "SATA link power",
+        "/sys/class/scsi_host",
+        "host",
+        "link_power_management_policy",
+    ),
+];
+
+/// Owned by power-profiles-daemon while it runs; writing them behind
+/// its back only lasts until its next change
+const PPD_OWNED: [&str; 2] = ["EPP", "Platform profile"];
+
+#[derive(Serialize, Deserialize)]
+struct SettingsFile {
+    /// RFC 3339, when the settings were saved
+    saved: String,
+    /// Through the profile backend, so power-profiles-daemon keeps track
+    governor: Option<String>,
+    #[serde(default, rename = "setting")]
+    settings: Vec<Setting>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Setting {
+    name: String,
+    path: String,
+    value: String,
+}
+
+/// `powertui settings save FILE | restore FILE [--dry-run]`
+pub fn run(args: &[String]) -> Result<()> {
+    match args.first().map(String::as_str) {
+        Some("save") => {
+            let [_, file] = args else {
+                bail!("usage: powertui settings save FILE");
+            };
+            save(Path::new(file))
+        }
+        Some("restore") => {
+            let mut file = None;
+            for arg in &args[1..] {
+                match arg.as_str() {
+                    "--dry-run" => privileged::set_dry_run(true),
+                    _ if arg.starts_with('-') => bail!("unknown settings option: {}", arg),
+                    _ if file.is_none() => file = Some(arg),
+                    _ => bail!("usage: powertui settings restore FILE [--dry-run]"),
+                }
+            }
+            let file =
+                file.ok_or_else(|| eyre!("usage: powertui settings restore FILE [--dry-run]"))?;
+            restore(Path::new(file))
+        }
+        _ => bail!("usage: powertui settings save FILE | restore FILE [--dry-run]"),
+    }
+}
+
+/// The value as written back: the bracketed option of a choice like
+/// `s2idle [deep]`, or the whole attribute
+fn read(path: &Path) -> Option<String> {
+    let value = sysfs::read_string(path)?;
+    if value.contains('[') {
+        return sysfs::read_choice(path)?.current;
+    }
+    Some(value)
+}
+
+/// Every saved attribute present on this machine, in a stable order
+fn current() -> Vec<Setting> {
+    let mut settings: Vec<Setting> = FIXED
+        .iter()
+        .filter_map(|(name, path)| {
+            Some(Setting {
+                name: name.to_string(),
+                path: path.to_string(),
+                value: read(Path::new(path))?,
+            })
+        })
+        .collect();
+    for (name, dir, prefix, attribute) in PER_DEVICE {
+        let mut entries: Vec<_> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with(prefix))
+            .map(|e| e.path().join(attribute))
+            .collect();
+        entries.sort();
+        settings.extend(entries.into_iter().filter_map(|path| {
+            Some(Setting {
+                name: name.to_string(),
+                value: read(&path)?,
+                path: path.to_string_lossy().into_owned(),
+            })
+        }));
+    }
+    settings
+}
+
+fn save(file: &Path) -> Result<()> {
+    let saved = SettingsFile {
+        saved: Local::now().to_rfc3339(),
+        governor: backend::active().current(),
+        settings: current(),
+    };
+    let text = toml::to_string_pretty(&saved)?;
+    fs::write(file, text).map_err(|e| eyre!("{}: {}", file.display(), e))?;
+    let governor = match saved.governor {
+        Some(ref governor) => format!("the {} governor and ", governor),
+        None => String::new(),
+    };
+    println!(
+        "Saved {}{} settings to {}",
+        governor,
+        saved.settings.len(),
+        file.display()
+    );
+    Ok(())
+}
+
+fn restore(file: &Path) -> Result<()> {
+    let text = fs::read_to_string(file).map_err(|e| eyre!("{}: {}", file.display(), e))?;
+    let saved: SettingsFile =
+        toml::from_str(&text).map_err(|e| eyre!("{}: {}", file.display(), e))?;
+    let backend = backend::active();
+    let ppd = backend.owns_epp();
+    let verb = if privileged::dry_run() {
+        "would set"
+    } else {
+        "set"
+    };
+    let mut failed = 0;
+
+    if let Some(ref governor) = saved.governor {
+        let old = backend.current();
+        if old.as_ref() != Some(governor) {
+            match backend.set_governor(governor) {
+                Ok(()) => println!("Governor: {} {} via {}", verb, governor, backend.name()),
+                Err(e) => {
+                    failed += 1;
+                    println!("Governor: {}", e);
+                }
+            }
+        }
+    }
+
+    // Settings that depend on each other, like a charge start threshold
+    // above the current end, go through on a second try
+    let mut pending: Vec<&Setting> = Vec::new();
+    for setting in &saved.settings {
+        let path = Path::new(&setting.path);
+        if ppd && PPD_OWNED.contains(&setting.name.as_str()) {
+            println!("{}: left to power-profiles-daemon", setting.name);
+            continue;
+        }
+        let Some(old) = read(path) else {
+            println!("{}: {} isn't on this machine", setting.name, setting.path);
+            continue;
+        };
+        if old != setting.value {
+            pending.push(setting);
+        }
+    }
+    for attempt in 0..2 {
+        let mut retry = Vec::new();
+        for setting in pending {
+            let old = read(Path::new(&setting.path));
+            match privileged::write(&setting.name, old, &setting.path, &setting.value) {
+                Ok(()) => println!(
+                    "{}: {} {} ({})",
+                    setting.name, verb, setting.value, setting.path
+                ),
+                Err(_) if attempt == 0 => retry.push(setting),
+                Err(e) => {
+                    failed += 1;
+                    println!("{}: {} ({})", setting.name, e, setting.path);
+                }
+            }
+        }
+        pending = retry;
+    }
+
+    if failed > 0 {
+        bail!("{} settings could not be restored", failed);
+    }
+    println!("Restored settings saved {}", saved.saved);
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::battery::read_battery_info;
+use crate::privileged;
+use crate::sysfs;
+
+const POWER_SUPPLY: &str = "/sys/class/power_supply";
+const THERMAL: &str = "/sys/class/thermal";
+
+/// Battery states cycled through with `~`, as sysfs spells them
+pub const STATUSES: [&str; 4] = ["Discharging", "Charging", "Full", "Not charging"];
+
+/// Draw written to `power_now` so time estimates have something to
+/// work with, in µW
+const DISCHARGE_POWER: u64 = 8_000_000;
+const CHARGE_POWER: u64 = 25_000_000;
+
+/// One adjustment to the simulated machine
+pub enum Change {
+    Battery(u8),
+    Status(String),
+    Temp(f64),
+}
+
+/// A throwaway sysfs tree the TUI runs against, so alerts, colors and
+/// rules can be tried without draining a real battery. Starts from a
+/// recorded snapshot when given one, or a plain laptop otherwise, and
+/// turns on dry-run so nothing reaches the real hardware.
+pub fn start(snapshot: Option<&Path>) -> Result<(), String> {
+    let dir = env::temp_dir().join(format!("powertui-sim-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    match snapshot {
+        Some(snapshot) => {
+            if !snapshot.is_dir() {
+                return Err(format!(
+                    "{} is not a snapshot directory",
+                    snapshot.display()
+                ));
+            }
+            copy_dir(snapshot, &dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        None => laptop(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?,
+    }
+    sysfs::set_root(Some(dir));
+    privileged::set_dry_run(true);
+    Ok(())
+}
+
+/// Remove the simulated tree
+pub fn stop() {
+    let dir = sysfs::path("/");
+    sysfs::set_root(None);
+    let _ = fs::remove_dir_all(dir);
+}
+
+fn copy_dir(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// A battery, an unplugged adapter and a CPU thermal zone
+fn laptop(dir: &Path) -> std::io::Result<()> {
+    let files: [(&str, &[(&str, &str)]); 3] = [
+        (
+            "sys/class/power_supply/BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("capacity", "60"),
+                ("energy_full", "50000000"),
+                ("energy_full_design", "57000000"),
+                ("energy_now", "30000000"),
+                ("power_now", "8000000"),
+                ("charge_behaviour", "[auto] inhibit-charge force-discharge"),
+            ],
+        ),
+        (
+            "sys/class/power_supply/AC",
+            &[("type", "Mains"), ("online", "0")],
+        ),
+        (
+            "sys/class/thermal/thermal_zone0",
+            &[("type", "x86_pkg_temp"), ("temp", "50000")],
+        ),
+    ];
+    for (path, attributes) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(&path)?;
+        for (name, value) in attributes {
+            fs::write(path.join(name), format!("{}\n", value))?;
+        }
+    }
+    Ok(())
+}
+
+fn write(path: PathBuf, value: impl ToString) -> Result<(), String> {
+    fs::write(&path, format!("{}\n", value.to_string()))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Apply a change, describing it for the event log
+pub fn apply(change: &Change) -> Result<String, String> {
+    if !sysfs::replaying() {
+        return Err("Not simulating; start with --simulate".to_string());
+    }
+    match change {
+        Change::Battery(percent) => {
+            let battery = read_battery_info().ok_or("The simulation has no battery")?;
+            let percent = (*percent).min(100);
+            write(battery.path.join("capacity"), percent)?;
+            if let Some(full) = battery.energy_full {
+                let now = (full * 1e6 * percent as f64 / 100.0) as u64;
+                write(battery.path.join("energy_now"), now)?;
+            }
+            Ok(format!("Simulated battery at {}%", percent))
+        }
+        Change::Status(status) => {
+            let status = STATUSES
+                .iter()
+                .find(|s| s.eq_ignore_ascii_case(&status.replace('-', " ")))
+                .ok_or_else(|| format!("unknown battery status: {}", status))?;
+            let battery = read_battery_info().ok_or("The simulation has no battery")?;
+            write(battery.path.join("status"), status)?;
+            let power = match *status {
+                "Discharging" => DISCHARGE_POWER,
+                "Charging" => CHARGE_POWER,
+                _ => 0,
+            };
+            write(battery.path.join("power_now"), power)?;
+            // Anything but discharging means a charger is plugged in
+            let online = u8::from(*status != "Discharging");
+            for entry in fs::read_dir(sysfs::path(POWER_SUPPLY))
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                let kind = sysfs::read_string(entry.path().join("type"));
+                if matches!(kind.as_deref(), Some("Mains" | "USB")) {
+                    write(entry.path().join("online"), online)?;
+                }
+            }
+            Ok(format!("Simulated battery {}", status.to_lowercase()))
+        }
+        Change::Temp(celsius) => {
+            // Every zone, so whichever one is read as the CPU follows
+            let mut zones = 0;
+            for entry in fs::read_dir(sysfs::path(THERMAL))
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                if entry.path().join("temp").exists() {
+                    write(entry.path().join("temp"), (celsius * 1000.0) as i64)?;
+                    zones += 1;
+                }
+            }
+            if zones == 0 {
+                return Err("The simulation has no thermal zones".to_string());
+            }
+            Ok(format!("Simulated temperature {:.0}°C", celsius))
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::process::Command;
+
+use crate::charging;
+use crate::privileged;
+use crate::sysfs::{self, Choice};
+
+const MEM_SLEEP: &str = "/sys/power/mem_sleep";
+
+/// Drain above this is worth a look; deep sleep usually stays well under 1%/h
+pub const ABNORMAL_DRAIN: f64 = 1.5;
+
+/// One suspend/resume cycle
+pub struct Cycle {
+    /// Unix timestamps of suspend entry and exit
+    pub start: i64,
+    pub end: i64,
+    /// `s2idle`, `deep` or whatever the kernel logged
+    pub mode: String,
+    /// Battery percentage around the cycle, from UPower's history
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+}
+
+impl Cycle {
+    pub fn hours(&self) -> f64 {
+        (self.end - self.start) as f64 / 3600.0
+    }
+
+    /// Percent of battery lost per suspended hour
+    pub fn drain_per_hour(&self) -> Option<f64> {
+        let hours = self.hours();
+        // Very short naps make the rate meaningless
+        if hours < 0.25 {
+            return None;
+        }
+        Some((self.before? - self.after?).max(0.0) / hours)
+    }
+
+    pub fn abnormal(&self) -> bool {
+        self.drain_per_hour().is_some_and(|d| d > ABNORMAL_DRAIN)
+    }
+}
+
+/// Suspend cycles from the kernel's `PM: suspend entry/exit` messages
+/// over the last 30 days, newest last
+pub fn read_cycles() -> Vec<Cycle> {
+    let Ok(output) = Command::new("journalctl")
+        .args([
+            "_TRANSPORT=kernel",
+            "--since=-30d",
+            "--no-pager",
+            "-o",
+            "short-unix",
+            "-g",
+            "PM: suspend (entry|exit)",
+        ])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let history: Vec<(i64, f64)> = charging::read_history()
+        .iter()
+        .map(|s| (s.time, s.percent))
+        .collect();
+
+    let mut cycles = Vec::new();
+    let mut entry: Option<(i64, String)> = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Some(time) = line
+            .split_whitespace()
+            .next()
+            .and_then(|t| t.parse::<f64>().ok())
+        else {
+            continue;
+        };
+        let time = time as i64;
+        if let Some(rest) = line.split("PM: suspend entry").nth(1) {
+            let mode = rest.trim().trim_matches(|c| c == '(' || c == ')');
+            entry = Some((time, mode.to_string()));
+        } else if line.contains("PM: suspend exit") {
+            if let Some((start, mode)) = entry.take() {
+                cycles.push(Cycle {
+                    start,
+                    end: time,
+                    mode,
+                    before: charge_before(&history, start),
+                    after: charge_after(&history, time),
+                });
+            }
+        }
+    }
+    cycles
+}
+
+fn charge_before(history: &[(i64, f64)], time: i64) -> Option<f64> {
+    history
+        .iter()
+        .rev()
+        .find(|(t, _)| *t <= time)
+        .map(|(_, p)| *p)
+}
+
+fn charge_after(history: &[(i64, f64)], time: i64) -> Option<f64> {
+    history.iter().find(|(t, _)| *t >= time).map(|(_, p)| *p)
+}
+
+/// Suspend modes the kernel offers, and the active one
+pub fn read_mem_sleep() -> Option<Choice> {
+    sysfs::read_choice(sysfs::path(MEM_SLEEP))
+}
+
+pub fn set_mem_sleep(mode: &str) -> Result<(), String> {
+    let old = read_mem_sleep().and_then(|m| m.current);
+    privileged::write("mem_sleep", old, MEM_SLEEP, mode)
+}
+
+/// Average drain per suspended hour for each mode, weighted by time asleep
+pub fn drain_by_mode(cycles: &[Cycle]) -> Vec<(String, f64, f64)> {
+    let mut modes: Vec<(String, f64, f64)> = Vec::new();
+    for cycle in cycles {
+        let Some(drain) = cycle.drain_per_hour() else {
+            continue;
+        };
+        let hours = cycle.hours();
+        match modes.iter_mut().find(|(m, _, _)| *m == cycle.mode) {
+            Some((_, lost, total)) => {
+                *lost += drain * hours;
+                *total += hours;
+            }
+            None => modes.push((cycle.mode.clone(), drain * hours, hours)),
+        }
+    }
+    modes
+        .into_iter()
+        .map(|(mode, lost, hours)| (mode, lost / hours, hours))
+        .collect()
+}
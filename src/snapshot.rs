@@ -0,0 +1,399 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+
+use crate::battery::read_battery_info;
+use crate::charger;
+use crate::config;
+use crate::cores;
+use crate::cpufreq;
+use crate::hwp;
+use crate::lid;
+use crate::netpower;
+use crate::profile::{available_governors, read_current_governor};
+use crate::rapl::{self, Limit};
+use crate::rfkill;
+use crate::ryzen;
+use crate::sleep;
+use crate::sysfs;
+use crate::thermal;
+use crate::typec;
+use crate::ups;
+
+/// Where `snapshot check` looks when given no directories
+const FIXTURES: &str = "fixtures";
+
+/// What the parsers should make of the recorded machine
+const EXPECTED: &str = "expected.txt";
+
+/// Recorded output of the commands whose parsers are covered
+const COMMANDS: &str = "commands";
+
+/// Marks a directory that's recorded empty, since git drops those
+const KEEP: &str = ".keep";
+
+/// Trees the parsers read, with how many directory levels to descend.
+/// Class entries are symlinks into /sys/devices, so one level reaches
+/// the device's attributes.
+const TREES: [(&str, usize); 13] = [
+    ("/sys/class/power_supply", 1),
+    ("/sys/class/thermal", 1),
+    ("/sys/class/typec", 2),
+    ("/sys/class/drm", 1),
+    ("/sys/class/input", 1),
+    ("/sys/class/rfkill", 1),
+    ("/sys/class/powercap", 1),
+    ("/sys/class/net", 1),
+    ("/sys/class/bluetooth", 0),
+    ("/sys/class/dmi/id", 0),
+    ("/sys/devices/system/cpu", 2),
+    ("/sys/power", 0),
+    ("/proc/acpi/button/lid", 1),
+];
+
+const FILES: [&str; 2] = ["/proc/cpuinfo", "/proc/sys/kernel/osrelease"];
+
+/// Links back up the device tree; recorded as directories holding only
+/// `KEEP` so existence checks still work, but never followed
+const LINKS: [&str; 8] = [
+    "device",
+    "driver",
+    "subsystem",
+    "firmware_node",
+    "of_node",
+    "module",
+    "power",
+    "hwmon",
+];
+
+/// Attributes that identify the machine or owner, or can block on read
+fn skipped(name: &str) -> bool {
+    name.contains("serial")
+        || name.contains("uuid")
+        || name.contains("asset_tag")
+        || matches!(name, "address" | "perm_addr" | "uevent" | "wakeup_count")
+}
+
+/// `powertui snapshot capture DIR | check [--update] [DIR...]`
+pub fn run(args: &[String]) -> Result<()> {
+    match args.first().map(String::as_str) {
+        Some("capture") => {
+            let [_, dir] = args else {
+                bail!("usage: powertui snapshot capture DIR");
+            };
+            capture(Path::new(dir))
+        }
+        Some("check") => {
+            let mut update = false;
+            let mut dirs = Vec::new();
+            for arg in &args[1..] {
+                match arg.as_str() {
+                    "--update" => update = true,
+                    _ if arg.starts_with('-') => bail!("unknown snapshot option: {}", arg),
+                    _ => dirs.push(PathBuf::from(arg)),
+                }
+            }
+            if dirs.is_empty() {
+                dirs = fixtures()?;
+            }
+            check(&dirs, update)
+        }
+        _ => bail!("usage: powertui snapshot capture DIR | check [--update] [DIR...]"),
+    }
+}
+
+fn capture(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        bail!("{} already exists", dir.display());
+    }
+    record(dir)?;
+    println!("Snapshot written to {}", dir.display());
+    println!("Check {} before sharing it", dir.join(EXPECTED).display());
+    Ok(())
+}
+
+/// Record this machine: the sysfs and procfs files the parsers read,
+/// the commands they parse, and what they currently make of it all.
+/// Serial numbers, asset tags, UUIDs and MAC addresses are left out.
+pub fn record(dir: &Path) -> Result<()> {
+    for (tree, levels) in TREES {
+        copy_tree(Path::new(tree), &under(dir, tree), levels);
+    }
+    for file in FILES {
+        if let Ok(contents) = fs::read(file) {
+            let target = under(dir, file);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(target, contents)?;
+        }
+    }
+
+    let commands = dir.join(COMMANDS);
+    fs::create_dir_all(&commands)?;
+    if ryzen::available() {
+        if let Some(table) = ryzen::read_table() {
+            fs::write(commands.join("ryzenadj-i"), table)?;
+        }
+    }
+    if let Some(ups) = config::load().ok().and_then(|c| c.ups) {
+        if let Ok(vars) = ups::query(&ups.name) {
+            fs::write(commands.join("upsc"), vars)?;
+        }
+    }
+
+    // Parsed from the copy rather than the live system, so anything the
+    // copy missed shows up here instead of in a later check
+    fs::write(dir.join(EXPECTED), describe(dir))?;
+    Ok(())
+}
+
+fn under(root: &Path, path: &str) -> PathBuf {
+    root.join(path.trim_start_matches('/'))
+}
+
+/// Copy readable attributes, following symlinks down to `levels`
+/// directories deep. Failures are skipped: plenty of attributes are
+/// write-only or need root.
+fn copy_tree(src: &Path, dst: &Path, levels: usize) {
+    let Ok(entries) = fs::read_dir(src) else {
+        return;
+    };
+    if fs::create_dir_all(dst).is_err() {
+        return;
+    }
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = entry.path();
+        let target = dst.join(&name);
+        if path.is_dir() {
+            if levels == 0 || LINKS.contains(&name.as_str()) {
+                let _ = fs::create_dir_all(&target).and_then(|()| fs::write(target.join(KEEP), ""));
+            } else {
+                copy_tree(&path, &target, levels - 1);
+            }
+        } else if !skipped(&name) {
+            // Binary blobs like EDIDs and ACPI tables aren't parsed
+            match fs::read(&path) {
+                Ok(contents) if contents.len() <= 64 * 1024 => {
+                    let _ = fs::write(&target, contents);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Every snapshot under the fixtures directory
+fn fixtures() -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(FIXTURES).map_err(|e| eyre!("{}: {}", FIXTURES, e))?;
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.join(EXPECTED).exists())
+        .collect();
+    dirs.sort();
+    if dirs.is_empty() {
+        bail!("no snapshots in {}", FIXTURES);
+    }
+    Ok(dirs)
+}
+
+/// Parse each snapshot and compare against what was recorded with it
+fn check(dirs: &[PathBuf], update: bool) -> Result<()> {
+    let mut failed = 0;
+    for dir in dirs {
+        let expected_path = dir.join(EXPECTED);
+        let expected = fs::read_to_string(&expected_path)
+            .map_err(|e| eyre!("{}: {}", expected_path.display(), e))?;
+        let actual = describe(dir);
+        if actual == expected {
+            println!("ok      {}", dir.display());
+            continue;
+        }
+        if update {
+            fs::write(&expected_path, &actual)?;
+            println!("updated {}", dir.display());
+            continue;
+        }
+        failed += 1;
+        println!("FAILED  {}", dir.display());
+        let expected: Vec<&str> = expected.lines().collect();
+        let actual: Vec<&str> = actual.lines().collect();
+        for i in 0..expected.len().max(actual.len()) {
+            let (want, got) = (expected.get(i), actual.get(i));
+            if want != got {
+                if let Some(want) = want {
+                    println!("  - {}", want);
+                }
+                if let Some(got) = got {
+                    println!("  + {}", got);
+                }
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} of {} snapshots failed", failed, dirs.len());
+    }
+    Ok(())
+}
+
+/// Everything the parsers read from a snapshot, one fact per line
+fn describe(root: &Path) -> String {
+    sysfs::set_root(Some(root.to_path_buf()));
+    let mut lines = Vec::new();
+
+    let dmi = |attr: &str| sysfs::read_string(sysfs::path(format!("/sys/class/dmi/id/{}", attr)));
+    let machine: Vec<String> = [dmi("sys_vendor"), dmi("product_name")]
+        .into_iter()
+        .flatten()
+        .collect();
+    lines.push(format!("machine: {}", machine.join(" ")));
+
+    match read_battery_info() {
+        Some(bat) => {
+            lines.push(format!(
+                "battery: {} {}% {}",
+                bat.path.file_name().unwrap_or_default().to_string_lossy(),
+                bat.reported,
+                bat.status
+            ));
+            lines.push(format!(
+                "  health {:?}, power {:?} W, full {:?} Wh of {:?} Wh",
+                bat.health, bat.power, bat.energy_full, bat.energy_full_design
+            ));
+            lines.push(format!(
+                "  remaining {:?}, alarm {:?}, behaviour {:?}",
+                bat.time_remaining,
+                bat.alarm,
+                bat.charge_behaviour.and_then(|c| c.current)
+            ));
+        }
+        None => lines.push("battery: none".to_string()),
+    }
+    match charger::read_charger() {
+        Some(c) => lines.push(format!("charger: {} {:?} W", c.name, c.watts)),
+        None => lines.push("charger: none".to_string()),
+    }
+
+    lines.push(format!(
+        "governor: {:?} of {}",
+        read_current_governor(),
+        available_governors().join(" ")
+    ));
+    for policy in cpufreq::read_policies() {
+        lines.push(format!(
+            "policy {}: {} {:?} {} steps [{}] latency {}",
+            policy.cpu_list(),
+            policy.driver,
+            policy.governor,
+            policy.range(),
+            policy.steps(),
+            policy.format_latency()
+        ));
+    }
+    let cores = cores::read_frequencies();
+    lines.push(format!("cores with cpufreq: {}", cores.len()));
+    if let Some(hwp) = hwp::read_status() {
+        lines.push(format!(
+            "intel_pstate: {} hwp {} boost {:?} no_turbo {:?} epp {:?}",
+            hwp.mode, hwp.hwp, hwp.dynamic_boost, hwp.no_turbo, hwp.epp
+        ));
+    }
+    if let Some(package) = rapl::package() {
+        lines.push(format!(
+            "rapl: enabled {} pl1 {:?} W pl2 {:?} W",
+            package.enabled,
+            package.limit(Limit::Pl1),
+            package.limit(Limit::Pl2)
+        ));
+    }
+
+    for (zone, temp) in thermal::read_zones() {
+        lines.push(format!("thermal {}: {:.1}", zone, temp));
+    }
+    if let Some(choice) = sleep::read_mem_sleep() {
+        lines.push(format!(
+            "mem_sleep: {} [{}]",
+            choice.options.join(" "),
+            choice.current.unwrap_or_default()
+        ));
+    }
+    lines.push(format!("lid closed: {:?}", lid::read_lid_closed()));
+    for display in lid::connected_displays() {
+        lines.push(format!(
+            "display {}: internal {}",
+            display.name, display.internal
+        ));
+    }
+    for port in typec::read_ports() {
+        let partner = match port.partner {
+            Some(ref p) => format!(
+                "pd {:?} rev {:?} altmodes {:?}",
+                p.pd,
+                p.pd_revision,
+                p.altmodes.iter().map(|a| a.name()).collect::<Vec<_>>()
+            ),
+            None => "nothing".to_string(),
+        };
+        lines.push(format!(
+            "typec {}: {} data {:?} cable {:?}, {}",
+            port.name,
+            port.power_summary(),
+            port.data_role,
+            port.cable,
+            partner
+        ));
+    }
+    for radio in rfkill::read_radios() {
+        lines.push(format!(
+            "rfkill {} ({}): blocked {}",
+            radio.name, radio.kind, radio.soft_blocked
+        ));
+    }
+    for nic in netpower::wired_nics() {
+        lines.push(format!(
+            "nic {}: up {} carrier {}",
+            nic.name, nic.up, nic.carrier
+        ));
+    }
+
+    let command = |name: &str| fs::read_to_string(root.join(COMMANDS).join(name)).ok();
+    if let Some(table) = command("ryzenadj-i") {
+        for (limit, current, value) in ryzen::parse_status(&table).limits {
+            lines.push(format!(
+                "ryzen {}: limit {:?} value {:?}",
+                limit.label(),
+                current,
+                value
+            ));
+        }
+    }
+    if let Some(vars) = command("upsc") {
+        let status = ups::parse_status(&vars);
+        lines.push(format!(
+            "ups: {} charge {:?} runtime {:?} load {:?}",
+            status.describe(),
+            status.charge,
+            status.runtime,
+            status.load
+        ));
+    }
+
+    sysfs::set_root(None);
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The machines recorded under `fixtures/` still parse as they did
+    #[test]
+    fn fixtures_match() {
+        check(&fixtures().unwrap(), false).unwrap();
+    }
+}
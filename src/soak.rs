@@ -0,0 +1,518 @@
+use std::fs;
+use std::io::{stderr, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::backend;
+use crate::battery::{combined, read_batteries};
+use crate::command::parse_duration;
+use crate::cores;
+use crate::locale;
+use crate::privileged;
+use crate::profile::{self, load_profiles, Profile};
+use crate::rapl::{self, EnergyMeter};
+use crate::report::{escape, format_timestamp, Format, STYLE};
+use crate::sysfs;
+use crate::thermal;
+
+const DEFAULT_DWELL: Duration = Duration::from_secs(10 * 60);
+
+/// Samples at the end of each dwell that count as settled, once the
+/// cooling has caught up with the new profile
+const SETTLED: usize = 60;
+
+/// One second of measurements
+#[derive(Clone, Copy)]
+struct Sample {
+    watts: Option<f64>,
+    celsius: Option<f64>,
+    mhz: Option<f64>,
+}
+
+/// One profile's dwell within a cycle
+struct Segment {
+    cycle: usize,
+    profile: String,
+    /// Set in place of the profile's own governor
+    substitute: Option<String>,
+    samples: Vec<Sample>,
+}
+
+/// What a run found per profile, saved with `--save` so a later run
+/// after a repaste or kernel upgrade can be compared against it
+#[derive(Serialize, Deserialize)]
+struct Summary {
+    profile: String,
+    seconds: usize,
+    avg_watts: Option<f64>,
+    max_watts: Option<f64>,
+    avg_temp: Option<f64>,
+    max_temp: Option<f64>,
+    settled_temp: Option<f64>,
+    avg_mhz: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    /// RFC 3339, when the run finished
+    generated: String,
+    kernel: String,
+    summaries: Vec<Summary>,
+}
+
+struct Soak {
+    started: DateTime<Local>,
+    generated: DateTime<Local>,
+    hostname: String,
+    kernel: String,
+    /// Where power readings came from
+    source: &'static str,
+    dwell: Duration,
+    segments: Vec<Segment>,
+    summaries: Vec<Summary>,
+    baseline: Option<Saved>,
+}
+
+/// `powertui soak [--profiles A,B] [--dwell DURATION] [--cycles N]
+/// [--format html|md] [-o FILE] [--save FILE] [--baseline FILE] [--dry-run]`
+pub fn run(args: &[String]) -> Result<()> {
+    let mut names: Option<Vec<String>> = None;
+    let mut dwell = DEFAULT_DWELL;
+    let mut cycles = 1;
+    let mut format = Format::Html;
+    let mut output = None;
+    let mut save = None;
+    let mut baseline = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| eyre!("{} needs a value", arg))
+                .cloned()
+        };
+        match arg.as_str() {
+            "--profiles" => {
+                names = Some(value()?.split(',').map(|s| s.trim().to_string()).collect());
+            }
+            "--dwell" => {
+                dwell = parse_duration(&value()?)
+                    .ok_or_else(|| eyre!("--dwell needs a duration like 5m"))?;
+            }
+            "--cycles" => {
+                cycles = value()?
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| eyre!("--cycles needs a positive number"))?;
+            }
+            "--format" => {
+                format =
+                    Format::parse(&value()?).ok_or_else(|| eyre!("--format needs html or md"))?;
+            }
+            "-o" | "--output" => output = Some(value()?),
+            "--save" => save = Some(value()?),
+            "--baseline" => {
+                let path = value()?;
+                let text = fs::read_to_string(&path).map_err(|e| eyre!("{}: {}", path, e))?;
+                baseline = Some(
+                    serde_json::from_str::<Saved>(&text).map_err(|e| eyre!("{}: {}", path, e))?,
+                );
+            }
+            "--dry-run" => privileged::set_dry_run(true),
+            _ => bail!("unknown soak option: {}", arg),
+        }
+    }
+
+    let all = load_profiles().unwrap_or_else(|_| Profile::builtin());
+    let profiles: Vec<Profile> = match names {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                all.iter()
+                    .find(|p| p.name.eq_ignore_ascii_case(name))
+                    .cloned()
+                    .ok_or_else(|| eyre!("no profile named {}", name))
+            })
+            .collect::<Result<_>>()?,
+        None => all.clone(),
+    };
+    if profiles.is_empty() {
+        bail!("no profiles to cycle through");
+    }
+    let output = output.unwrap_or_else(|| match format {
+        Format::Html => "powertui-soak.html".to_string(),
+        Format::Markdown => "-".to_string(),
+    });
+
+    let total = dwell * (profiles.len() * cycles) as u32;
+    eprintln!(
+        "Cycling {} profile{} {} time{}, {} each ({} in all)",
+        profiles.len(),
+        if profiles.len() == 1 { "" } else { "s" },
+        cycles,
+        if cycles == 1 { "" } else { "s" },
+        locale::duration(dwell.as_secs()),
+        locale::duration(total.as_secs())
+    );
+
+    let previous = backend::active().current();
+    let result = soak(&profiles, dwell, cycles);
+    // Put back the profile the machine was on, or at least its governor
+    let restore = match all.iter().find(|p| previous.as_ref() == Some(&p.governor)) {
+        Some(p) => profile::apply(p).map(|_| ()),
+        None => previous.map_or(Ok(()), |g| backend::active().set_governor(&g)),
+    };
+    if let Err(e) = restore {
+        eprintln!("Couldn't restore the previous profile: {}", e);
+    }
+    let (started, segments, source) = result?;
+    let mut summaries = summarize(&profiles, &segments);
+    let generated = Local::now();
+    let kernel =
+        sysfs::read_string("/proc/sys/kernel/osrelease").unwrap_or_else(|| "unknown".to_string());
+
+    if let Some(path) = save {
+        let saved = Saved {
+            generated: generated.to_rfc3339(),
+            kernel: kernel.clone(),
+            summaries,
+        };
+        fs::write(&path, serde_json::to_string_pretty(&saved)?)?;
+        eprintln!("Results saved to {}", path);
+        summaries = saved.summaries;
+    }
+
+    let soak = Soak {
+        started,
+        generated,
+        hostname: sysfs::read_string("/proc/sys/kernel/hostname")
+            .unwrap_or_else(|| "unknown".to_string()),
+        kernel,
+        source,
+        dwell,
+        summaries,
+        segments,
+        baseline,
+    };
+
+    let rendered = match format {
+        Format::Html => render_html(&soak),
+        Format::Markdown => render_markdown(&soak),
+    };
+    if output == "-" {
+        print!("{}", rendered);
+    } else {
+        fs::write(&output, rendered)?;
+        eprintln!("Report written to {}", output);
+    }
+    Ok(())
+}
+
+/// Apply each profile in turn and sample once a second while it holds
+fn soak(
+    profiles: &[Profile],
+    dwell: Duration,
+    cycles: usize,
+) -> Result<(DateTime<Local>, Vec<Segment>, &'static str)> {
+    let started = Local::now();
+    let package = rapl::package();
+    let mut meter = EnergyMeter::default();
+    if let Some(ref package) = package {
+        meter.sample(package.zone());
+    }
+    let source = if package.is_some() {
+        "RAPL package"
+    } else {
+        "battery discharge"
+    };
+
+    let mut segments = Vec::new();
+    for cycle in 1..=cycles {
+        for profile in profiles {
+            let substitute =
+                profile::apply(profile).map_err(|e| eyre!("{}: {}", profile.name, e))?;
+            let mut samples = Vec::new();
+            let since = Instant::now();
+            while since.elapsed() < dwell {
+                thread::sleep(Duration::from_secs(1));
+                let watts = match package {
+                    Some(ref package) => meter.sample(package.zone()),
+                    None => combined(&read_batteries()).and_then(|b| b.power),
+                };
+                let frequencies = cores::read_frequencies();
+                let mhz = (!frequencies.is_empty()).then(|| {
+                    frequencies.iter().map(|c| c.cur as f64).sum::<f64>()
+                        / frequencies.len() as f64
+                        / 1000.0
+                });
+                samples.push(Sample {
+                    watts,
+                    celsius: thermal::read_cpu_temp(),
+                    mhz,
+                });
+                let left = dwell.saturating_sub(since.elapsed()).as_secs();
+                eprint!(
+                    "\r\x1b[KCycle {}/{} · {} · {} left",
+                    cycle,
+                    cycles,
+                    profile.name,
+                    locale::duration(left)
+                );
+                let _ = stderr().flush();
+            }
+            segments.push(Segment {
+                cycle,
+                profile: profile.name.clone(),
+                substitute,
+                samples,
+            });
+        }
+    }
+    eprintln!();
+    Ok((started, segments, source))
+}
+
+/// Average and maximum of the readings present
+fn stats(values: impl Iterator<Item = Option<f64>>) -> (Option<f64>, Option<f64>) {
+    let values: Vec<f64> = values.flatten().collect();
+    if values.is_empty() {
+        return (None, None);
+    }
+    let avg = values.iter().sum::<f64>() / values.len() as f64;
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    (Some(avg), Some(max))
+}
+
+fn summarize_samples(profile: &str, samples: &[Sample]) -> Summary {
+    let (avg_watts, max_watts) = stats(samples.iter().map(|s| s.watts));
+    let (avg_temp, max_temp) = stats(samples.iter().map(|s| s.celsius));
+    let tail = &samples[samples.len().saturating_sub(SETTLED)..];
+    Summary {
+        profile: profile.to_string(),
+        seconds: samples.len(),
+        avg_watts,
+        max_watts,
+        avg_temp,
+        max_temp,
+        settled_temp: stats(tail.iter().map(|s| s.celsius)).0,
+        avg_mhz: stats(samples.iter().map(|s| s.mhz)).0,
+    }
+}
+
+/// Every cycle of each profile taken together
+fn summarize(profiles: &[Profile], segments: &[Segment]) -> Vec<Summary> {
+    profiles
+        .iter()
+        .map(|profile| {
+            let samples: Vec<Sample> = segments
+                .iter()
+                .filter(|s| s.profile == profile.name)
+                .flat_map(|s| s.samples.iter().copied())
+                .collect();
+            let mut summary = summarize_samples(&profile.name, &samples);
+            // Settled means the end of each dwell, not of the whole run
+            summary.settled_temp = stats(
+                segments
+                    .iter()
+                    .filter(|s| s.profile == profile.name)
+                    .map(|s| summarize_samples(&s.profile, &s.samples).settled_temp),
+            )
+            .0;
+            summary
+        })
+        .collect()
+}
+
+fn value(v: Option<f64>, unit: &str, decimals: usize) -> String {
+    match v {
+        Some(v) => format!("{:.*} {}", decimals, v, unit),
+        None => "–".to_string(),
+    }
+}
+
+/// Change from the baseline run, e.g. `−4.5`
+fn delta(now: Option<f64>, then: Option<f64>, decimals: usize) -> String {
+    match (now, then) {
+        (Some(now), Some(then)) => {
+            let d = now - then;
+            let sign = if d < 0.0 { "−" } else { "+" };
+            format!("{}{:.*}", sign, decimals, d.abs())
+        }
+        _ => "–".to_string(),
+    }
+}
+
+/// Table rows as plain cells, shared by both formats
+fn summary_rows(soak: &Soak) -> (Vec<&'static str>, Vec<Vec<String>>) {
+    let mut header = vec![
+        "Profile",
+        "Avg power",
+        "Max power",
+        "Avg temp",
+        "Max temp",
+        "Settled temp",
+        "Avg freq",
+    ];
+    if soak.baseline.is_some() {
+        header.extend(["Δ power (W)", "Δ settled temp (°C)", "Δ freq (MHz)"]);
+    }
+    let rows = soak
+        .summaries
+        .iter()
+        .map(|s| {
+            let mut row = vec![
+                s.profile.clone(),
+                value(s.avg_watts, "W", 2),
+                value(s.max_watts, "W", 2),
+                value(s.avg_temp, "°C", 1),
+                value(s.max_temp, "°C", 1),
+                value(s.settled_temp, "°C", 1),
+                value(s.avg_mhz, "MHz", 0),
+            ];
+            if let Some(ref baseline) = soak.baseline {
+                match baseline.summaries.iter().find(|b| b.profile == s.profile) {
+                    Some(b) => row.extend([
+                        delta(s.avg_watts, b.avg_watts, 2),
+                        delta(s.settled_temp, b.settled_temp, 1),
+                        delta(s.avg_mhz, b.avg_mhz, 0),
+                    ]),
+                    None => {
+                        row.extend(["not in baseline".to_string(), String::new(), String::new()])
+                    }
+                }
+            }
+            row
+        })
+        .collect();
+    (header, rows)
+}
+
+fn segment_rows(soak: &Soak) -> Vec<Vec<String>> {
+    soak.segments
+        .iter()
+        .map(|seg| {
+            let s = summarize_samples(&seg.profile, &seg.samples);
+            let name = match seg.substitute {
+                Some(ref governor) => format!("{} ({})", seg.profile, governor),
+                None => seg.profile.clone(),
+            };
+            vec![
+                seg.cycle.to_string(),
+                name,
+                value(s.avg_watts, "W", 2),
+                value(s.max_temp, "°C", 1),
+                value(s.settled_temp, "°C", 1),
+                value(s.avg_mhz, "MHz", 0),
+            ]
+        })
+        .collect()
+}
+
+const SEGMENT_HEADER: [&str; 6] = [
+    "Cycle",
+    "Profile",
+    "Avg power",
+    "Max temp",
+    "Settled temp",
+    "Avg freq",
+];
+
+fn describe_run(soak: &Soak) -> String {
+    format!(
+        "{} per profile, power from {}, settled temperature over each dwell's last {}",
+        locale::duration(soak.dwell.as_secs()),
+        soak.source,
+        locale::duration(SETTLED as u64)
+    )
+}
+
+fn describe_baseline(baseline: &Saved) -> String {
+    let when = DateTime::parse_from_rfc3339(&baseline.generated)
+        .map(|t| locale::date_time(&t.with_timezone(&Local)))
+        .unwrap_or_else(|_| baseline.generated.clone());
+    format!(
+        "Compared with the run of {} on Linux {}",
+        when, baseline.kernel
+    )
+}
+
+fn render_html(soak: &Soak) -> String {
+    let table = |header: &[&str], rows: &[Vec<String>]| {
+        let mut html = String::from("<table>\n<tr>");
+        for h in header {
+            html.push_str(&format!("<th>{}</th>", h));
+        }
+        html.push_str("</tr>\n");
+        for row in rows {
+            html.push_str("<tr>");
+            for cell in row {
+                html.push_str(&format!("<td>{}</td>", escape(cell)));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        html
+    };
+
+    let mut body = format!(
+        "<h1>powertui soak test</h1>\n<p class=\"meta\">{} &middot; Linux {} &middot; {} to {}</p>\n<p>{}</p>\n",
+        escape(&soak.hostname),
+        escape(&soak.kernel),
+        format_timestamp(&soak.started),
+        format_timestamp(&soak.generated),
+        escape(&describe_run(soak))
+    );
+    body.push_str("<h2>By profile</h2>\n");
+    if let Some(ref baseline) = soak.baseline {
+        body.push_str(&format!(
+            "<p>{}</p>\n",
+            escape(&describe_baseline(baseline))
+        ));
+    }
+    let (header, rows) = summary_rows(soak);
+    body.push_str(&table(&header, &rows));
+    body.push_str("<h2>By cycle</h2>\n");
+    body.push_str(&table(&SEGMENT_HEADER, &segment_rows(soak)));
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>powertui soak test - {}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(&soak.hostname),
+        STYLE,
+        body
+    )
+}
+
+fn render_markdown(soak: &Soak) -> String {
+    let table = |header: &[&str], rows: &[Vec<String>]| {
+        let mut md = format!("| {} |\n", header.join(" | "));
+        md.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+        for row in rows {
+            md.push_str(&format!("| {} |\n", row.join(" | ")));
+        }
+        md
+    };
+
+    let mut md = format!(
+        "## powertui soak test\n\n{} · Linux {} · {} to {}\n\n{}\n\n### By profile\n\n",
+        soak.hostname,
+        soak.kernel,
+        format_timestamp(&soak.started),
+        format_timestamp(&soak.generated),
+        describe_run(soak)
+    );
+    if let Some(ref baseline) = soak.baseline {
+        md.push_str(&format!("{}\n\n", describe_baseline(baseline)));
+    }
+    let (header, rows) = summary_rows(soak);
+    md.push_str(&table(&header, &rows));
+    md.push_str("\n### By cycle\n\n");
+    md.push_str(&table(&SEGMENT_HEADER, &segment_rows(soak)));
+    md
+}
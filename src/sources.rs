@@ -0,0 +1,63 @@
+use crate::battery::BatteryInfo;
+use crate::budget;
+use crate::ups::UpsStatus;
+
+/// One thing the machine can run from, for comparing them side by side
+pub struct Source {
+    pub name: String,
+    /// `Battery` or `UPS`
+    pub kind: &'static str,
+    pub charge: Option<f64>,
+    pub health: Option<u8>,
+    pub cycles: Option<u32>,
+    /// Watt-hours left, for batteries that report their capacity
+    pub energy: Option<f64>,
+    /// Seconds this source can keep the machine going at the current
+    /// draw
+    pub runtime: Option<u64>,
+}
+
+/// Every battery, then the UPS. A battery's runtime is its share of
+/// the current draw from what it holds, since firmware drains them one
+/// after the other; the UPS says for itself.
+pub fn collect(batteries: &[BatteryInfo], ups: Option<(&str, &UpsStatus)>) -> Vec<Source> {
+    let draw = budget::system_draw(batteries).filter(|d| *d > 0.0);
+    let mut sources: Vec<Source> = batteries
+        .iter()
+        .map(|b| {
+            let energy = b.energy_full.map(|full| full * b.capacity as f64 / 100.0);
+            Source {
+                name: b.name.clone(),
+                kind: "Battery",
+                charge: Some(b.capacity as f64),
+                health: b.health,
+                cycles: b.cycles,
+                energy,
+                runtime: energy
+                    .zip(draw)
+                    .map(|(wh, watts)| (wh / watts * 3600.0) as u64),
+            }
+        })
+        .collect();
+    if let Some((name, status)) = ups {
+        sources.push(Source {
+            name: name.to_string(),
+            kind: "UPS",
+            charge: status.charge,
+            health: None,
+            cycles: None,
+            energy: None,
+            runtime: status.runtime.map(u64::from),
+        });
+    }
+    sources
+}
+
+/// Each source's runtime as a share of all of them together
+pub fn shares(sources: &[Source]) -> Vec<Option<f64>> {
+    let total: u64 = sources.iter().filter_map(|s| s.runtime).sum();
+    sources
+        .iter()
+        .map(|s| Some(s.runtime? as f64 * 100.0 / total.max(1) as f64))
+        .collect()
+}
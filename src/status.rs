@@ -0,0 +1,204 @@
+use std::env;
+use std::io::{stdout, IsTerminal};
+
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+use serde_json::json;
+
+use crate::battery::{combined, read_batteries};
+use crate::charger;
+use crate::config::{self, Band};
+use crate::profile::{load_profiles, read_current_governor, Profile};
+use crate::thermal;
+
+pub const RED: &str = "31";
+pub const GREEN: &str = "32";
+pub const YELLOW: &str = "33";
+pub const DIM: &str = "2";
+pub const BOLD: &str = "1";
+
+/// ANSI colors for CLI output, or plain text when they're unwanted
+pub struct Painter {
+    pub color: bool,
+}
+
+impl Painter {
+    /// Color when writing to a terminal and NO_COLOR is unset
+    pub fn detect() -> Painter {
+        Painter {
+            color: stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+        }
+    }
+
+    pub fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// `powertui status [--no-color | --json | --waybar]`: a short summary
+/// for MOTD scripts, or one JSON object for status bars
+pub fn run(args: &[String]) -> Result<()> {
+    let mut p = Painter::detect();
+    for arg in args {
+        match arg.as_str() {
+            "--no-color" => p.color = false,
+            "--json" => return print_json(false),
+            "--waybar" => return print_json(true),
+            _ => bail!("unknown status option: {}", arg),
+        }
+    }
+    let width = crossterm::terminal::size()
+        .map(|(w, _)| w as usize)
+        .unwrap_or(80);
+
+    let label = |name: &str| p.paint(BOLD, &format!("{:<9}", name));
+    // The thresholds apply here too; colors stay the terminal's own
+    let gauges = config::load().unwrap_or_default().gauges;
+    let ansi = |band: Band| match band {
+        Band::Good => GREEN,
+        Band::Warn => YELLOW,
+        Band::Critical => RED,
+    };
+
+    let batteries = read_batteries();
+    match combined(&batteries) {
+        Some(bat) => {
+            let color = ansi(gauges.battery(bat.capacity));
+            // Leave room for the label and percentage on narrow terminals
+            let bar_width = width.saturating_sub(18).clamp(5, 30);
+            let filled = bar_width * bat.capacity.min(100) as usize / 100;
+            let bar = format!(
+                "{}{}",
+                p.paint(color, &"█".repeat(filled)),
+                p.paint(DIM, &"░".repeat(bar_width - filled))
+            );
+            println!(
+                "{}{} {}",
+                label("Battery"),
+                bar,
+                p.paint(color, &format!("{}%", bat.capacity))
+            );
+
+            let mut state = bat.status.clone();
+            if let Some(ref t) = bat.time_remaining {
+                state.push_str(&format!(", {}", t));
+            }
+            println!("{}{}", label(""), state);
+            if let Some(power) = bat.power {
+                println!("{}{:.1} W", label("Power"), power);
+            }
+            if let Some(charger) = charger::read_charger() {
+                let watts = charger
+                    .watts
+                    .map(|w| format!("{:.0} W ", w))
+                    .unwrap_or_default();
+                println!("{}{}{}", label("Charger"), watts, charger.name);
+                if let Some(warning) = charger::shortfall_warning(&charger, &bat, &batteries) {
+                    println!("{}{}", label(""), p.paint(RED, &warning));
+                }
+            }
+            if let Some(health) = bat.health {
+                let color = if health < 70 { YELLOW } else { GREEN };
+                println!(
+                    "{}{}",
+                    label("Health"),
+                    p.paint(color, &format!("{}%", health))
+                );
+            }
+        }
+        None => println!("{}{}", label("Battery"), p.paint(DIM, "none")),
+    }
+
+    let governor = read_current_governor();
+    let profile = governor
+        .as_ref()
+        .map(|g| match current_profile(g) {
+            Some(name) => format!("{} ({})", name, g),
+            None => g.clone(),
+        })
+        .unwrap_or_else(|| p.paint(DIM, "unknown"));
+    println!("{}{}", label("Profile"), profile);
+
+    if let Some(temp) = thermal::read_cpu_temp() {
+        let color = ansi(gauges.temp(temp));
+        println!(
+            "{}{}",
+            label("CPU"),
+            p.paint(color, &format!("{:.0}°C", temp))
+        );
+    }
+
+    Ok(())
+}
+
+/// The profile whose governor is in effect
+fn current_profile(governor: &str) -> Option<String> {
+    let profiles = load_profiles().unwrap_or_else(|_| Profile::builtin());
+    profiles
+        .into_iter()
+        .find(|p| p.governor == governor)
+        .map(|p| p.name)
+}
+
+/// One line of JSON for status bars. `--waybar` wraps the same figures
+/// in Waybar's `text`/`tooltip`/`class` fields, the class being the
+/// battery's gauge band or `charging`, for styling in its CSS.
+fn print_json(waybar: bool) -> Result<()> {
+    let bat = combined(&read_batteries());
+    let governor = read_current_governor();
+    let profile = governor
+        .as_deref()
+        .and_then(current_profile)
+        .or_else(|| governor.clone());
+
+    if !waybar {
+        let status = json!({
+            "battery": bat.as_ref().map(|b| b.capacity),
+            "status": bat.as_ref().map(|b| &b.status),
+            "power": bat.as_ref().and_then(|b| b.power),
+            "profile": profile,
+            "time_remaining": bat.as_ref().and_then(|b| b.time_remaining.as_ref()),
+        });
+        println!("{}", status);
+        return Ok(());
+    }
+
+    let Some(bat) = bat else {
+        let status = json!({
+            "text": "",
+            "tooltip": format!("No battery\nProfile: {}", profile.as_deref().unwrap_or("unknown")),
+            "class": "none",
+        });
+        println!("{}", status);
+        return Ok(());
+    };
+    let mut text = format!("{}%", bat.capacity);
+    if let Some(power) = bat.power {
+        text.push_str(&format!(" {:.1} W", power));
+    }
+    let mut tooltip = vec![bat.status.clone()];
+    tooltip.extend(bat.time_remaining.clone());
+    tooltip.push(format!(
+        "Profile: {}",
+        profile.as_deref().unwrap_or("unknown")
+    ));
+    let gauges = config::load().unwrap_or_default().gauges;
+    let class = match (bat.status.as_str(), gauges.battery(bat.capacity)) {
+        ("Charging", _) => "charging",
+        (_, Band::Good) => "good",
+        (_, Band::Warn) => "warning",
+        (_, Band::Critical) => "critical",
+    };
+    let status = json!({
+        "text": text,
+        "tooltip": tooltip.join("\n"),
+        "class": class,
+        "percentage": bat.capacity,
+    });
+    println!("{}", status);
+    Ok(())
+}
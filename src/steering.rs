@@ -0,0 +1,95 @@
+use std::fs;
+
+use crate::privileged;
+use crate::sysfs;
+
+/// CPU list of the efficiency cores on Intel hybrid parts, e.g. `12-19`
+pub fn ecore_cpus() -> Option<String> {
+    sysfs::read_string("/sys/devices/cpu_atom/cpus").filter(|c| !c.is_empty())
+}
+
+fn online_cpus() -> Option<String> {
+    sysfs::read_string("/sys/devices/system/cpu/online")
+}
+
+#[derive(Clone)]
+pub struct Process {
+    pub pid: u32,
+    pub name: String,
+    /// systemd unit of an app launched from the desktop, e.g.
+    /// `app-firefox-1234.scope`
+    pub unit: Option<String>,
+}
+
+/// Running processes whose name contains one of the patterns,
+/// ignoring case
+pub fn matching(patterns: &[String]) -> Vec<Process> {
+    let patterns: Vec<String> = patterns.iter().map(|p| p.to_lowercase()).collect();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let mut processes: Vec<Process> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let name = sysfs::read_string(entry.path().join("comm"))?;
+            let lower = name.to_lowercase();
+            if !patterns.iter().any(|p| lower.contains(p.as_str())) {
+                return None;
+            }
+            Some(Process {
+                pid,
+                name,
+                unit: app_unit(pid),
+            })
+        })
+        .collect();
+    processes.sort_by_key(|p| p.pid);
+    processes
+}
+
+/// The app scope or service a process runs in, if it has its own. A
+/// process in a session scope shares it with the whole desktop.
+fn app_unit(pid: u32) -> Option<String> {
+    let cgroup = sysfs::read_string(format!("/proc/{}/cgroup", pid))?;
+    let unit = cgroup
+        .lines()
+        .find_map(|l| l.strip_prefix("0::"))?
+        .rsplit('/')
+        .next()?;
+    (unit.starts_with("app-") && (unit.ends_with(".scope") || unit.ends_with(".service")))
+        .then(|| unit.to_string())
+}
+
+/// Restrict a process to `cpus`, or lift the restriction with `None`.
+/// App units get a cgroup cpuset through systemd; anything else falls
+/// back to plain CPU affinity. Audited, and skipped in dry-run mode.
+pub fn restrict(process: &Process, cpus: Option<&str>) -> Result<(), String> {
+    let setting = format!("E-cores {} ({})", process.name, process.pid);
+    let result = match process.unit {
+        Some(ref unit) => {
+            let allowed = format!("AllowedCPUs={}", cpus.unwrap_or(""));
+            privileged::run_unprivileged(
+                &setting,
+                None,
+                cpus.unwrap_or("all"),
+                "systemctl",
+                &["--user", "set-property", "--runtime", unit, &allowed],
+            )
+        }
+        None => {
+            let cpus = match cpus {
+                Some(cpus) => cpus.to_string(),
+                None => online_cpus().ok_or("Cannot read online CPUs")?,
+            };
+            privileged::run_unprivileged(
+                &setting,
+                None,
+                &cpus,
+                "taskset",
+                &["-a", "-p", "-c", &cpus, &process.pid.to_string()],
+            )
+        }
+    };
+    result.map_err(|e| format!("Could not steer {} ({}): {}", process.name, process.pid, e))
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::locale;
+
+/// `[storage]`: the charge a laptop is put away at and when to look at
+/// it again
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// End threshold while stored; lithium cells age slowest around 60%
+    pub level: u8,
+    /// Weeks in storage before reminding to check the charge
+    pub remind_weeks: u32,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            level: 60,
+            remind_weeks: 8,
+        }
+    }
+}
+
+/// A battery's thresholds before storage mode, to put back after
+#[derive(Serialize, Deserialize)]
+pub struct Previous {
+    pub battery: String,
+    pub start: Option<u8>,
+    pub end: Option<u8>,
+}
+
+/// Storage mode, kept in the state directory so it outlives the months
+/// powertui isn't running
+#[derive(Serialize, Deserialize)]
+pub struct Storage {
+    /// Unix time storage mode started
+    pub at: i64,
+    pub level: u8,
+    pub previous: Vec<Previous>,
+}
+
+impl Storage {
+    pub fn since(&self) -> Option<DateTime<Local>> {
+        Some(DateTime::from_timestamp(self.at, 0)?.with_timezone(&Local))
+    }
+
+    /// Whole weeks since storage mode started
+    pub fn weeks(&self) -> i64 {
+        (Local::now().timestamp() - self.at) / (7 * 24 * 3600)
+    }
+
+    /// What to say once the machine has been stored for `remind_weeks`
+    pub fn reminder(&self, remind_weeks: u32) -> Option<String> {
+        let weeks = self.weeks();
+        (weeks >= remind_weeks as i64).then(|| {
+            format!(
+                "In storage mode since {} ({} weeks): check the charge and top it up to {}%",
+                self.since().map(|t| locale::date(&t)).unwrap_or_default(),
+                weeks,
+                self.level
+            )
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = storage_path().ok_or("No state directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let text = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| e.to_string())
+    }
+}
+
+fn storage_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("storage.json"))
+}
+
+/// Storage mode, if it's on
+pub fn read() -> Option<Storage> {
+    let text = fs::read_to_string(storage_path()?).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+pub fn clear() {
+    if let Some(path) = storage_path() {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,165 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::process;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::daemon;
+
+/// How long a subscriber that stopped reading may hold up the daemon
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// One line on the daemon's socket
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Message {
+    /// A powertui process changed a setting
+    Changed { pid: u32, setting: String },
+    /// The daemon switched profiles, or failed to
+    State(daemon::State),
+}
+
+impl Message {
+    /// Sent by this process, which knows already
+    pub fn own(&self) -> bool {
+        let pid = match self {
+            Message::Changed { pid, .. } => *pid,
+            Message::State(state) => state.pid,
+        };
+        pid == process::id()
+    }
+}
+
+fn socket_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("daemon.sock"))
+}
+
+/// Connections the daemon passes every message on to, while it serves
+static SUBSCRIBERS: Mutex<Option<Vec<UnixStream>>> = Mutex::new(None);
+
+/// Whether a daemon answers on the socket
+pub fn running() -> bool {
+    socket_path().is_some_and(|path| UnixStream::connect(path).is_ok())
+}
+
+/// Listen on the socket, for the daemon: everything a client sends
+/// goes to every client. False when another daemon already is, or the
+/// socket can't be made.
+pub fn serve() -> bool {
+    let Some(path) = socket_path() else {
+        return false;
+    };
+    if running() {
+        return false;
+    }
+    // Left behind by a daemon that was killed
+    let _ = fs::remove_file(&path);
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let Ok(listener) = UnixListener::bind(&path) else {
+        return false;
+    };
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        *subscribers = Some(Vec::new());
+    }
+    thread::spawn(move || {
+        for stream in listener.incoming().map_while(Result::ok) {
+            let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+            let Ok(reader) = stream.try_clone() else {
+                continue;
+            };
+            if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+                subscribers.get_or_insert_with(Vec::new).push(stream);
+            }
+            thread::spawn(move || {
+                for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                    if let Ok(message) = serde_json::from_str(&line) {
+                        broadcast(&message);
+                    }
+                }
+            });
+        }
+    });
+    true
+}
+
+/// To every subscriber, dropping those that have gone
+fn broadcast(message: &Message) {
+    let Ok(line) = serde_json::to_string(message) else {
+        return;
+    };
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        if let Some(subscribers) = subscribers.as_mut() {
+            subscribers.retain_mut(|s| writeln!(s, "{}", line).is_ok());
+        }
+    }
+}
+
+/// Tell any open TUI. In the daemon that's straight to its subscribers;
+/// anywhere else through the daemon's socket, when a daemon is running.
+pub fn publish(message: &Message) {
+    if SUBSCRIBERS.lock().is_ok_and(|s| s.is_some()) {
+        return broadcast(message);
+    }
+    let (Some(path), Ok(line)) = (socket_path(), serde_json::to_string(message)) else {
+        return;
+    };
+    if let Ok(mut stream) = UnixStream::connect(path) {
+        let _ = writeln!(stream, "{}", line);
+    }
+}
+
+/// A setting this process just changed
+pub fn changed(setting: &str) {
+    publish(&Message::Changed {
+        pid: process::id(),
+        setting: setting.to_string(),
+    });
+}
+
+/// The TUI's end: messages from the daemon as they arrive
+pub struct Subscription {
+    messages: Receiver<Message>,
+}
+
+impl Subscription {
+    /// None without a daemon to listen to
+    pub fn connect() -> Option<Subscription> {
+        let stream = UnixStream::connect(socket_path()?).ok()?;
+        let (tx, messages) = mpsc::channel();
+        // Ends with the daemon, or at the first message after the
+        // subscription is dropped
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                let Ok(message) = serde_json::from_str(&line) else {
+                    continue;
+                };
+                if tx.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        Some(Subscription { messages })
+    }
+
+    /// Messages since the last call, or None once the daemon has gone
+    pub fn drain(&self) -> Option<Vec<Message>> {
+        let mut messages = Vec::new();
+        loop {
+            match self.messages.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => return Some(messages),
+                Err(TryRecvError::Disconnected) if messages.is_empty() => return None,
+                Err(TryRecvError::Disconnected) => return Some(messages),
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::privileged;
+use crate::sysfs;
+
+const ZSWAP: &str = "/sys/module/zswap/parameters";
+const SWAPPINESS: &str = "/proc/sys/vm/swappiness";
+
+/// zswap, the compressed cache in front of the swap device. Pages it
+/// keeps in memory are pages the disk doesn't wake up for.
+pub struct Zswap {
+    pub enabled: bool,
+    pub compressor: Option<String>,
+    /// Most of RAM the pool may take, in percent
+    pub max_pool_percent: Option<u8>,
+    /// Memory the pool takes, from `Zswap:` in /proc/meminfo
+    pub pool: Option<u64>,
+    /// What the pool holds uncompressed, from `Zswapped:`
+    pub stored: Option<u64>,
+    /// Pages read back from the pool rather than the disk (`zswpin`)
+    pub hits: Option<u64>,
+    /// Pages pushed out of the pool to the disk (`zswpwb`)
+    pub written_back: Option<u64>,
+}
+
+/// A zram device, swap compressed in RAM with no disk behind it
+pub struct Zram {
+    pub name: String,
+    pub algorithm: Option<String>,
+    pub disksize: u64,
+    /// Data stored, uncompressed and compressed, from `mm_stat`
+    pub original: u64,
+    pub compressed: u64,
+}
+
+/// Compressed swap on this machine, and how eagerly the kernel swaps
+pub struct SwapStatus {
+    pub zswap: Option<Zswap>,
+    pub zram: Vec<Zram>,
+    pub swappiness: Option<u8>,
+}
+
+impl SwapStatus {
+    /// One line each for zswap and every zram device in use
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(ref z) = self.zswap {
+            let mut parts = vec![format!("zswap {}", if z.enabled { "on" } else { "off" })];
+            if let Some(ref compressor) = z.compressor {
+                parts.push(compressor.clone());
+            }
+            if let Some(percent) = z.max_pool_percent {
+                parts.push(format!("pool up to {}%", percent));
+            }
+            if let (Some(stored), Some(pool)) = (z.stored, z.pool) {
+                parts.push(format!(
+                    "{} in {}{}",
+                    size(stored),
+                    size(pool),
+                    ratio(stored, pool)
+                ));
+            }
+            if let Some(hits) = z.hits {
+                parts.push(format!("{} hits", hits));
+            }
+            if let Some(written_back) = z.written_back {
+                parts.push(format!("{} written back", written_back));
+            }
+            lines.push(parts.join(" · "));
+        }
+        for zram in self.zram.iter().filter(|z| z.disksize > 0) {
+            let mut parts = vec![zram.name.clone()];
+            if let Some(ref algorithm) = zram.algorithm {
+                parts.push(algorithm.clone());
+            }
+            parts.push(format!("{} device", size(zram.disksize)));
+            parts.push(format!(
+                "{} in {}{}",
+                size(zram.original),
+                size(zram.compressed),
+                ratio(zram.original, zram.compressed)
+            ));
+            lines.push(parts.join(" · "));
+        }
+        if let (false, Some(swappiness)) = (lines.is_empty(), self.swappiness) {
+            lines.push(format!("swappiness {}", swappiness));
+        }
+        lines
+    }
+}
+
+/// `412 MiB`, `1.2 GiB`
+fn size(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    let mib = bytes as f64 / MIB;
+    if mib >= 1024.0 {
+        format!("{:.1} GiB", mib / 1024.0)
+    } else {
+        format!("{:.0} MiB", mib)
+    }
+}
+
+/// ` (3.1×)`, or nothing before anything is stored
+fn ratio(original: u64, compressed: u64) -> String {
+    if compressed == 0 {
+        return String::new();
+    }
+    format!(" ({:.1}×)", original as f64 / compressed as f64)
+}
+
+/// A `key value` line's value from /proc/meminfo or /proc/vmstat
+fn proc_value(file: &str, key: &str) -> Option<u64> {
+    let text = sysfs::read_string(sysfs::path(file))?;
+    text.lines().find_map(|line| {
+        let (name, rest) = line.split_once(|c: char| c == ':' || c.is_whitespace())?;
+        (name == key).then(|| rest.split_whitespace().next()?.parse().ok())?
+    })
+}
+
+pub fn read_zswap() -> Option<Zswap> {
+    let params = sysfs::path(ZSWAP);
+    let enabled = sysfs::read_string(params.join("enabled"))?;
+    let kib = |key| proc_value("/proc/meminfo", key).map(|k| k * 1024);
+    Some(Zswap {
+        enabled: enabled == "Y" || enabled == "1",
+        compressor: sysfs::read_string(params.join("compressor")),
+        max_pool_percent: sysfs::read_value(params.join("max_pool_percent")),
+        pool: kib("Zswap"),
+        stored: kib("Zswapped"),
+        hits: proc_value("/proc/vmstat", "zswpin"),
+        written_back: proc_value("/proc/vmstat", "zswpwb"),
+    })
+}
+
+fn read_zram() -> Vec<Zram> {
+    let mut devices: Vec<PathBuf> = fs::read_dir(sysfs::path("/sys/block"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with("zram"))
+        .map(|e| e.path())
+        .collect();
+    devices.sort();
+    devices
+        .into_iter()
+        .map(|dev| {
+            // orig_data_size compr_data_size mem_used_total ...
+            let stat: Vec<u64> = sysfs::read_string(dev.join("mm_stat"))
+                .unwrap_or_default()
+                .split_whitespace()
+                .filter_map(|v| v.parse().ok())
+                .collect();
+            Zram {
+                name: dev
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                algorithm: sysfs::read_choice(dev.join("comp_algorithm")).and_then(|c| c.current),
+                disksize: sysfs::read_value(dev.join("disksize")).unwrap_or(0),
+                original: stat.first().copied().unwrap_or(0),
+                compressed: stat.get(1).copied().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+pub fn read_swappiness() -> Option<u8> {
+    sysfs::read_value(sysfs::path(SWAPPINESS))
+}
+
+/// None on a machine with neither zswap nor zram
+pub fn read_status() -> Option<SwapStatus> {
+    let zswap = read_zswap();
+    let zram = read_zram();
+    if zswap.is_none() && zram.is_empty() {
+        return None;
+    }
+    Some(SwapStatus {
+        zswap,
+        zram,
+        swappiness: read_swappiness(),
+    })
+}
+
+/// What a profile sets; None leaves that one alone
+pub struct Tuning<'a> {
+    pub zswap: Option<bool>,
+    pub compressor: Option<&'a str>,
+    pub pool: Option<u8>,
+    pub swappiness: Option<u8>,
+}
+
+/// Write whatever differs. The compressor and pool size only apply to
+/// pages stored from now on.
+pub fn apply(tuning: &Tuning) -> Result<(), String> {
+    if tuning.zswap.is_some() || tuning.compressor.is_some() || tuning.pool.is_some() {
+        let zswap = read_zswap().ok_or("zswap isn't built into this kernel")?;
+        let params = sysfs::path(ZSWAP);
+        if let Some(compressor) = tuning.compressor {
+            if zswap.compressor.as_deref() != Some(compressor) {
+                write(
+                    "zswap compressor",
+                    zswap.compressor.clone(),
+                    params.join("compressor"),
+                    compressor,
+                )?;
+            }
+        }
+        if let Some(pool) = tuning.pool {
+            if zswap.max_pool_percent != Some(pool) {
+                write(
+                    "zswap pool",
+                    zswap.max_pool_percent.map(|p| p.to_string()),
+                    params.join("max_pool_percent"),
+                    &pool.to_string(),
+                )?;
+            }
+        }
+        if let Some(on) = tuning.zswap {
+            if zswap.enabled != on {
+                let yes_no = |on| if on { "Y" } else { "N" };
+                write(
+                    "zswap",
+                    Some(yes_no(zswap.enabled).to_string()),
+                    params.join("enabled"),
+                    yes_no(on),
+                )?;
+            }
+        }
+    }
+    if let Some(swappiness) = tuning.swappiness {
+        let old = read_swappiness();
+        if old != Some(swappiness) {
+            write(
+                "swappiness",
+                old.map(|s| s.to_string()),
+                sysfs::path(SWAPPINESS),
+                &swappiness.to_string(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write(setting: &str, old: Option<String>, path: PathBuf, value: &str) -> Result<(), String> {
+    privileged::write(setting, old, &path.to_string_lossy(), value)
+}
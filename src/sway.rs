@@ -0,0 +1,184 @@
+use std::env;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::Value;
+
+// i3/sway IPC message types
+const RUN_COMMAND: u32 = 0;
+const GET_OUTPUTS: u32 = 3;
+const GET_TREE: u32 = 4;
+const SEND_TICK: u32 = 10;
+
+const MAGIC: &[u8] = b"i3-ipc";
+
+/// How long a wedged compositor may hold up a refresh
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Client for the i3/sway IPC socket. Connects per request, so a
+/// restarted compositor doesn't leave us holding a dead socket.
+pub struct Sway {
+    socket: PathBuf,
+    is_sway: bool,
+}
+
+pub struct Output {
+    pub name: String,
+    pub active: bool,
+}
+
+impl Sway {
+    pub fn detect() -> Option<Sway> {
+        if let Some(socket) = env::var_os("SWAYSOCK") {
+            return Some(Sway {
+                socket: socket.into(),
+                is_sway: true,
+            });
+        }
+        env::var_os("I3SOCK").map(|socket| Sway {
+            socket: socket.into(),
+            is_sway: false,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        if self.is_sway {
+            "Sway"
+        } else {
+            "i3"
+        }
+    }
+
+    /// i3 has no output power control of its own
+    pub fn is_sway(&self) -> bool {
+        self.is_sway
+    }
+
+    fn request(&self, kind: u32, payload: &str) -> Option<Value> {
+        let mut stream = UnixStream::connect(&self.socket).ok()?;
+        stream.set_read_timeout(Some(TIMEOUT)).ok()?;
+        stream.set_write_timeout(Some(TIMEOUT)).ok()?;
+
+        let mut msg = Vec::with_capacity(MAGIC.len() + 8 + payload.len());
+        msg.extend_from_slice(MAGIC);
+        msg.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        msg.extend_from_slice(&kind.to_ne_bytes());
+        msg.extend_from_slice(payload.as_bytes());
+        stream.write_all(&msg).ok()?;
+
+        let mut header = [0u8; 14];
+        stream.read_exact(&mut header).ok()?;
+        if &header[..6] != MAGIC {
+            return None;
+        }
+        let len = u32::from_ne_bytes(header[6..10].try_into().ok()?) as usize;
+        let mut body = vec![0u8; len];
+        stream.read_exact(&mut body).ok()?;
+        serde_json::from_slice(&body).ok()
+    }
+
+    pub fn run_command(&self, command: &str) -> Result<(), String> {
+        let reply = self
+            .request(RUN_COMMAND, command)
+            .ok_or_else(|| format!("{} IPC not responding", self.name()))?;
+
+        // One result object per command in the payload
+        let failed = reply
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|r| !r.get("success").and_then(Value::as_bool).unwrap_or(false));
+        match failed {
+            Some(r) => Err(r
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or("command failed")
+                .to_string()),
+            None => Ok(()),
+        }
+    }
+
+    /// Broadcast a tick event; bars subscribed to ticks use it to redraw
+    pub fn send_tick(&self, payload: &str) {
+        self.request(SEND_TICK, payload);
+    }
+
+    pub fn outputs(&self) -> Vec<Output> {
+        let Some(reply) = self.request(GET_OUTPUTS, "") else {
+            return Vec::new();
+        };
+        reply
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|o| {
+                Some(Output {
+                    name: o.get("name")?.as_str()?.to_string(),
+                    active: o.get("active").and_then(Value::as_bool).unwrap_or(false),
+                })
+            })
+            .collect()
+    }
+
+    /// Turn all outputs off or back on. i3 has no output power control,
+    /// that's left to X11 DPMS.
+    pub fn set_outputs_power(&self, on: bool) -> Result<(), String> {
+        if !self.is_sway {
+            return Err("i3 cannot power off outputs, use xset dpms".to_string());
+        }
+        self.run_command(if on {
+            "output * power on"
+        } else {
+            "output * power off"
+        })
+    }
+
+    /// Names of views currently holding an idle inhibitor
+    pub fn idle_inhibiting_views(&self) -> Vec<String> {
+        let mut views = Vec::new();
+        if let Some(tree) = self.request(GET_TREE, "") {
+            collect_inhibiting(&tree, &mut views);
+        }
+        views
+    }
+}
+
+fn collect_inhibiting(node: &Value, views: &mut Vec<String>) {
+    if node.get("inhibit_idle").and_then(Value::as_bool) == Some(true) {
+        let name = node
+            .get("app_id")
+            .and_then(Value::as_str)
+            .or_else(|| {
+                node.pointer("/window_properties/class")
+                    .and_then(Value::as_str)
+            })
+            .or_else(|| node.get("name").and_then(Value::as_str))
+            .unwrap_or("unknown");
+        views.push(name.to_string());
+    }
+    for key in ["nodes", "floating_nodes"] {
+        for child in node
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            collect_inhibiting(child, views);
+        }
+    }
+}
+
+/// Docked means something other than the built-in panel is lit up
+pub fn is_docked(outputs: &[Output]) -> bool {
+    outputs
+        .iter()
+        .filter(|o| o.active)
+        .any(|o| !is_internal_panel(&o.name))
+}
+
+/// Built-in laptop panels, by connector name
+pub fn is_internal_panel(name: &str) -> bool {
+    ["eDP", "LVDS", "DSI"].iter().any(|p| name.starts_with(p))
+}
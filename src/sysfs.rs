@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// Where /sys and /proc are read from: `/`, or a snapshot directory
+/// when replaying a recorded machine
+static ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+pub fn set_root(root: Option<PathBuf>) {
+    if let Ok(mut current) = ROOT.write() {
+        *current = root;
+    }
+}
+
+/// Reading a snapshot, so live sources like D-Bus and MSRs are off limits
+pub fn replaying() -> bool {
+    ROOT.read().is_ok_and(|root| root.is_some())
+}
+
+/// An absolute system path, under the snapshot root if one is set
+pub fn path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    match ROOT.read().ok().and_then(|root| root.clone()) {
+        Some(root) => root.join(path.strip_prefix("/").unwrap_or(path)),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Read a sysfs attribute, trimmed of the trailing newline
+pub fn read_string(path: impl AsRef<Path>) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Read and parse a single-value sysfs attribute
+pub fn read_value<T: FromStr>(path: impl AsRef<Path>) -> Option<T> {
+    read_string(path)?.parse().ok()
+}
+
+/// An attribute listing the options with the active one bracketed,
+/// e.g. `s2idle [deep]`
+#[derive(Clone)]
+pub struct Choice {
+    pub options: Vec<String>,
+    pub current: Option<String>,
+}
+
+impl Choice {
+    /// The option after the active one, wrapping around
+    pub fn next(&self) -> Option<&String> {
+        let i = self
+            .options
+            .iter()
+            .position(|o| Some(o) == self.current.as_ref())?;
+        self.options.get((i + 1) % self.options.len())
+    }
+}
+
+pub fn read_choice(path: impl AsRef<Path>) -> Option<Choice> {
+    let contents = read_string(path)?;
+    let mut current = None;
+    let options = contents
+        .split_whitespace()
+        .map(
+            |o| match o.strip_prefix('[').and_then(|o| o.strip_suffix(']')) {
+                Some(active) => {
+                    current = Some(active.to_string());
+                    active.to_string()
+                }
+                None => o.to_string(),
+            },
+        )
+        .collect();
+    Some(Choice { options, current })
+}
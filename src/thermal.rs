@@ -0,0 +1,184 @@
+use std::fs;
+
+use crate::sysfs;
+
+/// Thermal zone types that track the CPU package, best first
+const CPU_ZONE_TYPES: [&str; 5] = [
+    "x86_pkg_temp",
+    "k10temp",
+    "cpu-thermal",
+    "cpu_thermal",
+    "acpitz",
+];
+
+/// Every thermal zone as `(type, °C)`
+pub fn read_zones() -> Vec<(String, f64)> {
+    let Ok(entries) = fs::read_dir(sysfs::path("/sys/class/thermal")) else {
+        return Vec::new();
+    };
+    let mut zones: Vec<(String, f64)> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let kind = sysfs::read_string(path.join("type"))?;
+            let millideg: f64 = sysfs::read_value(path.join("temp"))?;
+            Some((kind, millideg / 1000.0))
+        })
+        .collect();
+    zones.sort_by(|a, b| a.0.cmp(&b.0));
+    zones
+}
+
+/// CPU temperature in °C, from the most specific thermal zone available
+pub fn read_cpu_temp() -> Option<f64> {
+    let zones = read_zones();
+
+    CPU_ZONE_TYPES
+        .iter()
+        .find_map(|wanted| zones.iter().find(|(kind, _)| kind == wanted))
+        .map(|(_, temp)| *temp)
+}
+
+/// A trip point, or an hwmon chip's high and critical limits
+pub struct Trip {
+    /// `passive`, `active`, `hot` or `critical`; hwmon's limits read as
+    /// `high` and `critical`
+    pub kind: String,
+    pub temp: f64,
+}
+
+/// One temperature reading with the limits the firmware or driver set
+pub struct Sensor {
+    /// Zone type, or hwmon chip name and label (`coretemp Core 0`)
+    pub name: String,
+    /// `zone` or `hwmon`
+    pub source: &'static str,
+    pub temp: f64,
+    /// Lowest first; fan (`active`) trips are left out
+    pub trips: Vec<Trip>,
+}
+
+impl Sensor {
+    /// The highest trip reached, if any
+    pub fn tripped(&self) -> Option<&Trip> {
+        self.trips.iter().rev().find(|t| self.temp >= t.temp)
+    }
+}
+
+/// Thermal zones with their trip points, then hwmon temperature inputs
+/// with their limits
+pub fn read_sensors() -> Vec<Sensor> {
+    let mut sensors = read_zone_sensors();
+    sensors.extend(read_hwmon_sensors());
+    sensors
+}
+
+fn read_zone_sensors() -> Vec<Sensor> {
+    let mut zones: Vec<Sensor> = fs::read_dir(sysfs::path("/sys/class/thermal"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .filter_map(|entry| {
+            let path = entry.path();
+            let millideg: f64 = sysfs::read_value(path.join("temp"))?;
+            let trips = (0..)
+                .map_while(|i| {
+                    let kind = sysfs::read_string(path.join(format!("trip_point_{}_type", i)))?;
+                    let temp: Option<f64> =
+                        sysfs::read_value(path.join(format!("trip_point_{}_temp", i)));
+                    Some((kind, temp))
+                })
+                // Disabled trips read as 0 or negative
+                .filter_map(|(kind, temp)| Some((kind, temp.filter(|t| *t > 0.0)? / 1000.0)))
+                .filter(|(kind, _)| kind != "active")
+                .map(|(kind, temp)| Trip { kind, temp })
+                .collect();
+            Some(Sensor {
+                name: sysfs::read_string(path.join("type"))?,
+                source: "zone",
+                temp: millideg / 1000.0,
+                trips: sorted(trips),
+            })
+        })
+        .collect();
+    zones.sort_by(|a, b| a.name.cmp(&b.name));
+    zones
+}
+
+fn read_hwmon_sensors() -> Vec<Sensor> {
+    let mut chips: Vec<_> = fs::read_dir(sysfs::path("/sys/class/hwmon"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    chips.sort();
+    let mut sensors = Vec::new();
+    for chip in chips {
+        let Some(chip_name) = sysfs::read_string(chip.join("name")) else {
+            continue;
+        };
+        let mut inputs: Vec<u32> = fs::read_dir(&chip)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.strip_prefix("temp")?
+                    .strip_suffix("_input")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        inputs.sort();
+        for n in inputs {
+            let attr = |suffix: &str| chip.join(format!("temp{}_{}", n, suffix));
+            let Some(millideg) = sysfs::read_value::<f64>(attr("input")) else {
+                continue;
+            };
+            let label = sysfs::read_string(attr("label")).unwrap_or_else(|| format!("temp{}", n));
+            let trips = [("high", "max"), ("critical", "crit")]
+                .into_iter()
+                .filter_map(|(kind, suffix)| {
+                    let temp: f64 = sysfs::read_value(attr(suffix))?;
+                    (temp > 0.0).then(|| Trip {
+                        kind: kind.to_string(),
+                        temp: temp / 1000.0,
+                    })
+                })
+                .collect();
+            sensors.push(Sensor {
+                name: format!("{} {}", chip_name, label),
+                source: "hwmon",
+                temp: millideg / 1000.0,
+                trips: sorted(trips),
+            });
+        }
+    }
+    sensors
+}
+
+fn sorted(mut trips: Vec<Trip>) -> Vec<Trip> {
+    trips.sort_by(|a, b| a.temp.total_cmp(&b.temp));
+    trips
+}
+
+/// Times the CPU has throttled itself for heat since boot, summed over
+/// CPUs as `(core, package)`; Intel only
+pub fn read_throttle_counts() -> Option<(u64, u64)> {
+    let cpus = fs::read_dir(sysfs::path("/sys/devices/system/cpu")).ok()?;
+    let mut core = 0;
+    let mut package = None;
+    let mut found = false;
+    for entry in cpus.flatten() {
+        let dir = entry.path().join("thermal_throttle");
+        let Some(count) = sysfs::read_value::<u64>(dir.join("core_throttle_count")) else {
+            continue;
+        };
+        found = true;
+        core += count;
+        // Every CPU in a package reports the same package count
+        package = package.max(sysfs::read_value::<u64>(dir.join("package_throttle_count")));
+    }
+    found.then(|| (core, package.unwrap_or(0)))
+}
@@ -0,0 +1,44 @@
+use std::time::{Duration, Instant};
+
+use crate::events::Level;
+
+/// How long informational toasts stay up. Errors stay until dismissed.
+const TOAST_TTL: Duration = Duration::from_secs(4);
+
+pub struct Toast {
+    pub level: Level,
+    pub text: String,
+    created: Instant,
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, level: Level, text: String) {
+        self.toasts.push(Toast {
+            level,
+            text,
+            created: Instant::now(),
+        });
+    }
+
+    /// Drop informational toasts that have been shown long enough
+    pub fn expire(&mut self) {
+        self.toasts
+            .retain(|t| t.level == Level::Error || t.created.elapsed() < TOAST_TTL);
+    }
+
+    /// Clear sticky error toasts, returning whether there were any
+    pub fn dismiss(&mut self) -> bool {
+        let before = self.toasts.len();
+        self.toasts.retain(|t| t.level != Level::Error);
+        self.toasts.len() != before
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Toast> {
+        self.toasts.iter()
+    }
+}
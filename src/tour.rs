@@ -0,0 +1,122 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::app::Page;
+use crate::config;
+use crate::keymap::{self, Action, Keymap};
+
+/// One stop on the tour: what to say, and the page to show meanwhile.
+/// `{name}` in the text stands for the key bound to that action, so
+/// the tour follows `[keys]`.
+pub struct Step {
+    pub title: &'static str,
+    pub text: &'static str,
+    pub page: Page,
+}
+
+pub const STEPS: [Step; 7] = [
+    Step {
+        title: "Welcome to powertui",
+        text: "Everything is split into pages, named along the top. {next_page} and {prev_page} \
+               step through them, and the number keys jump straight to one. {help} lists every \
+               key that does something on this machine.",
+        page: Page::Overview,
+    },
+    Step {
+        title: "Overview",
+        text: "Battery, charger, draw and temperatures at a glance. The widgets and their order \
+               come from [overview] in config.toml, and the banners above the page say which \
+               modes are on and how to leave them.",
+        page: Page::Overview,
+    },
+    Step {
+        title: "Switching profiles",
+        text: "The profile list sets the governor, EPP, turbo, power limits and more in one go. \
+               {down} and {up} pick one, and {select} shows what would change before y applies \
+               it. {new_profile} makes your own and {edit_profile} edits one. {boost} runs \
+               Performance for a while, then switches back.",
+        page: Page::Overview,
+    },
+    Step {
+        title: "Automation",
+        text: "The Automatic entry at the bottom of the list hands switching to the [[rules]] in \
+               config.toml: on battery, low charge, a hot CPU, a lid closed in a bag. \
+               `powertui daemon` follows the charger the same way without the TUI, and the \
+               banner shows when one is running.",
+        page: Page::Overview,
+    },
+    Step {
+        title: "CPU and power",
+        text: "Governors per policy, thermal trip points, fans, RAPL power limits and the GPU. \
+               {quiet} picks the quietest settings that keep up with the load, {turbo} flips \
+               turbo, and {gpu_level} forces the GPU low, auto or high.",
+        page: Page::Cpu,
+    },
+    Step {
+        title: "History",
+        text: "Charge over the day and week, time in each profile, battery runtime by kernel, \
+               and every setting powertui has changed, with its old value and outcome.",
+        page: Page::History,
+    },
+    Step {
+        title: "Privileges",
+        text: "Reading needs nothing special; changing a setting goes through root, then pkexec \
+               with the polkit policy installed, then sudo. `powertui doctor` checks that setup \
+               and --dry-run shows what would be written without writing it. That's the tour: \
+               {tour} brings it back, and it's listed under {help}.",
+        page: Page::Overview,
+    },
+];
+
+/// Where the tour is, shown over the page until it's finished or
+/// dismissed
+pub struct Tour {
+    pub step: usize,
+}
+
+impl Tour {
+    pub fn current(&self) -> &'static Step {
+        &STEPS[self.step.min(STEPS.len() - 1)]
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.step + 1 >= STEPS.len()
+    }
+}
+
+/// `{name}` replaced by the first key bound to each action, or left as
+/// the action's name where nothing is bound
+pub fn fill(text: &str, keymap: &Keymap) -> String {
+    let mut text = text.to_string();
+    for action in Action::ALL {
+        let placeholder = format!("{{{}}}", action.name());
+        if text.contains(&placeholder) {
+            let key = keymap
+                .keys(action)
+                .first()
+                .map(|k| keymap::key_name(*k))
+                .unwrap_or_else(|| action.name().to_string());
+            text = text.replace(&placeholder, &key);
+        }
+    }
+    text
+}
+
+fn seen_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("tour-seen"))
+}
+
+/// Whether the tour has been finished or dismissed before
+pub fn seen() -> bool {
+    seen_path().is_some_and(|path| path.exists())
+}
+
+pub fn mark_seen() {
+    let Some(path) = seen_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, "");
+}
@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::Path;
+
+use crate::sysfs;
+
+/// A USB-C port from /sys/class/typec
+pub struct Port {
+    /// `port0`
+    pub name: String,
+    /// `host` or `device`
+    pub data_role: Option<String>,
+    /// `source` or `sink`
+    pub power_role: Option<String>,
+    /// `usb_power_delivery`, `default`, `1.5A` or `3.0A`
+    pub power_mode: Option<String>,
+    pub partner: Option<Partner>,
+    /// `active` or `passive`, when the cable identifies itself
+    pub cable: Option<String>,
+}
+
+/// Whatever is plugged into a port
+pub struct Partner {
+    pub pd: Option<bool>,
+    /// e.g. `3.0`
+    pub pd_revision: Option<String>,
+    pub altmodes: Vec<AltMode>,
+}
+
+pub struct AltMode {
+    /// Standard or vendor ID, 0xff01 for DisplayPort
+    pub svid: u16,
+    pub active: bool,
+}
+
+impl AltMode {
+    pub fn name(&self) -> String {
+        match self.svid {
+            0xff01 => "DisplayPort".to_string(),
+            0x8087 => "Thunderbolt".to_string(),
+            svid => format!("SVID {:04x}", svid),
+        }
+    }
+}
+
+impl Port {
+    /// What the port delivers, in the terms people ask about
+    pub fn power_summary(&self) -> String {
+        let mode = match self.power_mode.as_deref() {
+            Some("usb_power_delivery") => "USB PD",
+            Some("3.0A") => "5 V 3 A (Type-C current)",
+            Some("1.5A") => "5 V 1.5 A (Type-C current)",
+            Some("default") => "5 V USB default",
+            Some(other) => other,
+            None => "?",
+        };
+        match self.power_role.as_deref() {
+            Some(role) => format!("{} {}", mode, role),
+            None => mode.to_string(),
+        }
+    }
+}
+
+pub fn read_ports() -> Vec<Port> {
+    let base = sysfs::path("/sys/class/typec");
+    let Ok(entries) = fs::read_dir(&base) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        // Partners, cables and altmodes live alongside as port0-partner etc.
+        .filter(|n| n.starts_with("port") && !n.contains(['-', '.']))
+        .collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            let path = base.join(&name);
+            let current = |attr: &str| sysfs::read_choice(path.join(attr)).and_then(|c| c.current);
+            let partner_path = base.join(format!("{}-partner", name));
+            let cable_path = base.join(format!("{}-cable", name));
+            Port {
+                data_role: current("data_role"),
+                power_role: current("power_role"),
+                power_mode: sysfs::read_string(path.join("power_operation_mode")),
+                partner: partner_path.exists().then(|| read_partner(&partner_path)),
+                cable: sysfs::read_string(cable_path.join("type")),
+                name,
+            }
+        })
+        .collect()
+}
+
+fn read_partner(path: &Path) -> Partner {
+    let mut altmodes: Vec<AltMode> = fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().contains('.'))
+        .filter_map(|e| {
+            let svid = sysfs::read_string(e.path().join("svid"))?;
+            Some(AltMode {
+                svid: u16::from_str_radix(&svid, 16).ok()?,
+                active: sysfs::read_string(e.path().join("active")).as_deref() == Some("yes"),
+            })
+        })
+        .collect();
+    altmodes.sort_by_key(|a| a.svid);
+    Partner {
+        pd: sysfs::read_string(path.join("supports_usb_power_delivery")).map(|s| s == "yes"),
+        pd_revision: sysfs::read_string(path.join("usb_power_delivery_revision"))
+            .filter(|r| r != "0.0"),
+        altmodes,
+    }
+}
@@ -0,0 +1,119 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Sparkline},
+};
+
+use std::time::Duration;
+
+use crate::app::App;
+use crate::cores::CoreTrace;
+
+/// Narrowest a core's cell gets before the grid drops a column
+const CELL_WIDTH: u16 = 28;
+/// Borders around two rows of sparkline
+const CELL_HEIGHT: u16 = 4;
+
+/// A sparkline per core over the last minute, with its range and
+/// average, so a profile change shows up in the clocks
+pub fn render(f: &mut Frame, area: Rect, app: &mut App) {
+    let cores = &app.core_history.cores;
+    if cores.is_empty() {
+        let empty = Paragraph::new("No per-core frequencies (scaling_cur_freq) on this machine")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(
+                Block::default()
+                    .title(" Cores ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::DarkGray)),
+            );
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let [summary_area, grid_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+    render_summary(f, summary_area, cores, app.core_history.every());
+
+    let columns = (grid_area.width / CELL_WIDTH).max(1) as usize;
+    let rows = cores.len().div_ceil(columns);
+    let visible = (grid_area.height / CELL_HEIGHT).max(1) as usize;
+    app.page_scroll = app.page_scroll.min(rows.saturating_sub(visible));
+
+    let row_areas =
+        Layout::vertical(vec![Constraint::Length(CELL_HEIGHT); visible]).split(grid_area);
+    let cores = &app.core_history.cores;
+    for (row_area, row) in row_areas
+        .iter()
+        .zip(cores.chunks(columns).skip(app.page_scroll))
+    {
+        let cells = Layout::horizontal(vec![Constraint::Ratio(1, columns as u32); columns])
+            .split(*row_area);
+        for (cell, trace) in cells.iter().zip(row) {
+            render_core(f, *cell, trace);
+        }
+    }
+}
+
+/// Every core at once: the spread right now and the average
+fn render_summary(f: &mut Frame, area: Rect, cores: &[CoreTrace], every: Duration) {
+    let current: Vec<u64> = cores.iter().filter_map(|t| t.current()).collect();
+    let (Some(low), Some(high)) = (current.iter().min(), current.iter().max()) else {
+        return;
+    };
+    let avg = current.iter().sum::<u64>() / current.len() as u64;
+    let text = format!(
+        "{} cores · now {} – {} GHz · avg {} GHz · last minute, every {:?}",
+        cores.len(),
+        ghz(*low),
+        ghz(*high),
+        ghz(avg),
+        every
+    );
+    f.render_widget(
+        Paragraph::new(text).style(Style::default().fg(Color::DarkGray)),
+        area,
+    );
+}
+
+fn render_core(f: &mut Frame, area: Rect, trace: &CoreTrace) {
+    let current = trace.current().unwrap_or(0);
+    let load = trace
+        .max
+        .map_or(0.0, |max| current as f64 / max.max(1) as f64);
+    let color = match load {
+        l if l < 0.4 => Color::Blue,
+        l if l < 0.7 => Color::Yellow,
+        _ => Color::Red,
+    };
+    let mut block = Block::default()
+        .title(format!(" CPU{} {} GHz ", trace.cpu, ghz(current)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    if let Some((min, max, avg)) = trace.stats() {
+        block = block.title_bottom(
+            Line::from(format!(" {}–{} avg {} ", ghz(min), ghz(max), ghz(avg))).right_aligned(),
+        );
+    }
+    // Newest on the right, scaled to the core's top clock so cores
+    // compare at a glance
+    let width = area.width.saturating_sub(2) as usize;
+    let data: Vec<u64> = trace
+        .samples
+        .iter()
+        .skip(trace.samples.len().saturating_sub(width))
+        .copied()
+        .collect();
+    let mut sparkline = Sparkline::default()
+        .block(block)
+        .data(&data)
+        .style(Style::default().fg(color));
+    if let Some(max) = trace.max {
+        sparkline = sparkline.max(max);
+    }
+    f.render_widget(sparkline, area);
+}
+
+/// kHz as `2.41`
+fn ghz(khz: u64) -> String {
+    format!("{:.2}", khz as f64 / 1e6)
+}
@@ -0,0 +1,578 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+};
+
+use crate::app::App;
+use crate::config::Band;
+use crate::cpufreq::Policy;
+use crate::fans::Fan;
+use crate::gpu::{Gpu, Level};
+use crate::hwp::HwpStatus;
+use crate::keymap::{self, Action};
+use crate::rapl::{Limit, Package};
+use crate::ryzen::RyzenStatus;
+use crate::thermal::Sensor;
+
+pub fn render(f: &mut Frame, area: Rect, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(scaling_height(&app.cpufreq)), // cpufreq policies
+            Constraint::Length(thermal_height(&app.sensors, area)), // thermal
+            Constraint::Length(fans_height(&app.fans)),       // fans
+            Constraint::Length(if app.hwp.is_some() { 5 } else { 0 }), // intel_pstate
+            Constraint::Length(if app.rapl.is_some() { 3 } else { 0 }), // RAPL
+            Constraint::Length(if app.caps.ryzenadj { 3 } else { 0 }), // ryzenadj
+            Constraint::Length(gpus_height(&app.gpus)),       // GPUs
+            Constraint::Min(0),                               // Steering
+        ])
+        .split(area);
+    if !app.cpufreq.is_empty() {
+        render_scaling(f, chunks[0], &app.cpufreq);
+    }
+    if !app.sensors.is_empty() {
+        render_thermal(f, chunks[1], app);
+    }
+    if !app.fans.is_empty() {
+        render_fans(f, chunks[2], app);
+    }
+    if let Some(ref status) = app.hwp {
+        render_hwp(f, chunks[3], status);
+    }
+    if let Some(ref package) = app.rapl {
+        render_rapl(f, chunks[4], package, app.package_power, &app.domain_power);
+    }
+    if app.caps.ryzenadj {
+        render_ryzen(f, chunks[5], app.ryzen.as_ref());
+    }
+    if !app.gpus.is_empty() {
+        render_gpus(f, chunks[6], app);
+    }
+    render_steering(f, chunks[7], app);
+}
+
+/// Borders, the governor line and a header around one row per policy
+fn scaling_height(policies: &[Policy]) -> u16 {
+    if policies.is_empty() {
+        0
+    } else {
+        policies.len() as u16 + 4
+    }
+}
+
+/// The cpufreq driver and what it allows, per policy
+fn render_scaling(f: &mut Frame, area: Rect, policies: &[Policy]) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let block = Block::default()
+        .title(" Frequency Scaling ")
+        .borders(Borders::ALL)
+        .border_style(dim);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(block.inner(area));
+    f.render_widget(block, area);
+
+    let governors = Line::from(vec![
+        Span::styled("Governors ", dim),
+        Span::raw(policies[0].governors.join(" ")),
+    ]);
+    f.render_widget(Paragraph::new(governors), chunks[0]);
+
+    let rows: Vec<Row> = policies
+        .iter()
+        .map(|p| {
+            Row::new(vec![
+                p.cpu_list(),
+                p.driver.clone(),
+                p.governor.clone().unwrap_or_else(|| "?".to_string()),
+                p.epp.clone().unwrap_or_else(|| "n/a".to_string()),
+                p.range(),
+                p.limits(),
+                p.steps(),
+                p.format_latency(),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(16),
+            Constraint::Length(13),
+            Constraint::Length(20),
+            Constraint::Length(16),
+            Constraint::Length(16),
+            Constraint::Length(11),
+            Constraint::Min(8),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "CPUs", "Driver", "Governor", "EPP", "Range", "Limits", "Steps", "Latency",
+        ])
+        .style(dim.bold()),
+    );
+    f.render_widget(table, chunks[1]);
+}
+
+/// Borders and a header around a row per sensor, taking at most a third
+/// of the page on machines with a sensor per core
+fn thermal_height(sensors: &[Sensor], area: Rect) -> u16 {
+    if sensors.is_empty() {
+        0
+    } else {
+        (sensors.len() as u16 + 3).min(area.height / 3).max(4)
+    }
+}
+
+/// Every temperature sensor against its trip points, so throttling that
+/// looks like a governor problem shows up as heat
+fn render_thermal(f: &mut Frame, area: Rect, app: &App) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut title = " Thermal ".to_string();
+    if let Some((core, package)) = app.throttle_counts {
+        title.push_str(&format!(
+            "· throttled {} core / {} package times since boot ",
+            core, package
+        ));
+    }
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(dim);
+
+    let rows: Vec<Row> = app
+        .sensors
+        .iter()
+        .map(|sensor| {
+            // Past a trip point the firmware is acting on it; below, the
+            // usual temperature bands apply
+            let (band, status) = match sensor.tripped() {
+                Some(trip) if matches!(trip.kind.as_str(), "critical" | "hot") => {
+                    (Band::Critical, format!("past {}", trip.kind))
+                }
+                Some(trip) => (Band::Warn, format!("past {}", trip.kind)),
+                None => (app.gauges.temp(sensor.temp), String::new()),
+            };
+            let trips: Vec<String> = sensor
+                .trips
+                .iter()
+                .map(|t| format!("{} {:.0}°C", t.kind, t.temp))
+                .collect();
+            Row::new(vec![
+                Cell::from(sensor.name.clone()),
+                Cell::from(sensor.source).style(dim),
+                Cell::from(format!("{:.1}°C", sensor.temp))
+                    .style(Style::default().fg(app.gauges.color(band))),
+                Cell::from(trips.join("  ")),
+                Cell::from(status).style(Style::default().fg(app.gauges.color(band))),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(24),
+            Constraint::Length(6),
+            Constraint::Length(9),
+            Constraint::Min(20),
+            Constraint::Length(14),
+        ],
+    )
+    .header(Row::new(vec!["Sensor", "", "Temp", "Trip points", ""]).style(dim.bold()))
+    .block(block);
+    f.render_widget(table, area);
+}
+
+fn gpus_height(gpus: &[Gpu]) -> u16 {
+    if gpus.is_empty() {
+        0
+    } else {
+        gpus.len() as u16 + 3
+    }
+}
+
+/// Each GPU's clock against the range it may use, and the level it's
+/// forced to
+fn render_gpus(f: &mut Frame, area: Rect, app: &App) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let key = app
+        .keymap
+        .keys(Action::CycleGpuLevel)
+        .first()
+        .map(|k| keymap::key_name(*k))
+        .unwrap_or_default();
+    let block = Block::default()
+        .title(Line::from(vec![
+            Span::raw(" GPU "),
+            Span::styled(format!("· {} low/auto/high ", key), dim),
+        ]))
+        .borders(Borders::ALL)
+        .border_style(dim);
+    let rows: Vec<Row> = app
+        .gpus
+        .iter()
+        .map(|gpu| {
+            let clock = gpu
+                .clock()
+                .map(|mhz| format!("{} MHz", mhz))
+                .unwrap_or_else(|| "n/a".to_string());
+            let range = gpu
+                .range()
+                .map(|(min, max)| format!("{}–{} MHz", min, max))
+                .unwrap_or_default();
+            // Anything but auto holds the GPU away from what the driver
+            // would pick
+            let level = match gpu.level() {
+                Some(Level::Auto) => Cell::from(gpu.level_name()),
+                _ => Cell::from(gpu.level_name()).style(Style::default().fg(Color::Yellow)),
+            };
+            Row::new(vec![
+                Cell::from(format!("{} {}", gpu.card, gpu.driver())),
+                Cell::from(clock),
+                Cell::from(range).style(dim),
+                level,
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(16),
+            Constraint::Length(10),
+            Constraint::Length(16),
+            Constraint::Min(8),
+        ],
+    )
+    .header(Row::new(vec!["Card", "Clock", "Range", "Level"]).style(dim.bold()))
+    .block(block);
+    f.render_widget(table, area);
+}
+
+fn fans_height(fans: &[Fan]) -> u16 {
+    if fans.is_empty() {
+        0
+    } else {
+        fans.len() as u16 + 3
+    }
+}
+
+/// Fan speeds, and who drives each fan where the chip says, with the
+/// quiet suggestion in the title
+fn render_fans(f: &mut Frame, area: Rect, app: &App) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut title = vec![Span::raw(" Fans ")];
+    if let Some(suggestion) = app
+        .quiet
+        .is_none()
+        .then(|| app.quiet_suggestion())
+        .flatten()
+    {
+        let mut text = format!("quiet: {:.0} W", suggestion.limit);
+        if let Some(rpm) = suggestion.rpm_expected {
+            text.push_str(&format!(", ~{:.0} RPM", rpm));
+        }
+        let key = app
+            .keymap
+            .keys(Action::Quiet)
+            .first()
+            .map(|k| keymap::key_name(*k))
+            .unwrap_or_default();
+        title.push(Span::styled(format!("· {} · {} ", text, key), dim));
+    }
+    let block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .border_style(dim);
+    let rows: Vec<Row> = app
+        .fans
+        .iter()
+        .map(|fan| {
+            let rpm = fan
+                .rpm
+                .map(|r| format!("{} RPM", r))
+                .unwrap_or_else(|| "n/a".to_string());
+            let (mode, duty, control) = match fan.pwm {
+                Some(ref pwm) => {
+                    let style = if pwm.manual() {
+                        Style::default().fg(Color::Yellow)
+                    } else {
+                        Style::default()
+                    };
+                    let control = if pwm.writable { "" } else { "read-only" };
+                    (
+                        Cell::from(pwm.mode()).style(style),
+                        format!("{}%", pwm.percent()),
+                        control,
+                    )
+                }
+                None => (Cell::from("n/a").style(dim), String::new(), ""),
+            };
+            Row::new(vec![
+                Cell::from(fan.name.clone()),
+                Cell::from(rpm),
+                mode,
+                Cell::from(duty),
+                Cell::from(control).style(dim),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(24),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Min(10),
+        ],
+    )
+    .header(Row::new(vec!["Fan", "Speed", "Mode", "PWM", ""]).style(dim.bold()))
+    .block(block);
+    f.render_widget(table, area);
+}
+
+/// Speed Shift state, which explains most "why won't it clock down"
+/// questions on intel_pstate
+fn render_hwp(f: &mut Frame, area: Rect, status: &HwpStatus) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let on_off = |v: Option<bool>| match v {
+        Some(true) => "on",
+        Some(false) => "off",
+        None => "n/a",
+    };
+    let mhz = |khz: Option<u64>| {
+        khz.map(|k| format!("{} MHz", k / 1000))
+            .unwrap_or_else(|| "?".to_string())
+    };
+
+    let hwp = if status.hwp {
+        Span::styled("active", Style::default().fg(Color::Green))
+    } else {
+        Span::styled("inactive", Style::default().fg(Color::Yellow))
+    };
+    let first = Line::from(vec![
+        Span::styled("Mode ", dim),
+        Span::raw(format!("{}  ", status.mode)),
+        Span::styled("HWP ", dim),
+        hwp,
+        Span::styled("  Dynamic boost ", dim),
+        Span::raw(on_off(status.dynamic_boost)),
+        Span::styled("  Turbo ", dim),
+        Span::raw(on_off(status.no_turbo.map(|n| !n))),
+    ]);
+    let second = Line::from(vec![
+        Span::styled("Limits ", dim),
+        Span::raw(format!(
+            "{} – {}",
+            mhz(status.min_freq),
+            mhz(status.max_freq)
+        )),
+        Span::styled("  EPP ", dim),
+        Span::raw(status.epp.clone().unwrap_or_else(|| "n/a".to_string())),
+    ]);
+    let third = match status.request {
+        Some(ref r) => Line::from(vec![
+            Span::styled("HWP request ", dim),
+            Span::raw(format!(
+                "min {} max {} desired {}",
+                r.min,
+                r.max,
+                if r.desired == 0 {
+                    "auto".to_string()
+                } else {
+                    r.desired.to_string()
+                }
+            )),
+            Span::styled(format!("  (perf levels {}–{})", r.lowest, r.highest), dim),
+        ]),
+        None if status.hwp => Line::styled("HWP request needs MSR access (run as root)", dim),
+        None => Line::default(),
+    };
+
+    let block = Block::default()
+        .title(" Intel P-state ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    f.render_widget(
+        Paragraph::new(vec![first, second, third]).block(block),
+        area,
+    );
+}
+
+/// Package power limits, and whether the package is riding PL1
+fn render_rapl(
+    f: &mut Frame,
+    area: Rect,
+    package: &Package,
+    power: Option<f64>,
+    domains: &[(&str, f64)],
+) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut spans = Vec::new();
+    for limit in [Limit::Pl1, Limit::Pl2] {
+        let Some(watts) = package.limit(limit) else {
+            continue;
+        };
+        spans.push(Span::styled(format!("{} ", limit.label()), dim));
+        spans.push(Span::raw(format!("{:.0} W", watts)));
+        if let Some(max) = package.max(limit) {
+            spans.push(Span::styled(format!(" of {:.0}", max), dim));
+        }
+        spans.push(Span::raw("  "));
+    }
+    if let Some(power) = power {
+        spans.push(Span::styled("Package ", dim));
+        spans.push(Span::raw(format!("{:.1} W", power)));
+        // Sustained draw at PL1 means the limit is what's holding it back
+        if package
+            .limit(Limit::Pl1)
+            .is_some_and(|pl1| power >= pl1 * 0.95)
+        {
+            spans.push(Span::styled(
+                "  clamped at PL1",
+                Style::default().fg(Color::Red),
+            ));
+        }
+    } else if !package.energy_readable() {
+        spans.push(Span::styled(
+            "Power needs read access to energy_uj (root-only)",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    for (name, watts) in domains {
+        spans.push(Span::styled(format!("  {} ", name), dim));
+        spans.push(Span::raw(format!("{:.1} W", watts)));
+    }
+    if !package.enabled {
+        spans.push(Span::styled(
+            "  limits disabled",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    let block = Block::default()
+        .title(" Power Limits (RAPL) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    f.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+}
+
+/// STAPM and PPT limits next to what the SMU reports right now
+fn render_ryzen(f: &mut Frame, area: Rect, status: Option<&RyzenStatus>) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let line = match status {
+        Some(status) => {
+            let mut spans = Vec::new();
+            for (limit, watts, value) in &status.limits {
+                let Some(watts) = watts else {
+                    continue;
+                };
+                spans.push(Span::styled(format!("{} ", limit.label()), dim));
+                spans.push(Span::raw(format!("{:.0} W", watts)));
+                if let Some(value) = value {
+                    spans.push(Span::styled(format!(" (at {:.1})", value), dim));
+                }
+                spans.push(Span::raw("  "));
+            }
+            Line::from(spans)
+        }
+        None => Line::styled(
+            "ryzenadj needs root (pkexec or passwordless sudo) to read limits",
+            dim,
+        ),
+    };
+    let block = Block::default()
+        .title(" Ryzen Power Limits ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    f.render_widget(Paragraph::new(line).block(block), area);
+}
+
+/// Processes held on the efficiency cores while on battery
+fn render_steering(f: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .title(" E-core Steering ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let Some(ref ecores) = app.ecores else {
+        let text = Paragraph::new("This CPU has no efficiency cores")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        f.render_widget(text, area);
+        return;
+    };
+    if app.steer_patterns.is_empty() {
+        let text = Paragraph::new(format!(
+            "E-cores {}. Add process name patterns under [steering] in config.toml \
+             to keep them there on battery.",
+            ecores
+        ))
+        .style(Style::default().fg(Color::DarkGray))
+        .block(block);
+        f.render_widget(text, area);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(block.inner(area));
+    f.render_widget(block, area);
+
+    let state = if app.steered.is_empty() {
+        "unrestricted".to_string()
+    } else {
+        format!("{} processes on E-cores", app.steered.len())
+    };
+    let summary = format!(
+        "E-cores {} · patterns: {} · {}",
+        ecores,
+        app.steer_patterns.join(", "),
+        state
+    );
+    f.render_widget(
+        Paragraph::new(summary).style(Style::default().fg(Color::DarkGray)),
+        chunks[0],
+    );
+
+    let rows: Vec<Row> = app
+        .steer_matches
+        .iter()
+        .map(|p| {
+            let steered = app.steered.iter().any(|s| s.pid == p.pid);
+            let via = match p.unit {
+                Some(ref unit) => format!("cpuset {}", unit),
+                None => "affinity".to_string(),
+            };
+            let (state, color) = if steered {
+                ("E-cores", Color::Green)
+            } else {
+                ("all cores", Color::Reset)
+            };
+            Row::new(vec![
+                p.pid.to_string(),
+                p.name.clone(),
+                state.to_string(),
+                via,
+            ])
+            .style(Style::default().fg(color))
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(16),
+            Constraint::Length(10),
+            Constraint::Min(10),
+        ],
+    )
+    .header(
+        Row::new(vec!["PID", "Process", "Running on", "Via"])
+            .style(Style::default().fg(Color::DarkGray).bold()),
+    );
+    f.render_widget(table, chunks[1]);
+}
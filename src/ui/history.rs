@@ -0,0 +1,357 @@
+use chrono::{Local, NaiveDateTime, TimeZone};
+use ratatui::{
+    prelude::*,
+    widgets::{Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Paragraph, Row, Table},
+};
+
+use crate::app::App;
+use crate::history::{self, Sample};
+use crate::locale;
+use crate::sessions::Group;
+use crate::usage::{Day, DAYS};
+
+/// Profiles are colored in order of time spent, most first
+const PALETTE: [Color; 6] = [
+    Color::Green,
+    Color::Yellow,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Red,
+    Color::Blue,
+];
+
+/// Rows for the charge charts
+const CHARGE_HEIGHT: u16 = 12;
+
+/// A new line starts after a gap this long in the log, in seconds:
+/// the machine was off, asleep or without powertui
+const GAP: i64 = 10 * 60;
+
+/// Most kernel and profile pairs compared at once
+const SESSION_ROWS: usize = 8;
+
+/// Charge over the last day and week, then time in profile and runtime
+/// by kernel, above the audit trail; sections with nothing recorded are
+/// left out
+pub fn render(f: &mut Frame, area: Rect, app: &mut App) {
+    let charge = !app.charge_history.is_empty();
+    let usage = !app.usage.iter().all(|d| d.totals.is_empty());
+    let sessions = !app.battery_sessions.is_empty();
+    let mut constraints = Vec::new();
+    if charge {
+        constraints.push(Constraint::Length(CHARGE_HEIGHT));
+    }
+    if usage {
+        // A row per day, the legend and the borders
+        constraints.push(Constraint::Length(DAYS as u16 + 3));
+    }
+    if sessions {
+        constraints.push(Constraint::Length(
+            app.battery_sessions.len().min(SESSION_ROWS) as u16 + 3,
+        ));
+    }
+    constraints.push(Constraint::Min(5));
+    let areas = Layout::vertical(constraints).split(area);
+    let mut areas = areas.iter().copied();
+    if charge {
+        let [day, week] = Layout::horizontal([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+            .areas(areas.next().unwrap_or_default());
+        render_charge(f, day, &app.charge_history, 24 * 3600, " Last 24 Hours ");
+        render_charge(f, week, &app.charge_history, history::KEEP, " Last 7 Days ");
+    }
+    if usage {
+        render_usage(f, areas.next().unwrap_or_default(), &app.usage);
+    }
+    if sessions {
+        render_sessions(f, areas.next().unwrap_or_default(), &app.battery_sessions);
+    }
+    render_audit(f, areas.next().unwrap_or_default(), app);
+}
+
+/// Charge over the last `span` seconds from the battery log, green
+/// while charging, with the average draw on battery in the title
+fn render_charge(f: &mut Frame, area: Rect, samples: &[Sample], span: i64, title: &str) {
+    let now = Local::now().timestamp();
+    let samples: Vec<&Sample> = samples.iter().filter(|s| s.at >= now - span).collect();
+
+    // A line per unbroken run of samples in one state
+    let mut runs: Vec<(bool, Vec<(f64, f64)>)> = Vec::new();
+    let mut last: Option<&Sample> = None;
+    for sample in &samples {
+        let charging = sample.status == "Charging";
+        let point = ((sample.at - now) as f64, sample.capacity as f64);
+        match (last, runs.last_mut()) {
+            (Some(prev), Some((was_charging, points)))
+                if sample.at - prev.at <= GAP && *was_charging == charging =>
+            {
+                points.push(point)
+            }
+            // Carry the line over from the previous run so there's no
+            // hole where the state changed
+            (Some(prev), _) if sample.at - prev.at <= GAP => runs.push((
+                charging,
+                vec![((prev.at - now) as f64, prev.capacity as f64), point],
+            )),
+            _ => runs.push((charging, vec![point])),
+        }
+        last = Some(sample);
+    }
+    let datasets: Vec<Dataset> = runs
+        .iter()
+        .map(|(charging, points)| {
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(if *charging { Color::Green } else { Color::Cyan }))
+                .data(points)
+        })
+        .collect();
+
+    let draws: Vec<f64> = samples
+        .iter()
+        .filter(|s| s.status == "Discharging")
+        .filter_map(|s| s.power)
+        .collect();
+    let title = match draws.len() {
+        0 => title.to_string(),
+        n => format!(
+            "{}· {:.1} W average on battery ",
+            title,
+            draws.iter().sum::<f64>() / n as f64
+        ),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let axis_style = Style::default().fg(Color::DarkGray);
+    let x_labels = if span > 24 * 3600 {
+        vec![format!("-{}d", span / 86400), "now".to_string()]
+    } else {
+        vec![
+            format!("-{}h", span / 3600),
+            format!("-{}h", span / 7200),
+            "now".to_string(),
+        ]
+    };
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(
+            Axis::default()
+                .style(axis_style)
+                .bounds([-span as f64, 0.0])
+                .labels(x_labels),
+        )
+        .y_axis(
+            Axis::default()
+                .style(axis_style)
+                .bounds([0.0, 100.0])
+                .labels(["0%", "50%", "100%"]),
+        );
+    f.render_widget(chart, area);
+}
+
+/// A bar per day split by profile, scaled to the busiest day
+fn render_usage(f: &mut Frame, area: Rect, days: &[Day]) {
+    let block = Block::default()
+        .title(" Time in Profile (while powertui runs) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let mut week: Vec<(&str, u64)> = Vec::new();
+    for (profile, secs) in days.iter().flat_map(|d| &d.totals) {
+        match week.iter_mut().find(|(p, _)| p == profile) {
+            Some((_, total)) => *total += secs,
+            None => week.push((profile, *secs)),
+        }
+    }
+    week.sort_by_key(|(_, secs)| std::cmp::Reverse(*secs));
+    let color = |profile: &str| {
+        let i = week.iter().position(|(p, _)| *p == profile).unwrap_or(0);
+        PALETTE[i % PALETTE.len()]
+    };
+
+    // `Mon 14 ` before the bar and the day's total after it
+    let bar_width = area.width.saturating_sub(2 + 7 + 9) as u64;
+    let busiest = days
+        .iter()
+        .map(|d| d.totals.values().sum::<u64>())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut lines: Vec<Line> = days
+        .iter()
+        .map(|day| {
+            let mut spans = vec![Span::styled(
+                format!("{:<7}", day.date.format("%a %e").to_string()),
+                Style::default().fg(Color::DarkGray),
+            )];
+            let mut segments: Vec<(&String, &u64)> = day.totals.iter().collect();
+            segments.sort_by_key(|(p, _)| week.iter().position(|(w, _)| w == p));
+            let mut used = 0;
+            for (profile, secs) in segments {
+                let width = (secs * bar_width / busiest) as usize;
+                used += width;
+                spans.push(Span::styled(
+                    "█".repeat(width),
+                    Style::default().fg(color(profile)),
+                ));
+            }
+            let total: u64 = day.totals.values().sum();
+            if total > 0 {
+                spans.push(Span::raw(
+                    " ".repeat(bar_width as usize - used.min(bar_width as usize)),
+                ));
+                spans.push(Span::raw(format!(" {:>8}", locale::duration(total))));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let all: u64 = week.iter().map(|(_, s)| s).sum::<u64>().max(1);
+    let mut legend = Vec::new();
+    for (profile, secs) in &week {
+        legend.push(Span::styled("■ ", Style::default().fg(color(profile))));
+        legend.push(Span::raw(format!(
+            "{} {} ({}%)   ",
+            profile,
+            locale::duration(*secs),
+            secs * 100 / all
+        )));
+    }
+    lines.push(Line::from(legend));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Recorded discharges by kernel and profile, newest kernel first, with
+/// the change in runtime from the kernel before
+fn render_sessions(f: &mut Frame, area: Rect, groups: &[Group]) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let block = Block::default()
+        .title(" Battery Runtime by Kernel ")
+        .borders(Borders::ALL)
+        .border_style(dim);
+    let rows: Vec<Row> = groups
+        .iter()
+        .take(SESSION_ROWS)
+        .map(|group| {
+            let change = match group.change {
+                Some(change) => {
+                    let color = if change >= 0.0 {
+                        Color::Green
+                    } else {
+                        Color::Red
+                    };
+                    Cell::from(format!("{:+.0}%", change * 100.0)).style(Style::default().fg(color))
+                }
+                None => Cell::from(""),
+            };
+            Row::new(vec![
+                Cell::from(group.kernel.clone()),
+                Cell::from(group.profile.clone().unwrap_or_else(|| "—".to_string())),
+                Cell::from(group.sessions.to_string()),
+                Cell::from(locale::duration((group.hours * 3600.0) as u64)),
+                Cell::from(
+                    group
+                        .power
+                        .map(|w| format!("{:.1} W", w))
+                        .unwrap_or_default(),
+                ),
+                Cell::from(locale::duration((group.runtime * 3600.0) as u64)),
+                change,
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(24),
+            Constraint::Length(14),
+            Constraint::Length(9),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Min(7),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "Kernel",
+            "Profile",
+            "Sessions",
+            "On battery",
+            "Draw",
+            "Full charge",
+            "Change",
+        ])
+        .style(dim.bold()),
+    )
+    .block(block);
+    f.render_widget(table, area);
+}
+
+/// Audit trail of privileged operations, newest first
+fn render_audit(f: &mut Frame, area: Rect, app: &mut App) {
+    let block = Block::default()
+        .title(" Privileged Operations ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    if app.audit.is_empty() {
+        let empty = Paragraph::new("Nothing has been changed yet")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let visible = area.height.saturating_sub(3) as usize;
+    app.page_scroll = app
+        .page_scroll
+        .min(app.audit.len().saturating_sub(visible.max(1)));
+
+    let rows: Vec<Row> = app
+        .audit
+        .iter()
+        .rev()
+        .skip(app.page_scroll)
+        .take(visible)
+        .map(|e| {
+            let color = match e.outcome.as_str() {
+                "ok" => Color::Green,
+                "dry-run" => Color::Yellow,
+                _ => Color::Red,
+            };
+            // Stored as ISO 8601 local time; shown the locale's way
+            let time = NaiveDateTime::parse_from_str(&e.time, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .and_then(|t| Local.from_local_datetime(&t).single())
+                .map(|t| format!("{} {}", locale::date(&t), locale::clock_seconds(&t)))
+                .unwrap_or_else(|| e.time.clone());
+            Row::new(vec![
+                time,
+                e.setting.clone(),
+                format!("{} → {}", e.old.as_deref().unwrap_or("?"), e.new),
+                e.outcome.clone(),
+            ])
+            .style(Style::default().fg(color))
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(22),
+            Constraint::Length(12),
+            Constraint::Min(20),
+            Constraint::Length(30),
+        ],
+    )
+    .header(
+        Row::new(vec!["Time", "Setting", "Change", "Outcome"])
+            .style(Style::default().fg(Color::DarkGray).bold()),
+    )
+    .block(block);
+    f.render_widget(table, area);
+}
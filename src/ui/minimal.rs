@@ -0,0 +1,49 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Gauge, Paragraph},
+};
+
+use crate::app::{App, AUTO_NAME};
+
+/// A borderless battery gauge over the profile and draw, for a small
+/// terminal kept open on the side
+pub fn render(f: &mut Frame, area: Rect, app: &mut App) {
+    let [gauge_area, status_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(area);
+
+    match app.combined {
+        Some(ref bat) => {
+            let mut label = format!("{}% {}", bat.capacity, bat.status);
+            if let Some(ref t) = bat.time_remaining {
+                label.push_str(&format!(" ({})", t));
+            }
+            let gauge = Gauge::default()
+                .gauge_style(
+                    Style::default().fg(app.gauges.color(app.gauges.battery(bat.capacity))),
+                )
+                .ratio(app.gauge.value().clamp(0.0, 1.0))
+                .label(label);
+            f.render_widget(gauge, gauge_area);
+        }
+        None => f.render_widget(
+            Paragraph::new("No battery").style(Style::default().fg(Color::DarkGray)),
+            gauge_area,
+        ),
+    }
+
+    let profile = app
+        .current_profile
+        .and_then(|i| app.profiles.get(i))
+        .map_or("custom", |p| p.name.as_str());
+    let mut spans = vec![Span::styled(profile, Style::default().fg(Color::Green))];
+    if app.auto {
+        spans.push(Span::styled(
+            format!(" ({})", AUTO_NAME),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if let Some(w) = app.power_history.back() {
+        spans.push(Span::raw(format!("  {:.1} W", w)));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), status_area);
+}
@@ -0,0 +1,287 @@
+mod cores;
+mod cpu;
+mod history;
+mod minimal;
+mod msr;
+mod overview;
+mod popups;
+mod ports;
+mod sleep;
+
+use ratatui::{
+    prelude::*,
+    widgets::{Paragraph, Tabs},
+};
+
+use crate::app::{self, App, Page};
+use crate::budget;
+use crate::config;
+use crate::doctor::Severity;
+use crate::keymap::{self, Action};
+use crate::locale;
+use crate::privileged;
+use crate::rapl::Limit;
+
+pub fn ui(f: &mut Frame, app: &mut App) {
+    if app.minimal {
+        minimal::render(f, f.area(), app);
+    } else {
+        render_full(f, app);
+    }
+
+    if app.show_log {
+        popups::render_event_log(f, app);
+    }
+    if app.show_help {
+        popups::render_help(f, app);
+    }
+    if let Some(ref editor) = app.editor {
+        let driver = app.cpufreq.first().map(|p| p.driver.as_str());
+        popups::render_editor(f, editor, driver);
+    }
+    if let Some(ref editor) = app.threshold_editor {
+        popups::render_threshold_editor(f, editor);
+    }
+    if let Some(ref editor) = app.fan_editor {
+        popups::render_fan_editor(f, editor);
+    }
+    if let Some(ref preview) = app.preview {
+        popups::render_preview(f, app, preview);
+    }
+    if let Some(ref tour) = app.tour {
+        popups::render_tour(f, app, tour);
+    }
+    popups::render_toasts(f, app);
+}
+
+/// Tabs, mode banners, the page and the help line
+fn render_full(f: &mut Frame, app: &mut App) {
+    let banners = banners(app);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1),                    // Tabs
+            Constraint::Length(banners.len() as u16), // Mode banners
+            Constraint::Min(0),                       // Page
+            Constraint::Length(1),                    // Help/command line
+        ])
+        .split(f.area());
+
+    let pages = app.pages();
+    let titles: Vec<Line> = pages
+        .iter()
+        .enumerate()
+        .map(|(i, p)| Line::from(format!("{} {}", i + 1, p.title())))
+        .collect();
+    let selected = pages.iter().position(|p| *p == app.page).unwrap_or(0);
+    let tabs = Tabs::new(titles)
+        .select(selected)
+        .style(Style::default().fg(Color::DarkGray))
+        .highlight_style(Style::default().fg(Color::White).bold());
+    f.render_widget(tabs, chunks[0]);
+    if let Some(brightness) = app.brightness() {
+        f.render_widget(
+            Paragraph::new(format!("☀ {}%", brightness))
+                .style(Style::default().fg(Color::DarkGray))
+                .alignment(Alignment::Right),
+            chunks[0],
+        );
+    }
+
+    let banner_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); banners.len()])
+        .split(chunks[1]);
+    for (row, (text, color)) in banner_rows.iter().zip(banners) {
+        let banner = Paragraph::new(text)
+            .style(Style::default().fg(Color::Black).bg(color))
+            .alignment(Alignment::Center);
+        f.render_widget(banner, *row);
+    }
+
+    let page_area = chunks[2];
+    match app.page {
+        Page::Overview => overview::render(f, page_area, app),
+        Page::Cpu => cpu::render(f, page_area, app),
+        Page::Cores => cores::render(f, page_area, app),
+        Page::Sleep => sleep::render(f, page_area, app),
+        Page::Ports => ports::render(f, page_area, app),
+        Page::History => history::render(f, page_area, app),
+        Page::Msr => msr::render(f, page_area, app),
+    }
+
+    let help_area = chunks[3];
+    // Command line replaces the help line while typing
+    if let Some(ref line) = app.command_line {
+        let prompt = Paragraph::new(format!(":{}_", line.input));
+        f.render_widget(prompt, help_area);
+    } else {
+        // Help line, generated from the keymap so remapped keys show up
+        let mut spans = Vec::new();
+        if app.simulating {
+            spans.push(Span::styled(
+                " SIMULATION ",
+                Style::default().fg(Color::Black).bg(Color::Yellow),
+            ));
+            spans.push(Span::raw("  "));
+        } else if privileged::dry_run() {
+            spans.push(Span::styled(
+                " DRY RUN ",
+                Style::default().fg(Color::Black).bg(Color::Red),
+            ));
+            spans.push(Span::raw("  "));
+        }
+        if let Some(name) = config::config_profile() {
+            spans.push(Span::styled(
+                format!(" {} ", name),
+                Style::default().fg(Color::Black).bg(Color::Cyan),
+            ));
+            spans.push(Span::raw("  "));
+        }
+        if app.in_quiet_hours() {
+            spans.push(Span::styled(
+                " QUIET ",
+                Style::default().fg(Color::Black).bg(Color::Blue),
+            ));
+            spans.push(Span::raw("  "));
+        }
+        spans.push(Span::raw(app.keymap.help_line(|a| app.action_available(a))));
+        let help = Paragraph::new(Line::from(spans))
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center);
+        f.render_widget(help, help_area);
+    }
+}
+
+/// One line per active mode, saying what it holds and how to leave
+fn banners(app: &App) -> Vec<(String, Color)> {
+    let key = |action| {
+        app.keymap
+            .keys(action)
+            .first()
+            .map(|k| keymap::key_name(*k))
+            .unwrap_or_default()
+    };
+    let mut banners = Vec::new();
+    for finding in &app.health {
+        let page = app
+            .pages()
+            .iter()
+            .position(|p| *p == finding.page)
+            .unwrap_or(0);
+        let color = match finding.check.severity {
+            Severity::Fail => Color::Red,
+            _ => Color::Yellow,
+        };
+        banners.push((
+            format!(
+                " {}: {} · {} {} · {} to dismiss ",
+                finding.check.name,
+                finding.check.detail,
+                page + 1,
+                finding.page.title(),
+                key(Action::DismissErrors)
+            ),
+            color,
+        ));
+    }
+    if app.presentation.is_some() {
+        banners.push((
+            format!(
+                " PRESENTING · screen stays on · brightness and profile pinned · {} to exit ",
+                key(Action::Presentation)
+            ),
+            Color::Magenta,
+        ));
+    }
+    if let Some(ref call) = app.call {
+        let mut text = format!(" ON A CALL · {}", call.apps);
+        if app.calls.hold_profile {
+            text.push_str(" · profile held");
+        }
+        if app.calls.no_suspend {
+            text.push_str(" · no suspend");
+        }
+        banners.push((format!("{} ", text), Color::Green));
+    }
+    if let Some(ref budget) = app.budget {
+        let mut text = format!(
+            " RUNTIME TARGET · {} left",
+            app::format_countdown(budget.remaining())
+        );
+        let watts = budget.watts(&app.batteries);
+        let draw = budget::system_draw(&app.batteries);
+        if let Some(watts) = watts {
+            text.push_str(&format!(" · budget {:.1} W", watts));
+        }
+        if let Some(draw) = draw {
+            text.push_str(&format!(" · drawing {:.1} W", draw));
+        }
+        if budget.restore.is_some() {
+            if let Some(pl1) = app.rapl.as_ref().and_then(|p| p.limit(Limit::Pl1)) {
+                text.push_str(&format!(" · package capped at {:.0} W", pl1));
+            }
+        }
+        // Yellow while drawing more than the target allows
+        let color = match (watts, draw) {
+            (Some(watts), Some(draw)) if draw > watts => Color::Yellow,
+            _ => Color::Blue,
+        };
+        banners.push((format!("{} · :budget off to clear ", text), color));
+    }
+    if let Some(ref quiet) = app.quiet {
+        let mut text = format!(" QUIET · package held to {:.0} W", quiet.limit);
+        if let Some(rpm) = app.loudest_fan() {
+            text.push_str(&format!(" · fans {} RPM", rpm));
+        }
+        banners.push((
+            format!("{} · {} to undo ", text, key(Action::Quiet)),
+            Color::Cyan,
+        ));
+    }
+    if let Some(ref daemon) = app.daemon {
+        let mut text = format!(" DAEMON · pid {}", daemon.pid);
+        match (&daemon.rule, &daemon.profile) {
+            (Some(rule), Some(profile)) => {
+                text.push_str(&format!(" · {} → {}", rule, profile));
+                if let Some(fired) = daemon.fired() {
+                    text.push_str(&format!(" since {}", locale::clock(&fired)));
+                }
+            }
+            _ => text.push_str(" · no rule fired yet"),
+        }
+        // Red when its last switch failed
+        let color = match daemon.error {
+            Some(ref e) => {
+                text.push_str(&format!(" · {}", e));
+                Color::Red
+            }
+            None => Color::Gray,
+        };
+        banners.push((format!("{} ", text), color));
+    }
+    if let Some(ref storage) = app.storage {
+        let since = storage
+            .since()
+            .map(|t| format!(" since {}", locale::date(&t)))
+            .unwrap_or_default();
+        banners.push((
+            format!(
+                " STORAGE MODE · charging stops at {}%{} · :storage off to restore ",
+                storage.level, since
+            ),
+            Color::Cyan,
+        ));
+    }
+    if app.travel.is_some() {
+        banners.push((
+            format!(
+                " TRAVEL MODE · Power Saver · radios off · dimmed · {} to restore ",
+                key(Action::Travel)
+            ),
+            Color::Cyan,
+        ));
+    }
+    banners
+}
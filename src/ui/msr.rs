@@ -0,0 +1,43 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Row, Table},
+};
+
+use crate::app::App;
+
+/// CPU 0's registers, each with its raw value and the fields decoded
+/// underneath
+pub fn render(f: &mut Frame, area: Rect, app: &mut App) {
+    let block = Block::default()
+        .title(" Model-Specific Registers (CPU 0) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let registers = match app.msr {
+        Ok(ref registers) if !registers.is_empty() => registers,
+        Ok(_) => return,
+        Err(ref e) => {
+            let error = Paragraph::new(e.as_str())
+                .style(Style::default().fg(Color::DarkGray))
+                .block(block);
+            f.render_widget(error, area);
+            return;
+        }
+    };
+
+    let mut rows = Vec::new();
+    for register in registers {
+        rows.push(
+            Row::new(vec![
+                format!("{:#05x} {}", register.address, register.name),
+                format!("{:#018x}", register.raw),
+            ])
+            .style(Style::default().fg(Color::Cyan)),
+        );
+        for (field, value) in &register.fields {
+            rows.push(Row::new(vec![format!("  {}", field), value.clone()]));
+        }
+    }
+    let table = Table::new(rows, [Constraint::Length(36), Constraint::Min(0)]).block(block);
+    f.render_widget(table, area);
+}
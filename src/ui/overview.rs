@@ -0,0 +1,741 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    prelude::*,
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Dataset, Gauge, GraphType, List, ListItem, Paragraph,
+        Row, Sparkline, SparklineBar, Table,
+    },
+};
+
+use crate::app::{self, App, AUTO_NAME};
+use crate::backend;
+use crate::battery::BatteryInfo;
+use crate::config::{Band, GaugesConfig, Widget};
+use crate::desktop;
+use crate::locale;
+use crate::profile;
+use crate::sources;
+use crate::sway;
+
+pub fn render(f: &mut Frame, area: Rect, app: &mut App) {
+    let slots: Vec<(Widget, u16)> = app
+        .overview
+        .iter()
+        .filter_map(|slot| {
+            // Widgets with nothing to show stay hidden whatever the config says
+            let natural = natural_height(slot.widget, app);
+            (natural > 0).then(|| (slot.widget, slot.height.unwrap_or(natural)))
+        })
+        .collect();
+    let mut constraints: Vec<Constraint> =
+        slots.iter().map(|(_, h)| Constraint::Length(*h)).collect();
+    constraints.push(Constraint::Min(0));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(area);
+
+    for (i, (widget, _)) in slots.iter().enumerate() {
+        let area = chunks[i];
+        match widget {
+            Widget::Battery => render_battery(f, area, app),
+            Widget::Charger => render_charger(f, area, app),
+            Widget::Lid => render_lid(f, area, app),
+            Widget::Graphics => render_graphics(f, area, app),
+            Widget::Swap => render_swap(f, area, app),
+            Widget::Profiles => render_profiles(f, area, app),
+            Widget::Power => render_power(f, area, app),
+            Widget::Temps => render_temps(f, area, app),
+            Widget::Cores => render_cores(f, area, app),
+            Widget::Desktop => render_desktop(f, area, app),
+            Widget::Sway => render_sway(f, area, app),
+            Widget::Inhibitors => render_inhibitors(f, area, app),
+            Widget::Devices => render_devices(f, area, app),
+            Widget::Ups => render_ups(f, area, app),
+            Widget::Sources => render_sources(f, area, app),
+            Widget::Chart => render_chart(f, area, app),
+        }
+    }
+}
+
+/// Rows a widget needs, 0 when it has nothing to show on this machine
+fn natural_height(widget: Widget, app: &App) -> u16 {
+    let shown = |cond: bool, height: u16| if cond { height } else { 0 };
+    match widget {
+        // The total, then each battery
+        Widget::Battery if app.batteries.len() > 1 => 3 * (app.batteries.len() as u16 + 1),
+        Widget::Battery => 5,
+        Widget::Charger => {
+            app.charger_warning().is_some() as u16 + app.charge_anomalies.len() as u16
+        }
+        Widget::Lid => shown(app.lid_closed.is_some(), 1),
+        Widget::Graphics => shown(app.graphics.is_some(), 1),
+        Widget::Swap => app.swap.as_ref().map_or(0, |s| s.summary().len() as u16),
+        Widget::Profiles => app.profiles.len() as u16 + 3,
+        Widget::Power => 6,
+        Widget::Chart => shown(app.draw_history.latest().is_some(), 10),
+        Widget::Temps => shown(!app.temps.is_empty(), 3),
+        Widget::Cores => shown(!app.core_freqs.is_empty(), 3),
+        Widget::Desktop => shown(app.desktop.is_some() || app.backlight.is_some(), 3),
+        Widget::Sway => shown(app.sway.is_some(), 3),
+        Widget::Inhibitors => shown(!app.inhibitors.is_empty(), 1),
+        Widget::Devices => shown(!app.peripherals.is_empty(), 1),
+        Widget::Ups => shown(app.ups.is_some(), 3),
+        // Only worth comparing with more than one
+        Widget::Sources => {
+            let count = app.batteries.len() + app.ups.is_some() as usize;
+            shown(count > 1, count as u16 + 3)
+        }
+    }
+}
+
+fn block(title: impl Into<String>) -> Block<'static> {
+    Block::default()
+        .title(title.into())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray))
+}
+
+fn render_battery(f: &mut Frame, area: Rect, app: &App) {
+    if let (true, Some(total)) = (app.batteries.len() > 1, &app.combined) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(3); app.batteries.len() + 1])
+            .split(area);
+        // The total has the animated gauge
+        render_battery_gauge(f, rows[0], app, total, app.gauge.value());
+        for (bat, row) in app.batteries.iter().zip(rows.iter().skip(1)) {
+            render_battery_gauge(f, *row, app, bat, bat.capacity as f64 / 100.0);
+        }
+    } else if let Some(ref bat) = app.battery {
+        render_battery_gauge(f, area, app, bat, app.gauge.value());
+    } else {
+        let no_battery = Paragraph::new("No battery found")
+            .block(block(" Battery "))
+            .style(Style::default().fg(Color::DarkGray));
+        f.render_widget(no_battery, area);
+    }
+}
+
+/// One battery's gauge, or the total's; on dual-battery machines the
+/// title names it and its place in the charge order
+fn render_battery_gauge(f: &mut Frame, area: Rect, app: &App, bat: &BatteryInfo, ratio: f64) {
+    let color = app.gauges.color(app.gauges.battery(bat.capacity));
+
+    let title = if app.batteries.len() > 1 {
+        let mut title = format!(" {} ", bat.name);
+        if app.charge_first.as_ref() == Some(&bat.name) {
+            title.push_str("· charges first ");
+        }
+        if app.discharge_first.as_ref() == Some(&bat.name) {
+            title.push_str("· discharges first ");
+        }
+        title
+    } else {
+        " Battery ".to_string()
+    };
+
+    let label = format!(
+        "{}%  {}{}",
+        bat.capacity,
+        bat.status,
+        bat.time_remaining
+            .as_ref()
+            .map(|t| format!("  ({})", t))
+            .unwrap_or_default()
+    );
+
+    // Anything but auto means the battery isn't charging normally
+    let behaviour = match bat.behaviour() {
+        Some("inhibit-charge") => "  · charging inhibited",
+        Some("force-discharge") => "  · FORCED DISCHARGE",
+        _ => "",
+    };
+
+    let thresholds = bat
+        .thresholds()
+        .map(|t| format!("  Limit: {}", t))
+        .unwrap_or_default();
+    let health_str = bat
+        .health
+        .map(|h| format!("  Health: {}%", h))
+        .unwrap_or_default();
+    let alarm = bat
+        .alarm
+        .filter(|a| *a > 0)
+        .map(|a| format!("  Alarm: {}%", a))
+        .unwrap_or_default();
+
+    let gauge = Gauge::default()
+        .block(block(title))
+        .gauge_style(Style::default().fg(color))
+        .ratio(ratio.clamp(0.0, 1.0))
+        .label(format!(
+            "{}{}{}{}{}",
+            label, thresholds, health_str, alarm, behaviour
+        ));
+
+    f.render_widget(gauge, area);
+    render_threshold_marks(f, area, bat);
+}
+
+/// Tick marks on the gauge's lower border where charging stops and
+/// resumes, so the limit shows against the fill
+fn render_threshold_marks(f: &mut Frame, area: Rect, bat: &BatteryInfo) {
+    let (inner_x, inner_width) = (area.x + 1, area.width.saturating_sub(2));
+    if inner_width == 0 || area.height < 3 {
+        return;
+    }
+    let y = area.bottom() - 1;
+    let marks = [
+        (bat.start_threshold.filter(|s| *s > 0), Color::DarkGray),
+        (bat.end_threshold.filter(|e| *e < 100), Color::Yellow),
+    ];
+    for (percent, color) in marks {
+        let Some(percent) = percent else {
+            continue;
+        };
+        let x = inner_x + (inner_width - 1) * percent.min(100) as u16 / 100;
+        if let Some(cell) = f.buffer_mut().cell_mut((x, y)) {
+            cell.set_symbol("▲").set_fg(color);
+        }
+    }
+}
+
+/// Charger shortfall and charging anomalies, one line each
+fn render_charger(f: &mut Frame, area: Rect, app: &App) {
+    let lines: Vec<Line> = app
+        .charger_warning()
+        .into_iter()
+        .chain(app.charge_anomalies.iter().map(|a| a.text.clone()))
+        .map(|warning| Line::from(format!("⚠ {}", warning)))
+        .collect();
+    let warnings = Paragraph::new(lines)
+        .style(Style::default().fg(Color::Red))
+        .alignment(Alignment::Center);
+    f.render_widget(warnings, area);
+}
+
+/// Lid and displays, the inputs of clamshell rules
+fn render_lid(f: &mut Frame, area: Rect, app: &App) {
+    let Some(closed) = app.lid_closed else {
+        return;
+    };
+    let external: Vec<&str> = app
+        .displays
+        .iter()
+        .filter(|d| !d.internal)
+        .map(|d| d.name.as_str())
+        .collect();
+    let mut text = format!(
+        "Lid {}  ·  {} display{}",
+        if closed { "closed" } else { "open" },
+        app.displays.len(),
+        if app.displays.len() == 1 { "" } else { "s" }
+    );
+    if !external.is_empty() {
+        text.push_str(&format!(" ({} external)", external.join(", ")));
+    }
+    if closed && !external.is_empty() {
+        text.push_str("  ·  clamshell");
+    }
+    let lid = Paragraph::new(text)
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    f.render_widget(lid, area);
+}
+
+/// Intel display power features, which users can't see elsewhere
+fn render_graphics(f: &mut Frame, area: Rect, app: &App) {
+    let Some(ref graphics) = app.graphics else {
+        return;
+    };
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut spans = vec![Span::styled("Intel graphics", dim)];
+    for (name, state) in graphics.summary() {
+        // Only PSR can be switched here, so say when it was
+        let state = if name == "PSR" && app.psr_off {
+            "off (set here)".to_string()
+        } else {
+            state
+        };
+        let color = match state.as_str() {
+            s if s.starts_with("off") || s == "disabled" || s.contains("lacks") => Color::Yellow,
+            "?" => Color::DarkGray,
+            _ => Color::Reset,
+        };
+        spans.push(Span::styled(format!("  ·  {} ", name), dim));
+        spans.push(Span::styled(state, Style::default().fg(color)));
+    }
+    f.render_widget(
+        Paragraph::new(Line::from(spans)).alignment(Alignment::Center),
+        area,
+    );
+}
+
+/// Compressed swap, one centred line per mechanism
+fn render_swap(f: &mut Frame, area: Rect, app: &App) {
+    let Some(ref swap) = app.swap else {
+        return;
+    };
+    let dim = Style::default().fg(Color::DarkGray);
+    let lines: Vec<Line> = swap
+        .summary()
+        .into_iter()
+        .map(|line| {
+            let (name, rest) = line.split_once(" · ").unwrap_or((&line, ""));
+            let mut spans = vec![Span::styled(name.to_string(), dim)];
+            if !rest.is_empty() {
+                spans.push(Span::raw(format!("  {}", rest)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+}
+
+fn render_profiles(f: &mut Frame, area: Rect, app: &mut App) {
+    let mut profiles: Vec<ListItem> = app
+        .profiles
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let is_current = app.current_profile == Some(i);
+            let marker = if is_current { " ● " } else { "   " };
+            let style = if is_current {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default()
+            };
+            let mut spans = vec![Span::raw(format!("{}{} ({}", marker, p.name, p.governor))];
+            // Flag a governor the driver doesn't offer, and what stands in
+            match profile::resolve_governor(&p.governor, &app.governors) {
+                Some(ref g) if *g == p.governor => {}
+                Some(g) => spans.push(Span::styled(
+                    format!(" → {}", g),
+                    Style::default().fg(Color::Yellow),
+                )),
+                None => spans.push(Span::styled(
+                    " unavailable",
+                    Style::default().fg(Color::Red),
+                )),
+            }
+            // The hint in effect for the active profile, else the one
+            // it would set
+            let live = app.cpufreq.first().and_then(|c| c.epp.as_deref());
+            let epp = if is_current {
+                live
+            } else {
+                p.epp
+                    .as_deref()
+                    .filter(|_| live.is_some() && !backend::active().owns_epp())
+            };
+            if let Some(epp) = epp {
+                spans.push(Span::raw(format!(" · {}", epp)));
+            }
+            spans.push(Span::raw(")"));
+            if let Some(pl1) = p.pl1 {
+                spans.push(Span::raw(format!("  {:.0} W cap", pl1)));
+            }
+            if p.custom {
+                spans.push(Span::styled(
+                    "  custom",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(spans)).style(style)
+        })
+        .collect();
+    profiles.push(auto_item(app));
+
+    let title = match app.boost {
+        Some(ref boost) => format!(
+            " Power Profile · Boost {} left ",
+            app::format_countdown(boost.remaining())
+        ),
+        None => " Power Profile ".to_string(),
+    };
+
+    // Which backend switches profiles, since that decides what they do
+    let backend = Line::from(format!(" via {} ", backend::active().name())).right_aligned();
+    let mut block = block(title).title_bottom(backend);
+    if let Some(turbo) = app.turbo {
+        block = block.title_bottom(if turbo {
+            Line::from(" turbo on ")
+        } else {
+            Line::styled(" turbo off ", Style::default().fg(Color::Yellow))
+        });
+    }
+    let list = List::new(profiles)
+        .block(block)
+        .highlight_style(Style::default().bg(Color::DarkGray).fg(Color::White))
+        .highlight_symbol("▶ ");
+
+    f.render_stateful_widget(list, area, &mut app.list_state);
+}
+
+/// Recent draw as a sparkline, newest on the right. With a smart plug
+/// configured, wall power gets its own row above the estimate.
+fn render_power(f: &mut Frame, area: Rect, app: &App) {
+    let mut title = match app.power_history.back() {
+        Some(w) => format!(" Power {:.1} W ", w),
+        None => " Power ".to_string(),
+    };
+    if let Some(w) = app.wall_history.back() {
+        title.push_str(&format!("· wall {:.1} W ", w));
+    }
+    // Alongside the battery's rate, what the CPU takes of it
+    let battery_rate = app.combined.as_ref().is_some_and(|b| b.power.is_some());
+    if let (true, Some(package)) = (battery_rate, app.package_power) {
+        title.push_str(&format!("· package {:.1} W", package));
+        let parts: Vec<String> = app
+            .domain_power
+            .iter()
+            .filter(|(name, _)| matches!(*name, "core" | "uncore"))
+            .map(|(name, watts)| format!("{} {:.1}", name, watts))
+            .collect();
+        if !parts.is_empty() {
+            title.push_str(&format!(" ({})", parts.join(", ")));
+        }
+        title.push(' ');
+    }
+    let power_block = block(title);
+    let inner = power_block.inner(area);
+    f.render_widget(power_block, area);
+
+    if app.wall_history.is_empty() {
+        render_sparkline(f, inner, &app.power_history, Color::Cyan, &app.gauges);
+        return;
+    }
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+        .split(inner);
+    let estimate = if battery_rate { "bat" } else { "rapl" };
+    for (row, label, history, color) in [
+        (rows[0], "wall", &app.wall_history, Color::Yellow),
+        (rows[1], estimate, &app.power_history, Color::Cyan),
+    ] {
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(5), Constraint::Min(0)])
+            .split(row);
+        f.render_widget(
+            Paragraph::new(label).style(Style::default().fg(Color::DarkGray)),
+            cols[0],
+        );
+        render_sparkline(f, cols[1], history, color, &app.gauges);
+    }
+}
+
+/// Draw over the chart window as a line, now on the right edge, to watch
+/// it settle after a profile change
+fn render_chart(f: &mut Frame, area: Rect, app: &App) {
+    let history = &app.draw_history;
+    let window = history.window().as_secs_f64();
+    let points = history.points();
+    let latest = history.latest().unwrap_or_default();
+    // Headroom above the peak, in whole watts
+    let top = (history.peak().unwrap_or_default() * 1.25).ceil().max(1.0);
+    let dataset = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(app.gauges.color(app.gauges.power(latest))))
+        .data(&points);
+    let axis_style = Style::default().fg(Color::DarkGray);
+    let chart = Chart::new(vec![dataset])
+        .block(block(format!(" Power Draw {:.1} W ", latest)))
+        .x_axis(
+            Axis::default()
+                .style(axis_style)
+                .bounds([-window, 0.0])
+                .labels([
+                    format!("-{}", locale::duration(window as u64)),
+                    format!("-{}", locale::duration(window as u64 / 2)),
+                    "now".to_string(),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(axis_style)
+                .bounds([0.0, top])
+                .labels(["0".to_string(), format!("{:.0} W", top)]),
+        );
+    f.render_widget(chart, area);
+}
+
+/// Bars in the normal band keep the row's color; higher draw takes the
+/// warning and critical colors
+fn render_sparkline(
+    f: &mut Frame,
+    area: Rect,
+    history: &VecDeque<f64>,
+    color: Color,
+    gauges: &GaugesConfig,
+) {
+    // Milliwatts, since the sparkline takes integers
+    let data: Vec<SparklineBar> = history
+        .iter()
+        .skip(history.len().saturating_sub(area.width as usize))
+        .map(|&w| {
+            let bar = SparklineBar::from((w * 1000.0) as u64);
+            match gauges.power(w) {
+                Band::Good => bar,
+                band => bar.style(Style::default().fg(gauges.color(band))),
+            }
+        })
+        .collect();
+    let sparkline = Sparkline::default()
+        .data(data)
+        .style(Style::default().fg(color));
+    f.render_widget(sparkline, area);
+}
+
+fn render_temps(f: &mut Frame, area: Rect, app: &App) {
+    let mut spans = Vec::new();
+    for (i, (zone, temp)) in app.temps.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw("  "));
+        }
+        let color = app.gauges.color(app.gauges.temp(*temp));
+        spans.push(Span::styled(
+            format!("{} ", zone),
+            Style::default().fg(Color::DarkGray),
+        ));
+        spans.push(Span::styled(
+            format!("{:.0}°C", temp),
+            Style::default().fg(color),
+        ));
+    }
+    f.render_widget(
+        Paragraph::new(Line::from(spans)).block(block(" Temperatures ")),
+        area,
+    );
+}
+
+/// One cell per core, colored by how close it runs to its top clock
+fn render_cores(f: &mut Frame, area: Rect, app: &App) {
+    let mut spans: Vec<Span> = app
+        .core_freqs
+        .iter()
+        .map(|core| {
+            let color = match core.load() {
+                l if l < 0.4 => Color::Blue,
+                l if l < 0.7 => Color::Yellow,
+                _ => Color::Red,
+            };
+            Span::styled("■ ", Style::default().fg(color))
+        })
+        .collect();
+    let avg = app.core_freqs.iter().map(|c| c.cur).sum::<u64>() / app.core_freqs.len() as u64;
+    spans.push(Span::styled(
+        format!(" avg {:.1} GHz", avg as f64 / 1e6),
+        Style::default().fg(Color::DarkGray),
+    ));
+    f.render_widget(
+        Paragraph::new(Line::from(spans)).block(block(" Cores ")),
+        area,
+    );
+}
+
+/// The desktop's brightness and suspend timeouts, or just the backlight
+/// without a desktop
+fn render_desktop(f: &mut Frame, area: Rect, app: &App) {
+    let (Some(desktop), Some(settings)) = (app.desktop, app.desktop_settings.as_ref()) else {
+        if let Some(ref backlight) = app.backlight {
+            f.render_widget(
+                Paragraph::new(format!("Brightness {}%", backlight.percent()))
+                    .block(block(format!(" {} ", backlight.name))),
+                area,
+            );
+        }
+        return;
+    };
+    let brightness = settings
+        .brightness
+        .map(|b| format!("{}%", b))
+        .unwrap_or_else(|| "?".to_string());
+    let text = format!(
+        "Brightness {}  Suspend {} battery / {} AC",
+        brightness,
+        desktop::format_timeout(settings.suspend_battery),
+        desktop::format_timeout(settings.suspend_ac)
+    );
+    f.render_widget(
+        Paragraph::new(text).block(block(format!(" {} ", desktop.name()))),
+        area,
+    );
+}
+
+fn render_sway(f: &mut Frame, area: Rect, app: &App) {
+    let Some(ref sway) = app.sway else {
+        return;
+    };
+    let active: Vec<&str> = app
+        .outputs
+        .iter()
+        .filter(|o| o.active)
+        .map(|o| o.name.as_str())
+        .collect();
+    let text = if active.is_empty() {
+        "No active outputs".to_string()
+    } else {
+        let docked = if sway::is_docked(&app.outputs) {
+            "  (docked)"
+        } else {
+            ""
+        };
+        format!("Outputs: {}{}", active.join(", "), docked)
+    };
+    f.render_widget(
+        Paragraph::new(text).block(block(format!(" {} ", sway.name()))),
+        area,
+    );
+}
+
+/// Idle inhibitors are the usual reason the screen never blanks
+fn render_inhibitors(f: &mut Frame, area: Rect, app: &App) {
+    let Some(first) = app.inhibitors.first() else {
+        return;
+    };
+    let mut text = format!("Idle inhibited by {} ({})", first.who, first.source);
+    if !first.why.is_empty() {
+        text.push_str(&format!(": {}", first.why));
+    }
+    if app.inhibitors.len() > 1 {
+        text.push_str(&format!("  +{} more", app.inhibitors.len() - 1));
+    }
+    let inhibit = Paragraph::new(text)
+        .style(Style::default().fg(Color::Yellow))
+        .alignment(Alignment::Center);
+    f.render_widget(inhibit, area);
+}
+
+/// Battery-powered peripherals from UPower
+fn render_devices(f: &mut Frame, area: Rect, app: &App) {
+    let mut spans = vec![Span::styled(
+        "Devices ",
+        Style::default().fg(Color::DarkGray),
+    )];
+    for (i, device) in app.peripherals.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" · ", Style::default().fg(Color::DarkGray)));
+        }
+        let color = if app.low_peripherals().any(|p| p == device.path) {
+            Color::Red
+        } else {
+            Color::Reset
+        };
+        spans.push(Span::styled(
+            format!("{} {:.0}%", device.name, device.percentage),
+            Style::default().fg(color),
+        ));
+    }
+    let devices = Paragraph::new(Line::from(spans)).alignment(Alignment::Center);
+    f.render_widget(devices, area);
+}
+
+/// A NUT-monitored UPS, red once it's on battery
+fn render_ups(f: &mut Frame, area: Rect, app: &App) {
+    let Some(ref status) = app.ups else {
+        return;
+    };
+    let color = if status.on_battery() {
+        Color::Red
+    } else {
+        Color::Reset
+    };
+    let mut spans = vec![Span::styled(status.describe(), Style::default().fg(color))];
+    if let Some(load) = status.load {
+        spans.push(Span::raw(format!("  Load {:.0}%", load)));
+    }
+    if let Some(charge) = status.charge {
+        spans.push(Span::raw("  Battery "));
+        spans.push(Span::styled(
+            format!("{:.0}%", charge),
+            Style::default().fg(app.gauges.color(app.gauges.battery(charge as u8))),
+        ));
+    }
+    if let Some(runtime) = status.runtime {
+        spans.push(Span::raw(format!(
+            "  Runtime {}",
+            locale::duration(runtime as u64)
+        )));
+    }
+    let title = app
+        .ups_config
+        .as_ref()
+        .map(|c| format!(" UPS {} ", c.name))
+        .unwrap_or_else(|| " UPS ".to_string());
+    f.render_widget(Paragraph::new(Line::from(spans)).block(block(title)), area);
+}
+
+/// Batteries and the UPS side by side: health, wear and how much of
+/// the runtime left each one holds
+fn render_sources(f: &mut Frame, area: Rect, app: &App) {
+    let sources = app.sources();
+    let shares = sources::shares(&sources);
+    let dash = || "–".to_string();
+    let rows: Vec<Row> = sources
+        .iter()
+        .zip(shares)
+        .map(|(source, share)| {
+            let color = source
+                .charge
+                .map(|c| app.gauges.color(app.gauges.battery(c as u8)))
+                .unwrap_or(Color::Reset);
+            Row::new(vec![
+                Cell::from(source.name.clone()),
+                Cell::from(source.kind),
+                Cell::from(source.charge.map_or_else(dash, |c| format!("{:.0}%", c)))
+                    .style(Style::default().fg(color)),
+                Cell::from(source.health.map_or_else(dash, |h| format!("{}%", h))),
+                Cell::from(source.cycles.map_or_else(dash, |c| c.to_string())),
+                Cell::from(source.energy.map_or_else(dash, |e| format!("{:.1} Wh", e))),
+                Cell::from(source.runtime.map_or_else(dash, locale::duration)),
+                Cell::from(share.map_or_else(dash, |s| format!("{:.0}%", s))),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(10),
+            Constraint::Length(8),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Length(6),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "Source", "Kind", "Charge", "Health", "Cycles", "Energy", "Runtime", "Share",
+        ])
+        .style(Style::default().fg(Color::DarkGray).bold()),
+    )
+    .block(block(" Power Sources "));
+    f.render_widget(table, area);
+}
+
+/// The virtual Automatic entry, with the rule behind the current profile
+fn auto_item(app: &App) -> ListItem<'static> {
+    let marker = if app.auto { " ● " } else { "   " };
+    let mut spans = vec![Span::raw(format!("{}{}", marker, AUTO_NAME))];
+    if app.auto {
+        let reason = match app.auto_reason {
+            Some((ref rule, ref time)) => format!("  {} since {}", rule, time),
+            None => "  no rule matched yet".to_string(),
+        };
+        spans.push(Span::styled(reason, Style::default().fg(Color::DarkGray)));
+    }
+    let style = if app.auto {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
+    ListItem::new(Line::from(spans)).style(style)
+}
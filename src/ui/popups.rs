@@ -0,0 +1,498 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::app::App;
+use crate::cpufreq;
+use crate::editor::{FanEditor, Field, ProfileEditor, ThresholdEditor};
+use crate::events::Level;
+use crate::keymap::{self, Action, Context};
+use crate::locale;
+use crate::preview::Preview;
+use crate::tour::{self, Tour};
+
+pub fn level_color(level: Level) -> Color {
+    match level {
+        Level::Info => Color::Green,
+        Level::Warning => Color::Yellow,
+        Level::Error => Color::Red,
+    }
+}
+
+/// Stack toasts in the top-right corner, newest on top
+pub fn render_toasts(f: &mut Frame, app: &App) {
+    const WIDTH: u16 = 40;
+
+    let area = f.area();
+    let width = WIDTH.min(area.width);
+    let mut y = area.y + 1;
+
+    for toast in app.toasts.iter().rev() {
+        let lines = (toast.text.chars().count() as u16).div_ceil(width.saturating_sub(4).max(1));
+        let height = lines + 2;
+        if y + height > area.bottom() {
+            break;
+        }
+        let rect = Rect::new(area.right().saturating_sub(width + 1), y, width, height);
+        let color = level_color(toast.level);
+        let title = if toast.level == Level::Error {
+            " Error (x to dismiss) "
+        } else {
+            ""
+        };
+        let widget = Paragraph::new(toast.text.as_str())
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(color)),
+            );
+        f.render_widget(Clear, rect);
+        f.render_widget(widget, rect);
+        y += height;
+    }
+}
+
+pub fn render_event_log(f: &mut Frame, app: &App) {
+    let area = centered(f.area(), 80, 80);
+    let height = area.height.saturating_sub(2) as usize;
+
+    // Show the newest entries that fit, oldest at the top
+    let mut lines: Vec<Line> = app
+        .events
+        .iter()
+        .rev()
+        .take(height)
+        .map(|e| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", locale::clock_seconds(&e.time)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(e.text.clone(), Style::default().fg(level_color(e.level))),
+            ])
+        })
+        .collect();
+    lines.reverse();
+    if lines.is_empty() {
+        lines.push(Line::from("No events yet").style(Style::default().fg(Color::DarkGray)));
+    }
+
+    let log = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Event Log ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(log, area);
+}
+
+/// Cheat sheet of every available binding, grouped by context
+pub fn render_help(f: &mut Frame, app: &App) {
+    let mut lines = Vec::new();
+    for context in Context::ALL {
+        let actions: Vec<Action> = Action::ALL
+            .into_iter()
+            .filter(|a| a.context() == context && app.action_available(*a))
+            .collect();
+        if actions.is_empty() {
+            continue;
+        }
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(context.title()).style(Style::default().fg(Color::Cyan).bold()));
+        for action in actions {
+            let keys: Vec<String> = app
+                .keymap
+                .keys(action)
+                .into_iter()
+                .map(keymap::key_name)
+                .collect();
+            if keys.is_empty() {
+                continue;
+            }
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<14}", keys.join(", ")),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(action.description()),
+            ]));
+        }
+    }
+
+    let height = (lines.len() as u16 + 2).min(f.area().height);
+    let area = centered(f.area(), 60, 100);
+    let area = Rect::new(
+        area.x,
+        area.y + area.height.saturating_sub(height) / 2,
+        area.width,
+        height,
+    );
+    let help = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Keys ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(help, area);
+}
+
+/// Side-by-side current vs. profile values, changed rows highlighted
+pub fn render_preview(f: &mut Frame, app: &App, preview: &Preview) {
+    let name = app
+        .profiles
+        .get(preview.profile)
+        .map(|p| p.name.as_str())
+        .unwrap_or("profile");
+
+    let mut lines = vec![Line::from(vec![
+        Span::styled(
+            format!(" {:<12}", "Setting"),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(
+            format!("{:<18}", "Current"),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled("New", Style::default().fg(Color::DarkGray)),
+    ])];
+    for change in &preview.changes {
+        let style = if change.is_change() {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        let arrow = if change.is_change() { "→ " } else { "  " };
+        lines.push(Line::from(vec![
+            Span::raw(format!(" {:<12}", change.setting)),
+            Span::raw(format!("{:<16}", change.current)),
+            Span::styled(format!("{}{}", arrow, change.target), style),
+        ]));
+    }
+    if !preview.changes.iter().any(|c| c.is_change()) {
+        lines.push(Line::default());
+        lines.push(Line::from(" Nothing would change").style(Style::default().fg(Color::DarkGray)));
+    }
+    lines.push(Line::default());
+    lines.push(
+        Line::from("Enter apply  Esc cancel")
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center),
+    );
+
+    let height = lines.len() as u16 + 2;
+    let area = centered(f.area(), 60, 100);
+    let area = Rect::new(
+        area.x,
+        area.y + area.height.saturating_sub(height) / 2,
+        area.width,
+        height,
+    );
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!(" Apply {}? ", name))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
+
+/// `driver` is the cpufreq driver, to explain the governor and EPP
+/// choices in its terms
+pub fn render_editor(f: &mut Frame, editor: &ProfileEditor, driver: Option<&str>) {
+    // What the focused governor or hint does here, as governor names
+    // don't mean the same under every driver
+    let semantics = match (driver, editor.field) {
+        (Some(driver), Field::Governor) if !editor.governors.is_empty() => Some(
+            cpufreq::governor_semantics(driver, &editor.value(Field::Governor)),
+        ),
+        (Some(driver), Field::Epp) if editor.epp > 0 => {
+            Some(cpufreq::epp_semantics(driver, &editor.value(Field::Epp)))
+        }
+        _ => None,
+    };
+    let height = editor.fields().len() as u16
+        + 4
+        + if semantics.is_some() { 3 } else { 0 }
+        + if editor.error.is_some() { 2 } else { 0 };
+    let area = centered(f.area(), 60, 100);
+    let area = Rect::new(
+        area.x,
+        area.y + area.height.saturating_sub(height) / 2,
+        area.width,
+        height,
+    );
+
+    let mut lines: Vec<Line> = editor
+        .fields()
+        .iter()
+        .map(|&field| {
+            let focused = field == editor.field;
+            let value = if focused && field == Field::Name {
+                format!("{}_", editor.value(field))
+            } else if let Some(typed) = editor.typed.as_ref().filter(|_| focused) {
+                format!("{}_", typed)
+            } else if focused {
+                format!("◀ {} ▶", editor.value(field))
+            } else {
+                editor.value(field)
+            };
+            let style = if focused {
+                Style::default().fg(Color::White).bg(Color::DarkGray)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(" {:<10}", field.label()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(value, style),
+            ])
+        })
+        .collect();
+    if let Some(semantics) = semantics {
+        lines.push(Line::default());
+        lines.push(Line::styled(
+            format!(" {}", semantics),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    if let Some(ref error) = editor.error {
+        lines.push(Line::default());
+        lines.push(Line::styled(
+            format!(" {}", error),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    lines.push(Line::default());
+    lines.push(
+        Line::from("Tab field  ←/→ change  or type a value  Enter save  Esc cancel")
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center),
+    );
+
+    let title = match editor.original {
+        Some(ref name) => format!(" Edit {} ", name),
+        None => " New Profile ".to_string(),
+    };
+    // Explanations wrap onto the two rows kept for them
+    let form = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(form, area);
+}
+
+/// Start and end percentages for one battery, typed or stepped
+pub fn render_threshold_editor(f: &mut Frame, editor: &ThresholdEditor) {
+    let area = centered(f.area(), 50, 100);
+    let height = if editor.has_start { 7 } else { 6 };
+    let area = Rect::new(
+        area.x,
+        area.y + area.height.saturating_sub(height) / 2,
+        area.width,
+        height,
+    );
+
+    let field = |label: &str, value: &str, focused: bool| {
+        let style = if focused {
+            Style::default().fg(Color::White).bg(Color::DarkGray)
+        } else {
+            Style::default()
+        };
+        // An empty start keeps whatever the battery has
+        let value = match (value, focused) {
+            ("", true) => "_ (unchanged)".to_string(),
+            ("", false) => "unchanged".to_string(),
+            (v, true) => format!("{}_%", v),
+            (v, false) => format!("{}%", v),
+        };
+        Line::from(vec![
+            Span::styled(
+                format!(" {:<8}", label),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(value, style),
+        ])
+    };
+    let mut lines = Vec::new();
+    if editor.has_start {
+        lines.push(field("Start", &editor.start, !editor.on_end));
+    }
+    lines.push(field("Stop at", &editor.end, editor.on_end));
+    let explanation = if editor.has_start {
+        " Charging stops at the end and resumes below the start"
+    } else {
+        " Charging stops at this level"
+    };
+    lines.push(Line::from(explanation).style(Style::default().fg(Color::Cyan)));
+    lines.push(Line::default());
+    let keys = if editor.has_start {
+        "0-9 type  ←/→ ±1  Tab field  Enter apply  Esc cancel"
+    } else {
+        "0-9 type  ←/→ ±1  Enter apply  Esc cancel"
+    };
+    lines.push(
+        Line::from(keys)
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center),
+    );
+
+    let form = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!(" {} Charge Thresholds ", editor.battery))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(form, area);
+}
+
+/// A row per controllable fan with its mode and duty cycle, and a
+/// warning to accept before any goes manual
+pub fn render_fan_editor(f: &mut Frame, editor: &FanEditor) {
+    let area = centered(f.area(), 60, 100);
+    let height = editor.fans.len() as u16 + if editor.confirming { 7 } else { 4 };
+    let area = Rect::new(
+        area.x,
+        area.y + area.height.saturating_sub(height) / 2,
+        area.width,
+        height,
+    );
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines: Vec<Line> = editor
+        .fans
+        .iter()
+        .enumerate()
+        .map(|(i, fan)| {
+            let style = if i == editor.selected {
+                Style::default().fg(Color::White).bg(Color::DarkGray)
+            } else {
+                Style::default()
+            };
+            let setting = if fan.manual {
+                let filled = fan.percent as usize / 5;
+                format!(
+                    "manual [{}{}] {:>3}%",
+                    "█".repeat(filled),
+                    "·".repeat(20 - filled),
+                    fan.percent
+                )
+            } else {
+                "automatic".to_string()
+            };
+            let mark = if fan.changed() { " *" } else { "" };
+            Line::from(vec![
+                Span::styled(format!(" {:<20}", fan.name), dim),
+                Span::styled(setting, style),
+                Span::styled(mark, Style::default().fg(Color::Yellow)),
+            ])
+        })
+        .collect();
+    lines.push(Line::default());
+    if editor.confirming {
+        lines.push(
+            Line::from(" A fan held at a fixed speed won't speed up as the machine heats")
+                .style(Style::default().fg(Color::Red)),
+        );
+        lines.push(
+            Line::from(" up, and stays that way after powertui exits until set back")
+                .style(Style::default().fg(Color::Red)),
+        );
+        lines.push(Line::from(" to automatic.").style(Style::default().fg(Color::Red)));
+        lines.push(
+            Line::from("y take manual control  any other key go back")
+                .style(Style::default().fg(Color::Yellow))
+                .alignment(Alignment::Center),
+        );
+    } else {
+        lines.push(
+            Line::from("↑/↓ fan  m auto/manual  ←/→ ±5%  Enter apply  Esc cancel")
+                .style(dim)
+                .alignment(Alignment::Center),
+        );
+    }
+
+    let border = if editor.confirming {
+        Color::Red
+    } else {
+        Color::Cyan
+    };
+    let form = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Fan Control ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(form, area);
+}
+
+/// A rect covering the given percentage of `area`, centered
+/// The tour's current step, low on the screen so the page it's about
+/// stays in view
+pub fn render_tour(f: &mut Frame, app: &App, tour: &Tour) {
+    let step = tour.current();
+    let text = tour::fill(step.text, &app.keymap);
+    let width = f.area().width.clamp(20, 72);
+    // Wrapped at word boundaries, so allow a line of slack
+    let wrapped = text.chars().count() as u16 / width.saturating_sub(4).max(1) + 2;
+    let height = (wrapped + 4).min(f.area().height);
+    let area = Rect::new(
+        f.area().x + (f.area().width - width) / 2,
+        f.area().y + f.area().height.saturating_sub(height + 2),
+        width,
+        height,
+    );
+    let dim = Style::default().fg(Color::DarkGray);
+    let next = if tour.is_last() { "finish" } else { "next" };
+    let lines = vec![
+        Line::from(text),
+        Line::default(),
+        Line::from(vec![
+            Span::styled("→", Style::default().fg(Color::Yellow)),
+            Span::styled(format!(" {}  ", next), dim),
+            Span::styled("←", Style::default().fg(Color::Yellow)),
+            Span::styled(" back  ", dim),
+            Span::styled("Esc", Style::default().fg(Color::Yellow)),
+            Span::styled(" close", dim),
+        ]),
+    ];
+    let block = Block::default()
+        .title(format!(
+            " {} · {}/{} ",
+            step.title,
+            tour.step + 1,
+            tour::STEPS.len()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: true }).block(block),
+        area,
+    );
+}
+
+pub fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
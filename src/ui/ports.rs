@@ -0,0 +1,212 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Row, Table},
+};
+
+use crate::app::App;
+use crate::usb::Device;
+
+/// USB-C ports above, every USB device and what it may draw below
+pub fn render(f: &mut Frame, area: Rect, app: &mut App) {
+    // Room for the ports, or the one-line explanation when there are none
+    let ports_height = if app.typec.is_empty() {
+        3
+    } else {
+        app.typec.len() as u16 + 3
+    };
+    let [ports_area, devices_area] =
+        Layout::vertical([Constraint::Length(ports_height), Constraint::Min(0)]).areas(area);
+    render_typec(f, ports_area, app);
+    render_devices(f, devices_area, app);
+}
+
+/// USB-C ports, what's plugged in, and whether it negotiated PD
+fn render_typec(f: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .title(" USB-C Ports ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    if app.typec.is_empty() {
+        let empty = Paragraph::new(
+            "No ports in /sys/class/typec (needs the UCSI or TCPM driver for the port controller)",
+        )
+        .style(Style::default().fg(Color::DarkGray))
+        .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let rows: Vec<Row> = app
+        .typec
+        .iter()
+        .map(|port| {
+            let Some(ref partner) = port.partner else {
+                return Row::new(vec![
+                    port.name.clone(),
+                    "nothing".to_string(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ])
+                .style(Style::default().fg(Color::DarkGray));
+            };
+            let connected = match (partner.pd, partner.pd_revision.as_deref()) {
+                (Some(true), Some(rev)) => format!("PD {}", rev),
+                (Some(true), None) => "PD".to_string(),
+                (Some(false), _) => "no PD".to_string(),
+                (None, _) => "connected".to_string(),
+            };
+            let altmodes = partner
+                .altmodes
+                .iter()
+                .map(|a| {
+                    if a.active {
+                        format!("{} (active)", a.name())
+                    } else {
+                        a.name()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            // PD is what people hope for; plain 5 V explains a slow charge
+            let color = if port.power_mode.as_deref() == Some("usb_power_delivery") {
+                Color::Green
+            } else {
+                Color::Yellow
+            };
+            Row::new(vec![
+                port.name.clone(),
+                connected,
+                port.power_summary(),
+                port.data_role.clone().unwrap_or_default(),
+                altmodes,
+                port.cable.clone().unwrap_or_default(),
+            ])
+            .style(Style::default().fg(color))
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(7),
+            Constraint::Length(10),
+            Constraint::Length(34),
+            Constraint::Length(7),
+            Constraint::Min(16),
+            Constraint::Length(8),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "Port",
+            "Partner",
+            "Power",
+            "Data",
+            "Alt modes",
+            "Cable",
+        ])
+        .style(Style::default().fg(Color::DarkGray).bold()),
+    )
+    .block(block);
+    f.render_widget(table, area);
+}
+
+/// Devices by declared draw, bus-powered ones that are awake and want
+/// more than 100 mA highlighted as the likely battery eaters
+fn render_devices(f: &mut Frame, area: Rect, app: &mut App) {
+    let declared: u32 = app
+        .usb
+        .iter()
+        .filter(|d| !d.self_powered && !d.suspended)
+        .filter_map(|d| d.max_power)
+        .sum();
+    let block = Block::default()
+        .title(" USB Devices ")
+        .title_bottom(
+            Line::from(format!(
+                " awake and bus powered: up to {} mA, {:.1} W at 5 V ",
+                declared,
+                declared as f64 * 5.0 / 1000.0
+            ))
+            .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    if app.usb.is_empty() {
+        let empty = Paragraph::new("No USB devices in /sys/bus/usb/devices")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let visible = area.height.saturating_sub(3) as usize;
+    app.page_scroll = app
+        .page_scroll
+        .min(app.usb.len().saturating_sub(visible.max(1)));
+
+    let rows: Vec<Row> = app
+        .usb
+        .iter()
+        .skip(app.page_scroll)
+        .take(visible)
+        .map(device_row)
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(9),
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(9),
+            Constraint::Length(8),
+            Constraint::Length(5),
+            Constraint::Length(10),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "Port", "Device", "Speed", "Declares", "Port max", "Power", "State",
+        ])
+        .style(Style::default().fg(Color::DarkGray).bold()),
+    )
+    .block(block);
+    f.render_widget(table, area);
+}
+
+fn device_row(device: &Device) -> Row<'static> {
+    let speed = match device.speed {
+        Some(s) if s >= 1000.0 => format!("{} Gb/s", s / 1000.0),
+        Some(s) => format!("{} Mb/s", s),
+        None => String::new(),
+    };
+    let style = if device.heavy() {
+        Style::default().fg(Color::Yellow)
+    } else if device.suspended || device.self_powered {
+        Style::default().fg(Color::DarkGray)
+    } else {
+        Style::default()
+    };
+    Row::new(vec![
+        device.port.clone(),
+        device.name.clone(),
+        speed,
+        device
+            .max_power
+            .map(|ma| format!("{} mA", ma))
+            .unwrap_or_default(),
+        format!("{} mA", device.budget),
+        if device.self_powered { "self" } else { "bus" }.to_string(),
+        if device.suspended {
+            "suspended"
+        } else {
+            "active"
+        }
+        .to_string(),
+    ])
+    .style(style)
+}
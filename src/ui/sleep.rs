@@ -0,0 +1,216 @@
+use chrono::{Local, TimeZone};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Row, Table},
+};
+
+use crate::app::App;
+use crate::doctor::{self, Severity};
+use crate::keymap::{self, Action};
+use crate::locale;
+use crate::sleep::{self, ABNORMAL_DRAIN};
+
+/// Suspend cycles, newest first, with drain per suspended hour
+pub fn render(f: &mut Frame, area: Rect, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            // A header row over the timers, or just the empty message
+            Constraint::Length(match app.wakeups.len() {
+                0 => 3,
+                n => n as u16 + 3,
+            }),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    // Active suspend mode, then what each mode has cost so far
+    let mut mode_line = vec![Span::styled("Mode ", Style::default().fg(Color::DarkGray))];
+    match app.mem_sleep {
+        Some(ref mem_sleep) => {
+            for mode in &mem_sleep.options {
+                let style = if mem_sleep.current.as_ref() == Some(mode) {
+                    Style::default().fg(Color::Green).bold()
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                };
+                mode_line.push(Span::styled(format!("{} ", mode), style));
+            }
+            if mem_sleep.options.len() > 1 {
+                let keys = app.keymap.keys(Action::CycleSleepMode);
+                if let Some(key) = keys.first() {
+                    mode_line.push(Span::styled(
+                        format!(" ({} to switch)", keymap::key_name(*key)),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+            }
+        }
+        None => mode_line.push(Span::styled(
+            "unknown",
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+
+    let by_mode = sleep::drain_by_mode(&app.sleep_cycles);
+    let summary = if by_mode.is_empty() {
+        format!("{} suspends in the last 30 days", app.sleep_cycles.len())
+    } else {
+        let modes: Vec<String> = by_mode
+            .iter()
+            .map(|(mode, drain, hours)| format!("{} {:.2}%/h over {:.1} h", mode, drain, hours))
+            .collect();
+        format!(
+            "{} suspends in the last 30 days · {}",
+            app.sleep_cycles.len(),
+            modes.join(" · ")
+        )
+    };
+    // Worth knowing before a critical battery relies on it
+    let hibernate = match app
+        .hibernate
+        .iter()
+        .find(|c| c.severity == doctor::verdict(&app.hibernate))
+    {
+        Some(check) if check.severity == Severity::Fail => Span::styled(
+            format!("will not work: {}", check.detail),
+            Style::default().fg(Color::Red),
+        ),
+        Some(check) if check.severity == Severity::Warn => Span::styled(
+            format!("may fail: {}", check.detail),
+            Style::default().fg(Color::Yellow),
+        ),
+        _ => Span::styled("ready", Style::default().fg(Color::Green)),
+    };
+    let header = vec![
+        Line::from(mode_line),
+        Line::from(vec![
+            Span::styled("Hibernate ", Style::default().fg(Color::DarkGray)),
+            hibernate,
+        ]),
+        Line::styled(summary, Style::default().fg(Color::DarkGray)),
+    ];
+    f.render_widget(Paragraph::new(header), chunks[0]);
+    render_wakeups(f, chunks[1], app);
+
+    let block = Block::default()
+        .title(" Suspend Cycles ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    if app.sleep_cycles.is_empty() {
+        let empty = Paragraph::new("No suspends found in the journal")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        f.render_widget(empty, chunks[2]);
+        return;
+    }
+
+    let visible = chunks[2].height.saturating_sub(3) as usize;
+    app.page_scroll = app
+        .page_scroll
+        .min(app.sleep_cycles.len().saturating_sub(visible.max(1)));
+
+    let rows: Vec<Row> = app
+        .sleep_cycles
+        .iter()
+        .rev()
+        .skip(app.page_scroll)
+        .take(visible)
+        .map(|c| {
+            let start = Local
+                .timestamp_opt(c.start, 0)
+                .single()
+                .map(|t| locale::date_time(&t))
+                .unwrap_or_default();
+            let percent = |p: Option<f64>| p.map(|p| format!("{:.0}%", p));
+            let battery = match (percent(c.before), percent(c.after)) {
+                (Some(before), Some(after)) => format!("{} → {}", before, after),
+                _ => "?".to_string(),
+            };
+            let drain = match c.drain_per_hour() {
+                Some(d) if c.abnormal() => format!("{:.2}%/h ⚠", d),
+                Some(d) => format!("{:.2}%/h", d),
+                None => "–".to_string(),
+            };
+            let style = if c.abnormal() {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                start,
+                locale::duration((c.hours() * 3600.0) as u64),
+                c.mode.clone(),
+                battery,
+                drain,
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(16),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Min(10),
+        ],
+    )
+    .header(
+        Row::new(vec!["Suspended", "Asleep", "Mode", "Battery", "Drain"])
+            .style(Style::default().fg(Color::DarkGray).bold()),
+    )
+    .block(block.title_bottom(format!(" over {:.1}%/h is flagged ", ABNORMAL_DRAIN)));
+    f.render_widget(table, chunks[2]);
+}
+
+/// What's armed to wake the machine, soonest first
+fn render_wakeups(f: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .title(" Wake Timers ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    if app.wakeups.is_empty() {
+        let empty = Paragraph::new("Nothing is set to wake the machine from suspend")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+    let now = Local::now().timestamp();
+    let rows: Vec<Row> = app
+        .wakeups
+        .iter()
+        .map(|w| {
+            let when = match w.at.and_then(|t| Local.timestamp_opt(t, 0).single()) {
+                Some(at) if at.timestamp() >= now => format!(
+                    "{} (in {})",
+                    locale::date_time(&at),
+                    locale::duration((at.timestamp() - now) as u64)
+                ),
+                // An RTC alarm in the past has fired already
+                Some(at) => format!("{} (passed)", locale::date_time(&at)),
+                None => "not scheduled".to_string(),
+            };
+            Row::new(vec![w.source.clone(), w.activates.clone(), when])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(28),
+            Constraint::Length(28),
+            Constraint::Min(16),
+        ],
+    )
+    .header(
+        Row::new(vec!["Source", "Runs", "Next wake"])
+            .style(Style::default().fg(Color::DarkGray).bold()),
+    )
+    .block(block);
+    f.render_widget(table, area);
+}
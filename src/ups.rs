@@ -0,0 +1,152 @@
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long upsd gets to answer, so a host that can't be reached
+/// doesn't hold up whoever asked
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A UPS as reported by Network UPS Tools
+pub struct UpsStatus {
+    /// `ups.status` flags, e.g. `OL CHRG` or `OB LB`
+    pub flags: Vec<String>,
+    pub charge: Option<f64>,
+    /// Seconds left at the current load
+    pub runtime: Option<u32>,
+    /// Percent of rated output
+    pub load: Option<f64>,
+}
+
+impl UpsStatus {
+    pub fn on_battery(&self) -> bool {
+        self.flags.iter().any(|f| f == "OB")
+    }
+
+    pub fn describe(&self) -> &'static str {
+        let has = |flag: &str| self.flags.iter().any(|f| f == flag);
+        if has("OB") && has("LB") {
+            "On battery, low"
+        } else if has("OB") {
+            "On battery"
+        } else if has("CHRG") {
+            "Online, charging"
+        } else if has("OL") {
+            "Online"
+        } else {
+            "Unknown"
+        }
+    }
+}
+
+/// Queries the UPS on its own thread every `interval`, so the TUI
+/// never waits on upsd
+pub struct Poller {
+    statuses: Receiver<Result<UpsStatus, String>>,
+}
+
+impl Poller {
+    pub fn start(name: String, interval: Duration) -> Poller {
+        let (tx, statuses) = mpsc::channel();
+        // Ends with the next status after the poller is dropped
+        thread::spawn(move || loop {
+            if tx.send(read_status(&name)).is_err() {
+                break;
+            }
+            thread::sleep(interval);
+        });
+        Poller { statuses }
+    }
+
+    /// Statuses since the last call, oldest first
+    pub fn drain(&self) -> Vec<Result<UpsStatus, String>> {
+        self.statuses.try_iter().collect()
+    }
+}
+
+/// Query `name` (`ups@host`) through `upsc`, which talks to upsd
+pub fn read_status(name: &str) -> Result<UpsStatus, String> {
+    Ok(parse_status(&query(name)?))
+}
+
+/// Raw `upsc` output, one `key: value` per line
+pub fn query(name: &str) -> Result<String, String> {
+    let mut child = Command::new("upsc")
+        .arg(name)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("upsc: {}", e))?;
+    let started = Instant::now();
+    while child
+        .try_wait()
+        .map_err(|e| format!("upsc: {}", e))?
+        .is_none()
+    {
+        if started.elapsed() > TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("{}: no answer in {} s", name, TIMEOUT.as_secs()));
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("upsc: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{}: {}", name, stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn parse_status(stdout: &str) -> UpsStatus {
+    let var = |key: &str| {
+        stdout.lines().find_map(|line| {
+            let (k, v) = line.split_once(": ")?;
+            (k == key).then(|| v.trim().to_string())
+        })
+    };
+    UpsStatus {
+        flags: var("ups.status")
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect(),
+        charge: var("battery.charge").and_then(|v| v.parse().ok()),
+        runtime: var("battery.runtime").and_then(|v| v.parse::<f64>().ok().map(|s| s as u32)),
+        load: var("ups.load").and_then(|v| v.parse().ok()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UPSC: &str = "battery.charge: 87\n\
+                        battery.runtime: 1520.5\n\
+                        device.model: Back-UPS XS 700U\n\
+                        ups.load: 23\n\
+                        ups.status: OB DISCHRG LB\n";
+
+    #[test]
+    fn status_from_upsc() {
+        let status = parse_status(UPSC);
+        assert_eq!(status.flags, ["OB", "DISCHRG", "LB"]);
+        assert_eq!(status.charge, Some(87.0));
+        assert_eq!(status.runtime, Some(1520));
+        assert_eq!(status.load, Some(23.0));
+        assert!(status.on_battery());
+        assert_eq!(status.describe(), "On battery, low");
+    }
+
+    #[test]
+    fn missing_variables() {
+        let status = parse_status("ups.status: OL CHRG\nbattery.charge: n/a\n");
+        assert_eq!(status.charge, None);
+        assert_eq!(status.runtime, None);
+        assert!(!status.on_battery());
+        assert_eq!(status.describe(), "Online, charging");
+        assert_eq!(parse_status("").describe(), "Unknown");
+    }
+}
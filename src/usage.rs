@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{Days, Local, NaiveDate};
+
+use crate::config;
+
+/// Days kept and charted
+pub const DAYS: usize = 7;
+
+/// How often time counted in memory is written out
+const FLUSH_EVERY: Duration = Duration::from_secs(60);
+
+/// Seconds in each profile, by profile name
+pub type Totals = BTreeMap<String, u64>;
+
+/// Seconds in each profile, by `%Y-%m-%d` local date
+type Store = BTreeMap<String, Totals>;
+
+fn usage_path() -> Option<PathBuf> {
+    Some(config::state_dir()?.join("profile-time.json"))
+}
+
+fn load() -> Store {
+    usage_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// One day of the chart, oldest first
+pub struct Day {
+    pub date: NaiveDate,
+    pub totals: Totals,
+}
+
+/// The last week, one entry per day including days with nothing
+/// recorded
+pub fn read_week() -> Vec<Day> {
+    let mut store = load();
+    let today = Local::now().date_naive();
+    (0..DAYS as u64)
+        .rev()
+        .filter_map(|back| today.checked_sub_days(Days::new(back)))
+        .map(|date| Day {
+            date,
+            totals: store
+                .remove(&date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Counts how long each profile is in effect while powertui runs.
+/// Time goes to the file a minute at a time, merged with what's there,
+/// so two instances don't overwrite each other.
+pub struct Tracker {
+    pending: Totals,
+    last: Instant,
+    flushed: Instant,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Tracker {
+            pending: Totals::new(),
+            last: Instant::now(),
+            flushed: Instant::now(),
+        }
+    }
+}
+
+impl Tracker {
+    /// Credit the time since the last call to `profile`, if one is in
+    /// effect. The monotonic clock stops during suspend, so sleeping
+    /// isn't counted.
+    pub fn tick(&mut self, profile: Option<&str>) {
+        let elapsed = self.last.elapsed().as_secs();
+        if elapsed == 0 {
+            return;
+        }
+        self.last += Duration::from_secs(elapsed);
+        if let Some(profile) = profile {
+            *self.pending.entry(profile.to_string()).or_default() += elapsed;
+        }
+        if self.flushed.elapsed() >= FLUSH_EVERY {
+            self.flush();
+        }
+    }
+
+    /// Add pending time to today and drop days older than a week
+    pub fn flush(&mut self) {
+        self.flushed = Instant::now();
+        if self.pending.is_empty() {
+            return;
+        }
+        let Some(path) = usage_path() else {
+            return;
+        };
+        let mut store = load();
+        let today = Local::now().date_naive();
+        let day = store
+            .entry(today.format("%Y-%m-%d").to_string())
+            .or_default();
+        for (profile, secs) in std::mem::take(&mut self.pending) {
+            *day.entry(profile).or_default() += secs;
+        }
+        if let Some(oldest) = today.checked_sub_days(Days::new(DAYS as u64 - 1)) {
+            let oldest = oldest.format("%Y-%m-%d").to_string();
+            store.retain(|date, _| *date >= oldest);
+        }
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(text) = serde_json::to_string(&store) {
+            let _ = fs::write(path, text);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::Path;
+
+use crate::sysfs;
+
+const DEVICES: &str = "/sys/bus/usb/devices";
+
+/// A USB device, hubs included, from /sys/bus/usb/devices
+pub struct Device {
+    /// Bus and port path, `1-2.3`
+    pub port: String,
+    /// Product string, or vendor:product IDs when it has none
+    pub name: String,
+    /// Link speed in Mbit/s
+    pub speed: Option<f64>,
+    /// Most the active configuration may draw from the bus, in mA, as
+    /// the device declares it
+    pub max_power: Option<u32>,
+    pub self_powered: bool,
+    /// Runtime PM has suspended it, so it draws next to nothing
+    pub suspended: bool,
+    /// What the port it's plugged into supplies, in mA
+    pub budget: u32,
+}
+
+impl Device {
+    /// Bus powered, awake and declaring more than a low-power device
+    pub fn heavy(&self) -> bool {
+        !self.self_powered && !self.suspended && self.max_power.is_some_and(|ma| ma > 100)
+    }
+}
+
+/// Every USB device, the hungriest first. Only the declared maximum is
+/// known: ports don't report measured current.
+pub fn read_devices() -> Vec<Device> {
+    let base = sysfs::path(DEVICES);
+    let mut devices: Vec<Device> = fs::read_dir(&base)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        // Interfaces are `1-2:1.0`, root hubs `usb1`
+        .filter(|n| !n.contains(':') && !n.starts_with("usb"))
+        .filter_map(|port| read_device(&base, port))
+        .collect();
+    devices.sort_by(|a, b| b.max_power.cmp(&a.max_power).then(a.port.cmp(&b.port)));
+    devices
+}
+
+fn read_device(base: &Path, port: String) -> Option<Device> {
+    let path = base.join(&port);
+    let vendor = sysfs::read_string(path.join("idVendor"))?;
+    let name = sysfs::read_string(path.join("product")).unwrap_or_else(|| {
+        format!(
+            "{}:{}",
+            vendor,
+            sysfs::read_string(path.join("idProduct")).unwrap_or_default()
+        )
+    });
+    let speed: Option<f64> = sysfs::read_value(path.join("speed"));
+    Some(Device {
+        name,
+        speed,
+        max_power: sysfs::read_string(path.join("bMaxPower"))
+            .and_then(|p| p.trim_end_matches("mA").parse().ok()),
+        self_powered: self_powered(&path),
+        suspended: sysfs::read_string(path.join("power/runtime_status")).as_deref()
+            == Some("suspended"),
+        budget: budget(base, &port, speed),
+        port,
+    })
+}
+
+/// bmAttributes bit 6
+fn self_powered(path: &Path) -> bool {
+    sysfs::read_string(path.join("bmAttributes"))
+        .and_then(|a| u8::from_str_radix(&a, 16).ok())
+        .is_some_and(|a| a & 0x40 != 0)
+}
+
+/// The spec's allowance for the port: 900 mA at SuperSpeed, 500 mA
+/// otherwise, and 100 mA behind a hub that is itself bus powered.
+/// Charging ports may give more without saying so.
+fn budget(base: &Path, port: &str, speed: Option<f64>) -> u32 {
+    // `1-2.3` hangs off the hub at `1-2`; `1-2` off the root hub
+    if let Some((hub, _)) = port.rsplit_once('.') {
+        if !self_powered(&base.join(hub)) {
+            return 100;
+        }
+    }
+    if speed.is_some_and(|s| s >= 5000.0) {
+        900
+    } else {
+        500
+    }
+}
@@ -0,0 +1,113 @@
+use std::fs;
+use std::process::Command;
+
+use crate::sysfs;
+
+const RTC: &str = "/sys/class/rtc";
+
+/// Something armed to wake the machine from suspend
+pub struct Wakeup {
+    /// `rtc0`, or the timer unit
+    pub source: String,
+    /// What runs on waking, or who set an RTC alarm when we can't tell
+    pub activates: String,
+    /// Unix time it fires, when known
+    pub at: Option<i64>,
+}
+
+/// RTC alarms and systemd timers with `WakeSystem=yes`, soonest first
+pub fn read_wakeups() -> Vec<Wakeup> {
+    let mut wakeups = rtc_alarms();
+    wakeups.extend(waking_timers(false));
+    wakeups.extend(waking_timers(true));
+    // Unknown times last
+    wakeups.sort_by_key(|w| w.at.unwrap_or(i64::MAX));
+    wakeups
+}
+
+/// Alarms set through `wakealarm`, e.g. by `rtcwake` or a BIOS
+/// scheduler. systemd arms the RTC for its own timers only while
+/// suspending, so outside of that only other alarms show up here.
+fn rtc_alarms() -> Vec<Wakeup> {
+    fs::read_dir(sysfs::path(RTC))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let at = sysfs::read_value::<i64>(entry.path().join("wakealarm"))?;
+            Some(Wakeup {
+                source: entry.file_name().to_string_lossy().into_owned(),
+                activates: "RTC alarm".to_string(),
+                at: Some(at),
+            })
+        })
+        .collect()
+}
+
+/// Timers that will wake the machine, from the system or the user's
+/// manager
+fn waking_timers(user: bool) -> Vec<Wakeup> {
+    let systemctl = || {
+        let mut command = Command::new("systemctl");
+        if user {
+            command.arg("--user");
+        }
+        command
+    };
+    let Ok(output) = systemctl()
+        .args([
+            "list-units",
+            "--type=timer",
+            "--all",
+            "--plain",
+            "--no-legend",
+        ])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let timers: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|l| l.split_whitespace().next())
+        .filter(|unit| unit.ends_with(".timer"))
+        .map(String::from)
+        .collect();
+    if timers.is_empty() {
+        return Vec::new();
+    }
+    let Ok(output) = systemctl()
+        .args(["show", "--timestamp=unix"])
+        .args(["-p", "Id", "-p", "Unit", "-p", "WakeSystem"])
+        .args(["-p", "NextElapseUSecRealtime"])
+        .args(&timers)
+        .output()
+    else {
+        return Vec::new();
+    };
+    // One block of key=value lines per unit, separated by blank lines
+    String::from_utf8_lossy(&output.stdout)
+        .split("\n\n")
+        .filter_map(|block| {
+            let prop = |key: &str| {
+                block
+                    .lines()
+                    .find_map(|l| l.strip_prefix(key)?.strip_prefix('='))
+            };
+            if prop("WakeSystem")? != "yes" {
+                return None;
+            }
+            let id = prop("Id")?;
+            Some(Wakeup {
+                source: if user {
+                    format!("{} (user)", id)
+                } else {
+                    id.to_string()
+                },
+                activates: prop("Unit").unwrap_or("?").to_string(),
+                at: prop("NextElapseUSecRealtime")
+                    .and_then(|t| t.strip_prefix('@'))
+                    .and_then(|t| t.parse().ok()),
+            })
+        })
+        .collect()
+}
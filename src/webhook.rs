@@ -0,0 +1,100 @@
+use std::fs;
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::json;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Deliveries that failed, waiting to be shown to the user
+static FAILED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Alerts posted even during quiet hours, since powertui is about to
+/// act on the machine: shut it down for the UPS, or put it to sleep in
+/// a bag
+pub const URGENT: [&str; 2] = ["ups_low", "bag"];
+
+/// Where alerts go besides the screen, for machines nobody is looking at
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub kind: WebhookKind,
+}
+
+/// The payload the service at `url` expects
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    /// `{"event", "message", "host", "time"}`, for scripts and
+    /// automation tools
+    #[default]
+    Json,
+    /// The message as the body of a POST to the topic URL
+    Ntfy,
+    Slack,
+    /// `url` includes `?token=`
+    Gotify,
+}
+
+impl WebhookConfig {
+    /// POST `message` in the background. `event` names the alert
+    /// (`battery_low`) for receivers that sort on it.
+    pub fn send(&self, event: &str, message: &str) {
+        let host = fs::read_to_string("/proc/sys/kernel/hostname")
+            .map(|h| h.trim().to_string())
+            .unwrap_or_default();
+        let title = format!("powertui on {}", host);
+        let mut command = Command::new("curl");
+        command.args(["-sf", "-m", &TIMEOUT.as_secs().to_string(), "-X", "POST"]);
+        let body = match self.kind {
+            WebhookKind::Json => json!({
+                "event": event,
+                "message": message,
+                "host": host,
+                "time": Local::now().to_rfc3339(),
+            })
+            .to_string(),
+            WebhookKind::Ntfy => {
+                command
+                    .args(["-H", &format!("Title: {}", title)])
+                    .args(["-H", &format!("Tags: {}", event)])
+                    .args(["-H", "Priority: high"]);
+                message.to_string()
+            }
+            WebhookKind::Slack => json!({ "text": format!("{}: {}", title, message) }).to_string(),
+            WebhookKind::Gotify => json!({
+                "title": title,
+                "message": message,
+                "priority": 8,
+            })
+            .to_string(),
+        };
+        if !matches!(self.kind, WebhookKind::Ntfy) {
+            command.args(["-H", "Content-Type: application/json"]);
+        }
+        command.arg("--data-binary").arg(body).arg(&self.url);
+        let url = self.url.clone();
+        thread::spawn(move || {
+            let delivered = command.output().is_ok_and(|o| o.status.success());
+            if !delivered {
+                if let Ok(mut failed) = FAILED.lock() {
+                    failed.push(format!("Webhook {} didn't accept the alert", url));
+                }
+            }
+        });
+    }
+}
+
+/// Failed deliveries since the last call
+pub fn drain_failed() -> Vec<String> {
+    FAILED
+        .lock()
+        .map(|mut failed| failed.drain(..).collect())
+        .unwrap_or_default()
+}